@@ -0,0 +1,91 @@
+//! # AMQP Publisher
+//! Relay outboxes of externally notifiable events to RabbitMQ exchange, routed by topic of the event.
+//! ### example
+//! ```rust,no_run
+//! let connection = Connection::connect("amqp://localhost:5672", ConnectionProperties::default()).await?;
+//! let publisher = RabbitPublisher::new(connection.create_channel().await?, "account-service").await?;
+//!
+//! publisher.publish_all(&mut outboxes).await?;
+//! ```
+
+use crate::{
+	outbox::{OutBox, TOutBoxPublisher},
+	prelude::BaseError,
+	serialization::FORMAT_HEADER,
+};
+use async_trait::async_trait;
+use lapin::{
+	options::{BasicPublishOptions, ConfirmSelectOptions, ExchangeDeclareOptions},
+	publisher_confirm::Confirmation,
+	types::{AMQPValue, FieldTable},
+	BasicProperties, Channel, ExchangeKind,
+};
+use std::collections::HashMap;
+
+/// Message to be published, detached from the channel that sends it.
+#[derive(Debug, Clone)]
+pub struct AmqpMessage {
+	pub exchange: String,
+	pub routing_key: String,
+	pub payload: Vec<u8>,
+	pub headers: HashMap<String, String>,
+}
+
+/// Interface [RabbitPublisher] works on. It is implemented for [Channel].
+#[async_trait]
+pub trait TAmqpChannel: Send + Sync {
+	/// Declare topic exchange and put the channel in confirm mode
+	async fn setup(&self, exchange: &str) -> Result<(), BaseError>;
+	/// Resolves once the broker acknowledges the message. Negative acknowledgement is [BaseError::MessageBrokerError].
+	async fn publish(&self, message: AmqpMessage) -> Result<(), BaseError>;
+}
+
+#[async_trait]
+impl TAmqpChannel for Channel {
+	async fn setup(&self, exchange: &str) -> Result<(), BaseError> {
+		self.confirm_select(ConfirmSelectOptions::default()).await.map_err(|err| BaseError::MessageBrokerError(err.to_string()))?;
+		self.exchange_declare(exchange, ExchangeKind::Topic, ExchangeDeclareOptions { durable: true, ..Default::default() }, FieldTable::default())
+			.await
+			.map_err(|err| BaseError::MessageBrokerError(err.to_string()))
+	}
+
+	async fn publish(&self, message: AmqpMessage) -> Result<(), BaseError> {
+		let mut headers = FieldTable::default();
+		message.headers.into_iter().for_each(|(key, value)| headers.insert(key.into(), AMQPValue::LongString(value.into())));
+		let confirmation = self
+			.basic_publish(&message.exchange, &message.routing_key, BasicPublishOptions::default(), &message.payload, BasicProperties::default().with_headers(headers))
+			.await
+			.map_err(|err| BaseError::MessageBrokerError(err.to_string()))?
+			.await
+			.map_err(|err| BaseError::MessageBrokerError(err.to_string()))?;
+		match confirmation {
+			Confirmation::Ack(_) => Ok(()),
+			Confirmation::Nack(_) => Err(BaseError::MessageBrokerError(format!("Message Nacked! Routing Key:{}", message.routing_key))),
+			Confirmation::NotRequested => Err(BaseError::MessageBrokerError("Channel Is Not In Confirm Mode!".into())),
+		}
+	}
+}
+
+pub struct RabbitPublisher<C> {
+	channel: C,
+	exchange: String,
+}
+
+impl<C: TAmqpChannel> RabbitPublisher<C> {
+	/// Declare `exchange` on the channel and publish in confirm mode
+	pub async fn new(channel: C, exchange: &str) -> Result<Self, BaseError> {
+		channel.setup(exchange).await?;
+		Ok(Self { channel, exchange: exchange.to_string() })
+	}
+}
+
+#[async_trait]
+impl<C: TAmqpChannel> TOutBoxPublisher for RabbitPublisher<C> {
+	/// Headers of the event are sent as message headers along with [FORMAT_HEADER] of the payload.
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		let mut headers: HashMap<String, String> = serde_json::from_str(&outbox.headers).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		headers.insert(FORMAT_HEADER.to_string(), outbox.format.as_str().to_string());
+		let message = AmqpMessage { exchange: self.exchange.clone(), routing_key: outbox.topic.clone(), payload: outbox.payload.clone(), headers };
+		self.channel.publish(message).await
+	}
+}
@@ -0,0 +1,265 @@
+//! # Kafka Consumer Driver
+//! Consume externally notifiable events published by other services and feed them into the message bus.
+//! Outboxes are relayed the other way round by [KafkaPublisher].
+//! ### example
+//! ```rust,no_run
+//! let consumer: StreamConsumer = ClientConfig::new()
+//!     .set("group.id", "account-service")
+//!     .set("bootstrap.servers", "localhost:9092")
+//!     .set("enable.auto.commit", "false")
+//!     .create()?;
+//!
+//! KafkaConsumerDriver::new(consumer, &CONNECTION, YourDeadLetterSink)
+//!     .register::<AccountCreated>("AccountCreated")
+//!     .register::<AccountClosed>("AccountClosed")
+//!     .run::<YourServiceError>(&MessageBus)
+//!     .await?;
+//! ```
+//!
+//! # Kafka Publisher
+//! Relay outboxes to topic of the event, keyed by [OutBox::partition_key] so that events of an aggregate land in the same partition in order.
+//! ### example
+//! ```rust,no_run
+//! let producer: FutureProducer = ClientConfig::new().set("bootstrap.servers", "localhost:9092").set("enable.idempotence", "true").create()?;
+//!
+//! KafkaPublisher::new(producer).publish_all(&mut outboxes).await?;
+//! ```
+
+use crate::{
+	bus_components::{
+		dead_letter::{DeadLetter, DeserializationErrorStrategy, TDeadLetterSink},
+		executor::TConnection,
+		messagebus::{MessageBus, TEventBus},
+	},
+	compression,
+	outbox::{OutBox, TOutBoxPublisher, BATCH_HEADER},
+	prelude::{ApplicationError, BaseError, TEvent},
+	serialization::{EventDeserializers, SerFormat, FORMAT_HEADER},
+	upcaster::{Upcaster, VERSION_HEADER},
+};
+use async_trait::async_trait;
+use rdkafka::{
+	consumer::{CommitMode, Consumer, StreamConsumer},
+	message::{Header, Headers, OwnedHeaders},
+	producer::{FutureProducer, FutureRecord},
+	util::Timeout,
+	Message, Offset, TopicPartitionList,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Record consumed from a topic, detached from the consumer that polled it.
+#[derive(Debug, Clone)]
+pub struct ConsumedRecord {
+	pub topic: String,
+	pub partition: i32,
+	pub offset: i64,
+	pub payload: Vec<u8>,
+	pub headers: HashMap<String, String>,
+}
+
+/// Interface [KafkaConsumerDriver] works on. It is implemented for [StreamConsumer].
+#[async_trait]
+pub trait TKafkaConsumer: Send + Sync {
+	fn subscribe(&self, topics: &[&str]) -> Result<(), BaseError>;
+	async fn recv(&self) -> Result<ConsumedRecord, BaseError>;
+	/// Commit offset of the given record so it is not consumed again by the same group.
+	async fn commit(&self, record: &ConsumedRecord) -> Result<(), BaseError>;
+}
+
+#[async_trait]
+impl TKafkaConsumer for StreamConsumer {
+	fn subscribe(&self, topics: &[&str]) -> Result<(), BaseError> {
+		Consumer::subscribe(self, topics).map_err(|err| BaseError::MessageBrokerError(err.to_string()))
+	}
+
+	async fn recv(&self) -> Result<ConsumedRecord, BaseError> {
+		let message = StreamConsumer::recv(self).await.map_err(|err| BaseError::MessageBrokerError(err.to_string()))?;
+		Ok(ConsumedRecord {
+			topic: message.topic().to_string(),
+			partition: message.partition(),
+			offset: message.offset(),
+			payload: message.payload().map(|payload| payload.to_vec()).unwrap_or_default(),
+			headers: message.headers().map(|headers| headers.iter().filter_map(|header| Some((header.key.to_string(), String::from_utf8(header.value?.to_vec()).ok()?))).collect()).unwrap_or_default(),
+		})
+	}
+
+	async fn commit(&self, record: &ConsumedRecord) -> Result<(), BaseError> {
+		let mut offsets = TopicPartitionList::new();
+		offsets.add_partition_offset(&record.topic, record.partition, Offset::Offset(record.offset + 1)).map_err(|err| BaseError::MessageBrokerError(err.to_string()))?;
+		Consumer::commit(self, &offsets, CommitMode::Async).map_err(|err| BaseError::MessageBrokerError(err.to_string()))
+	}
+}
+
+pub struct KafkaConsumerDriver<C> {
+	consumer: C,
+	deserializers: EventDeserializers,
+	dead_letter_sink: Box<dyn TDeadLetterSink>,
+	upcaster: Upcaster,
+	on_deserialization_error: DeserializationErrorStrategy,
+	conn: &'static dyn TConnection,
+}
+
+impl<C: TKafkaConsumer> KafkaConsumerDriver<C> {
+	pub fn new(consumer: C, conn: &'static dyn TConnection, dead_letter_sink: impl TDeadLetterSink + 'static) -> Self {
+		Self { consumer, deserializers: Default::default(), dead_letter_sink: Box::new(dead_letter_sink), upcaster: Default::default(), on_deserialization_error: Default::default(), conn }
+	}
+
+	/// Subscribe to `topic`, deserializing its records into `T` in the format given by [FORMAT_HEADER], json if absent
+	pub fn register<T>(mut self, topic: &str) -> Self
+	where
+		T: TEvent + serde::de::DeserializeOwned,
+	{
+		self.deserializers = self.deserializers.register::<T>(topic);
+		self
+	}
+
+	/// Subscribe to every topic of `deserializers`, shared with other drivers or dead letter replay
+	pub fn deserializers(mut self, deserializers: EventDeserializers) -> Self {
+		self.deserializers = self.deserializers.merge(deserializers);
+		self
+	}
+
+	/// Upcast payload of older version before deserialization. Version is read from [VERSION_HEADER] of record, 1 if absent.
+	/// Only json payload is upcasted.
+	pub fn upcaster(mut self, upcaster: Upcaster) -> Self {
+		self.upcaster = upcaster;
+		self
+	}
+
+	/// What to do with record that can't be deserialized. It is dead-lettered by default.
+	/// Dead letter carries topic, partition and offset of the record in its metadata, along with headers of the record.
+	pub fn on_deserialization_error(mut self, strategy: DeserializationErrorStrategy) -> Self {
+		self.on_deserialization_error = strategy;
+		self
+	}
+
+	/// Consume records until error occurs either on consumer or in handling event, or until shutdown of [MessageBus] is signaled.
+	/// As offset of the record whose handling failed is not committed, it will be consumed again when the driver restarts.
+	pub async fn run<E>(&self, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let topics = self.deserializers.topics().collect::<Vec<_>>();
+		self.consumer.subscribe(&topics)?;
+
+		let shutdown = MessageBus::shutdown_handle();
+		loop {
+			let record = tokio::select! {
+				_ = shutdown.signaled() => return Ok(()),
+				record = self.consumer.recv() => record?,
+			};
+			self.process(record, bus).await?;
+		}
+	}
+
+	/// Consume a record and commit its offset once the event is handled.
+	/// Native headers of the record are set on the event when it has `#[headers]` field.
+	/// Record that can't be deserialized is committed so that it doesn't block the partition, unless [DeserializationErrorStrategy::Halt] is given.
+	pub async fn consume_one<E>(&self, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let record = self.consumer.recv().await?;
+		self.process(record, bus).await
+	}
+
+	async fn process<E>(&self, record: ConsumedRecord, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let events = compression::decompress_payload(&record.headers, &record.payload).and_then(|payload| {
+			if record.headers.contains_key(BATCH_HEADER) {
+				return self.deserializers.deserialize_batch(&payload, &self.upcaster);
+			}
+			let format = record.headers.get(FORMAT_HEADER).map(|format| format.parse::<SerFormat>()).transpose()?.unwrap_or_default();
+			if format != SerFormat::Json {
+				return self.deserializers.deserialize(&record.topic, &payload, format).map(|event| vec![event]);
+			}
+			let version = record.headers.get(VERSION_HEADER).and_then(|version| version.parse().ok()).unwrap_or(1);
+			self.upcaster.upcast(&record.topic, version, &payload).and_then(|payload| self.deserializers.deserialize(&record.topic, &payload, SerFormat::Json)).map(|event| vec![event])
+		});
+
+		match events {
+			Ok(events) => {
+				for mut event in events {
+					// * Native headers are handed over to event that keeps `#[headers]` field. Those its envelope carried in batch take precedence.
+					if let Some(headers) = Arc::get_mut(&mut event).and_then(|event| event.headers_mut()) {
+						record.headers.iter().for_each(|(key, value)| {
+							headers.entry(key.clone()).or_insert_with(|| value.clone());
+						});
+					}
+					bus.handle_event(event, self.conn).await?
+				}
+			}
+			Err(err) => {
+				tracing::error!("Failed to deserialize record at {}:{}:{}! Error:{:?}", record.topic, record.partition, record.offset, err);
+				match self.on_deserialization_error {
+					DeserializationErrorStrategy::SkipAndLog => {}
+					DeserializationErrorStrategy::DeadLetter => {
+						let mut metadata = record.headers.clone();
+						metadata.extend([("topic".to_string(), record.topic.clone()), ("partition".to_string(), record.partition.to_string()), ("offset".to_string(), record.offset.to_string())]);
+						let dead_letter = DeadLetter { topic: record.topic.clone(), payload: record.payload.clone(), reason: format!("{:?}", err), handler: None, metadata };
+						self.dead_letter_sink.send(dead_letter).await?;
+					}
+					// * Not committed, so the record is consumed again when the driver restarts
+					DeserializationErrorStrategy::Halt => return Err(err.into()),
+				}
+			}
+		}
+
+		self.consumer.commit(&record).await?;
+		Ok(())
+	}
+}
+
+/// Record to be produced, detached from the producer that sends it.
+#[derive(Debug, Clone)]
+pub struct ProducedRecord {
+	pub topic: String,
+	pub key: Option<String>,
+	pub payload: Vec<u8>,
+	pub headers: HashMap<String, String>,
+}
+
+/// Interface [KafkaPublisher] works on. It is implemented for [FutureProducer].
+#[async_trait]
+pub trait TKafkaProducer: Send + Sync {
+	/// Resolves once the broker acknowledges the record
+	async fn send(&self, record: ProducedRecord) -> Result<(), BaseError>;
+}
+
+#[async_trait]
+impl TKafkaProducer for FutureProducer {
+	async fn send(&self, record: ProducedRecord) -> Result<(), BaseError> {
+		let headers = record.headers.iter().fold(OwnedHeaders::new(), |headers, (key, value)| headers.insert(Header { key, value: Some(value) }));
+		let mut future_record = FutureRecord::<str, [u8]>::to(&record.topic).payload(&record.payload).headers(headers);
+		if let Some(key) = record.key.as_deref() {
+			future_record = future_record.key(key);
+		}
+		FutureProducer::send(self, future_record, Timeout::Never).await.map(|_| ()).map_err(|(err, _)| BaseError::MessageBrokerError(err.to_string()))
+	}
+}
+
+pub struct KafkaPublisher<P> {
+	producer: P,
+}
+
+impl<P: TKafkaProducer> KafkaPublisher<P> {
+	pub fn new(producer: P) -> Self {
+		Self { producer }
+	}
+}
+
+#[async_trait]
+impl<P: TKafkaProducer> TOutBoxPublisher for KafkaPublisher<P> {
+	/// Headers of the event are sent as record headers along with [FORMAT_HEADER] of the payload.
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		let mut headers: HashMap<String, String> = serde_json::from_str(&outbox.headers).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		headers.insert(FORMAT_HEADER.to_string(), outbox.format.as_str().to_string());
+		let record = ProducedRecord { topic: outbox.topic.clone(), key: outbox.partition_key(), payload: outbox.payload.clone(), headers };
+		self.producer.send(record).await
+	}
+}
@@ -1,2 +1,14 @@
 #[cfg(feature = "sqlx-postgres")]
 pub mod sqlx;
+
+#[cfg(feature = "kafka")]
+pub mod kafka;
+
+#[cfg(feature = "event-driven-amqp")]
+pub mod amqp;
+
+#[cfg(feature = "event-driven-prometheus")]
+pub mod prometheus;
+
+#[cfg(feature = "event-driven-redis")]
+pub mod redis;
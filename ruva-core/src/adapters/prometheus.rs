@@ -0,0 +1,76 @@
+//! # Prometheus Recorder
+//! [TMetricsRecorder] that keeps measurements of [MessageBus] as Prometheus metrics on the registry it is given.
+//! - `commands_total` : counter labelled with `command` and `outcome`
+//! - `events_total` : counter labelled with `event` and `outcome`
+//! - `handler_failures_total` : counter labelled with `event`
+//! - `handler_duration_seconds` : histogram labelled with `event` and `outcome`
+//!
+//! `outcome` is either `success` or `failure`.
+//! ### example
+//! ```rust,no_run
+//! let recorder = PrometheusRecorder::new(Registry::new())?;
+//! MessageBus::configure(MessageBusConfig::default().with_metrics_recorder(recorder.clone()));
+//!
+//! // On scrape
+//! let body = TextEncoder::new().encode_to_string(&recorder.registry().gather())?;
+//! ```
+//!
+//! [MessageBus]: crate::prelude::MessageBus
+
+use crate::bus_components::metrics::TMetricsRecorder;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use std::time::Duration;
+
+#[derive(Clone)]
+pub struct PrometheusRecorder {
+	registry: Registry,
+	commands: IntCounterVec,
+	events: IntCounterVec,
+	handler_failures: IntCounterVec,
+	handler_duration: HistogramVec,
+}
+
+impl PrometheusRecorder {
+	/// Register the metrics on `registry`. It fails when metrics of the same names are already registered on it.
+	pub fn new(registry: Registry) -> prometheus::Result<Self> {
+		let commands = IntCounterVec::new(Opts::new("commands_total", "Commands executed"), &["command", "outcome"])?;
+		let events = IntCounterVec::new(Opts::new("events_total", "Events whose handlers were run"), &["event", "outcome"])?;
+		let handler_failures = IntCounterVec::new(Opts::new("handler_failures_total", "Event handlers that failed after their retries"), &["event"])?;
+		let handler_duration = HistogramVec::new(HistogramOpts::new("handler_duration_seconds", "Time event handlers took, including their retries"), &["event", "outcome"])?;
+		registry.register(Box::new(commands.clone()))?;
+		registry.register(Box::new(events.clone()))?;
+		registry.register(Box::new(handler_failures.clone()))?;
+		registry.register(Box::new(handler_duration.clone()))?;
+		Ok(Self { registry, commands, events, handler_failures, handler_duration })
+	}
+
+	/// Registry the metrics are registered on, to be gathered on scrape
+	pub fn registry(&self) -> &Registry {
+		&self.registry
+	}
+}
+
+fn outcome(succeeded: bool) -> &'static str {
+	if succeeded {
+		"success"
+	} else {
+		"failure"
+	}
+}
+
+impl TMetricsRecorder for PrometheusRecorder {
+	fn record_command(&self, command: &str, succeeded: bool, _elapsed: Duration) {
+		self.commands.with_label_values(&[command, outcome(succeeded)]).inc();
+	}
+
+	fn record_event(&self, topic: &str, succeeded: bool) {
+		self.events.with_label_values(&[topic, outcome(succeeded)]).inc();
+	}
+
+	fn record_handler(&self, topic: &str, succeeded: bool, elapsed: Duration) {
+		if !succeeded {
+			self.handler_failures.with_label_values(&[topic]).inc();
+		}
+		self.handler_duration.with_label_values(&[topic, outcome(succeeded)]).observe(elapsed.as_secs_f64());
+	}
+}
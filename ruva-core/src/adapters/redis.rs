@@ -0,0 +1,267 @@
+//! # Redis Streams
+//! Relay outboxes to Redis Streams and consume them back with consumer groups, for teams already running Redis.
+//! Each topic is a stream of its own, named after the topic.
+//!
+//! No Redis client is bundled. Publisher and driver work on [TRedisStreams], which is implemented on the client of your choice.
+//! ### example
+//! ```rust,no_run
+//! let publisher = RedisStreamPublisher::new(client.clone());
+//! publisher.publish_all(&mut outboxes).await?;
+//!
+//! RedisStreamConsumerDriver::new(client, "account-service", "account-service-1", &CONNECTION, YourDeadLetterSink)
+//!     .register::<AccountCreated>("AccountCreated")
+//!     .reclaim_after(Duration::from_secs(60))
+//!     .run::<YourServiceError>(&MessageBus)
+//!     .await?;
+//! ```
+
+use crate::{
+	bus_components::{
+		dead_letter::{DeadLetter, DeserializationErrorStrategy, TDeadLetterSink},
+		executor::TConnection,
+		messagebus::{MessageBus, TEventBus},
+	},
+	compression,
+	outbox::{OutBox, TOutBoxPublisher, BATCH_HEADER},
+	prelude::{ApplicationError, BaseError, TEvent},
+	serialization::{EventDeserializers, SerFormat, FORMAT_HEADER},
+	upcaster::{Upcaster, VERSION_HEADER},
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// Fields of stream entry that [RedisStreamPublisher] writes and [RedisStreamConsumerDriver] reads
+pub mod stream_fields {
+	pub const ID: &str = "id";
+	pub const AGGREGATE_ID: &str = "aggregate_id";
+	pub const AGGREGATE_NAME: &str = "aggregate_name";
+	pub const TOPIC: &str = "topic";
+	/// Headers of the event in json
+	pub const HEADERS: &str = "headers";
+	pub const FORMAT: &str = "format";
+	/// Event serialized in [FORMAT], without envelope
+	pub const PAYLOAD: &str = "payload";
+	pub const CREATE_DT: &str = "create_dt";
+}
+
+/// Entry of a stream, detached from the client that read it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+	pub stream: String,
+	pub id: String,
+	pub fields: HashMap<String, Vec<u8>>,
+}
+
+impl StreamEntry {
+	fn field(&self, name: &str) -> Option<String> {
+		self.fields.get(name).and_then(|value| String::from_utf8(value.clone()).ok())
+	}
+}
+
+/// Commands of Redis Streams that [RedisStreamPublisher] and [RedisStreamConsumerDriver] work on
+#[async_trait]
+pub trait TRedisStreams: Send + Sync {
+	/// `XADD stream * field value ...`, returning id of the entry
+	async fn xadd(&self, stream: &str, fields: Vec<(String, Vec<u8>)>) -> Result<String, BaseError>;
+	/// `XGROUP CREATE stream group $ MKSTREAM`. Group that already exists is not an error.
+	async fn create_group(&self, stream: &str, group: &str) -> Result<(), BaseError>;
+	/// `XREADGROUP GROUP group consumer STREAMS stream ... > ...`, entries never delivered to the group.
+	/// It may block for a while, returning nothing when no entry arrives.
+	async fn read_group(&self, streams: &[&str], group: &str, consumer: &str) -> Result<Vec<StreamEntry>, BaseError>;
+	/// `XACK stream group id`
+	async fn ack(&self, stream: &str, group: &str, id: &str) -> Result<(), BaseError>;
+	/// Entries pending in the group for longer than `min_idle`, claimed for `consumer`.
+	/// That is `XPENDING stream group IDLE min_idle - + count` followed by `XCLAIM` of the ids found.
+	async fn claim_pending(&self, stream: &str, group: &str, consumer: &str, min_idle: Duration) -> Result<Vec<StreamEntry>, BaseError>;
+}
+
+pub struct RedisStreamPublisher<C> {
+	client: C,
+}
+
+impl<C: TRedisStreams> RedisStreamPublisher<C> {
+	pub fn new(client: C) -> Self {
+		Self { client }
+	}
+}
+
+#[async_trait]
+impl<C: TRedisStreams> TOutBoxPublisher for RedisStreamPublisher<C> {
+	/// `XADD` to the stream of the topic, with metadata of the outbox as [stream_fields] next to its payload
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		let fields = vec![
+			(stream_fields::ID.to_string(), outbox.id.to_string().into_bytes()),
+			(stream_fields::AGGREGATE_ID.to_string(), outbox.aggregate_id.clone().into_bytes()),
+			(stream_fields::AGGREGATE_NAME.to_string(), outbox.aggregate_name.clone().into_bytes()),
+			(stream_fields::TOPIC.to_string(), outbox.topic.clone().into_bytes()),
+			(stream_fields::HEADERS.to_string(), outbox.headers.clone().into_bytes()),
+			(stream_fields::FORMAT.to_string(), outbox.format.as_str().as_bytes().to_vec()),
+			(stream_fields::PAYLOAD.to_string(), outbox.payload.clone()),
+			(stream_fields::CREATE_DT.to_string(), outbox.create_dt.to_rfc3339().into_bytes()),
+		];
+		self.client.xadd(&outbox.topic, fields).await?;
+		Ok(())
+	}
+}
+
+pub struct RedisStreamConsumerDriver<C> {
+	client: C,
+	group: String,
+	consumer: String,
+	deserializers: EventDeserializers,
+	dead_letter_sink: Box<dyn TDeadLetterSink>,
+	upcaster: Upcaster,
+	reclaim_after: Option<Duration>,
+	on_deserialization_error: DeserializationErrorStrategy,
+	conn: &'static dyn TConnection,
+}
+
+impl<C: TRedisStreams> RedisStreamConsumerDriver<C> {
+	/// Consume as `consumer` of consumer `group`. Consumers of the same group share the entries of the streams.
+	pub fn new(client: C, group: &str, consumer: &str, conn: &'static dyn TConnection, dead_letter_sink: impl TDeadLetterSink + 'static) -> Self {
+		Self {
+			client,
+			group: group.to_string(),
+			consumer: consumer.to_string(),
+			deserializers: Default::default(),
+			dead_letter_sink: Box::new(dead_letter_sink),
+			upcaster: Default::default(),
+			reclaim_after: None,
+			on_deserialization_error: Default::default(),
+			conn,
+		}
+	}
+
+	/// Consume stream of `topic`, deserializing its entries into `T` in the format given by [stream_fields::FORMAT], json if absent
+	pub fn register<T>(mut self, topic: &str) -> Self
+	where
+		T: TEvent + serde::de::DeserializeOwned,
+	{
+		self.deserializers = self.deserializers.register::<T>(topic);
+		self
+	}
+
+	/// Consume stream of every topic of `deserializers`, shared with other drivers or dead letter replay
+	pub fn deserializers(mut self, deserializers: EventDeserializers) -> Self {
+		self.deserializers = self.deserializers.merge(deserializers);
+		self
+	}
+
+	/// Upcast payload of older version before deserialization. Version is read from [VERSION_HEADER] in headers of entry, 1 if absent.
+	/// Only json payload is upcasted.
+	pub fn upcaster(mut self, upcaster: Upcaster) -> Self {
+		self.upcaster = upcaster;
+		self
+	}
+
+	/// Claim entries left pending for longer than `min_idle`, such as the ones delivered to consumer that crashed before acking them.
+	/// They are claimed and handled before new entries are read.
+	pub fn reclaim_after(mut self, min_idle: Duration) -> Self {
+		self.reclaim_after = Some(min_idle);
+		self
+	}
+
+	/// What to do with entry that can't be deserialized. It is dead-lettered by default.
+	/// Dead letter carries stream and id of the entry in its metadata, along with headers of the entry.
+	pub fn on_deserialization_error(mut self, strategy: DeserializationErrorStrategy) -> Self {
+		self.on_deserialization_error = strategy;
+		self
+	}
+
+	/// Create consumer group on every registered stream and poll until error occurs either on client or in handling event,
+	/// or until shutdown of [MessageBus] is signaled.
+	/// As the entry whose handling failed is not acked, it stays pending and is reclaimed once it has been idle for [Self::reclaim_after].
+	pub async fn run<E>(&self, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		self.create_groups().await?;
+		let shutdown = MessageBus::shutdown_handle();
+		loop {
+			tokio::select! {
+				_ = shutdown.signaled() => return Ok(()),
+				polled = self.poll(bus) => polled?,
+			};
+		}
+	}
+
+	pub async fn create_groups(&self) -> Result<(), BaseError> {
+		for topic in self.deserializers.topics() {
+			self.client.create_group(topic, &self.group).await?;
+		}
+		Ok(())
+	}
+
+	/// Handle entries reclaimed from pending ones, then the ones newly read, acking each once its event is handled.
+	/// Entry that can't be deserialized is acked so that it is not reclaimed endlessly, unless [DeserializationErrorStrategy::Halt] is given.
+	pub async fn poll<E>(&self, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let streams = self.deserializers.topics().collect::<Vec<_>>();
+		let mut entries = vec![];
+		if let Some(min_idle) = self.reclaim_after {
+			for stream in streams.iter() {
+				entries.extend(self.client.claim_pending(stream, &self.group, &self.consumer, min_idle).await?);
+			}
+		}
+		entries.extend(self.client.read_group(&streams, &self.group, &self.consumer).await?);
+
+		for entry in entries {
+			self.process(entry, bus).await?;
+		}
+		Ok(())
+	}
+
+	async fn process<E>(&self, entry: StreamEntry, bus: &(impl TEventBus<E> + Sync)) -> Result<(), E>
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let mut headers: HashMap<String, String> = entry.field(stream_fields::HEADERS).and_then(|headers| serde_json::from_str(&headers).ok()).unwrap_or_default();
+		let payload = entry.fields.get(stream_fields::PAYLOAD).cloned().unwrap_or_default();
+		let events = compression::decompress_payload(&headers, &payload).and_then(|decompressed| {
+			if headers.contains_key(BATCH_HEADER) {
+				return self.deserializers.deserialize_batch(&decompressed, &self.upcaster);
+			}
+			let format = entry.field(stream_fields::FORMAT).or_else(|| headers.get(FORMAT_HEADER).cloned()).map(|format| format.parse::<SerFormat>()).transpose()?.unwrap_or_default();
+			if format != SerFormat::Json {
+				return self.deserializers.deserialize(&entry.stream, &decompressed, format).map(|event| vec![event]);
+			}
+			let version = headers.get(VERSION_HEADER).and_then(|version| version.parse().ok()).unwrap_or(1);
+			self.upcaster.upcast(&entry.stream, version, &decompressed).and_then(|payload| self.deserializers.deserialize(&entry.stream, &payload, SerFormat::Json)).map(|event| vec![event])
+		});
+
+		match events {
+			Ok(events) => {
+				for mut event in events {
+					// * Headers are handed over to event that keeps `#[headers]` field. Those its envelope carried in batch take precedence.
+					if let Some(event_headers) = Arc::get_mut(&mut event).and_then(|event| event.headers_mut()) {
+						headers.iter().for_each(|(key, value)| {
+							event_headers.entry(key.clone()).or_insert_with(|| value.clone());
+						});
+					}
+					bus.handle_event(event, self.conn).await?
+				}
+			}
+			Err(err) => {
+				tracing::error!("Failed to deserialize entry {} of {}! Error:{:?}", entry.id, entry.stream, err);
+				match self.on_deserialization_error {
+					DeserializationErrorStrategy::SkipAndLog => {}
+					DeserializationErrorStrategy::DeadLetter => {
+						headers.extend([("stream".to_string(), entry.stream.clone()), ("entry_id".to_string(), entry.id.clone())]);
+						let dead_letter = DeadLetter { topic: entry.stream.clone(), payload, reason: format!("{:?}", err), handler: None, metadata: headers };
+						self.dead_letter_sink.send(dead_letter).await?;
+					}
+					// * Not acked, so the entry stays pending to be reclaimed
+					DeserializationErrorStrategy::Halt => return Err(err.into()),
+				}
+			}
+		}
+
+		self.client.ack(&entry.stream, &self.group, &entry.id).await?;
+		Ok(())
+	}
+}
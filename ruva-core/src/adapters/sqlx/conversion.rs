@@ -32,7 +32,11 @@ impl sqlx::Type<Postgres> for SnowFlake {
 impl From<sqlx::Error> for BaseError {
 	fn from(value: sqlx::Error) -> Self {
 		tracing::error!("{:?}", value);
-		Self::DatabaseError(value.to_string())
+		// * 40001 is SQLSTATE of serialization_failure
+		if value.as_database_error().and_then(|err| err.code()).is_some_and(|code| code == "40001") {
+			return Self::SerializationFailure;
+		}
+		Self::DatabaseError(value.to_string().into())
 	}
 }
 
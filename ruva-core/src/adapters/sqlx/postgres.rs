@@ -1,9 +1,17 @@
 use crate::bus_components::contexts::Context;
 use crate::{
-	prelude::{BaseError, TUnitOfWork},
+	prelude::{BaseError, IsolationLevel, MessageBus, TRequestTransaction, TUnitOfWork},
 	prepare_bulk_operation,
 };
-use sqlx::{PgConnection, PgPool};
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool, Postgres, Transaction};
+
+const INSERT_OUTBOX: &str = r#"
+    INSERT INTO service_outbox
+        (id, aggregate_id, topic, state, aggregate_name, headers, format, payload)
+    SELECT * FROM UNNEST
+        ($1::BIGINT[], $2::text[],  $3::text[], $4::text[], $5::text[], $6::text[], $7::text[], $8::bytea[])
+"#;
 
 impl Context {
 	pub fn transaction(&mut self) -> &mut PgConnection {
@@ -14,7 +22,7 @@ impl Context {
 	}
 
 	pub(crate) async fn save_outbox(&mut self) -> Result<(), BaseError> {
-		let outboxes = self.curr_events.iter().filter(|e| e.externally_notifiable()).map(|o| o.outbox()).collect::<Vec<_>>();
+		let outboxes = self.outboxes()?;
 
 		prepare_bulk_operation!(
 			&outboxes,
@@ -22,27 +30,26 @@ impl Context {
 			aggregate_id: String,
 			aggregate_name:String,
 			topic: String,
-			state: String
+			state: String,
+			headers: String,
+			payload: Vec<u8>
 		);
-		sqlx::query(
-			r#"
-            INSERT INTO service_outbox
-                (id, aggregate_id, topic, state, aggregate_name)
-            SELECT * FROM UNNEST
-                ($1::BIGINT[], $2::text[],  $3::text[], $4::text[], $5::text[])
-            "#,
-		)
-		.bind(&id)
-		.bind(&aggregate_id)
-		.bind(&topic)
-		.bind(&state)
-		.bind(&aggregate_name)
-		.execute(self.transaction())
-		.await
-		.map_err(|err| {
-			tracing::error!("failed to insert outbox! {}", err);
-			BaseError::DatabaseError(err.to_string())
-		})?;
+		let format = outboxes.iter().map(|o| o.format.as_str()).collect::<Vec<_>>();
+		sqlx::query(INSERT_OUTBOX)
+			.bind(&id)
+			.bind(&aggregate_id)
+			.bind(&topic)
+			.bind(&state)
+			.bind(&aggregate_name)
+			.bind(&headers)
+			.bind(&format)
+			.bind(&payload)
+			.execute(self.transaction())
+			.await
+			.map_err(|err| {
+				tracing::error!("failed to insert outbox! {}", err);
+				BaseError::DatabaseError(err.to_string().into()).with_query("insert_outbox", INSERT_OUTBOX)
+			})?;
 		Ok(())
 	}
 }
@@ -73,12 +80,18 @@ impl TUnitOfWork for Context {
 	async fn _commit(&mut self) -> Result<(), BaseError> {
 		match self.pg_transaction.take() {
 			None => panic!("Tranasction Has Not Begun!"),
-			Some(trx) => Ok(trx.commit().await?),
+			Some(trx) => {
+				self.savepoints.clear();
+				trx.commit().await?;
+				MessageBus::relay_monitor().staged(self.curr_events.iter().filter(|e| e.externally_notifiable()).count());
+				Ok(())
+			}
 		}
 	}
 
 	async fn rollback(&mut self) -> Result<(), BaseError> {
-		self.curr_events.clear();
+		self.discard_events();
+		self.savepoints.clear();
 		match self.pg_transaction.take() {
 			None => panic!("Tranasction Has Not Begun!"),
 			Some(trx) => Ok(trx.rollback().await?),
@@ -93,13 +106,65 @@ impl TUnitOfWork for Context {
 		}
 	}
 
-	async fn process_internal_events(&mut self) -> Result<(), BaseError> {
-		self.send_internally_notifiable_messages().await;
+	async fn _set_isolation_level(&mut self, isolation_level: IsolationLevel) -> Result<(), BaseError> {
+		let query = format!("SET TRANSACTION ISOLATION LEVEL {}", isolation_level.as_str());
+		let trx = self.pg_transaction.as_mut().ok_or(BaseError::TransactionError)?;
+		sqlx::query(&query).execute(&mut **trx).await.map_err(|err| BaseError::from(err).with_query("set_isolation_level", &query))?;
+		Ok(())
+	}
+
+	async fn savepoint(&mut self, name: &str) -> Result<(), BaseError> {
+		let query = format!("SAVEPOINT {}", savepoint_identifier(name)?);
+		let trx = self.pg_transaction.as_mut().ok_or(BaseError::TransactionError)?;
+		sqlx::query(&query).execute(&mut **trx).await.map_err(|err| BaseError::from(err).with_query("savepoint", &query))?;
+		self.savepoints.push((name.to_string(), self.curr_events.len()));
+		Ok(())
+	}
+
+	async fn rollback_to(&mut self, name: &str) -> Result<(), BaseError> {
+		let query = format!("ROLLBACK TO SAVEPOINT {}", savepoint_identifier(name)?);
+		let trx = self.pg_transaction.as_mut().ok_or(BaseError::TransactionError)?;
+		sqlx::query(&query).execute(&mut **trx).await.map_err(|err| BaseError::from(err).with_query("rollback_to_savepoint", &query))?;
+		// * Events raised by the writes undone must not be staged as outbox nor queued. Like SQL, the savepoint itself is kept.
+		if let Some(position) = self.savepoints.iter().rposition(|(savepoint, _)| savepoint == name) {
+			self.curr_events.truncate(self.savepoints[position].1);
+			self.savepoints.truncate(position + 1);
+		}
 		Ok(())
 	}
 
+	async fn process_internal_events(&mut self) -> Result<(), BaseError> {
+		let sent = self.send_internally_notifiable_messages().await;
+		// * Events of the committed transaction must not be staged again when the context begins another one
+		self.curr_events.clear();
+		sent
+	}
+
 	async fn process_external_events(&mut self) -> Result<(), BaseError> {
 		self.save_outbox().await?;
 		Ok(())
 	}
 }
+
+#[async_trait]
+impl TRequestTransaction for Transaction<'static, Postgres> {
+	async fn commit(self: Box<Self>) -> Result<(), BaseError> {
+		Ok(Transaction::commit(*self).await?)
+	}
+
+	async fn rollback(self: Box<Self>) -> Result<(), BaseError> {
+		Ok(Transaction::rollback(*self).await?)
+	}
+}
+
+// * Savepoint name can't be bound as parameter, so only plain identifier is allowed to be put into query
+fn savepoint_identifier(name: &str) -> Result<&str, BaseError> {
+	let mut chars = name.chars();
+	match chars.next() {
+		Some(first) if (first.is_ascii_alphabetic() || first == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_') => Ok(name),
+		_ => {
+			tracing::error!("Invalid Savepoint Name! {}", name);
+			Err(BaseError::TransactionError)
+		}
+	}
+}
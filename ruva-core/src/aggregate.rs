@@ -1,8 +1,13 @@
 use std::collections::VecDeque;
 
+use chrono::{DateTime, Utc};
+
 use crate::prelude::TEvent;
 
 pub trait TAggregate: Send + Sync + Default {
+	/// Whether repository marks the aggregate deleted instead of removing it. It is set by `#[deleted_at]` field.
+	const SOFT_DELETE: bool = false;
+
 	fn collect_events(&mut self) -> VecDeque<std::sync::Arc<dyn TEvent>> {
 		if !self.events().is_empty() {
 			self.take_events()
@@ -14,4 +19,9 @@ pub trait TAggregate: Send + Sync + Default {
 
 	fn take_events(&mut self) -> std::collections::VecDeque<std::sync::Arc<dyn TEvent>>;
 	fn raise_event(&mut self, event: std::sync::Arc<dyn TEvent>);
+
+	fn deleted_at(&self) -> Option<DateTime<Utc>> {
+		None
+	}
+	fn set_deleted_at(&mut self, _deleted_at: Option<DateTime<Utc>>) {}
 }
@@ -0,0 +1,149 @@
+//! Record of every command received and every event handled by [MessageBus], for compliance.
+//!
+//! Once a sink is given to [MessageBusConfig::with_audit_sink], command is recorded as it is accepted for execution,
+//! that is after it passes validation, and event is recorded as it is dispatched to its handlers.
+//! Record carries correlation id of the request, which commands dispatched from event handlers share with the request they are dispatched in.
+//! Failure of the sink is logged and doesn't fail the command or the event.
+//!
+//! Payload is taken from `redacted_state`, so fields annotated with `#[redact]` are masked:
+//! ```rust,no_run
+//! #[derive(Serialize, Deserialize, Clone, TEvent)]
+//! #[internally_notifiable]
+//! pub struct UserSignedUp {
+//!     pub email: String,
+//!     #[redact]
+//!     pub phone: String,
+//! }
+//!
+//! MessageBus::configure(MessageBusConfig::default().with_audit_sink(JsonLinesAuditSink::open("audit.jsonl").await?));
+//! ```
+//!
+//! [MessageBus]: super::messagebus::MessageBus
+//! [MessageBusConfig::with_audit_sink]: super::messagebus::MessageBusConfig::with_audit_sink
+
+use super::messagebus::MessageBus;
+use crate::prelude::{BaseError, TCommand, TEvent, Timestamp};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+	path::Path,
+	sync::{Arc, Mutex},
+};
+use tokio::io::AsyncWriteExt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+	Command,
+	Event,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+	pub kind: AuditKind,
+	/// Type name of command, or topic of event
+	pub name: String,
+	/// Id shared by messages handled within the same request
+	pub correlation_id: String,
+	/// `redacted_state` of the message
+	pub payload: String,
+	pub timestamp: Timestamp,
+}
+
+/// Destination of [AuditRecord]s
+#[async_trait]
+pub trait TAuditSink: Send + Sync {
+	async fn record_command(&self, record: AuditRecord) -> Result<(), BaseError>;
+	async fn record_event(&self, record: AuditRecord) -> Result<(), BaseError>;
+}
+
+/// Sink that keeps records in memory, to assert on in tests. Clones share the records.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryAuditSink(Arc<Mutex<Vec<AuditRecord>>>);
+
+impl InMemoryAuditSink {
+	pub fn records(&self) -> Vec<AuditRecord> {
+		self.0.lock().unwrap().clone()
+	}
+}
+
+#[async_trait]
+impl TAuditSink for InMemoryAuditSink {
+	async fn record_command(&self, record: AuditRecord) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(record);
+		Ok(())
+	}
+	async fn record_event(&self, record: AuditRecord) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(record);
+		Ok(())
+	}
+}
+
+/// Sink that appends each record to a file as a line of json
+pub struct JsonLinesAuditSink {
+	file: tokio::sync::Mutex<tokio::fs::File>,
+}
+
+impl JsonLinesAuditSink {
+	/// Open `path` for appending, creating it when it doesn't exist
+	pub async fn open(path: impl AsRef<Path>) -> Result<Self, BaseError> {
+		let file = tokio::fs::OpenOptions::new().create(true).append(true).open(path.as_ref()).await.map_err(|err| {
+			tracing::error!("Failed to open audit log {}! Error:{}", path.as_ref().display(), err);
+			BaseError::ServiceError
+		})?;
+		Ok(Self { file: tokio::sync::Mutex::new(file) })
+	}
+
+	async fn append(&self, record: AuditRecord) -> Result<(), BaseError> {
+		let mut line = serde_json::to_vec(&record).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		line.push(b'\n');
+		// * Line is written at once under the lock so that records of concurrent requests don't interleave.
+		// Flushed as well, as tokio file hands the write over to background task and returns before it lands.
+		let mut file = self.file.lock().await;
+		let written: std::io::Result<()> = async {
+			file.write_all(&line).await?;
+			file.flush().await
+		}
+		.await;
+		written.map_err(|err| {
+			tracing::error!("Failed to write audit log! Error:{}", err);
+			BaseError::ServiceError
+		})
+	}
+}
+
+#[async_trait]
+impl TAuditSink for JsonLinesAuditSink {
+	async fn record_command(&self, record: AuditRecord) -> Result<(), BaseError> {
+		self.append(record).await
+	}
+	async fn record_event(&self, record: AuditRecord) -> Result<(), BaseError> {
+		self.append(record).await
+	}
+}
+
+pub(crate) async fn record_command<C: TCommand>(command: &C, correlation_id: &str) {
+	let Some(sink) = MessageBus::config().audit_sink.clone() else {
+		return;
+	};
+	let record = AuditRecord {
+		kind: AuditKind::Command,
+		name: std::any::type_name::<C>().to_string(),
+		correlation_id: correlation_id.to_string(),
+		payload: command.redacted_state(),
+		timestamp: MessageBus::now(),
+	};
+	if let Err(err) = sink.record_command(record).await {
+		tracing::error!("Failed to record command {} in audit log! Error:{:?}", std::any::type_name::<C>(), err);
+	}
+}
+
+pub(crate) async fn record_event(event: &dyn TEvent, topic: &str, correlation_id: &str) {
+	let Some(sink) = MessageBus::config().audit_sink.clone() else {
+		return;
+	};
+	let record = AuditRecord { kind: AuditKind::Event, name: topic.to_string(), correlation_id: correlation_id.to_string(), payload: event.redacted_state(), timestamp: MessageBus::now() };
+	if let Err(err) = sink.record_event(record).await {
+		tracing::error!("Failed to record event {} in audit log! Error:{:?}", topic, err);
+	}
+}
@@ -0,0 +1,83 @@
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc, Mutex, Weak,
+};
+use tokio::sync::Notify;
+
+/// Token that long-running handlers check to stop cooperatively, taken from [ContextManager::cancellation_token].
+/// It follows `CancellationToken` of `tokio-util`, except that it is built on what this crate already depends on.
+///
+/// Token of a request is cancelled when the command times out or shutdown is signaled.
+/// Nothing is aborted by the token itself. Handlers are responsible for checking it, for example:
+/// ```rust,no_run
+/// #[event_handler(ReportRequested)]
+/// async fn build_report(event: ReportRequested, context: HandlerContext) -> Result<(), ServiceError> {
+///     let token = context.cancellation_token();
+///     tokio::select! {
+///         _ = token.cancelled() => Err(BaseError::ShuttingDown.into()),
+///         report = render(event) => store(report).await,
+///     }
+/// }
+/// ```
+///
+/// [ContextManager::cancellation_token]: super::contexts::ContextManager::cancellation_token
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<TokenState>);
+
+#[derive(Default)]
+struct TokenState {
+	cancelled: AtomicBool,
+	notify: Notify,
+	children: Mutex<Vec<Weak<TokenState>>>,
+}
+
+impl CancellationToken {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Token that is cancelled along with this one, while cancelling it leaves this one as it is
+	pub fn child_token(&self) -> Self {
+		let child = Self::default();
+		let mut children = self.0.children.lock().unwrap();
+		// * Checked under the lock so that the child is never missed by `cancel` running concurrently
+		if self.is_cancelled() {
+			child.cancel();
+		}
+		children.retain(|child| child.strong_count() > 0);
+		children.push(Arc::downgrade(&child.0));
+		child
+	}
+
+	/// Cancel this token and its children
+	pub fn cancel(&self) {
+		let children = {
+			let mut children = self.0.children.lock().unwrap();
+			self.0.cancelled.store(true, Ordering::SeqCst);
+			std::mem::take(&mut *children)
+		};
+		self.0.notify.notify_waiters();
+		children.iter().filter_map(Weak::upgrade).for_each(|child| CancellationToken(child).cancel());
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.0.cancelled.load(Ordering::SeqCst)
+	}
+
+	/// Resolves once the token is cancelled
+	pub async fn cancelled(&self) {
+		loop {
+			let notified = self.0.notify.notified();
+			if self.is_cancelled() {
+				return;
+			}
+			notified.await;
+		}
+	}
+}
+
+impl std::fmt::Debug for CancellationToken {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("CancellationToken").field("is_cancelled", &self.is_cancelled()).finish()
+	}
+}
@@ -0,0 +1,125 @@
+//! ### CircuitBreaker
+//! When a dependency that handlers of a topic call is down, handling every event of the topic only to fail hammers the dependency
+//! and holds up the bus for as long as each attempt and its retries take.
+//! [CircuitBreaker] given to [MessageBusConfig::with_circuit_breaker_for] counts failures of the handlers of the topic and, once they reach the threshold,
+//! opens so that the handlers are not run for the cooldown. They fail fast with [BaseError::CircuitOpen] instead, which is dead-lettered
+//! to the sink of [MessageBusConfig::with_dead_letter_sink] like any other failure, or skipped when there is none.
+//! After the cooldown, a single handling is let through as a probe. The breaker closes if it succeeds and opens again for another cooldown if it fails.
+//!
+//! The breaker wraps the handler along with its retries of [MessageBusConfig::with_retry_policies], so retries happen while it is closed
+//! and a handling that fails after running out of retries counts as a single failure. Stop sentinels are not failures.
+//!
+//! Clones of a breaker share its state. Giving clones to the topics whose handlers call the same dependency trips them together.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! let payment_gateway = CircuitBreaker::new(5, Duration::from_secs(30));
+//! MessageBus::configure(
+//!     MessageBusConfig::default()
+//!         .with_circuit_breaker_for("OrderPlaced", payment_gateway.clone())
+//!         .with_circuit_breaker_for("OrderCancelled", payment_gateway),
+//! );
+//! ```
+//!
+//! [MessageBusConfig::with_circuit_breaker_for]: super::messagebus::MessageBusConfig::with_circuit_breaker_for
+//! [MessageBusConfig::with_dead_letter_sink]: super::messagebus::MessageBusConfig::with_dead_letter_sink
+//! [MessageBusConfig::with_retry_policies]: super::messagebus::MessageBusConfig::with_retry_policies
+
+use crate::prelude::BaseError;
+use std::{
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+	/// Handlers run as usual while failures are counted
+	Closed,
+	/// Handlers are not run until the cooldown elapses
+	Open,
+	/// Probe is let through and the others are not run until it finishes
+	HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Phase {
+	Closed { failures: usize },
+	Open { since: Instant },
+	HalfOpen { since: Instant },
+}
+
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+	failure_threshold: usize,
+	cooldown: Duration,
+	phase: Arc<Mutex<Phase>>,
+}
+
+impl CircuitBreaker {
+	/// Open after `failure_threshold` failures in a row, for `cooldown` before probing
+	pub fn new(failure_threshold: usize, cooldown: Duration) -> Self {
+		Self { failure_threshold: failure_threshold.max(1), cooldown, phase: Arc::new(Mutex::new(Phase::Closed { failures: 0 })) }
+	}
+
+	/// State the breaker is in. It stays [CircuitState::Open] after the cooldown until the next handling is let through as a probe.
+	pub fn state(&self) -> CircuitState {
+		match *self.phase.lock().unwrap() {
+			Phase::Closed { .. } => CircuitState::Closed,
+			Phase::Open { .. } => CircuitState::Open,
+			Phase::HalfOpen { .. } => CircuitState::HalfOpen,
+		}
+	}
+
+	/// Whether handling may run, turning half-open when the cooldown has elapsed.
+	fn admit(&self) -> bool {
+		let mut phase = self.phase.lock().unwrap();
+		match *phase {
+			Phase::Closed { .. } => true,
+			// * Probe that never finished, as when it was cancelled, doesn't keep the breaker half-open for good
+			Phase::Open { since } | Phase::HalfOpen { since } if since.elapsed() >= self.cooldown => {
+				*phase = Phase::HalfOpen { since: Instant::now() };
+				true
+			}
+			Phase::Open { .. } | Phase::HalfOpen { .. } => false,
+		}
+	}
+
+	fn record(&self, succeeded: bool) {
+		let mut phase = self.phase.lock().unwrap();
+		*phase = match (*phase, succeeded) {
+			(Phase::Open { .. }, true) => return,
+			(_, true) => Phase::Closed { failures: 0 },
+			(Phase::Closed { failures }, false) if failures + 1 < self.failure_threshold => Phase::Closed { failures: failures + 1 },
+			(Phase::Closed { .. } | Phase::HalfOpen { .. }, false) => {
+				tracing::warn!("Circuit Breaker Opened For {:?}!", self.cooldown);
+				Phase::Open { since: Instant::now() }
+			}
+			(Phase::Open { .. }, false) => return,
+		};
+	}
+}
+
+/// Run `handling` unless the breaker of the topic is open, in which case it fails with [BaseError::CircuitOpen] without running
+pub(crate) async fn handle_with_breaker<E>(handling: impl std::future::Future<Output = Result<(), E>>, topic: &str, breaker: Option<&CircuitBreaker>) -> Result<(), E>
+where
+	E: std::convert::From<BaseError>,
+	BaseError: std::convert::From<E>,
+{
+	let Some(breaker) = breaker else {
+		return handling.await;
+	};
+	if !breaker.admit() {
+		return Err(BaseError::CircuitOpen { topic: topic.to_string() }.into());
+	}
+	match handling.await {
+		Ok(()) => {
+			breaker.record(true);
+			Ok(())
+		}
+		Err(err) => {
+			let err = BaseError::from(err);
+			breaker.record(matches!(err, BaseError::StopSentinel | BaseError::StopSentinelWithEvent(_)));
+			Err(err.into())
+		}
+	}
+}
@@ -1,13 +1,49 @@
-use super::executor::TConnection;
-use crate::{make_smart_pointer, prelude::TEvent};
-use std::{collections::VecDeque, sync::Arc};
+use super::{
+	cancellation::CancellationToken,
+	effect::TEffect,
+	executor::TConnection,
+	messagebus::{EventReport, MessageBus, CORRELATION_ID},
+};
+use crate::{
+	make_smart_pointer,
+	prelude::{BaseError, OutBox, TCommand, TEvent, TRequestTransaction},
+};
+use std::{
+	any::{Any, TypeId},
+	cmp::Reverse,
+	collections::{BTreeMap, HashMap, HashSet, VecDeque},
+	sync::{Arc, RwLock},
+	time::{Duration, Instant},
+};
+use tokio::sync::Notify;
 
 /// Request Context Manager
 /// it lives as long as the request lives
 
 pub struct ContextManager {
-	pub event_queue: VecDeque<Arc<dyn TEvent>>,
+	pub event_queue: EventQueue,
 	pub conn: &'static dyn TConnection,
+	pub(crate) report: EventReport,
+	pub(crate) replaying: bool,
+	pub(crate) commands: VecDeque<Box<dyn TCommand>>,
+	/// Effects declared within the request, performed once it succeeds
+	pub(crate) effects: Vec<Box<dyn TEffect>>,
+	/// Ids of messages queued or handled within the request, kept only when deduplication is enabled
+	pub(crate) seen_message_ids: Option<HashSet<String>>,
+	/// How many times each message was re-enqueued through stop sentinel within the request
+	pub(crate) requeued: HashMap<String, usize>,
+	pub(crate) correlation_id: String,
+	pub(crate) cancellation: CancellationToken,
+	/// Point in time by which events of the request are to be handled, see [MessageBusConfig::with_request_deadline]
+	///
+	/// [MessageBusConfig::with_request_deadline]: super::messagebus::MessageBusConfig::with_request_deadline
+	pub(crate) deadline: Option<Instant>,
+	/// Transaction that command and event handlers of the request write through, settled by [MessageBus] when the request is done
+	pub(crate) transaction: Option<Box<dyn TRequestTransaction>>,
+	/// Whether any event handler failed within the request, other than by stop sentinel
+	pub(crate) handler_failed: bool,
+	/// Values of arbitrary types stashed for later stages of the request, see [Self::insert]
+	extensions: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
 }
 
 pub type AtomicContextManager = Arc<ContextManager>;
@@ -15,7 +51,203 @@ pub type AtomicContextManager = Arc<ContextManager>;
 impl ContextManager {
 	/// Creation of context manager returns context manager AND event receiver
 	pub fn new(conn: &'static dyn TConnection) -> Self {
-		Self { event_queue: VecDeque::new(), conn }
+		let config = MessageBus::config();
+		let event_queue = match config.event_queue_capacity {
+			Some((capacity, policy)) => EventQueue::bounded(capacity, policy),
+			None => EventQueue::default(),
+		};
+		let seen_message_ids = config.event_deduplication.then(HashSet::new);
+		// * Commands dispatched from event handlers carry on correlation id of the request they are dispatched in
+		let correlation_id = CORRELATION_ID.try_with(Clone::clone).unwrap_or_else(|_| uuid::Uuid::new_v4().to_string());
+		let cancellation = MessageBus::shutdown_handle().cancellation_token().child_token();
+		let deadline = config.request_deadline.map(|after| Instant::now() + after);
+		Self {
+			event_queue,
+			conn,
+			report: Default::default(),
+			replaying: false,
+			commands: Default::default(),
+			effects: Default::default(),
+			seen_message_ids,
+			requeued: Default::default(),
+			correlation_id,
+			cancellation,
+			deadline,
+			transaction: None,
+			handler_failed: false,
+			extensions: Default::default(),
+		}
+	}
+
+	/// Queue event raised within the request, respecting capacity of the queue. Event is enriched by [TEventEnricher]s before it is queued.
+	/// Under [OverflowPolicy::Block], it waits until an event is popped from the queue.
+	///
+	/// [TEventEnricher]: super::enricher::TEventEnricher
+	pub async fn push_event(self: &Arc<Self>, mut event: Arc<dyn TEvent>) -> Result<(), BaseError> {
+		self.enrich(&mut event);
+		self.push_enriched_event(event).await
+	}
+
+	/// Run enrichers registered on [MessageBus] in order
+	pub(crate) fn enrich(&self, event: &mut Arc<dyn TEvent>) {
+		let enrichers = &MessageBus::config().event_enrichers;
+		if enrichers.is_empty() {
+			return;
+		}
+		match Arc::get_mut(event) {
+			Some(event) => enrichers.iter().for_each(|enricher| enricher.enrich(event, self)),
+			None => tracing::warn!("{} Is Not Enriched As It Is Shared!", event.metadata().topic),
+		}
+	}
+
+	/// Mark the message seen within the request, returning false if it was already queued or handled.
+	/// Messages without [TEvent::message_id] are always new.
+	pub(crate) fn mark_seen(self: &Arc<Self>, event: &Arc<dyn TEvent>) -> bool {
+		match (self.get_mut().seen_message_ids.as_mut(), event.message_id()) {
+			(Some(seen), Some(message_id)) => seen.insert(message_id),
+			_ => true,
+		}
+	}
+
+	async fn push_enriched_event(self: &Arc<Self>, event: Arc<dyn TEvent>) -> Result<(), BaseError> {
+		if !self.mark_seen(&event) {
+			tracing::warn!("Duplicate {:?} Is Dropped! Message Id:{:?}", event, event.message_id());
+			return Ok(());
+		}
+		loop {
+			let drained = self.event_queue.drained.notified();
+			if !self.event_queue.is_full() {
+				self.get_mut().push_back(event);
+				return Ok(());
+			}
+			match self.event_queue.overflow_policy {
+				OverflowPolicy::Reject => {
+					tracing::error!("Event Queue Is Full! {:?} Is Rejected.", event);
+					return Err(BaseError::QueueFull);
+				}
+				OverflowPolicy::Block => drained.await,
+			}
+		}
+	}
+
+	/// Queue command to be handled once handlers of the event being handled return, before the next event is handled.
+	/// Command is routed to the handler registered with `init_dyn_command_handler!` or `register_uow_services!`, just like [TMessageBus::execute_and_wait].
+	///
+	/// ## Transaction boundary
+	/// Dispatched command is handled in its own [ContextManager] and committed or rolled back by its own unit of work.
+	/// By then, the command that raised the event has already been committed, so failure of dispatched command doesn't undo it.
+	/// Failure is logged and doesn't stop handling of the remaining events.
+	///
+	/// Chain of commands dispatched from events they raise is cut once it gets deeper than [MessageBusConfig::with_max_command_depth].
+	/// Commands dispatched while events are replayed are discarded.
+	///
+	/// [TMessageBus::execute_and_wait]: super::messagebus::TMessageBus::execute_and_wait
+	/// [MessageBusConfig::with_max_command_depth]: super::messagebus::MessageBusConfig::with_max_command_depth
+	pub fn dispatch_command(self: &Arc<Self>, command: Box<dyn TCommand>) {
+		self.get_mut().commands.push_back(command);
+	}
+
+	/// Declare side effect for the [TEffectHandler] registered for its type to perform once the request succeeds, instead of performing it inline.
+	/// Effects declared while events are replayed are discarded. See [effect](super::effect) module for when effects are performed.
+	///
+	/// [TEffectHandler]: super::effect::TEffectHandler
+	pub fn declare_effect(self: &Arc<Self>, effect: impl TEffect) {
+		self.get_mut().effects.push(Box::new(effect));
+	}
+
+	/// Effects declared within the request and not performed yet, for tests to assert on what handlers declared
+	pub fn declared_effects(&self) -> &[Box<dyn TEffect>] {
+		&self.effects
+	}
+
+	/// Whether events are being replayed from history rather than raised live.
+	/// Handlers with side effects that must not be repeated, such as sending emails, should skip them when it is set.
+	pub fn is_replaying(&self) -> bool {
+		self.replaying
+	}
+
+	/// Id shared by messages handled within the request, as recorded by [TAuditSink]
+	///
+	/// [TAuditSink]: super::audit::TAuditSink
+	pub fn correlation_id(&self) -> &str {
+		&self.correlation_id
+	}
+
+	/// Point in time by which events of the request are to be handled, if [MessageBusConfig::with_request_deadline] is given
+	///
+	/// [MessageBusConfig::with_request_deadline]: super::messagebus::MessageBusConfig::with_request_deadline
+	pub fn deadline(&self) -> Option<Instant> {
+		self.deadline
+	}
+
+	/// Time left until the deadline, which handlers can bound their own operations with, as in `tokio::time::timeout(remaining, call)`.
+	/// It is zero once the deadline has passed, and `None` when there is no deadline.
+	pub fn remaining(&self) -> Option<Duration> {
+		self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+	}
+
+	pub(crate) fn is_past_deadline(&self) -> bool {
+		self.remaining() == Some(Duration::ZERO)
+	}
+
+	/// Fail with [BaseError::DeadlineExceeded] once the deadline has passed
+	pub(crate) fn ensure_within_deadline(&self) -> Result<(), BaseError> {
+		if self.is_past_deadline() {
+			return Err(BaseError::DeadlineExceeded);
+		}
+		Ok(())
+	}
+
+	/// Cancelled when the command of the request times out or shutdown is signaled. Handlers that take long should stop once it is cancelled.
+	/// See [CancellationToken] for how to check it.
+	pub fn cancellation_token(&self) -> CancellationToken {
+		self.cancellation.clone()
+	}
+
+	/// Keep `transaction` for event handlers of the request to write through, leaving it to [MessageBus] to commit or roll back.
+	/// Transaction shared before is dropped. See [TRequestTransaction].
+	pub fn share_transaction(self: &Arc<Self>, transaction: impl TRequestTransaction) {
+		if self.get_mut().transaction.replace(Box::new(transaction)).is_some() {
+			tracing::warn!("Transaction Shared Before Is Dropped!");
+		}
+	}
+
+	/// Transaction shared by [Self::share_transaction], if it is of type `T`
+	pub fn transaction<T: TRequestTransaction>(self: &Arc<Self>) -> Option<&mut T> {
+		self.get_mut().transaction.as_mut().and_then(|transaction| transaction.downcast_mut())
+	}
+
+	/// Stash `value` for later stages of the request, such as user authenticated by [TRequestLifecycle] for handlers to read with [Self::get].
+	/// Values are keyed by their type, so value of the same type inserted before is replaced and returned.
+	///
+	/// Extensions are behind [RwLock], so that they are inserted through shared reference, as from lifecycle hooks and [TEventEnricher]s,
+	/// and read by handlers of the request running concurrently. The lock is held only while the map is accessed, not while the value is used.
+	///
+	/// [TRequestLifecycle]: super::lifecycle::TRequestLifecycle
+	/// [TEventEnricher]: super::enricher::TEventEnricher
+	pub fn insert<T: Any + Send + Sync>(&self, value: T) -> Option<Arc<T>> {
+		let previous = self.extensions.write().unwrap().insert(TypeId::of::<T>(), Arc::new(value))?;
+		previous.downcast().ok()
+	}
+
+	/// Value of type `T` stashed by [Self::insert]
+	pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+		let value = Arc::clone(self.extensions.read().unwrap().get(&TypeId::of::<T>())?);
+		value.downcast().ok()
+	}
+
+	/// Count re-enqueue of the message, returning [BaseError::PoisonMessage] once it is re-enqueued more than `threshold` times.
+	/// Messages without [TEvent::message_id] are not counted.
+	pub(crate) fn count_requeue(self: &Arc<Self>, event: &dyn TEvent, threshold: usize) -> Result<(), BaseError> {
+		let Some(message_id) = event.message_id() else {
+			return Ok(());
+		};
+		let requeued = self.get_mut().requeued.entry(message_id.clone()).or_default();
+		*requeued += 1;
+		if *requeued > threshold {
+			return Err(BaseError::PoisonMessage { message_id, requeued: *requeued });
+		}
+		Ok(())
 	}
 
 	/// SAFETY: This is safe because we are sure this method is used only in the context of command and event handling
@@ -24,7 +256,120 @@ impl ContextManager {
 	}
 }
 
-make_smart_pointer!(ContextManager, VecDeque<Arc<dyn TEvent>>, event_queue);
+make_smart_pointer!(ContextManager, EventQueue, event_queue);
+
+/// Context that `#[event_handler]` fn can take in place of [AtomicContextManager], raising events and commands without boxing them.
+/// It derefs to [AtomicContextManager], so whatever handlers did with it works the same.
+/// ## Example
+/// ```rust,no_run
+/// #[event_handler(OrderPlaced)]
+/// async fn reserve_stock(event: OrderPlaced, context: HandlerContext) -> Result<(), ServiceError> {
+///     context.emit(StockReserved { order_id: event.id }).await?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HandlerContext(AtomicContextManager);
+
+impl HandlerContext {
+	/// Queue event to be handled within the same request, as [ContextManager::push_event] does
+	pub async fn emit(&self, event: impl TEvent + 'static) -> Result<(), BaseError> {
+		self.0.push_event(Arc::new(event)).await
+	}
+
+	/// Queue command, as [ContextManager::dispatch_command] does
+	pub fn dispatch(&self, command: impl TCommand + 'static) {
+		self.0.dispatch_command(Box::new(command))
+	}
+
+	pub fn into_inner(self) -> AtomicContextManager {
+		self.0
+	}
+}
+
+impl From<AtomicContextManager> for HandlerContext {
+	fn from(context_manager: AtomicContextManager) -> Self {
+		Self(context_manager)
+	}
+}
+
+impl std::ops::Deref for HandlerContext {
+	type Target = AtomicContextManager;
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+/// Queue of events to be handled within a request.
+/// Events of higher [TEvent::priority] are popped first, and events of the same priority are popped in the order they were pushed.
+/// When every event has default priority, it behaves just like `VecDeque`.
+///
+/// It is unbounded by default. Capacity of bounded queue is enforced on [ContextManager::push_event], through which events raised by handlers are queued.
+#[derive(Default)]
+pub struct EventQueue {
+	queues: BTreeMap<Reverse<i8>, VecDeque<Arc<dyn TEvent>>>,
+	len: usize,
+	capacity: Option<usize>,
+	overflow_policy: OverflowPolicy,
+	drained: Notify,
+}
+
+/// What to do with an event raised when [EventQueue] is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+	/// Fail with [BaseError::QueueFull]
+	#[default]
+	Reject,
+	/// Wait until an event is popped from the queue.
+	/// As the queue is drained only after the handler that raised events returns, it makes progress only when events are raised concurrently with handling,
+	/// such as from a spawned task. Otherwise it waits until event handler timeout expires.
+	Block,
+}
+
+impl EventQueue {
+	pub fn bounded(capacity: usize, overflow_policy: OverflowPolicy) -> Self {
+		Self { capacity: Some(capacity), overflow_policy, ..Default::default() }
+	}
+
+	pub fn is_full(&self) -> bool {
+		self.capacity.is_some_and(|capacity| self.len >= capacity)
+	}
+
+	pub fn push_back(&mut self, event: Arc<dyn TEvent>) {
+		self.queues.entry(Reverse(event.priority())).or_default().push_back(event);
+		self.len += 1;
+	}
+
+	pub fn pop_front(&mut self) -> Option<Arc<dyn TEvent>> {
+		let mut queue = self.queues.first_entry()?;
+		let event = queue.get_mut().pop_front();
+		if queue.get().is_empty() {
+			queue.remove();
+		}
+		self.len -= 1;
+		self.drained.notify_waiters();
+		event
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Iterate events in the order they would be popped
+	pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn TEvent>> {
+		self.queues.values().flatten()
+	}
+}
+
+impl Extend<Arc<dyn TEvent>> for EventQueue {
+	fn extend<T: IntoIterator<Item = Arc<dyn TEvent>>>(&mut self, iter: T) {
+		iter.into_iter().for_each(|event| self.push_back(event));
+	}
+}
 
 /// Local context
 /// it lasts only until logical unit of operation is done
@@ -34,6 +379,9 @@ pub struct Context {
 
 	#[cfg(feature = "sqlx-postgres")]
 	pub(crate) pg_transaction: Option<sqlx::Transaction<'static, sqlx::Postgres>>,
+	/// Savepoints along with the number of events collected when they were set
+	#[cfg(feature = "sqlx-postgres")]
+	pub(crate) savepoints: Vec<(String, usize)>,
 }
 
 impl Context {
@@ -43,17 +391,56 @@ impl Context {
 			super_ctx,
 			#[cfg(feature = "sqlx-postgres")]
 			pg_transaction: None,
+			#[cfg(feature = "sqlx-postgres")]
+			savepoints: Default::default(),
+		}
+	}
+
+	/// Outboxes of externally notifiable events collected so far.
+	/// Unit of work must write them through the transaction that writes the aggregates, in `process_external_events`,
+	/// so that they are committed or rolled back together. Fails with [BaseError::SerializationError] when any of them could not be serialized.
+	///
+	/// With [MessageBusConfig::with_outbox_batching], outboxes of the same topic are coalesced into one, in the order their topics first appear.
+	///
+	/// [MessageBusConfig::with_outbox_batching]: crate::prelude::MessageBusConfig::with_outbox_batching
+	pub fn outboxes(&self) -> Result<Vec<OutBox>, BaseError> {
+		let outboxes = self.curr_events.iter().filter(|e| e.externally_notifiable()).map(|e| e.try_outbox()).collect::<Result<Vec<_>, _>>()?;
+		if !MessageBus::config().outbox_batching {
+			return Ok(outboxes);
 		}
+		let mut topics: Vec<(String, Vec<OutBox>)> = vec![];
+		for outbox in outboxes {
+			match topics.iter_mut().find(|(topic, _)| *topic == outbox.topic) {
+				Some((_, batch)) => batch.push(outbox),
+				None => topics.push((outbox.topic.clone(), vec![outbox])),
+			}
+		}
+		// * Outbox of a single event is left as it is
+		Ok(topics.into_iter().filter_map(|(_, mut batch)| if batch.len() == 1 { batch.pop() } else { OutBox::batch(batch) }).collect())
+	}
+
+	/// Drop events collected so far, so that neither outboxes are staged nor events are queued for the writes rolled back
+	pub fn discard_events(&mut self) {
+		self.curr_events.clear();
+	}
+
+	/// Stage event to be queued once the transaction commits. It never fires when the transaction rolls back or fails to commit,
+	/// unlike the one queued right away with [ContextManager::push_event].
+	pub fn emit_on_commit(&mut self, event: impl TEvent + 'static) {
+		self.set_current_events(VecDeque::from([Arc::new(event) as Arc<dyn TEvent>]));
 	}
 
 	pub fn event_hook(&mut self, aggregate: &mut impl crate::prelude::TAggregate) {
 		self.set_current_events(aggregate.take_events());
 	}
 
-	pub async fn send_internally_notifiable_messages(&mut self) {
-		// SAFETY: This is safe because we are sure that the context manager is not dropped
-
-		self.curr_events.iter().filter(|e| e.internally_notifiable()).for_each(|e| self.super_ctx.get_mut().push_back(e.clone()));
+	/// Fails with [BaseError::QueueFull] when event queue is full under [OverflowPolicy::Reject]
+	pub async fn send_internally_notifiable_messages(&mut self) -> Result<(), BaseError> {
+		// * Events are enriched as they are set on the context
+		for event in self.curr_events.iter().filter(|e| e.internally_notifiable()) {
+			self.super_ctx.push_enriched_event(event.clone()).await?;
+		}
+		Ok(())
 	}
 }
 
@@ -62,8 +449,14 @@ pub trait TSetCurrentEvents: Send + Sync {
 }
 
 impl TSetCurrentEvents for Context {
+	/// Events are enriched by [TEventEnricher]s here so that both outbox and event queue see the enriched ones
+	///
+	/// [TEventEnricher]: super::enricher::TEventEnricher
 	fn set_current_events(&mut self, events: VecDeque<std::sync::Arc<dyn TEvent>>) {
-		self.curr_events.extend(events)
+		self.curr_events.extend(events.into_iter().map(|mut event| {
+			self.super_ctx.enrich(&mut event);
+			event
+		}))
 	}
 }
 
@@ -99,3 +492,73 @@ async fn test_context_managers() {
 	let events = context_manager.iter().map(|e| e.downcast_ref::<CustomEvent>().unwrap().0).collect::<Vec<_>>();
 	assert_eq!(events, (0..count).collect::<Vec<_>>());
 }
+
+#[test]
+fn test_event_queue_pops_higher_priority_first() {
+	#[derive(Debug)]
+	struct PrioritizedEvent(i8, usize);
+	impl TEvent for PrioritizedEvent {
+		fn priority(&self) -> i8 {
+			self.0
+		}
+		fn state(&self) -> String {
+			"state".to_string()
+		}
+	}
+
+	let mut queue = EventQueue::default();
+	queue.extend([(0, 0), (1, 1), (0, 2), (-1, 3), (1, 4)].map(|(priority, order)| Arc::new(PrioritizedEvent(priority, order)) as Arc<dyn TEvent>));
+
+	assert_eq!(queue.len(), 5);
+	let popped = std::iter::from_fn(|| queue.pop_front()).map(|e| e.downcast_ref::<PrioritizedEvent>().unwrap().1).collect::<Vec<_>>();
+	assert_eq!(popped, vec![1, 4, 0, 2, 3]);
+	assert!(queue.is_empty());
+}
+
+#[cfg(test)]
+fn bounded_context_manager(capacity: usize, policy: OverflowPolicy) -> Arc<ContextManager> {
+	struct CustomConnection;
+	impl TConnection for CustomConnection {}
+	let mut context_manager = ContextManager::new(&CustomConnection);
+	context_manager.event_queue = EventQueue::bounded(capacity, policy);
+	Arc::new(context_manager)
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct OverflowingEvent;
+#[cfg(test)]
+impl TEvent for OverflowingEvent {
+	fn state(&self) -> String {
+		"state".to_string()
+	}
+}
+
+#[tokio::test]
+async fn test_bounded_event_queue_rejects_overflow() {
+	let context_manager = bounded_context_manager(2, OverflowPolicy::Reject);
+
+	context_manager.push_event(Arc::new(OverflowingEvent)).await.unwrap();
+	context_manager.push_event(Arc::new(OverflowingEvent)).await.unwrap();
+	let result = context_manager.push_event(Arc::new(OverflowingEvent)).await;
+
+	assert!(matches!(result, Err(BaseError::QueueFull)));
+	assert_eq!(context_manager.len(), 2);
+}
+
+#[tokio::test]
+async fn test_bounded_event_queue_blocks_until_drained() {
+	let context_manager = bounded_context_manager(1, OverflowPolicy::Block);
+	context_manager.push_event(Arc::new(OverflowingEvent)).await.unwrap();
+
+	let producer = tokio::spawn({
+		let context_manager = Arc::clone(&context_manager);
+		async move { context_manager.push_event(Arc::new(OverflowingEvent)).await }
+	});
+	tokio::task::yield_now().await;
+	assert!(!producer.is_finished());
+
+	context_manager.get_mut().pop_front();
+	producer.await.unwrap().unwrap();
+	assert_eq!(context_manager.len(), 1);
+}
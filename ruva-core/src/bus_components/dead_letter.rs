@@ -0,0 +1,100 @@
+use crate::{
+	bus_components::{executor::TConnection, messagebus::TEventBus},
+	prelude::ApplicationError,
+	responses::BaseError,
+	serialization::{EventDeserializers, SerFormat},
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+/// Message that could not be handled, kept with the reason for later inspection.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+	pub topic: String,
+	pub payload: Vec<u8>,
+	pub reason: String,
+	/// Index of the handler that failed, as it is given in [HandlerResults]. It is `None` when the message failed before reaching handlers,
+	/// for example in deserialization.
+	///
+	/// [HandlerResults]: crate::bus_components::messagebus::HandlerResults
+	pub handler: Option<usize>,
+	/// Where the message was in the message broker along with its headers, such as partition and offset of Kafka record.
+	/// It is empty when the message didn't come from message broker.
+	pub metadata: HashMap<String, String>,
+}
+
+/// What consumer drivers do with message that can't be deserialized into event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeserializationErrorStrategy {
+	/// Log the error and move past the message
+	SkipAndLog,
+	/// Send the message as it was received to dead letter sink, along with the error and where it was in the message broker, and move past it
+	#[default]
+	DeadLetter,
+	/// Stop the consumer with the error, leaving the message to be consumed again once the cause is fixed
+	Halt,
+}
+
+/// Destination of messages that can't be processed.
+#[async_trait]
+pub trait TDeadLetterSink: Send + Sync {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError>;
+}
+
+/// Outcome of [TDeadLetterStore::replay_dead_letters]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeadLetterReplay {
+	/// Dead letters handled without error and removed from the store
+	pub replayed: usize,
+	/// Dead letters that failed again and are left in the store
+	pub failed: usize,
+}
+
+/// Sink that keeps dead letters so that they can be replayed once the cause of the failure is fixed.
+/// ## Example
+/// ```rust,no_run
+/// let deserializers = EventDeserializers::default().register::<AccountCreated>("AccountCreated");
+/// let replay = store.replay_dead_letters::<YourServiceError, _>(|dead_letter| dead_letter.topic == "AccountCreated", &deserializers, &MessageBus, &CONNECTION).await?;
+/// ```
+/// With `local` feature, it is implemented with `#[async_trait(?Send)]`, as replay awaits event handlers that are not `Send`.
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
+pub trait TDeadLetterStore: TDeadLetterSink {
+	/// Stored dead letters along with the id they are kept under
+	async fn dead_letters(&self) -> Result<Vec<(i64, DeadLetter)>, BaseError>;
+	async fn remove(&self, id: i64) -> Result<(), BaseError>;
+
+	/// Feed dead letters selected by `filter` back through [TEventBus::handle_event_with_report] one after another.
+	/// Dead letter is removed once the event and the events it raised are handled without error. The one that fails again is left as it is.
+	/// Payload is deserialized as json, which is the format messages are dead-lettered in unless they specify otherwise.
+	async fn replay_dead_letters<E, F>(&self, filter: F, deserializers: &EventDeserializers, bus: &(impl TEventBus<E> + Sync), conn: &'static dyn TConnection) -> Result<DeadLetterReplay, E>
+	where
+		Self: Sized,
+		F: Fn(&DeadLetter) -> bool + Send + Sync,
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let mut replay = DeadLetterReplay::default();
+		for (id, dead_letter) in self.dead_letters().await? {
+			if !filter(&dead_letter) {
+				continue;
+			}
+			let handled = match deserializers.deserialize(&dead_letter.topic, &dead_letter.payload, SerFormat::Json) {
+				Ok(event) => bus.handle_event_with_report(event, conn).await.map(|report| report.failed == 0),
+				Err(err) => Err(err.into()),
+			};
+			match handled {
+				Ok(true) => {
+					self.remove(id).await?;
+					replay.replayed += 1;
+				}
+				Ok(false) => replay.failed += 1,
+				Err(err) => {
+					tracing::error!("Failed to replay dead letter {} of {}! Error:{:?}", id, dead_letter.topic, err);
+					replay.failed += 1;
+				}
+			}
+		}
+		Ok(replay)
+	}
+}
@@ -0,0 +1,144 @@
+//! ### DynamicMessageBus
+//! Handlers of [MessageBus] are collected into `&'static` maps the first time they are looked up, so they are fixed for the life of the process.
+//! [DynamicMessageBus] keeps its handlers behind [RwLock] instead, so that they are subscribed, unsubscribed and replaced at runtime,
+//! as when plugins are loaded and unloaded or a test swaps a handler for a fake.
+//!
+//! Events raised by commands and handlers are handled with the handlers subscribed to the bus at the moment each event is taken from the queue.
+//! Handlers of an event run one after another in the order they are subscribed, and the first failure stops the request.
+//! Only handlers are looked up dynamically; retry policies, stop sentinels and the other settings of [MessageBusConfig] don't apply.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! let bus = DynamicMessageBus::<ServiceResponse, ServiceError>::default();
+//! let id = bus.subscribe_event(|event: OrderPlaced, context: AtomicContextManager| notify(event, context));
+//! bus.replace_command(|command: PlaceOrder, context: AtomicContextManager| place_order(command, context));
+//!
+//! let res = bus.execute(PlaceOrder { .. }, &CONNECTION).await?;
+//! bus.unsubscribe_event::<OrderPlaced>(id);
+//! ```
+//!
+//! [MessageBus]: super::messagebus::MessageBus
+//! [MessageBusConfig]: super::messagebus::MessageBusConfig
+
+use super::contexts::{AtomicContextManager, ContextManager};
+use super::executor::TConnection;
+use super::handler::{Handler, MaybeSend, TDowncastCommand, TDowncastEvent};
+use super::messagebus::CommandFuture;
+use crate::prelude::{BaseError, TCommand, TEvent};
+use std::{
+	any::TypeId,
+	future::Future,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, RwLock,
+	},
+};
+
+/// Given when handler is subscribed, to unsubscribe it with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+type EventHandlers<E> = hashbrown::HashMap<TypeId, Vec<(HandlerId, Arc<Handler<E>>)>>;
+type CommandHandler<R, E> = Arc<dyn Fn(Box<dyn TCommand>, AtomicContextManager) -> CommandFuture<Result<R, E>> + Send + Sync>;
+
+pub struct DynamicMessageBus<R, E> {
+	event_handlers: RwLock<EventHandlers<E>>,
+	command_handlers: RwLock<hashbrown::HashMap<TypeId, CommandHandler<R, E>>>,
+	next_id: AtomicU64,
+}
+
+impl<R, E> Default for DynamicMessageBus<R, E> {
+	fn default() -> Self {
+		Self { event_handlers: Default::default(), command_handlers: Default::default(), next_id: AtomicU64::new(0) }
+	}
+}
+
+impl<R, E> DynamicMessageBus<R, E>
+where
+	R: 'static,
+	E: std::convert::From<BaseError> + std::fmt::Debug + 'static,
+{
+	/// Subscribe `handler` to events of type `Ev`, after the handlers already subscribed to them
+	pub fn subscribe_event<Ev, C, F, Fut>(&self, handler: F) -> HandlerId
+	where
+		Ev: TEvent + Clone,
+		C: From<AtomicContextManager>,
+		F: Fn(Ev, C) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<(), E>> + MaybeSend + 'static,
+	{
+		let handler: Handler<E> = Box::new(move |event, context_manager| match event.downcast_event::<Ev>() {
+			Ok(event) => Box::pin(handler(event, context_manager.into())),
+			Err(err) => Box::pin(async move { Err(err.into()) }),
+		});
+		let id = HandlerId(self.next_id.fetch_add(1, Ordering::Relaxed));
+		self.event_handlers.write().unwrap().entry(TypeId::of::<Ev>()).or_default().push((id, Arc::new(handler)));
+		id
+	}
+
+	/// Returns false when no handler of `Ev` has the id, as when it is already unsubscribed.
+	/// Event being handled when it is unsubscribed is still handled by it.
+	pub fn unsubscribe_event<Ev: TEvent>(&self, id: HandlerId) -> bool {
+		let mut event_handlers = self.event_handlers.write().unwrap();
+		let Some(handlers) = event_handlers.get_mut(&TypeId::of::<Ev>()) else {
+			return false;
+		};
+		let subscribed = handlers.len();
+		handlers.retain(|(handler_id, _)| *handler_id != id);
+		subscribed != handlers.len()
+	}
+
+	/// Handle commands of type `C` with `handler`, replacing the one that handled them so far. Returns whether there was one.
+	pub fn replace_command<C, Ctx, F, Fut>(&self, handler: F) -> bool
+	where
+		C: TCommand,
+		Ctx: From<AtomicContextManager>,
+		F: Fn(C, Ctx) -> Fut + Send + Sync + 'static,
+		Fut: Future<Output = Result<R, E>> + MaybeSend + 'static,
+	{
+		let handler: CommandHandler<R, E> = Arc::new(move |command, context_manager| match command.downcast_command::<C>() {
+			Ok(command) => Box::pin(handler(command, context_manager.into())),
+			Err(err) => Box::pin(async move { Err(err.into()) }),
+		});
+		self.command_handlers.write().unwrap().insert(TypeId::of::<C>(), handler).is_some()
+	}
+
+	/// Returns false when commands of type `C` had no handler
+	pub fn remove_command<C: TCommand>(&self) -> bool {
+		self.command_handlers.write().unwrap().remove(&TypeId::of::<C>()).is_some()
+	}
+
+	/// Handle command and then the events it raised. Returns [BaseError::CommandNotFound] when no handler is registered for the command.
+	pub async fn execute<C: TCommand>(&self, command: C, conn: &'static dyn TConnection) -> Result<R, E> {
+		// * Handler is cloned out of the map so that the lock is not held while it runs
+		let Some(handler) = self.command_handlers.read().unwrap().get(&TypeId::of::<C>()).cloned() else {
+			tracing::error!("No Handler Is Registered For Command {}! {:?}", command.command_name(), command);
+			return Err(BaseError::CommandNotFound(command.command_name().to_string()).into());
+		};
+		let context_manager = Arc::new(ContextManager::new(conn));
+		let res = handler(Box::new(command), Arc::clone(&context_manager)).await?;
+		self.handle_events(&context_manager).await?;
+		Ok(res)
+	}
+
+	/// Handle event and then the events raised while handling it. Event no handler is subscribed to is skipped.
+	pub async fn handle_event(&self, event: Arc<dyn TEvent>, conn: &'static dyn TConnection) -> Result<(), E> {
+		let context_manager = Arc::new(ContextManager::new(conn));
+		context_manager.get_mut().push_back(event);
+		self.handle_events(&context_manager).await
+	}
+
+	async fn handle_events(&self, context_manager: &AtomicContextManager) -> Result<(), E> {
+		while let Some(event) = context_manager.get_mut().pop_front() {
+			// * `as_any` is required. `type_id` of `Arc<dyn TEvent>` itself is not that of the event.
+			let handlers = self.event_handlers.read().unwrap().get(&event.as_any().type_id()).map(|handlers| handlers.iter().map(|(_, handler)| Arc::clone(handler)).collect::<Vec<_>>());
+			let Some(handlers) = handlers.filter(|handlers| !handlers.is_empty()) else {
+				tracing::warn!("No Handler Is Subscribed To {}!", event.metadata().topic);
+				continue;
+			};
+			for handler in handlers {
+				handler(Arc::clone(&event), Arc::clone(context_manager)).await.inspect_err(|err| tracing::error!("{:?}", err))?;
+			}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,91 @@
+//! ### Effect
+//! Handler that sends email or enqueues job inline can't be tested without the mail server or the job queue, or fakes standing in for them.
+//! Instead, it may declare what is to be done as [TEffect] with [ContextManager::declare_effect], just as it raises events,
+//! and leave performing it to the [TEffectHandler] registered for the type of the effect with [MessageBusConfig::with_effect_handler].
+//! Test of the handler then asserts on [ContextManager::declared_effects] without any I/O.
+//!
+//! Effects declared by command handler and event handlers of a request are performed once its events are all handled
+//! and its shared transaction is committed, in the order they were declared. Effects of a request that failed are discarded,
+//! as are the ones declared while events are replayed. Batch with [BatchContext::Shared] is the exception, whose effects are performed
+//! even when some of its commands failed, as the others are committed on their own.
+//! Failure in performing an effect is logged and doesn't fail the request, which is already committed by then.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! #[derive(Debug)]
+//! struct SendEmail { to: String, body: String }
+//! impl TEffect for SendEmail {}
+//!
+//! #[event_handler(OrderPlaced)]
+//! async fn notify_customer(event: OrderPlaced, context: AtomicContextManager) -> Result<(), ServiceError> {
+//!     context.declare_effect(SendEmail { to: event.email, body: "Thank you for your order!".into() });
+//!     Ok(())
+//! }
+//!
+//! struct Mailer;
+//! #[async_trait]
+//! impl TEffectHandler<SendEmail> for Mailer {
+//!     async fn perform(&self, effect: SendEmail) -> Result<(), BaseError> {
+//!         SMTP.send(&effect.to, &effect.body).await.map_err(|_| BaseError::ServiceError)
+//!     }
+//! }
+//!
+//! MessageBus::configure(MessageBusConfig::default().with_effect_handler(Mailer));
+//! ```
+//!
+//! [BatchContext::Shared]: super::messagebus::BatchContext::Shared
+//! [ContextManager::declare_effect]: super::contexts::ContextManager::declare_effect
+//! [ContextManager::declared_effects]: super::contexts::ContextManager::declared_effects
+//! [MessageBusConfig::with_effect_handler]: super::messagebus::MessageBusConfig::with_effect_handler
+
+use super::contexts::AtomicContextManager;
+use super::messagebus::MessageBus;
+use crate::responses::BaseError;
+use async_trait::async_trait;
+use downcast_rs::{impl_downcast, Downcast};
+use std::{fmt::Debug, marker::PhantomData};
+
+/// Side effect declared by handler, performed by the [TEffectHandler] registered for its type
+pub trait TEffect: 'static + Send + Sync + Debug + Downcast {}
+impl_downcast!(TEffect);
+
+#[async_trait]
+pub trait TEffectHandler<F: TEffect>: Send + Sync {
+	async fn perform(&self, effect: F) -> Result<(), BaseError>;
+}
+
+/// [TEffectHandler] of any effect type, so that handlers of different types are kept together
+#[async_trait]
+pub(crate) trait TErasedEffectHandler: Send + Sync {
+	async fn perform(&self, effect: Box<dyn TEffect>) -> Result<(), BaseError>;
+}
+
+pub(crate) struct TypedEffectHandler<F, H>(pub(crate) H, pub(crate) PhantomData<fn(F)>);
+
+#[async_trait]
+impl<F: TEffect, H: TEffectHandler<F>> TErasedEffectHandler for TypedEffectHandler<F, H> {
+	async fn perform(&self, effect: Box<dyn TEffect>) -> Result<(), BaseError> {
+		// * Handlers are keyed by type id of the effect, so downcast doesn't fail
+		let effect = effect.downcast::<F>().map_err(|_| BaseError::ServiceError)?;
+		self.0.perform(*effect).await
+	}
+}
+
+/// Perform effects declared within the request one after another, draining them from the context manager
+pub(crate) async fn perform_effects(context_manager: &AtomicContextManager) {
+	let effects = std::mem::take(&mut context_manager.get_mut().effects);
+	if effects.is_empty() {
+		return;
+	}
+	let config = MessageBus::config();
+	for effect in effects {
+		// * Deref is required. `Box` itself is `Any` as well, whose type id is not that of the effect.
+		let Some(handler) = config.effect_handlers.get(&(*effect).as_any().type_id()) else {
+			tracing::warn!("No Effect Handler Registered! Effect:{:?}", effect);
+			continue;
+		};
+		if let Err(err) = handler.perform(effect).await {
+			tracing::error!("Error Occurred While Performing Effect! Error:{:?}", err);
+		}
+	}
+}
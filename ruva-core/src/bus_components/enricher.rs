@@ -0,0 +1,25 @@
+use super::contexts::ContextManager;
+use crate::prelude::TEvent;
+
+/// Stamp events raised within a request with what every event should carry, such as tenant or correlation id, without touching each handler.
+/// It runs once per event as the event enters the request, either through [Context] or [ContextManager::push_event].
+/// Enrichers run in the order they were registered with [MessageBusConfig::with_event_enricher].
+///
+/// Only headers can be changed on events that don't own them. Events held elsewhere at that moment are left as they are with warning.
+/// ## Example
+/// ```rust,no_run
+/// struct TenantEnricher;
+/// impl TEventEnricher for TenantEnricher {
+///     fn enrich(&self, event: &mut dyn TEvent, _context: &ContextManager) {
+///         if let Some(headers) = event.headers_mut() {
+///             headers.insert("tenant".into(), TENANT.get());
+///         }
+///     }
+/// }
+/// ```
+///
+/// [Context]: super::contexts::Context
+/// [MessageBusConfig::with_event_enricher]: super::messagebus::MessageBusConfig::with_event_enricher
+pub trait TEventEnricher: Send + Sync {
+	fn enrich(&self, event: &mut dyn TEvent, context: &ContextManager);
+}
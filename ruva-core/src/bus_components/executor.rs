@@ -1,4 +1,10 @@
+use crate::prelude::BaseError;
 use downcast_rs::{impl_downcast, Downcast};
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Arc,
+};
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
 
 pub trait TConnection: Send + Sync + Downcast {}
 
@@ -20,3 +26,91 @@ impl TConnection for Box<&'static mut sqlx::PgConnection> {}
 // Design TConnection so each different connection can be implemented and return itself
 
 impl_downcast!(TConnection);
+
+/// Shared handle to transaction `T` that unit of work and repositories handed out from it work on.
+/// Cloning it gives another handle to the same transaction.
+pub struct Executor<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> Default for Executor<T> {
+	fn default() -> Self {
+		Self(Default::default())
+	}
+}
+
+impl<T> Clone for Executor<T> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<T: Send> Executor<T> {
+	/// Put transaction that has just begun. Returns [BaseError::TransactionError] if one is active already.
+	pub async fn begin(&self, trx: T) -> Result<(), BaseError> {
+		let mut guard = self.0.lock().await;
+		if guard.is_some() {
+			tracing::warn!("Transaction Begun Already!");
+			return Err(BaseError::TransactionError);
+		}
+		*guard = Some(trx);
+		Ok(())
+	}
+
+	/// Take out active transaction to commit or roll it back
+	pub async fn take(&self) -> Option<T> {
+		self.0.lock().await.take()
+	}
+
+	/// Lock active transaction to work on it. Returns [BaseError::TransactionError] if none has begun.
+	pub async fn transaction(&self) -> Result<MappedMutexGuard<'_, T>, BaseError> {
+		MutexGuard::try_map(self.0.lock().await, Option::as_mut).map_err(|_| BaseError::TransactionError)
+	}
+}
+
+impl<T: Send> Executor<T> {
+	/// Where reads go. While transaction is active, it is read from so that writes staged in it are seen.
+	/// Otherwise it is one of `replicas`, which spreads reads off the primary.
+	pub async fn reader<'a, R>(&'a self, replicas: &'a ReadReplicas<R>) -> Reader<'a, T, R> {
+		match MutexGuard::try_map(self.0.lock().await, Option::as_mut) {
+			Ok(trx) => Reader::Transaction(trx),
+			Err(_) => Reader::Replica(replicas.next()),
+		}
+	}
+}
+
+pub enum Reader<'a, T, R> {
+	/// Active transaction on the primary
+	Transaction(MappedMutexGuard<'a, T>),
+	Replica(&'a R),
+}
+
+/// Executors reads are routed to outside of transaction, picked in turn.
+/// Reads go to the primary when no replica is added.
+pub struct ReadReplicas<R> {
+	primary: Arc<R>,
+	replicas: Arc<Vec<R>>,
+	next: Arc<AtomicUsize>,
+}
+
+impl<R> Clone for ReadReplicas<R> {
+	fn clone(&self) -> Self {
+		Self { primary: self.primary.clone(), replicas: self.replicas.clone(), next: self.next.clone() }
+	}
+}
+
+impl<R> ReadReplicas<R> {
+	pub fn new(primary: R) -> Self {
+		Self { primary: Arc::new(primary), replicas: Default::default(), next: Default::default() }
+	}
+
+	pub fn with_replicas(self, replicas: impl IntoIterator<Item = R>) -> Self {
+		Self { replicas: Arc::new(replicas.into_iter().collect()), ..self }
+	}
+
+	/// Replica to read from next, round-robin
+	pub fn next(&self) -> &R {
+		match self.replicas.len() {
+			0 => &self.primary,
+			len => &self.replicas[self.next.fetch_add(1, Ordering::Relaxed) % len],
+		}
+	}
+}
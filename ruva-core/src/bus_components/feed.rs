@@ -0,0 +1,38 @@
+use crate::prelude::TEvent;
+use std::sync::Arc;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+pub const DEFAULT_EVENT_FEED_CAPACITY: usize = 1024;
+
+/// Broadcasts every event handled by [MessageBus] to subscribers of [MessageBus::subscribe], separately from handlers.
+/// Each subscriber keeps up to capacity of [super::messagebus::MessageBusConfig::with_event_feed_capacity] events it hasn't taken yet.
+/// Subscriber that falls behind further skips the oldest ones, so that it never holds the bus back.
+///
+/// [MessageBus]: super::messagebus::MessageBus
+/// [MessageBus::subscribe]: super::messagebus::MessageBus::subscribe
+pub(crate) struct EventFeed(broadcast::Sender<Arc<dyn TEvent>>);
+
+impl EventFeed {
+	pub(crate) fn new(capacity: usize) -> Self {
+		Self(broadcast::channel(capacity).0)
+	}
+
+	pub(crate) fn publish(&self, event: &Arc<dyn TEvent>) {
+		// * Nobody subscribing is not an error
+		if self.0.receiver_count() > 0 {
+			let _ = self.0.send(Arc::clone(event));
+		}
+	}
+
+	pub(crate) fn subscribe(&self) -> impl futures::Stream<Item = Arc<dyn TEvent>> + Send + 'static {
+		futures::stream::unfold(self.0.subscribe(), |mut receiver| async move {
+			loop {
+				match receiver.recv().await {
+					Ok(event) => return Some((event, receiver)),
+					Err(RecvError::Lagged(skipped)) => tracing::warn!("Subscriber Of Event Feed Fell Behind! {} Events Skipped", skipped),
+					Err(RecvError::Closed) => return None,
+				}
+			}
+		})
+	}
+}
@@ -15,6 +15,7 @@
 
 pub mod uow;
 use crate::{
+	bus_components::contexts::Context,
 	message::TCommand,
 	prelude::{ApplicationError, ApplicationResponse, BaseError, TCommandService, TSetCurrentEvents, TUnitOfWork},
 };
@@ -44,3 +45,74 @@ where
 pub trait TGetHandler<R, ApplicationResult>: Sized {
 	fn get_handler() -> impl AsyncFunc<Self, R, ApplicationResult>;
 }
+
+/// Result of command handler that is turned into `Result<R, E>` of the bus it is registered on.
+/// Response is converted with `TryInto` so that handler could return its own DTO rather than the bus-wide response,
+/// given `From` or `TryFrom` into the response is implemented. Failed conversion surfaces as error of the command.
+pub trait TIntoApplicationResult<ApplicationResult> {
+	fn into_application_result(self) -> ApplicationResult;
+}
+
+impl<T, R, E> TIntoApplicationResult<Result<R, E>> for Result<T, E>
+where
+	T: TryInto<R>,
+	T::Error: Into<BaseError>,
+	E: From<BaseError>,
+{
+	fn into_application_result(self) -> Result<R, E> {
+		self?.try_into().map_err(|err| E::from(err.into()))
+	}
+}
+
+/// Compile-time check on signature of handlers given to `register_uow_services!`.
+/// Handler is coerced to fn pointer first so mismatch is reported against this trait rather than deep inside `AsyncFunc` bound.
+/// See [command_handler]
+#[diagnostic::on_unimplemented(message = "`{Self}` is not a valid command handler for `{C}`", label = "handler must be `async fn(command: {C}, context: &mut ruva::Context) -> {ApplicationResult}`")]
+pub trait TCommandHandlerSignature<'a, C, ApplicationResult> {
+	/// What handler returns, which may differ from `ApplicationResult` only in its response
+	type Output: TIntoApplicationResult<ApplicationResult>;
+	type Handler: AsyncFunc<C, &'a mut Context, Self::Output>;
+	fn into_handler(self) -> Self::Handler;
+}
+
+impl<'a, C, Fut, ApplicationResult> TCommandHandlerSignature<'a, C, ApplicationResult> for fn(C, &'a mut Context) -> Fut
+where
+	C: TCommand,
+	Fut: std::future::Future + Send,
+	Fut::Output: TIntoApplicationResult<ApplicationResult>,
+{
+	type Output = Fut::Output;
+	type Handler = Self;
+	fn into_handler(self) -> Self::Handler {
+		self
+	}
+}
+
+#[diagnostic::on_unimplemented(message = "arguments of command handler for `{Self}` are given in wrong order", label = "command must come first, then `&mut ruva::Context`")]
+pub trait TArgumentsInOrder {}
+
+// * Matches handler whose arguments are swapped only to report it. As no command implements `TArgumentsInOrder`, handler is never built.
+impl<'a, C, Fut, ApplicationResult> TCommandHandlerSignature<'a, C, ApplicationResult> for fn(&'a mut Context, C) -> Fut
+where
+	C: TCommand + TArgumentsInOrder,
+	ApplicationResult: TIntoApplicationResult<ApplicationResult> + 'static,
+{
+	type Output = ApplicationResult;
+	type Handler = fn(C, &'a mut Context) -> std::future::Pending<ApplicationResult>;
+	fn into_handler(self) -> Self::Handler {
+		|_, _| std::future::pending()
+	}
+}
+
+/// Coerce handler into fn pointer, check its signature with [TCommandHandlerSignature] and convert what it returns into `ApplicationResult`
+pub fn command_handler<'a, C, ApplicationResult, A, B, Fut>(handler: fn(A, B) -> Fut) -> impl AsyncFunc<C, &'a mut Context, ApplicationResult>
+where
+	C: TCommand,
+	fn(A, B) -> Fut: TCommandHandlerSignature<'a, C, ApplicationResult>,
+{
+	let handler = handler.into_handler();
+	move |cmd: C, context: &'a mut Context| {
+		let result = handler(cmd, context);
+		async move { result.await.into_application_result() }
+	}
+}
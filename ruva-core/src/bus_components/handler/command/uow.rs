@@ -74,7 +74,8 @@ macro_rules! __register_uow_services_internal {
         $(
             impl<'a> ruva::TGetHandler<&'a mut ::ruva::Context, ApplicationResult> for $command {
                 fn get_handler() -> impl ::ruva::AsyncFunc<$command, &'a mut ::ruva::Context, ApplicationResult > {
-                    $handler
+                    // * Coercion to fn pointer lets signature mismatch be reported by `TCommandHandlerSignature`
+                    ::ruva::command_handler($handler)
                 }
             }
 
@@ -88,6 +89,8 @@ macro_rules! __register_uow_services_internal {
                 }
             }
         )*
+
+        ::ruva::init_dyn_command_handler!($response, $error, $($command),*);
     };
 }
 
@@ -0,0 +1,36 @@
+//! Downcast of type-erased messages into the type their handler takes.
+//!
+//! Handlers are looked up by topic or type id, so the message is expected to be of the right type.
+//! When the registry is misaligned, the handler fails with [BaseError::EventDowncastFailed] or [BaseError::CommandDowncastFailed]
+//! instead of panicking in the middle of the request. Handlers wrapping messages by hand can do the same through these traits.
+
+use crate::prelude::{BaseError, TCommand, TEvent};
+
+pub trait TDowncastEvent {
+	/// Clone of the event as `Ev`
+	fn downcast_event<Ev: TEvent + Clone>(&self) -> Result<Ev, BaseError>;
+}
+
+impl TDowncastEvent for dyn TEvent {
+	fn downcast_event<Ev: TEvent + Clone>(&self) -> Result<Ev, BaseError> {
+		self.downcast_ref::<Ev>().cloned().ok_or_else(|| {
+			let error = BaseError::EventDowncastFailed { topic: self.metadata().topic, expected_type: std::any::type_name::<Ev>() };
+			tracing::error!("{:?}", error);
+			error
+		})
+	}
+}
+
+pub trait TDowncastCommand {
+	fn downcast_command<C: TCommand>(self) -> Result<C, BaseError>;
+}
+
+impl TDowncastCommand for Box<dyn TCommand> {
+	fn downcast_command<C: TCommand>(self) -> Result<C, BaseError> {
+		self.downcast::<C>().map(|command| *command).map_err(|command| {
+			let error = BaseError::CommandDowncastFailed { expected_type: std::any::type_name::<C>() };
+			tracing::error!("{:?} Given:{:?}", error, command);
+			error
+		})
+	}
+}
@@ -1,11 +1,39 @@
-use crate::{bus_components::contexts::AtomicContextManager, prelude::TEvent};
+use super::downcast::TDowncastEvent;
+use crate::{
+	bus_components::{
+		contexts::AtomicContextManager,
+		inbox::{deliver_at_least_once, Delivery, InboxEntry},
+	},
+	prelude::{BaseError, TEvent},
+};
 
-use std::pin::Pin;
+use std::{
+	any::{Any, TypeId},
+	sync::Arc,
+};
 
-pub type Future<E> = Pin<Box<dyn futures::Future<Output = Result<(), E>> + Send>>;
+#[cfg(not(feature = "local"))]
+pub type Future<E> = futures::future::BoxFuture<'static, Result<(), E>>;
+/// Handlers are not required to be `Send` with `local` feature, so that they hold `!Send` values such as `Rc` across `.await`.
+/// Requests are then to be handled on a single-threaded runtime, within [tokio::task::LocalSet].
+#[cfg(feature = "local")]
+pub type Future<E> = futures::future::LocalBoxFuture<'static, Result<(), E>>;
 pub type FutureResult<E> = Result<Future<E>, E>;
 
-pub type Handlers<E> = Vec<Box<dyn Fn(std::sync::Arc<dyn TEvent>, AtomicContextManager) -> Future<E> + Send + Sync>>;
+/// Bound on futures of handlers, which is `Send` unless `local` feature is on so that they fit in [Future]
+#[cfg(not(feature = "local"))]
+pub trait MaybeSend: Send {}
+#[cfg(not(feature = "local"))]
+impl<T: Send> MaybeSend for T {}
+#[cfg(feature = "local")]
+pub trait MaybeSend {}
+#[cfg(feature = "local")]
+impl<T> MaybeSend for T {}
+
+pub type Handler<E> = Box<dyn Fn(std::sync::Arc<dyn TEvent>, AtomicContextManager) -> Future<E> + Send + Sync>;
+pub type Handlers<E> = Vec<Handler<E>>;
+/// Handlers along with their order, before they are sorted by it
+pub type OrderedHandlers<E> = Vec<(i16, Handler<E>)>;
 
 pub enum EventHandlers<E> {
 	Sync(Handlers<E>),
@@ -19,3 +47,187 @@ impl<E> EventHandlers<E> {
 		}
 	}
 }
+
+/// Event handler registered through `#[event_handler]` attribute macro.
+/// As handlers for different error types are collected in the same inventory, `handler` returns type-erased `Handler<E>`
+pub struct EventHandlerRegistration {
+	pub topic: &'static str,
+	pub event: fn() -> TypeId,
+	/// Handlers of lower order run first. See [merge_event_handlers]
+	pub order: i16,
+	/// Path of the handler function, which [InboxEntry::handler] refers to it by
+	pub name: &'static str,
+	pub delivery: Delivery,
+	pub handler: fn() -> Box<dyn Any + Send + Sync>,
+}
+
+inventory::collect!(EventHandlerRegistration);
+
+impl EventHandlerRegistration {
+	/// Wrap free function that takes concrete event and either [AtomicContextManager] or [HandlerContext] into `Handler<E>`
+	///
+	/// [HandlerContext]: crate::bus_components::contexts::HandlerContext
+	pub fn erase<Ev, C, E, F, Fut>(handler: F) -> Box<dyn Any + Send + Sync>
+	where
+		Ev: TEvent + Clone,
+		C: From<AtomicContextManager>,
+		E: From<BaseError> + 'static,
+		F: Fn(Ev, C) -> Fut + Send + Sync + 'static,
+		Fut: futures::Future<Output = Result<(), E>> + MaybeSend + 'static,
+	{
+		Self::erase_with(handler, std::any::type_name::<F>(), |_: &Ev| true, Delivery::AtMostOnce)
+	}
+
+	/// Same as [Self::erase], but the handler is not run for events `filter` returns `false` for, which are taken as handled without error.
+	/// With [Delivery::AtLeastOnce], the event is kept in the inbox under `name` until the handler succeeds on it.
+	pub fn erase_with<Ev, C, E, F, Fut, P>(handler: F, name: &'static str, filter: P, delivery: Delivery) -> Box<dyn Any + Send + Sync>
+	where
+		Ev: TEvent + Clone,
+		C: From<AtomicContextManager>,
+		E: From<BaseError> + 'static,
+		F: Fn(Ev, C) -> Fut + Send + Sync + 'static,
+		Fut: futures::Future<Output = Result<(), E>> + MaybeSend + 'static,
+		P: Fn(&Ev) -> bool + Send + Sync + 'static,
+	{
+		let handler: Handler<E> = Box::new(move |e, context_manager| match e.downcast_event::<Ev>() {
+			Ok(event) if !filter(&event) => {
+				tracing::trace!("{} Skipped By Filter! Topic:{}", std::any::type_name::<F>(), event.metadata().topic);
+				Box::pin(async { Ok(()) })
+			}
+			Ok(event) if delivery == Delivery::AtLeastOnce => {
+				let entry = InboxEntry::of(&event, name, &context_manager);
+				Box::pin(deliver_at_least_once(entry, handler(event, context_manager.into())))
+			}
+			Ok(event) => Box::pin(handler(event, context_manager.into())),
+			Err(err) => Box::pin(async move { Err(err.into()) }),
+		});
+		Box::new(handler)
+	}
+}
+
+/// Take every handler registered with `#[event_handler]` whose error type is `E`, keyed by topic, along with its order.
+/// Handlers of the same topic are kept in the order in which the linker collected them.
+pub fn collect_event_handlers<E: 'static>() -> hashbrown::HashMap<String, OrderedHandlers<E>> {
+	let mut map: hashbrown::HashMap<String, OrderedHandlers<E>> = hashbrown::HashMap::new();
+	for registration in inventory::iter::<EventHandlerRegistration> {
+		if let Ok(handler) = (registration.handler)().downcast::<Handler<E>>() {
+			map.entry(registration.topic.to_string()).or_default().push((registration.order, *handler));
+		}
+	}
+	map
+}
+
+/// Same as [collect_event_handlers] but keyed by type id of the event, for `init_typed_event_handler!`
+pub fn collect_typed_event_handlers<E: 'static>() -> hashbrown::HashMap<TypeId, OrderedHandlers<E>> {
+	let mut map: hashbrown::HashMap<TypeId, OrderedHandlers<E>> = hashbrown::HashMap::new();
+	for registration in inventory::iter::<EventHandlerRegistration> {
+		if let Ok(handler) = (registration.handler)().downcast::<Handler<E>>() {
+			map.entry((registration.event)()).or_default().push((registration.order, *handler));
+		}
+	}
+	map
+}
+
+/// Merge handlers declared on `init_event_handler!` with the ones discovered from `#[event_handler]`, each given with its order and
+/// whether the declared ones are async. Handlers of each event are sorted by order, lower first.
+/// Among the ones of the same order, declared ones come first in the order of declaration, followed by discovered ones.
+pub fn merge_event_handlers<K, E>(declared: Vec<(K, bool, OrderedHandlers<E>)>, discovered: hashbrown::HashMap<K, OrderedHandlers<E>>) -> hashbrown::HashMap<K, EventHandlers<E>>
+where
+	K: Eq + std::hash::Hash,
+{
+	let mut merged: hashbrown::HashMap<K, (bool, OrderedHandlers<E>)> = hashbrown::HashMap::new();
+	for (key, asynchronous, handlers) in declared {
+		merged.insert(key, (asynchronous, handlers));
+	}
+	for (key, handlers) in discovered {
+		merged.entry(key).or_insert_with(|| (false, vec![])).1.extend(handlers);
+	}
+	merged
+		.into_iter()
+		.map(|(key, (asynchronous, mut handlers))| {
+			handlers.sort_by_key(|(order, _)| *order);
+			let handlers = handlers.into_iter().map(|(_, handler)| handler).collect();
+			(key, if asynchronous { EventHandlers::Async(handlers) } else { EventHandlers::Sync(handlers) })
+		})
+		.collect()
+}
+
+/// Event handler subscribed to every topic matching `pattern`, in which `*` matches any sequence of characters.
+/// As it may receive events of different types, the event is given as it is.
+pub struct PatternEventHandler<E> {
+	pub pattern: String,
+	pub handler: Handler<E>,
+}
+
+impl<E> PatternEventHandler<E> {
+	pub fn matches(&self, topic: &str) -> bool {
+		matches_pattern(&self.pattern, topic)
+	}
+}
+
+fn matches_pattern(pattern: &str, topic: &str) -> bool {
+	let mut parts = pattern.split('*');
+	let Some(mut rest) = topic.strip_prefix(parts.next().unwrap_or_default()) else {
+		return false;
+	};
+	let parts = parts.collect::<Vec<_>>();
+	let Some((last, middle)) = parts.split_last() else {
+		return rest.is_empty();
+	};
+	for part in middle {
+		match rest.find(part) {
+			Some(index) => rest = &rest[index + part.len()..],
+			None => return false,
+		}
+	}
+	rest.ends_with(last)
+}
+
+/// Pattern event handler registered through `#[event_handler("Pattern*")]` attribute macro.
+pub struct PatternEventHandlerRegistration {
+	pub pattern: &'static str,
+	pub handler: fn() -> Box<dyn Any + Send + Sync>,
+}
+
+inventory::collect!(PatternEventHandlerRegistration);
+
+impl PatternEventHandlerRegistration {
+	/// Wrap free function that takes `Arc<dyn TEvent>` and either [AtomicContextManager] or [HandlerContext] into `Handler<E>`
+	///
+	/// [HandlerContext]: crate::bus_components::contexts::HandlerContext
+	pub fn erase<C, E, F, Fut>(handler: F) -> Box<dyn Any + Send + Sync>
+	where
+		C: From<AtomicContextManager>,
+		E: 'static,
+		F: Fn(Arc<dyn TEvent>, C) -> Fut + Send + Sync + 'static,
+		Fut: futures::Future<Output = Result<(), E>> + MaybeSend + 'static,
+	{
+		let handler: Handler<E> = Box::new(move |e, context_manager| Box::pin(handler(e, context_manager.into())));
+		Box::new(handler)
+	}
+}
+
+/// Take every pattern handler registered with `#[event_handler("Pattern*")]` whose error type is `E`.
+pub fn collect_pattern_event_handlers<E: 'static>() -> Vec<PatternEventHandler<E>> {
+	inventory::iter::<PatternEventHandlerRegistration>
+		.into_iter()
+		.filter_map(|registration| {
+			let handler = (registration.handler)().downcast::<Handler<E>>().ok()?;
+			Some(PatternEventHandler { pattern: registration.pattern.to_string(), handler: *handler })
+		})
+		.collect()
+}
+
+#[test]
+fn test_matches_pattern() {
+	assert!(matches_pattern("OrderCreated", "OrderCreated"));
+	assert!(!matches_pattern("OrderCreated", "OrderCreatedAgain"));
+	assert!(matches_pattern("Order*", "OrderCreated"));
+	assert!(matches_pattern("Order*", "Order"));
+	assert!(!matches_pattern("Order*", "AccountCreated"));
+	assert!(matches_pattern("*Created", "AccountCreated"));
+	assert!(matches_pattern("*", "AccountCreated"));
+	assert!(matches_pattern("Order*Item*Added", "OrderLineItemWasAdded"));
+	assert!(!matches_pattern("Order*Item*Added", "OrderLineItemRemoved"));
+	assert!(!matches_pattern("A*A", "A"));
+}
@@ -0,0 +1,41 @@
+//! Resolution of injectables listed on `init_event_handler!`, which may or may not fail.
+//!
+//! Whether the factory returns `Result` is told apart at the call site by autoref-based dispatch:
+//! [TResolveFallible] is implemented on `Injected<Result<T, E>>` itself and is picked first,
+//! while [TResolveInfallible] is implemented on the reference and is picked for anything else.
+
+use std::cell::Cell;
+
+/// Value returned by dependency factory, waiting to be resolved
+pub struct Injected<T>(Cell<Option<T>>);
+
+impl<T> Injected<T> {
+	pub fn new(value: T) -> Self {
+		Self(Cell::new(Some(value)))
+	}
+
+	fn take(&self) -> T {
+		self.0.take().expect("Injectable Is Resolved Only Once!")
+	}
+}
+
+pub trait TResolveFallible<T, E> {
+	/// Error of the factory is converted into error of the handler
+	fn resolve<HandlerError: From<E>>(&self) -> Result<T, HandlerError>;
+}
+
+impl<T, E> TResolveFallible<T, E> for Injected<Result<T, E>> {
+	fn resolve<HandlerError: From<E>>(&self) -> Result<T, HandlerError> {
+		self.take().map_err(HandlerError::from)
+	}
+}
+
+pub trait TResolveInfallible<T> {
+	fn resolve<HandlerError>(&self) -> Result<T, HandlerError>;
+}
+
+impl<T> TResolveInfallible<T> for &Injected<T> {
+	fn resolve<HandlerError>(&self) -> Result<T, HandlerError> {
+		Ok(self.take())
+	}
+}
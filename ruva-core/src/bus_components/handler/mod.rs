@@ -38,6 +38,10 @@
 
 pub mod command;
 
+pub mod downcast;
 pub mod event;
+pub mod injectable;
 pub use command::*;
+pub use downcast::*;
 pub use event::*;
+pub use injectable::*;
@@ -0,0 +1,64 @@
+use crate::prelude::Timestamp;
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Arc, Mutex,
+};
+
+/// Read-only view of [MessageBus] for liveness and readiness probes.
+///
+/// [MessageBus]: crate::prelude::MessageBus
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthSnapshot {
+	/// Commands registered with `init_dyn_command_handler!`
+	pub command_handlers: usize,
+	/// Handlers registered with `#[event_handler]`, regardless of their error type
+	pub event_handlers: usize,
+	/// When outboxes were last relayed by [TOutBoxPublisher::publish_all], `None` if never
+	///
+	/// [TOutBoxPublisher::publish_all]: crate::prelude::TOutBoxPublisher::publish_all
+	pub last_relay_tick: Option<Timestamp>,
+	/// Outboxes staged by committed transactions that are not relayed yet, as far as this process saw
+	pub outbox_backlog: usize,
+	pub shutting_down: bool,
+}
+
+/// Tracks outboxes from staging to relay so that [HealthSnapshot] can tell the backlog.
+/// Unit of work staging outboxes reports them on commit, which `sqlx-postgres` one does out of the box.
+/// ## Example
+/// ```rust,no_run
+/// async fn _commit(&mut self) -> Result<(), BaseError> {
+///     self.transaction.commit().await?;
+///     MessageBus::relay_monitor().staged(self.outboxes.len());
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct RelayMonitor(Arc<RelayState>);
+
+#[derive(Default)]
+struct RelayState {
+	backlog: AtomicUsize,
+	last_tick: Mutex<Option<Timestamp>>,
+}
+
+impl RelayMonitor {
+	/// Outboxes are written along with the transaction that raised them
+	pub fn staged(&self, count: usize) {
+		self.0.backlog.fetch_add(count, Ordering::SeqCst);
+	}
+
+	/// Relay ran at `at`, acknowledging `count` outboxes
+	pub fn relayed(&self, count: usize, at: Timestamp) {
+		// * Outboxes staged by other process may be relayed here, so backlog doesn't go below zero
+		let _ = self.0.backlog.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |backlog| Some(backlog.saturating_sub(count)));
+		*self.0.last_tick.lock().unwrap() = Some(at);
+	}
+
+	pub fn backlog(&self) -> usize {
+		self.0.backlog.load(Ordering::SeqCst)
+	}
+
+	pub fn last_tick(&self) -> Option<Timestamp> {
+		*self.0.last_tick.lock().unwrap()
+	}
+}
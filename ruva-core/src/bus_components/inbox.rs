@@ -0,0 +1,145 @@
+//! ### Delivery Guarantee
+//! Failure of event handler is logged, and dead-lettered when there is a sink, but the event is otherwise gone once the request is over,
+//! which is fine for handlers whose work is safe to lose, such as metrics. Handler whose work must not be lost, such as decrementing inventory,
+//! is registered with [Delivery::AtLeastOnce] instead. The event is then kept in the [TInbox] given to [MessageBusConfig::with_inbox]
+//! before the handler runs, and dropped from it only once the handler succeeds. Event left in the inbox, as by failure or crash in the middle,
+//! is run through the handler again by [TEventBus::redeliver] until it succeeds or runs out of attempts, when it is dead-lettered.
+//! As the name says, the handler may see the same event more than once, so it is to be idempotent.
+//!
+//! Entry is kept under message id of the event, or hash of its state within the request otherwise,
+//! so that attempts of [MessageBusConfig::with_retry_policies] are counted on the same entry.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! #[event_handler(OrderPlaced, delivery = Delivery::AtLeastOnce)]
+//! async fn decrement_inventory(event: OrderPlaced, context: AtomicContextManager) -> Result<(), ServiceError> {
+//!     Ok(())
+//! }
+//!
+//! MessageBus::configure(MessageBusConfig::default().with_inbox(inbox).with_dead_letter_sink(sink));
+//!
+//! // Periodically
+//! let deserializers = EventDeserializers::default().register_event::<OrderPlaced>();
+//! MessageBus.redeliver(&deserializers, 5, &CONNECTION).await?;
+//! ```
+//!
+//! [MessageBusConfig::with_inbox]: super::messagebus::MessageBusConfig::with_inbox
+//! [MessageBusConfig::with_retry_policies]: super::messagebus::MessageBusConfig::with_retry_policies
+//! [TEventBus::redeliver]: super::messagebus::TEventBus::redeliver
+
+use super::{contexts::AtomicContextManager, messagebus::MessageBus};
+use crate::prelude::{BaseError, TEvent};
+use async_trait::async_trait;
+use std::{
+	hash::{DefaultHasher, Hash, Hasher},
+	sync::{Arc, Mutex},
+};
+
+/// How far [MessageBus] goes to have event handler succeed on each event. See [inbox](self) module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Delivery {
+	/// Handler runs on the event as long as the request lasts. Failure is not recovered from, other than by retry policies and dead letter sink.
+	#[default]
+	AtMostOnce,
+	/// Event is kept in the inbox until the handler succeeds on it, to be redelivered after failure
+	AtLeastOnce,
+}
+
+/// Event kept for [Delivery::AtLeastOnce] handler until it succeeds on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboxEntry {
+	pub id: String,
+	pub topic: String,
+	pub payload: Vec<u8>,
+	/// Type name of the handler function
+	pub handler: &'static str,
+	/// How many times the handler was run on the event, counted by the inbox
+	pub attempts: usize,
+}
+
+/// Store of events that [Delivery::AtLeastOnce] handlers haven't succeeded on yet
+#[async_trait]
+pub trait TInbox: Send + Sync {
+	/// Keep the entry before the handler runs on it. Entry already kept under the same id counts another attempt instead.
+	async fn receive(&self, entry: InboxEntry) -> Result<(), BaseError>;
+	/// Drop the entry once the handler succeeded on it
+	async fn acknowledge(&self, id: &str) -> Result<(), BaseError>;
+	/// Entries the handlers haven't succeeded on yet
+	async fn pending(&self) -> Result<Vec<InboxEntry>, BaseError>;
+}
+
+/// Inbox that keeps entries in memory, which is lost along with the process. Useful for tests.
+#[derive(Default, Clone)]
+pub struct InMemoryInbox(Arc<Mutex<Vec<InboxEntry>>>);
+
+#[async_trait]
+impl TInbox for InMemoryInbox {
+	async fn receive(&self, entry: InboxEntry) -> Result<(), BaseError> {
+		let mut entries = self.0.lock().unwrap();
+		match entries.iter_mut().find(|kept| kept.id == entry.id) {
+			Some(kept) => kept.attempts += 1,
+			None => entries.push(InboxEntry { attempts: 1, ..entry }),
+		}
+		Ok(())
+	}
+	async fn acknowledge(&self, id: &str) -> Result<(), BaseError> {
+		self.0.lock().unwrap().retain(|entry| entry.id != id);
+		Ok(())
+	}
+	async fn pending(&self) -> Result<Vec<InboxEntry>, BaseError> {
+		Ok(self.0.lock().unwrap().clone())
+	}
+}
+
+/// Outcome of [TEventBus::redeliver]
+///
+/// [TEventBus::redeliver]: super::messagebus::TEventBus::redeliver
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Redelivery {
+	/// Entries the handler succeeded on this time
+	pub redelivered: usize,
+	/// Entries the handler failed on again, left in the inbox
+	pub failed: usize,
+	/// Entries that ran out of attempts, sent to dead letter sink and dropped from the inbox
+	pub dead_lettered: usize,
+}
+
+/// Id of the entry being redelivered, stashed on the context manager so that the handler acknowledges the entry as it is kept
+pub(crate) struct Redelivered(pub(crate) String);
+
+impl InboxEntry {
+	pub(crate) fn of(event: &dyn TEvent, handler: &'static str, context_manager: &AtomicContextManager) -> Result<Self, BaseError> {
+		let topic = event.metadata().topic;
+		let payload = event.try_state()?.into_bytes();
+		let id = match (context_manager.get::<Redelivered>(), event.message_id()) {
+			(Some(redelivered), _) => redelivered.0.clone(),
+			(None, Some(message_id)) => format!("{handler}:{message_id}"),
+			(None, None) => {
+				let mut hasher = DefaultHasher::new();
+				(context_manager.correlation_id(), &topic, &payload).hash(&mut hasher);
+				format!("{handler}:{:x}", hasher.finish())
+			}
+		};
+		Ok(Self { id, topic, payload, handler, attempts: 0 })
+	}
+}
+
+/// Keep the entry in the inbox while `handling` runs, dropping it once it succeeds
+pub(crate) async fn deliver_at_least_once<E>(entry: Result<InboxEntry, BaseError>, handling: impl std::future::Future<Output = Result<(), E>>) -> Result<(), E>
+where
+	E: std::convert::From<BaseError>,
+{
+	let Some(inbox) = MessageBus::config().inbox.clone() else {
+		tracing::warn!("No Inbox Given For At-Least-Once Handler! It Is Run At Most Once.");
+		return handling.await;
+	};
+	let entry = entry?;
+	let id = entry.id.clone();
+	inbox.receive(entry).await?;
+	handling.await?;
+	// * Entry left by failure to acknowledge is redelivered, which at-least-once handler is to tolerate
+	if let Err(err) = inbox.acknowledge(&id).await {
+		tracing::error!("Failed To Acknowledge Inbox Entry {}! Error:{:?}", id, err);
+	}
+	Ok(())
+}
@@ -0,0 +1,58 @@
+use super::contexts::{AtomicContextManager, ContextManager};
+use super::messagebus::MessageBus;
+use std::sync::Arc;
+
+/// Hooks bracketing a whole request, called once however many events and commands cascade from it.
+/// Request is one call of `execute_and_wait`, `execute_and_forget`, `handle_deferred`, `execute_stream`, `execute_batch` or `handle_event` on [MessageBus],
+/// from the moment it is accepted until the events it raised are handled.
+/// - `execute_and_forget` and `handle_deferred` finish when the spawned event handling is done, not when the command returns.
+/// - `execute_stream` finishes when the stream is exhausted or dropped.
+/// - Each command of `execute_batch` with [BatchContext::Isolated] is a request of its own.
+///
+/// Finish is called from a guard, so it is called even when the request fails or its future is dropped half-way.
+/// Hooks registered with [MessageBusConfig::with_request_lifecycle] start in the order of registration and finish in the reverse order.
+/// ## Example
+/// ```rust,no_run
+/// struct RequestTimer;
+/// impl TRequestLifecycle for RequestTimer {
+///     fn on_request_start(&self, context: &ContextManager) {
+///         STARTED.insert(context.correlation_id().to_string(), Instant::now());
+///     }
+///     fn on_request_finish(&self, context: &ContextManager, succeeded: bool) {
+///         let started = STARTED.remove(context.correlation_id());
+///         metrics::histogram!("request_duration", "succeeded" => succeeded.to_string()).record(started.elapsed());
+///     }
+/// }
+/// ```
+///
+/// [BatchContext::Isolated]: super::messagebus::BatchContext::Isolated
+/// [MessageBusConfig::with_request_lifecycle]: super::messagebus::MessageBusConfig::with_request_lifecycle
+pub trait TRequestLifecycle: Send + Sync {
+	fn on_request_start(&self, _context: &ContextManager) {}
+	fn on_request_finish(&self, _context: &ContextManager, _succeeded: bool) {}
+}
+
+/// Calls finish hooks as it is dropped. Request is taken as failed unless [Self::succeed] is called.
+pub(crate) struct RequestGuard {
+	hooks: Vec<Arc<dyn TRequestLifecycle>>,
+	context_manager: AtomicContextManager,
+	succeeded: bool,
+}
+
+impl RequestGuard {
+	pub(crate) fn start(context_manager: &AtomicContextManager) -> Self {
+		let hooks = MessageBus::config().request_lifecycles.clone();
+		hooks.iter().for_each(|hook| hook.on_request_start(context_manager));
+		Self { hooks, context_manager: Arc::clone(context_manager), succeeded: false }
+	}
+
+	pub(crate) fn succeed(&mut self) {
+		self.succeeded = true;
+	}
+}
+
+impl Drop for RequestGuard {
+	fn drop(&mut self) {
+		self.hooks.iter().rev().for_each(|hook| hook.on_request_finish(&self.context_manager, self.succeeded));
+	}
+}
@@ -18,156 +18,1228 @@
 //! }
 //! ```
 
+use super::audit::{self, TAuditSink};
+use super::cancellation::CancellationToken;
+use super::circuit_breaker::{handle_with_breaker, CircuitBreaker};
 use super::contexts::*;
+use super::dead_letter::{DeadLetter, TDeadLetterSink};
+use super::effect::{perform_effects, TEffect, TEffectHandler, TErasedEffectHandler, TypedEffectHandler};
+use super::enricher::TEventEnricher;
 use super::executor::TConnection;
-use super::handler::EventHandlers;
-use crate::prelude::{TCommand, TEvent};
+use super::feed::{EventFeed, DEFAULT_EVENT_FEED_CAPACITY};
+use super::handler::{EventHandlerRegistration, EventHandlers, Handler, MaybeSend, PatternEventHandler};
+use super::health::{HealthSnapshot, RelayMonitor};
+use super::inbox::{Redelivered, Redelivery, TInbox};
+use super::lifecycle::{RequestGuard, TRequestLifecycle};
+use super::metrics::{self, TMetricsRecorder};
+use super::pause::PauseGate;
+use super::retry::{handle_with_retry, RetryPolicies};
+use super::shutdown::ShutdownHandle;
+use super::telemetry;
+use crate::prelude::{EventDeserializers, OutBox, SerFormat, TClock, TCodec, TCommand, TEvent, TOutBoxPublisher, Timestamp};
 use crate::responses::{self, ApplicationError, ApplicationResponse, BaseError};
 use async_recursion::async_recursion;
 use async_trait::async_trait;
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use std::{
+	any::TypeId,
+	sync::{Arc, LazyLock, RwLock},
+	time::Duration,
+};
+use tokio::sync::Semaphore;
+use tracing::Instrument;
 
-/// Event handlers `TEventBus` work on
+/// Event handlers `TEventBus` work on, keyed by topic
 pub type TEventHandler<E> = hashbrown::HashMap<String, EventHandlers<E>>;
 
-#[async_trait]
+/// Event handlers keyed by type id of the event, registered with `init_typed_event_handler!`
+pub type TTypedEventHandler<E> = hashbrown::HashMap<TypeId, EventHandlers<E>>;
+
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
 pub trait TEventBus<E> {
 	fn event_handler(&self) -> &'static TEventHandler<E>;
+
+	/// Handlers looked up by type of the event rather than its topic. When handlers are found here, the ones keyed by topic are not looked up.
+	fn typed_event_handler(&self) -> Option<&'static TTypedEventHandler<E>> {
+		None
+	}
+
+	/// Handlers subscribed to topics by pattern. They run after the handlers registered for the exact topic.
+	fn pattern_event_handler(&self) -> &'static [PatternEventHandler<E>] {
+		&[]
+	}
+
+	/// Handlers of commands dispatched from event handlers through [ContextManager::dispatch_command]
+	fn command_dispatcher(&self) -> Option<&'static CommandDispatchers<E>> {
+		None
+	}
+
+	/// Handle event coming from outside of the application, such as message broker, along with the events it raises.
+	async fn handle_event(&self, event: Arc<dyn TEvent>, conn: &'static dyn TConnection) -> Result<(), E>
+	where
+		Self: Sync,
+		E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+		crate::responses::BaseError: std::convert::From<E>,
+	{
+		self.handle_event_with_report(event, conn).await?;
+		Ok(())
+	}
+
+	/// Same as [Self::handle_event], telling how handlers of the event and of the events it raised went
+	async fn handle_event_with_report(&self, event: Arc<dyn TEvent>, conn: &'static dyn TConnection) -> Result<EventReport, E>
+	where
+		Self: Sync,
+		E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+		crate::responses::BaseError: std::convert::From<E>,
+	{
+		let _in_flight = MessageBus::shutdown_handle().enter()?;
+		let context_manager = Arc::new(ContextManager::new(conn));
+		let mut request = RequestGuard::start(&context_manager);
+		let handled = handle_event(event, Arc::clone(&context_manager), Routes::of(self)).await;
+		settle_shared_transaction(&context_manager, handled).await?;
+		perform_effects(&context_manager).await;
+		request.succeed();
+		Ok(std::mem::take(&mut context_manager.get_mut().report))
+	}
+
+	/// Feed historical events through their handlers, one after another, without running any command handler.
+	/// Handlers see [ContextManager::is_replaying] set, for them and for the events they raise.
+	/// It stops at the first event that can't be handled, such as the one no handler is registered for.
+	/// ## Example
+	/// ```rust,no_run
+	/// let filter = ReplayFilter::default().topics(["OrderPlaced"]).from(since);
+	/// let events = outboxes.into_iter().filter(|outbox| filter.includes(outbox)).map(into_event);
+	/// let report = MessageBus.replay(futures::stream::iter(events), &CONNECTION).await?;
+	/// ```
+	async fn replay<S>(&self, events: S, conn: &'static dyn TConnection) -> Result<EventReport, E>
+	where
+		Self: Sync,
+		S: futures::Stream<Item = Arc<dyn TEvent>> + Send,
+		E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+		crate::responses::BaseError: std::convert::From<E>,
+	{
+		let _in_flight = MessageBus::shutdown_handle().enter()?;
+		let mut report = EventReport::default();
+		let mut events = std::pin::pin!(events);
+		while let Some(event) = futures::StreamExt::next(&mut events).await {
+			let mut context_manager = ContextManager::new(conn);
+			context_manager.replaying = true;
+			let context_manager = handle_event(event, Arc::new(context_manager), Routes::of(self)).await?;
+			report.merge(std::mem::take(&mut context_manager.get_mut().report));
+		}
+		Ok(report)
+	}
+
+	/// Run events left in the inbox of [MessageBusConfig::with_inbox] through the [Delivery::AtLeastOnce] handlers that haven't succeeded on them,
+	/// one after another, along with the events they raise. Entry attempted `max_attempts` times already is dead-lettered instead,
+	/// to the sink of [MessageBusConfig::with_dead_letter_sink] if there is one, and dropped from the inbox.
+	/// Payload is deserialized as json, which is the format events are kept in.
+	/// ## Example
+	/// ```rust,no_run
+	/// let deserializers = EventDeserializers::default().register_event::<OrderPlaced>();
+	/// let redelivery = MessageBus.redeliver(&deserializers, 5, &CONNECTION).await?;
+	/// ```
+	///
+	/// [Delivery::AtLeastOnce]: super::inbox::Delivery::AtLeastOnce
+	async fn redeliver(&self, deserializers: &EventDeserializers, max_attempts: usize, conn: &'static dyn TConnection) -> Result<Redelivery, E>
+	where
+		Self: Sync,
+		E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+		crate::responses::BaseError: std::convert::From<E>,
+	{
+		let config = MessageBus::config();
+		let Some(inbox) = config.inbox.clone() else {
+			return Ok(Redelivery::default());
+		};
+		let _in_flight = MessageBus::shutdown_handle().enter()?;
+		let mut redelivery = Redelivery::default();
+		for entry in inbox.pending().await? {
+			if entry.attempts >= max_attempts {
+				if let Some(sink) = config.dead_letter_sink.as_ref() {
+					let reason = format!("{} Failed {} Times!", entry.handler, entry.attempts);
+					let metadata = std::collections::HashMap::from([("handler".to_string(), entry.handler.to_string())]);
+					sink.send(DeadLetter { topic: entry.topic.clone(), payload: entry.payload.clone(), reason, handler: None, metadata }).await?;
+				}
+				inbox.acknowledge(&entry.id).await?;
+				redelivery.dead_lettered += 1;
+				continue;
+			}
+			let handler = inventory::iter::<EventHandlerRegistration>
+				.into_iter()
+				.find(|registration| registration.name == entry.handler)
+				.and_then(|registration| (registration.handler)().downcast::<Handler<E>>().ok());
+			let (Some(handler), Ok(event)) = (handler, deserializers.deserialize(&entry.topic, &entry.payload, SerFormat::Json)) else {
+				tracing::error!("Failed To Redeliver {} To {}! Handler Or Deserializer Is Not Registered.", entry.topic, entry.handler);
+				redelivery.failed += 1;
+				continue;
+			};
+
+			let context_manager = Arc::new(ContextManager::new(conn));
+			context_manager.insert(Redelivered(entry.id.clone()));
+			let mut request = RequestGuard::start(&context_manager);
+			let mut handled = handler(event, Arc::clone(&context_manager)).await;
+			if let (Ok(()), Some(event)) = (&handled, context_manager.get_mut().pop_front()) {
+				handled = handle_event(event, Arc::clone(&context_manager), Routes::of(self)).await.map(|_| ());
+			}
+			match settle_shared_transaction(&context_manager, handled).await {
+				Ok(()) => {
+					perform_effects(&context_manager).await;
+					request.succeed();
+					redelivery.redelivered += 1;
+				}
+				Err(err) => {
+					tracing::error!("Failed To Redeliver {} To {}! Error:{:?}", entry.topic, entry.handler, err);
+					redelivery.failed += 1;
+				}
+			}
+		}
+		Ok(redelivery)
+	}
+}
+
+/// Selects outboxes to replay by topic and by the time they were created
+#[derive(Debug, Clone, Default)]
+pub struct ReplayFilter {
+	topics: Option<Vec<String>>,
+	from: Option<DateTime<Utc>>,
+	until: Option<DateTime<Utc>>,
+}
+
+impl ReplayFilter {
+	pub fn topics(mut self, topics: impl IntoIterator<Item = impl Into<String>>) -> Self {
+		self.topics = Some(topics.into_iter().map(Into::into).collect());
+		self
+	}
+
+	/// Inclusive
+	pub fn from(mut self, from: DateTime<Utc>) -> Self {
+		self.from = Some(from);
+		self
+	}
+
+	/// Exclusive
+	pub fn until(mut self, until: DateTime<Utc>) -> Self {
+		self.until = Some(until);
+		self
+	}
+
+	pub fn includes(&self, outbox: &OutBox) -> bool {
+		self.topics.as_ref().is_none_or(|topics| topics.contains(&outbox.topic)) && self.from.is_none_or(|from| outbox.create_dt >= from) && self.until.is_none_or(|until| outbox.create_dt < until)
+	}
+}
+
+/// Handlers of [TEventBus] that events and commands dispatched from them are routed to
+struct Routes<E: 'static> {
+	event_handler: &'static TEventHandler<E>,
+	typed_event_handler: Option<&'static TTypedEventHandler<E>>,
+	pattern_event_handler: &'static [PatternEventHandler<E>],
+	command_dispatcher: Option<&'static CommandDispatchers<E>>,
+}
+
+impl<E> Clone for Routes<E> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<E> Copy for Routes<E> {}
+
+impl<E> Routes<E> {
+	fn of(bus: &(impl TEventBus<E> + ?Sized)) -> Self {
+		Self { event_handler: bus.event_handler(), typed_event_handler: bus.typed_event_handler(), pattern_event_handler: bus.pattern_event_handler(), command_dispatcher: bus.command_dispatcher() }
+	}
+
+	fn handlers_of(&self, msg: &Arc<dyn TEvent>, topic: &str) -> Option<&'static EventHandlers<E>> {
+		// * `as_any` is required. `type_id` of `Arc<dyn TEvent>` itself is not that of the event.
+		self.typed_event_handler.and_then(|handlers| handlers.get(&msg.as_any().type_id())).or_else(|| self.event_handler.get(topic))
+	}
 }
 
 /// This function is used to handle event. It is called recursively until there is no event left in the queue.
-#[async_recursion]
-async fn handle_event<E>(msg: Arc<dyn TEvent>, context_manager: AtomicContextManager, event_handler: &'static TEventHandler<E>) -> Result<AtomicContextManager, E>
+/// With [MessageBusConfig::with_aggregate_lanes], the events left are handled in lanes instead.
+#[cfg_attr(not(feature = "local"), async_recursion)]
+#[cfg_attr(feature = "local", async_recursion(?Send))]
+async fn handle_event<E>(msg: Arc<dyn TEvent>, context_manager: AtomicContextManager, routes: Routes<E>) -> Result<AtomicContextManager, E>
+where
+	E: ApplicationError + std::convert::From<crate::responses::BaseError> + std::convert::From<E>,
+	crate::responses::BaseError: std::convert::From<E>,
+{
+	wait_until_resumed().await;
+	run_handlers(msg, &context_manager, routes).await?;
+
+	let config = MessageBus::config();
+	if config.aggregate_lanes && !config.deterministic_execution {
+		handle_in_lanes(&context_manager, routes).await?;
+		return Ok(context_manager);
+	}
+
+	// Resursive case
+	let incoming_event = context_manager.get_mut().pop_front();
+
+	if let Some(event) = incoming_event {
+		if let Err(err) = handle_event(event, Arc::clone(&context_manager), routes).await {
+			// * Passing the deadline aborts the rest of the loop, rather than the event alone
+			if context_manager.is_past_deadline() {
+				return Err(err);
+			}
+			// ! Safety:: BaseError Must Be Enforced To Be Accepted As Variant On ServiceError
+			tracing::error!("{:?}", err);
+		}
+	}
+	Ok(context_manager)
+}
+
+/// Drain queued events round by round, splitting each round into lanes by [EventMetadata::aggregate_id].
+/// Lanes run concurrently while events in a lane are handled in the order they were queued, so events of an aggregate are never reordered.
+/// Events raised meanwhile are handled in the next round. Passing the deadline of the request aborts the rounds left.
+///
+/// [EventMetadata::aggregate_id]: crate::prelude::EventMetadata
+async fn handle_in_lanes<E>(context_manager: &AtomicContextManager, routes: Routes<E>) -> Result<(), E>
+where
+	E: ApplicationError + std::convert::From<crate::responses::BaseError> + std::convert::From<E>,
+	crate::responses::BaseError: std::convert::From<E>,
+{
+	loop {
+		let mut lanes: Vec<(String, Vec<Arc<dyn TEvent>>)> = vec![];
+		while let Some(event) = context_manager.get_mut().pop_front() {
+			let aggregate_id = event.metadata().aggregate_id;
+			match lanes.iter_mut().find(|(lane, _)| *lane == aggregate_id) {
+				Some((_, events)) => events.push(event),
+				None => lanes.push((aggregate_id, vec![event])),
+			}
+		}
+		if lanes.is_empty() {
+			return Ok(());
+		}
+		context_manager.ensure_within_deadline()?;
+		wait_until_resumed().await;
+		let lanes = lanes.into_iter().map(|(_, events)| async move {
+			for event in events {
+				// * Failure of an event doesn't hold back the ones behind it in the lane
+				if let Err(err) = run_handlers(event, context_manager, routes).await {
+					tracing::error!("{:?}", err);
+				}
+			}
+		});
+		futures::future::join_all(lanes).await;
+	}
+}
+
+/// Hold event handling back while the bus is paused. Shutdown lifts the pause so that events in flight are drained rather than waited on forever.
+async fn wait_until_resumed() {
+	if !PAUSE_GATE.is_paused() {
+		return;
+	}
+	tokio::select! {
+		_ = PAUSE_GATE.opened() => (),
+		_ = SHUTDOWN_HANDLE.signaled() => (),
+	}
+}
+
+/// Run handlers of the event and the commands they dispatched
+async fn run_handlers<E>(msg: Arc<dyn TEvent>, context_manager: &AtomicContextManager, routes: Routes<E>) -> Result<(), E>
 where
 	E: ApplicationError + std::convert::From<crate::responses::BaseError> + std::convert::From<E>,
 	crate::responses::BaseError: std::convert::From<E>,
 {
+	context_manager.ensure_within_deadline()?;
+
 	// ! msg.topic returns the name of event. It is crucial that it corresponds to the key registered on Event Handler.
 	#[cfg(feature = "tracing")]
 	{
 		tracing::info!("Processing {}...", msg.metadata().topic);
 	}
 
-	let handlers = event_handler.get(&msg.metadata().topic).ok_or_else(|| {
-		tracing::error!("Unprocessable Event Given! {:?}", msg);
-		BaseError::NotFound
-	})?;
+	let topic = msg.metadata().topic;
+	let config = MessageBus::config();
+	let handlers = routes.handlers_of(&msg, &topic);
+	let pattern_handlers = routes.pattern_event_handler.iter().filter(|handler| handler.matches(&topic)).collect::<Vec<_>>();
+	let catch_all_handlers = config.catch_all_handlers_of::<E>();
+	if handlers.is_none() && pattern_handlers.is_empty() && catch_all_handlers.is_empty() {
+		tracing::error!("Unprocessable Event Given! {:?}", msg);
+		Err(BaseError::NotFound)?
+	}
+
+	// * Event given from outside of the request is not queued, so it is marked here
+	context_manager.mark_seen(&msg);
+	audit::record_event(msg.as_ref(), &topic, &context_manager.correlation_id).await;
+	EVENT_FEED.publish(&msg);
+
+	let (timeout, permits, retry_policies, breaker) = (config.event_handler_timeout, config.handler_concurrency.get(&topic), config.retry_policies_of::<E>(), config.circuit_breakers.get(&topic));
+	context_manager.get_mut().report.topics.push(topic.clone());
+
+	let handler_count = match handlers {
+		Some(EventHandlers::Sync(h)) | Some(EventHandlers::Async(h)) => h.len(),
+		None => 0,
+	} + pattern_handlers.len()
+		+ catch_all_handlers.len();
+	let span = telemetry::event_span(&topic, handler_count);
+	let mut results = HandlerResults::new(&topic);
+	let mut stopped = false;
+
+	match handlers {
+		None => (),
+		Some(EventHandlers::Sync(h)) => {
+			for (i, handler) in h.iter().enumerate() {
+				context_manager.ensure_within_deadline()?;
+				let result = metrics::measure_handler(
+					handle_with_breaker(
+						handle_with_retry(|| handle_with_permit(handle_with_timeout(handler(msg.clone(), Arc::clone(context_manager)), &topic, timeout), permits), retry_policies),
+						&topic,
+						breaker,
+					),
+					&topic,
+				)
+				.instrument(span.clone())
+				.await;
+				if stop_at(&config, context_manager, results.push(result), i).await {
+					stopped = true;
+					break;
+				}
+			}
+		}
+		Some(EventHandlers::Async(h)) if config.deterministic_execution => {
+			// * Run one by one in the order of registration
+			for handler in h.iter() {
+				context_manager.ensure_within_deadline()?;
+				let result = metrics::measure_handler(
+					handle_with_breaker(
+						handle_with_retry(|| handle_with_permit(handle_with_timeout(handler(msg.clone(), Arc::clone(context_manager)), &topic, timeout), permits), retry_policies),
+						&topic,
+						breaker,
+					),
+					&topic,
+				)
+				.instrument(span.clone())
+				.await;
+				results.push(result);
+			}
+		}
+		Some(EventHandlers::Async(h)) => {
+			let futures = h.iter().map(|handler| {
+				metrics::measure_handler(
+					handle_with_breaker(
+						handle_with_retry(|| handle_with_permit(handle_with_timeout(handler(msg.clone(), Arc::clone(context_manager)), &topic, timeout), permits), retry_policies),
+						&topic,
+						breaker,
+					),
+					&topic,
+				)
+				.instrument(span.clone())
+			});
+			// * Every handler runs to completion even when another one fails, so that the failed ones are told apart from the others
+			for result in futures::future::join_all(futures).await {
+				results.push(result);
+			}
+		}
+	}
+
+	// * Pattern handlers observe every matching event, so they run even when stop sentinel arrived in exact handlers.
+	for handler in pattern_handlers {
+		let result = metrics::measure_handler(handle_with_retry(|| handle_with_timeout((handler.handler)(msg.clone(), Arc::clone(context_manager)), &topic, timeout), retry_policies), &topic)
+			.instrument(span.clone())
+			.await;
+		if let Err(err) = &result {
+			let error_msg = format!("Error Occurred While Handling Event In Handler Of Pattern {}! Error:{:?}", handler.pattern, err);
+			crate::backtrace_error!("{}", error_msg);
+		}
+		results.push(result);
+	}
+
+	// * Catch-all handlers run one after another, unless stop sentinel arrived in the handlers of the topic
+	for handler in catch_all_handlers.iter().filter(|_| !stopped) {
+		context_manager.ensure_within_deadline()?;
+		let i = results.results.len();
+		let result = metrics::measure_handler(handle_with_retry(|| handle_with_timeout(handler(msg.clone(), Arc::clone(context_manager)), &topic, timeout), retry_policies), &topic)
+			.instrument(span.clone())
+			.await;
+		if stop_at(&config, context_manager, results.push(result), i).await {
+			break;
+		}
+	}
+	results.report(msg.as_ref(), context_manager, &span).await;
+
+	dispatch_commands(context_manager, routes.command_dispatcher).instrument(span).await;
+	Ok(())
+}
+
+/// Commit transaction shared within the request once it is handled. It is rolled back instead when the command or any of the event handlers failed,
+/// in which case the request fails with [BaseError::TransactionError] if it was only event handlers that failed. See [crate::prelude::TRequestTransaction].
+async fn settle_shared_transaction<T, E>(context_manager: &AtomicContextManager, handled: Result<T, E>) -> Result<T, E>
+where
+	E: std::convert::From<crate::responses::BaseError>,
+{
+	let Some(transaction) = context_manager.get_mut().transaction.take() else {
+		return handled;
+	};
+	if handled.is_ok() && !context_manager.handler_failed {
+		transaction.commit().await?;
+		return handled;
+	}
+	if let Err(err) = transaction.rollback().await {
+		tracing::error!("Failed To Roll Back Shared Transaction! Error:{:?}", err);
+	}
+	if handled.is_ok() {
+		tracing::error!("Shared Transaction Is Rolled Back As Event Handler Failed!");
+		Err(BaseError::TransactionError)?
+	}
+	handled
+}
+
+/// Stop the rest of the handlers if `i`th handler returned stop sentinel, queueing the event it carries. Returns whether it did.
+async fn stop_at(config: &MessageBusConfig, context_manager: &AtomicContextManager, stop_sentinel: Option<StopSentinel>, i: usize) -> bool {
+	match stop_sentinel {
+		Some(StopSentinel::Plain) => {
+			let error_msg = format!("Stop Sentinel Arrived In {i}th Event!");
+			crate::backtrace_error!("{}", error_msg);
+			true
+		}
+		Some(StopSentinel::WithEvent(event)) => {
+			let error_msg = format!("Stop Sentinel With Event {} Arrived In {i}th Event! State:{}", event.metadata().topic, event.redacted_state());
+			crate::backtrace_error!("{}", error_msg);
+			publish_stop_sentinel_event(config, event.as_ref()).await;
+			match config.poison_message_threshold.map(|threshold| context_manager.count_requeue(event.as_ref(), threshold)) {
+				Some(Err(poison)) => dead_letter_poison_message(config, event.as_ref(), i, poison).await,
+				_ => context_manager.get_mut().push_back(event),
+			}
+			true
+		}
+		None => false,
+	}
+}
+
+/// Publish outbox of externally notifiable event carried by stop sentinel, when publisher is given to [MessageBusConfig::with_stop_sentinel_publisher]
+async fn publish_stop_sentinel_event(config: &MessageBusConfig, event: &dyn TEvent) {
+	let Some(publisher) = config.stop_sentinel_publisher.as_ref().filter(|_| event.externally_notifiable()) else {
+		return;
+	};
+	let outbox = match event.try_outbox() {
+		Ok(outbox) => outbox,
+		Err(err) => {
+			tracing::error!("Failed To Serialize Stop Sentinel Event {}! Error:{:?}", event.metadata().topic, err);
+			return;
+		}
+	};
+	if let Err(err) = publisher.publish(&outbox).await {
+		tracing::error!("Failed To Publish Outbox Of Stop Sentinel Event {}! Error:{:?}", event.metadata().topic, err);
+	}
+}
+
+/// Send event re-enqueued too many times to the sink given to [MessageBusConfig::with_dead_letter_sink] instead of queueing it again
+async fn dead_letter_poison_message(config: &MessageBusConfig, event: &dyn TEvent, handler: usize, poison: BaseError) {
+	let topic = event.metadata().topic;
+	tracing::error!("Poison Message {} Is Not Queued Again! Error:{:?}", topic, poison);
+	let Some(sink) = config.dead_letter_sink.as_ref() else {
+		return;
+	};
+	let dead_letter = DeadLetter {
+		topic: topic.clone(),
+		payload: event.try_state().map(String::into_bytes).unwrap_or_default(),
+		reason: format!("{:?}", poison),
+		handler: Some(handler),
+		metadata: Default::default(),
+	};
+	if let Err(err) = sink.send(dead_letter).await {
+		tracing::error!("Failed to dead-letter poison message {}! Error:{:?}", topic, err);
+	}
+}
+
+tokio::task_local! {
+	// * How deep the command being handled is in the chain of commands dispatched from event handlers
+	static COMMAND_DEPTH: usize;
+	// * Correlation id of the request that dispatched the command being handled
+	pub(crate) static CORRELATION_ID: String;
+}
+
+/// Handle commands queued by event handlers one after another, each in its own context.
+async fn dispatch_commands<E>(context_manager: &AtomicContextManager, command_dispatcher: Option<&'static CommandDispatchers<E>>)
+where
+	E: std::fmt::Debug,
+{
+	let commands = std::mem::take(&mut context_manager.get_mut().commands);
+	if commands.is_empty() {
+		return;
+	}
+	if context_manager.is_replaying() {
+		tracing::warn!("Commands Dispatched While Replaying Are Discarded! {:?}", commands);
+		return;
+	}
+
+	let depth = COMMAND_DEPTH.try_with(|depth| *depth).unwrap_or(0) + 1;
+	let max_depth = MessageBus::config().max_command_depth();
+	for command in commands {
+		if depth > max_depth {
+			tracing::error!("{:?}", BaseError::CommandDepthExceeded(max_depth));
+			continue;
+		}
+		let Some(dispatcher) = command_dispatcher.and_then(|dispatchers| dispatchers.get(&command.as_any().type_id())) else {
+			tracing::error!("Unregistered Command {} Dispatched! {:?}", command.command_name(), command);
+			continue;
+		};
+		let dispatching = CORRELATION_ID.scope(context_manager.correlation_id.clone(), dispatcher(command, context_manager.conn));
+		if let Err(err) = COMMAND_DEPTH.scope(depth, dispatching).await {
+			let error_msg = format!("Error Occurred While Handling Dispatched Command! Error:{:?}", err);
+			crate::backtrace_error!("{}", error_msg);
+		}
+	}
+}
+
+/// What happened while handling events within a request
+#[derive(Debug, Default, Clone)]
+pub struct EventReport {
+	/// Topics of processed events in the order they were processed
+	pub topics: Vec<String>,
+	/// Number of handler invocations that succeeded
+	pub succeeded: usize,
+	/// Number of handler invocations that returned error, including stop sentinels and timeouts
+	pub failed: usize,
+	/// Topic and index of each handler invocation counted in `failed`, as they are given in [HandlerResults]
+	pub failed_handlers: Vec<(String, usize)>,
+}
+
+impl EventReport {
+	pub fn processed(&self) -> usize {
+		self.topics.len()
+	}
+
+	fn merge(&mut self, other: EventReport) {
+		self.topics.extend(other.topics);
+		self.succeeded += other.succeeded;
+		self.failed += other.failed;
+		self.failed_handlers.extend(other.failed_handlers);
+	}
+}
+
+/// Result of each handler of an event. Handlers of the topic come in the order they were registered, followed by pattern handlers matching it.
+/// Handlers left unrun after stop sentinel have no result.
+///
+/// Handlers that failed are reported one by one to [EventReport::failed_handlers], to telemetry and, as [DeadLetter]s,
+/// to the sink given to [MessageBusConfig::with_dead_letter_sink]. Stop sentinels are not dead-lettered as they are not failures.
+///
+/// [DeadLetter]: super::dead_letter::DeadLetter
+pub struct HandlerResults<E> {
+	pub topic: String,
+	pub results: Vec<Result<(), E>>,
+	/// Indices of results that are stop sentinels
+	stop_sentinels: Vec<usize>,
+}
+
+enum StopSentinel {
+	Plain,
+	WithEvent(Arc<dyn TEvent>),
+}
+
+impl<E> HandlerResults<E>
+where
+	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+	crate::responses::BaseError: std::convert::From<E>,
+{
+	fn new(topic: &str) -> Self {
+		Self { topic: topic.to_string(), results: vec![], stop_sentinels: vec![] }
+	}
+
+	pub fn failed(&self) -> impl Iterator<Item = (usize, &E)> {
+		self.results.iter().enumerate().filter_map(|(i, result)| result.as_ref().err().map(|err| (i, err)))
+	}
+
+	/// Keep result of the next handler, telling whether it is stop sentinel
+	fn push(&mut self, result: Result<(), E>) -> Option<StopSentinel> {
+		let Err(err) = result else {
+			self.results.push(Ok(()));
+			return None;
+		};
+		// ! Safety:: BaseError Must Be Enforced To Be Accepted As Variant On ServiceError
+		let err = BaseError::from(err);
+		let stop_sentinel = match &err {
+			BaseError::StopSentinel => Some(StopSentinel::Plain),
+			BaseError::StopSentinelWithEvent(event) => Some(StopSentinel::WithEvent(event.clone())),
+			err => {
+				let error_msg = format!("Error Occurred While Handling Event In {}th Handler Of {}! Error:{:?}", self.results.len(), self.topic, err);
+				crate::backtrace_error!("{}", error_msg);
+				None
+			}
+		};
+		if stop_sentinel.is_some() {
+			self.stop_sentinels.push(self.results.len());
+		}
+		self.results.push(Err(E::from(err)));
+		stop_sentinel
+	}
+
+	async fn report(self, event: &dyn TEvent, context_manager: &AtomicContextManager, span: &tracing::Span) {
+		let failed = self.failed().map(|(i, _)| i).collect::<Vec<_>>();
+		telemetry::record_handler_outcome(span, self.results.len() - failed.len(), failed.len());
+		let handler_failed = failed.iter().any(|i| !self.stop_sentinels.contains(i));
+		if handler_failed {
+			context_manager.get_mut().handler_failed = true;
+		}
+		metrics::record_event(&self.topic, !handler_failed);
+		{
+			let report = &mut context_manager.get_mut().report;
+			report.succeeded += self.results.len() - failed.len();
+			report.failed += failed.len();
+			report.failed_handlers.extend(failed.iter().map(|i| (self.topic.clone(), *i)));
+		}
+
+		let Some(sink) = MessageBus::config().dead_letter_sink.clone() else {
+			return;
+		};
+		for (i, err) in self.failed().filter(|(i, _)| !self.stop_sentinels.contains(i)) {
+			let dead_letter = DeadLetter {
+				topic: self.topic.clone(),
+				payload: event.try_state().map(String::into_bytes).unwrap_or_default(),
+				reason: format!("{:?}", err),
+				handler: Some(i),
+				metadata: Default::default(),
+			};
+			if let Err(err) = sink.send(dead_letter).await {
+				tracing::error!("Failed to dead-letter {}th handler of {}! Error:{:?}", i, self.topic, err);
+			}
+		}
+	}
+}
+
+/// Wait for permit of the topic, if its concurrency is limited, before handling. Time spent waiting doesn't count towards handler timeout.
+async fn handle_with_permit<E>(handling: impl std::future::Future<Output = Result<(), E>>, permits: Option<&Arc<Semaphore>>) -> Result<(), E> {
+	// * Semaphore is never closed, so acquisition always succeeds
+	let _permit = match permits {
+		Some(permits) => permits.acquire().await.ok(),
+		None => None,
+	};
+	handling.await
+}
+
+/// Dropping handler on expiry cancels it. The error is then handled the same way as the one returned from handler.
+async fn handle_with_timeout<E>(handling: super::handler::Future<E>, topic: &str, timeout: Option<Duration>) -> Result<(), E>
+where
+	E: std::convert::From<crate::responses::BaseError>,
+{
+	let Some(after) = timeout else {
+		return handling.await;
+	};
+	tokio::time::timeout(after, handling).await.map_err(|_| BaseError::Timeout { command: topic.to_string(), after })?
+}
+
+/// Dropping command execution on expiry cancels it, rolling back transaction it holds.
+/// `cancellation` is cancelled as well, for the work the command left running, such as spawned tasks.
+async fn execute_with_timeout<C, R, E>(execution: impl std::future::Future<Output = Result<R, E>>, cancellation: Option<&CancellationToken>) -> Result<R, E>
+where
+	C: TCommand,
+	E: std::convert::From<crate::responses::BaseError>,
+{
+	let Some(after) = MessageBus::config().timeout_of::<C>() else {
+		return execution.await;
+	};
+	tokio::time::timeout(after, execution).await.map_err(|_| {
+		let command = std::any::type_name::<C>().to_string();
+		tracing::error!("{} timed out after {:?}!", command, after);
+		if let Some(cancellation) = cancellation {
+			cancellation.cancel();
+		}
+		BaseError::Timeout { command, after }
+	})?
+}
+
+/// Spawn `future` on the runtime. With `local` feature, it is spawned on the [tokio::task::LocalSet] being run instead, as it may not be `Send`.
+#[cfg(not(feature = "local"))]
+pub(crate) fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+	F: std::future::Future + Send + 'static,
+	F::Output: Send + 'static,
+{
+	tokio::spawn(future)
+}
+#[cfg(feature = "local")]
+pub(crate) fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+	F: std::future::Future + 'static,
+	F::Output: 'static,
+{
+	tokio::task::spawn_local(future)
+}
+
+/// Interface for messagebus to work on
+pub trait TCommandService<R, E>: Send + Sync {
+	fn execute(self) -> impl std::future::Future<Output = Result<R, E>> + Send;
+}
+
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
+pub trait TMessageBus<R, E, C>: TEventBus<E>
+where
+	responses::BaseError: std::convert::From<E>,
+	R: ApplicationResponse,
+	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+	C: TCommand,
+{
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: C) -> impl TCommandService<R, E>;
+
+	/// This method is used to handle command and return result.
+	/// ## Example
+	/// ```rust,no_run
+	/// let res = service.execute_and_wait(message).await?;
+	/// ```
+
+	async fn execute_and_wait(&self, message: C, conn: &'static dyn TConnection) -> Result<R, E> {
+		let (res, _) = self.execute_with_report(message, conn).await?;
+		Ok(res)
+	}
+
+	/// Same as [TMessageBus::execute_and_wait] but also returns what happened while handling events raised by the command.
+	/// ## Example
+	/// ```rust,no_run
+	/// let (res, report) = service.execute_with_report(message).await?;
+	/// tracing::info!("{} events processed : {:?}", report.processed(), report.topics);
+	/// ```
+	async fn execute_with_report(&self, message: C, conn: &'static dyn TConnection) -> Result<(R, EventReport), E> {
+		#[cfg(feature = "tracing")]
+		{
+			tracing::info!("{}", std::any::type_name::<C>());
+		}
+
+		message.validate()?;
+
+		// * Held until events raised by the command are handled so that shutdown waits for them
+		let _in_flight = MessageBus::shutdown_handle().enter()?;
+		let context_manager = Arc::new(ContextManager::new(conn));
+		let mut request = RequestGuard::start(&context_manager);
+		audit::record_command(&message, &context_manager.correlation_id).await;
+		let span = telemetry::command_span(&message);
+		let res = metrics::measure_command::<C, _, _>(execute_with_timeout::<C, _, _>(self.command_handler(Arc::clone(&context_manager), message).execute(), Some(&context_manager.cancellation)))
+			.instrument(span.clone())
+			.await;
+		telemetry::record_outcome(&span, res.is_ok());
+		let res = match res {
+			Ok(res) => res,
+			Err(err) => return settle_shared_transaction(&context_manager, Err(err)).await,
+		};
+
+		// Trigger event handler
+		let handled = match context_manager.get_mut().pop_front() {
+			Some(event) => handle_event(event, Arc::clone(&context_manager), Routes::of(self)).instrument(span).await.map(|_| ()),
+			None => Ok(()),
+		};
+		settle_shared_transaction(&context_manager, handled).await?;
+		perform_effects(&context_manager).await;
+		request.succeed();
+		Ok((res, std::mem::take(&mut context_manager.get_mut().report)))
+	}
+
+	/// This method is used to handle command and return result proxy which holds the result and join handler.
+	/// ## Example
+	/// ```rust,no_run
+	/// let res = service.execute_and_forget(message).await?;
+	/// let res = res.wait_until_event_processing_done().await?;
+	/// let res = res.result();
+	/// ```
+	async fn execute_and_forget(&self, message: C, conn: &'static dyn TConnection) -> Result<CommandResponseWithEventFutures<R, E>, E> {
+		#[cfg(feature = "tracing")]
+		{
+			tracing::info!("{}", std::any::type_name::<C>());
+		}
+
+		message.validate()?;
+
+		let in_flight = MessageBus::shutdown_handle().enter()?;
+		let context_manager = Arc::new(ContextManager::new(conn));
+		let mut request = RequestGuard::start(&context_manager);
+		audit::record_command(&message, &context_manager.correlation_id).await;
+		let span = telemetry::command_span(&message);
+		let res = metrics::measure_command::<C, _, _>(execute_with_timeout::<C, _, _>(self.command_handler(Arc::clone(&context_manager), message).execute(), Some(&context_manager.cancellation)))
+			.instrument(span.clone())
+			.await;
+		telemetry::record_outcome(&span, res.is_ok());
+		let result = match res {
+			Ok(result) => result,
+			Err(err) => return settle_shared_transaction(&context_manager, Err(err)).await,
+		};
+		let mut res = CommandResponseWithEventFutures { result, event_processing: None };
+
+		// Trigger event handler
+		if !context_manager.event_queue.is_empty() {
+			let event = context_manager.get_mut().pop_front().unwrap();
+			let routes = Routes::of(self);
+
+			if MessageBus::config().deterministic_execution {
+				let handled = handle_event(event, Arc::clone(&context_manager), routes).instrument(span).await;
+				let handled = settle_shared_transaction(&context_manager, handled).await;
+				if handled.is_ok() {
+					perform_effects(&context_manager).await;
+					request.succeed();
+				}
+				drop(request);
+				drop(in_flight);
+				res.event_processing = Some(EventProcessing::Done(handled));
+				return Ok(res);
+			}
+			res.event_processing = Some(EventProcessing::Spawned(spawn(
+				async move {
+					let _in_flight = in_flight;
+					let handled = handle_event(event, Arc::clone(&context_manager), routes).await;
+					let handled = settle_shared_transaction(&context_manager, handled).await;
+					if handled.is_ok() {
+						perform_effects(&context_manager).await;
+						request.succeed();
+					}
+					handled
+				}
+				.instrument(span),
+			)));
+			return Ok(res);
+		}
+		settle_shared_transaction(&context_manager, Ok(())).await?;
+		perform_effects(&context_manager).await;
+		request.succeed();
+		Ok(res)
+	}
+
+	/// Return the result as soon as the command is handled, leaving the events it raised to a task spawned with the [ContextManager] of the request,
+	/// so that the response is sent without waiting for side effects. Unlike [TMessageBus::execute_and_forget], nothing is left to wait on;
+	/// failure, and even panic, in handling the events is logged. Events are still handled before returning when execution is deterministic.
+	///
+	/// ## Durability
+	/// Events live only in memory of the spawned task, so they are lost when the process goes down before they are handled.
+	/// Handlers that must not be skipped belong behind `externally_notifiable` events, whose outboxes are committed along with the command.
+	/// ## Example
+	/// ```rust,no_run
+	/// let res = MessageBus.handle_deferred(message, &CONNECTION).await?;
+	/// ```
+	async fn handle_deferred(&self, message: C, conn: &'static dyn TConnection) -> Result<R, E> {
+		let res = self.execute_and_forget(message, conn).await?;
+		let CommandResponseWithEventFutures { result, event_processing } = res;
+		match event_processing {
+			Some(EventProcessing::Spawned(handle)) => {
+				// * Panic in the spawned task surfaces as error of its handle, which is otherwise dropped unnoticed
+				spawn(async move {
+					match handle.await {
+						Ok(Ok(_)) => {}
+						Ok(Err(err)) => tracing::error!("Error Occurred While Handling Deferred Events! Error:{:?}", err),
+						Err(err) => tracing::error!("Deferred Event Handling Panicked! Error:{:?}", err),
+					}
+				});
+			}
+			Some(EventProcessing::Done(Err(err))) => tracing::error!("Error Occurred While Handling Deferred Events! Error:{:?}", err),
+			Some(EventProcessing::Done(Ok(_))) | None => {}
+		}
+		Ok(result)
+	}
+
+	/// Handle commands in the given order, returning their results in the same order.
+	/// ## Transactional semantics
+	/// - [BatchContext::Isolated] : each command is handled as if it were given to [TMessageBus::execute_and_wait] one after another.
+	///   Events raised by a command are handled before the next command is executed.
+	/// - [BatchContext::Shared] : commands share one [ContextManager]. Each command is still committed or rolled back on its own by its unit of work,
+	///   so failure of a command doesn't undo the others and doesn't stop the batch. Events raised by committed commands are queued together
+	///   and handled after the last command, in the order of their priority. Failure in handling them is logged, not reported in the results.
+	/// - [BatchContext::Atomic] : commands share one [ContextManager] as with [BatchContext::Shared], and the batch is committed or rolled back as a whole
+	///   through the transaction shared on it by [ContextManager::share_transaction], which commands of the batch are to write through.
+	///   Once a command fails, the ones after it are not executed and the transaction is rolled back without handling the events.
+	///   Otherwise it is settled after the events as with [TMessageBus::execute_and_wait]. Commands undone by rollback, along with the ones not executed,
+	///   result in [BaseError::TransactionError], so only the command that failed carries its own error.
+	///
+	/// ## Example
+	/// ```rust,no_run
+	/// let results = MessageBus.execute_batch(commands, &CONNECTION, BatchContext::Shared).await;
+	/// ```
+	async fn execute_batch(&self, messages: Vec<C>, conn: &'static dyn TConnection, context: BatchContext) -> Vec<Result<R, E>> {
+		let mut results = Vec::with_capacity(messages.len());
+		match context {
+			BatchContext::Isolated => {
+				for message in messages {
+					results.push(self.execute_and_wait(message, conn).await);
+				}
+			}
+			BatchContext::Shared | BatchContext::Atomic => {
+				let _in_flight = match MessageBus::shutdown_handle().enter() {
+					Ok(in_flight) => in_flight,
+					Err(err) => return messages.iter().map(|_| Err(err.clone().into())).collect(),
+				};
+				let context_manager = Arc::new(ContextManager::new(conn));
+				let mut request = RequestGuard::start(&context_manager);
+				for message in messages {
+					if context == BatchContext::Atomic && results.iter().any(Result::is_err) {
+						results.push(Err(BaseError::TransactionError.into()));
+						continue;
+					}
+					if let Err(err) = message.validate() {
+						results.push(Err(err.into()));
+						continue;
+					}
+					audit::record_command(&message, &context_manager.correlation_id).await;
+					let span = telemetry::command_span(&message);
+					// * Commands of the batch share the context, so timeout of one doesn't cancel the others
+					let res = metrics::measure_command::<C, _, _>(execute_with_timeout::<C, _, _>(self.command_handler(Arc::clone(&context_manager), message).execute(), None))
+						.instrument(span.clone())
+						.await;
+					telemetry::record_outcome(&span, res.is_ok());
+					results.push(res);
+				}
+
+				if context == BatchContext::Atomic && results.iter().any(Result::is_err) {
+					let transaction = context_manager.get_mut().transaction.take();
+					if let Some(Err(err)) = futures::future::OptionFuture::from(transaction.map(|transaction| transaction.rollback())).await {
+						tracing::error!("Failed To Roll Back Transaction Of Batch! Error:{:?}", err);
+					}
+					return results.into_iter().map(|res| res.and_then(|_| Err(BaseError::TransactionError.into()))).collect();
+				}
+
+				let event = context_manager.get_mut().pop_front();
+				let mut handled = Ok(());
+				if let Some(event) = event {
+					if let Err(err) = handle_event(event, Arc::clone(&context_manager), Routes::of(self)).await {
+						tracing::error!("Error Occurred While Handling Events Of Batch! Error:{:?}", err);
+						handled = Err(err);
+					}
+				}
+				if context == BatchContext::Atomic {
+					if let Err(err) = settle_shared_transaction(&context_manager, handled).await {
+						tracing::error!("Batch Is Rolled Back! Error:{:?}", err);
+						return results.into_iter().map(|res| res.and_then(|_| Err(BaseError::TransactionError.into()))).collect();
+					}
+					perform_effects(&context_manager).await;
+					request.succeed();
+					return results;
+				}
+				// * Commands that succeeded are committed on their own, so their effects are performed even when the others failed
+				perform_effects(&context_manager).await;
+				// * Batch is taken as failed when any of its commands or events failed
+				if handled.is_ok() && results.iter().all(Result::is_ok) {
+					request.succeed();
+				}
+			}
+		}
+		results
+	}
+}
+
+/// Interface for messagebus to work on, for command that produces stream of results rather than a single one, such as export
+pub trait TStreamingCommandService<R, E>: Send + Sync {
+	fn execute(self) -> impl std::future::Future<Output = Result<impl futures::Stream<Item = R> + Send + 'static, E>> + Send;
+}
+
+/// When events raised by streaming command are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StreamedEvents {
+	/// After the stream is exhausted
+	#[default]
+	AfterStream,
+	/// Before each item is yielded, events raised while producing it are handled
+	WhileStreaming,
+}
+
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
+pub trait TStreamingMessageBus<R, E, C>: TEventBus<E>
+where
+	responses::BaseError: std::convert::From<E>,
+	R: Send + 'static,
+	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+	C: TCommand,
+{
+	fn stream_handler(&self, context_manager: AtomicContextManager, cmd: C) -> impl TStreamingCommandService<R, E>;
+
+	/// Handle command and return the stream it produces.
+	/// [ContextManager] of the command lives as long as the stream does, so events can be raised while streaming.
+	/// They are handled according to `events`, and failure in handling them is logged, not given to the caller.
+	/// Command timeout applies only until the stream is returned. Shutdown waits until the stream is dropped.
+	/// ## Example
+	/// ```rust,no_run
+	/// let mut rows = MessageBus.execute_stream(ExportOrders, &CONNECTION, StreamedEvents::AfterStream).await?;
+	/// while let Some(row) = rows.next().await {
+	///     writer.write(row).await?;
+	/// }
+	/// ```
+	async fn execute_stream(&self, message: C, conn: &'static dyn TConnection, events: StreamedEvents) -> Result<ResponseStream<R>, E> {
+		#[cfg(feature = "tracing")]
+		{
+			tracing::info!("{}", std::any::type_name::<C>());
+		}
+
+		message.validate()?;
+
+		let in_flight = MessageBus::shutdown_handle().enter()?;
+		let context_manager = Arc::new(ContextManager::new(conn));
+		let request = RequestGuard::start(&context_manager);
+		audit::record_command(&message, &context_manager.correlation_id).await;
+		let span = telemetry::command_span(&message);
+		let res = metrics::measure_command::<C, _, _>(execute_with_timeout::<C, _, _>(self.stream_handler(Arc::clone(&context_manager), message).execute(), Some(&context_manager.cancellation)))
+			.instrument(span.clone())
+			.await;
+		telemetry::record_outcome(&span, res.is_ok());
+		let stream = Box::pin(res?);
+
+		let routes = Routes::of(self);
+		let state = (stream, context_manager, in_flight, request);
+		let stream = futures::stream::unfold(state, move |(mut stream, context_manager, in_flight, mut request)| {
+			let span = span.clone();
+			async move {
+				let item = futures::StreamExt::next(&mut stream).await;
+				if item.is_none() || events == StreamedEvents::WhileStreaming {
+					handle_queued_events(&context_manager, routes).instrument(span).await;
+				}
+				if item.is_none() {
+					perform_effects(&context_manager).await;
+					request.succeed();
+				}
+				// * Context manager and guards are dropped once the stream is exhausted
+				item.map(|item| (item, (stream, context_manager, in_flight, request)))
+			}
+		});
+		Ok(Box::pin(stream))
+	}
+}
+
+async fn handle_queued_events<E>(context_manager: &AtomicContextManager, routes: Routes<E>)
+where
+	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
+	crate::responses::BaseError: std::convert::From<E>,
+{
+	let Some(event) = context_manager.get_mut().pop_front() else {
+		return;
+	};
+	if let Err(err) = handle_event(event, Arc::clone(context_manager), routes).await {
+		tracing::error!("Error Occurred While Handling Events Of Stream! Error:{:?}", err);
+	}
+}
+
+/// Future of command handled by bus. It is not required to be `Send` with `local` feature, as it awaits event handlers that are not.
+#[cfg(not(feature = "local"))]
+pub type CommandFuture<T> = futures::future::BoxFuture<'static, T>;
+#[cfg(feature = "local")]
+pub type CommandFuture<T> = futures::future::LocalBoxFuture<'static, T>;
+
+/// Stream returned by [TStreamingMessageBus::execute_stream], which is not `Send` with `local` feature for the same reason as [CommandFuture]
+#[cfg(not(feature = "local"))]
+pub type ResponseStream<R> = futures::stream::BoxStream<'static, R>;
+#[cfg(feature = "local")]
+pub type ResponseStream<R> = futures::stream::LocalBoxStream<'static, R>;
+
+/// Handler that takes boxed command and handles it with [TMessageBus::execute_and_wait] of its concrete type
+pub type DynCommandHandler<R, E> = fn(Box<dyn TCommand>, &'static dyn TConnection) -> CommandFuture<Result<R, E>>;
+
+/// Handler of command dispatched from event handler. Response is discarded as there is no one to receive it.
+pub type CommandDispatcher<E> = fn(Box<dyn TCommand>, &'static dyn TConnection) -> CommandFuture<Result<(), E>>;
 
-	match handlers {
-		EventHandlers::Sync(h) => {
-			for (i, handler) in h.iter().enumerate() {
-				if let Err(err) = handler(msg.clone(), Arc::clone(&context_manager)).await {
-					// ! Safety:: BaseError Must Be Enforced To Be Accepted As Variant On ServiceError
-					match err.into() {
-						BaseError::StopSentinel => {
-							let error_msg = format!("Stop Sentinel Arrived In {i}th Event!");
-							crate::backtrace_error!("{}", error_msg);
-							break;
-						}
-						BaseError::StopSentinelWithEvent(event) => {
-							let error_msg = format!("Stop Sentinel With Event Arrived In {i}th Event!");
-							crate::backtrace_error!("{}", error_msg);
-							context_manager.get_mut().push_back(event);
-							break;
-						}
-						err => {
-							let error_msg = format!("Error Occurred While Handling Event In {i}th Event! Error:{:?}", err);
-							crate::backtrace_error!("{}", error_msg);
-						}
-					}
-				}
-			}
-		}
-		EventHandlers::Async(h) => {
-			let futures = h.iter().map(|handler| handler(msg.clone(), Arc::clone(&context_manager)));
-			if let Err(err) = futures::future::try_join_all(futures).await {
-				let error_msg = format!("Error Occurred While Handling Event! Error:{:?}", err);
-				crate::backtrace_error!("{}", error_msg);
-			}
-		}
-	}
+/// Command dispatchers keyed by type id of the command
+pub type CommandDispatchers<E> = hashbrown::HashMap<TypeId, CommandDispatcher<E>>;
 
-	// Resursive case
-	let incoming_event = context_manager.get_mut().pop_front();
+/// Command dispatcher registered by `init_dyn_command_handler!`.
+/// As dispatchers for different error types are collected in the same inventory, `dispatcher` returns type-erased [CommandDispatcher]
+pub struct CommandDispatcherRegistration {
+	pub command: fn() -> TypeId,
+	pub name: fn() -> &'static str,
+	/// `file:line` of the macro invocation, reported when the command is registered more than once
+	pub location: &'static str,
+	/// Registered with `override` keyword, replacing registration of the same command made elsewhere
+	pub overriding: bool,
+	pub dispatcher: fn() -> Box<dyn std::any::Any + Send + Sync>,
+}
 
-	if let Some(event) = incoming_event {
-		if let Err(err) = handle_event(event, Arc::clone(&context_manager), event_handler).await {
-			// ! Safety:: BaseError Must Be Enforced To Be Accepted As Variant On ServiceError
-			tracing::error!("{:?}", err);
+inventory::collect!(CommandDispatcherRegistration);
+
+/// Take every command dispatcher whose error type is `E`.
+/// When a command is registered more than once, the one marked `override` wins. Otherwise it is reported by [report_duplicate_command].
+pub fn collect_command_dispatchers<E: 'static>() -> CommandDispatchers<E> {
+	let mut dispatchers = CommandDispatchers::new();
+	let mut registrations: hashbrown::HashMap<TypeId, &CommandDispatcherRegistration> = hashbrown::HashMap::new();
+	for registration in inventory::iter::<CommandDispatcherRegistration> {
+		let Ok(dispatcher) = (registration.dispatcher)().downcast::<CommandDispatcher<E>>() else { continue };
+		let command = (registration.command)();
+		if let Some(registered) = registrations.get(&command) {
+			match (registered.overriding, registration.overriding) {
+				(false, true) => {}
+				(true, false) => continue,
+				_ => report_duplicate_command((registration.name)(), registered.location, registration.location),
+			}
 		}
+		registrations.insert(command, registration);
+		dispatchers.insert(command, *dispatcher);
 	}
-	Ok(context_manager)
+	dispatchers
 }
 
-/// Interface for messagebus to work on
-pub trait TCommandService<R, E>: Send + Sync {
-	fn execute(self) -> impl std::future::Future<Output = Result<R, E>> + Send;
+/// Panic in strict mode set by [MessageBusConfig::with_strict_command_registration], otherwise log that the later registration replaces the earlier one.
+#[doc(hidden)]
+pub fn report_duplicate_command(command: &str, first: &str, second: &str) {
+	if MessageBus::config().strict_command_registration {
+		panic!("Command {command} Registered More Than Once! First:{first}, Second:{second}. Mark One Of Them With `override` If It Is Intended.");
+	}
+	tracing::warn!("Command {} Registered More Than Once! First:{}, Second:{}", command, first, second);
 }
 
-#[async_trait]
-pub trait TMessageBus<R, E, C>: TEventBus<E>
+/// Dispatch command whose type is not known at compile time, such as one deserialized by route
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
+pub trait TDynMessageBus<R, E>
 where
-	responses::BaseError: std::convert::From<E>,
-	R: ApplicationResponse,
+	R: ApplicationResponse + 'static,
 	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
-	C: TCommand,
 {
-	fn command_handler(&self, context_manager: AtomicContextManager, cmd: C) -> impl TCommandService<R, E>;
+	fn dyn_command_handler(&self) -> &'static hashbrown::HashMap<TypeId, DynCommandHandler<R, E>>;
 
-	/// This method is used to handle command and return result.
 	/// ## Example
 	/// ```rust,no_run
-	/// let res = service.execute_and_wait(message).await?;
+	/// let command: Box<dyn TCommand> = route(path, body)?;
+	/// let res = MessageBus.execute_dyn(command, &CONNECTION).await?;
 	/// ```
-
-	async fn execute_and_wait(&self, message: C, conn: &'static dyn TConnection) -> Result<R, E> {
-		#[cfg(feature = "tracing")]
-		{
-			tracing::info!("{}", std::any::type_name::<C>());
-		}
-
-		let context_manager = Arc::new(ContextManager::new(conn));
-		let res = self.command_handler(Arc::clone(&context_manager), message).execute().await?;
-
-		// Trigger event handler
-		if !context_manager.event_queue.is_empty() {
-			let event = context_manager.get_mut().pop_front();
-			handle_event(event.unwrap(), Arc::clone(&context_manager), self.event_handler()).await?;
-		}
-		Ok(res)
+	async fn execute_dyn(&self, message: Box<dyn TCommand>, conn: &'static dyn TConnection) -> Result<R, E> {
+		// * `as_any` is required. `type_id` of `Box<dyn TCommand>` itself is not that of the command.
+		let handler = self.dyn_command_handler().get(&message.as_any().type_id()).ok_or_else(|| {
+			tracing::error!("Unregistered Command {} Given! {:?}", message.command_name(), message);
+			BaseError::CommandNotFound(message.command_name().to_string())
+		})?;
+		handler(message, conn).await
 	}
 
-	/// This method is used to handle command and return result proxy which holds the result and join handler.
+	/// Check at startup that every command in `commands` is registered with `init_dyn_command_handler!`, rather than finding out when one is dispatched.
+	/// Returns [BaseError::CommandNotFound] listing names of the ones that are not, comma separated.
 	/// ## Example
 	/// ```rust,no_run
-	/// let res = service.execute_and_forget(message).await?;
-	/// let res = res.wait_until_event_processing_done().await?;
-	/// let res = res.result();
+	/// MessageBus.verify_commands(&[CommandType::of::<PlaceOrder>(), CommandType::of::<CancelOrder>()])?;
 	/// ```
-	async fn execute_and_forget(&self, message: C, conn: &'static dyn TConnection) -> Result<CommandResponseWithEventFutures<R, E>, E> {
-		#[cfg(feature = "tracing")]
-		{
-			tracing::info!("{}", std::any::type_name::<C>());
+	fn verify_commands(&self, commands: &[CommandType]) -> Result<(), BaseError> {
+		let unregistered = commands.iter().filter(|command| !self.dyn_command_handler().contains_key(&command.id)).map(|command| command.name).collect::<Vec<_>>();
+		if unregistered.is_empty() {
+			return Ok(());
 		}
+		tracing::error!("Commands Are Not Registered! {:?}", unregistered);
+		Err(BaseError::CommandNotFound(unregistered.join(", ")))
+	}
+}
 
-		let context_manager = Arc::new(ContextManager::new(conn));
-		let res = self.command_handler(Arc::clone(&context_manager), message).execute().await?;
-		let mut res = CommandResponseWithEventFutures { result: res, join_handler: None };
-
-		// Trigger event handler
-		if !context_manager.event_queue.is_empty() {
-			let event = context_manager.get_mut().pop_front().unwrap();
+/// Type of command given to [TDynMessageBus::verify_commands]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandType {
+	id: TypeId,
+	name: &'static str,
+}
 
-			res.join_handler = Some(tokio::spawn(handle_event(event, context_manager, self.event_handler())));
-		}
-		Ok(res)
+impl CommandType {
+	pub fn of<C: TCommand>() -> Self {
+		Self { id: TypeId::of::<C>(), name: std::any::type_name::<C>() }
 	}
 }
 
+/// How commands given to [TMessageBus::execute_batch] share context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchContext {
+	Shared,
+	Isolated,
+	/// Shared, and committed or rolled back as a whole
+	Atomic,
+}
+
 pub struct CommandResponseWithEventFutures<T, E> {
 	result: T,
-	join_handler: Option<tokio::task::JoinHandle<std::result::Result<AtomicContextManager, E>>>,
+	event_processing: Option<EventProcessing<E>>,
 }
+
+enum EventProcessing<E> {
+	Spawned(tokio::task::JoinHandle<std::result::Result<AtomicContextManager, E>>),
+	/// Handled before the command returned, with [MessageBusConfig::with_deterministic_execution]
+	Done(std::result::Result<AtomicContextManager, E>),
+}
+
 impl<T, E> CommandResponseWithEventFutures<T, E>
 where
 	responses::BaseError: std::convert::From<E>,
@@ -175,11 +1247,17 @@ where
 	E: ApplicationError + std::convert::From<crate::responses::BaseError>,
 {
 	pub async fn wait_until_event_processing_done(mut self) -> Result<Self, E> {
-		if let Some(join_handler) = self.join_handler.take() {
-			join_handler.await.map_err(|err| {
-				tracing::error!("{:?}", err);
-				BaseError::ServiceError
-			})??;
+		match self.event_processing.take() {
+			Some(EventProcessing::Spawned(join_handler)) => {
+				join_handler.await.map_err(|err| {
+					tracing::error!("{:?}", err);
+					BaseError::ServiceError
+				})??;
+			}
+			Some(EventProcessing::Done(handled)) => {
+				handled?;
+			}
+			None => (),
 		}
 		Ok(self)
 	}
@@ -201,61 +1279,640 @@ where
 /// );
 /// ```
 ///
+/// Handlers of an event run in the order of declaration unless `#[order(..)]` is given, in which case lower ones run first.
+/// Order is 0 by default, and handlers discovered from `#[event_handler(YourEvent, order = ..)]` are sorted along with the declared ones.
+/// ```rust,no_run
+/// init_event_handler!(
+///     YourServiceError,
+///     |ctx| YourEventHandler(ctx),
+///     YourEvent:[notify_customer, #[order(-1)] update_read_model],
+/// );
+/// ```
+///
+/// Handlers annotated with `#[event_handler(YourEvent)]` are discovered and appended to the map, so
+/// when every handler is registered that way, only the error type is required:
+/// ```rust,no_run
+/// init_event_handler!(YourServiceError);
+/// ```
+///
+/// Injectables listed after the handler are taken from the functions of the same name in `crate::dependencies` and passed to the handler after the event.
+/// Function may return `Result`, in which case its error is converted into the error of the handler and the handler doesn't run.
+/// ```rust,no_run
+/// init_event_handler!(
+///     YourServiceError,
+///     |ctx| YourEventHandler(ctx),
+///     YourEvent:[handler1 => (mail_client, pool)],
+/// );
+/// ```
+///
+/// Handlers registered with `#[event_handler("Pattern*")]` are collected as well. For each event, handlers of the exact topic run first,
+/// followed by the pattern handlers matching the topic.
+///
+/// Commands registered with `init_dyn_command_handler!` can be dispatched from the handlers through [ContextManager::dispatch_command].
 
 #[macro_export]
+// * `crate` deliberately points to `dependencies` module of the crate invoking the macro
+#[allow(clippy::crate_in_macro_def)]
 macro_rules! init_event_handler {
+	// Case where every handler is registered with `#[event_handler]`
+	(
+		$E:ty $(,)?
+	) => {
+		$crate::init_event_handler!($E, (),);
+	};
     (
 		$E:ty,
 		$event_handler :expr,
 			$(
 				$(#[$asynchrony:ident])?
-				$event:ty:[$($handler:ident $(=>($($injectable:ident $(( $($arg:ident),* ))? ),*))?),* $(,)? ]
+				$event:ty:[$($(#[order($order:expr)])? $handler:ident $(=>($($injectable:ident $(( $($arg:ident),* ))? ),*))?),* $(,)? ]
 			),*
 			$(,)?
 
     ) =>{
 		pub(crate) static EVENT_HANDLERS: std::sync::LazyLock<ruva::TEventHandler<$E>> = std::sync::LazyLock::new(
 			||{
-				let mut _map : ::ruva::TEventHandler<$E> = ::ruva::HandlerMapper::new();
+				let mut _declared: Vec<(String, bool, ::ruva::OrderedHandlers<$E>)> = vec![];
 				$(
-
-				let mut handlers = if stringify!($($asynchrony)?) == "async" {
-					::ruva::EventHandlers::Async(vec![])
-				} else {
-					::ruva::EventHandlers::Sync(vec![])
-				};
-				handlers.extend(vec![
+				_declared.push((
+					<$event as ::ruva::TEvent>::topic().into(),
+					stringify!($($asynchrony)?) == "async",
+					vec![
 					$(
-						Box::new(
+						(
+							0 $(+ ($order))?,
+							Box::new(
 							|e: ::std::sync::Arc<dyn ::ruva::TEvent>, context_manager: ruva::AtomicContextManager | -> ::ruva::Future<$E> {
+								#[allow(unused_imports)]
+								use ::ruva::{TResolveFallible, TResolveInfallible};
+								// * Injectables are resolved before the handler runs. Failure of the fallible one is returned as error of the handler.
+								$($(
+									let $injectable = match (&::ruva::Injected::new(crate::dependencies::$injectable($($($arg),*)?))).resolve::<$E>() {
+										Ok(injectable) => injectable,
+										Err(err) => return Box::pin(async move { Err(err) }),
+									};
+								)*)?
+								// * Convert event so event handler accepts not Arc<dyn TEvent> but `event_happend` type of message.
+								// Handlers are looked up by topic, so it fails only when they are registered under the wrong one.
+								let event = match ::ruva::TDowncastEvent::downcast_event::<$event>(&*e) {
+									Ok(event) => event,
+									Err(err) => return Box::pin(async move { Err(err.into()) }),
+								};
 								let event_handler = $event_handler(context_manager);
-								Box::pin(event_handler.$handler(
-									// * Convert event so event handler accepts not Arc<dyn TEvent> but `event_happend` type of message.
-									// Safety:: client should access this vector of handlers by providing the corresponding event name
-									// So, when it is followed, it logically doesn't make sense to cause an error.
-									e.downcast_ref::<$event>().expect("Not Convertible!").clone(),
-								))
+								Box::pin(event_handler.$handler(event, $($($injectable),*)?))
 							}
+							) as ::ruva::Handler<$E>,
 						),
 					)*
-				]);
-                _map.insert(
-                    stringify!($event).into(),
-					handlers
-                );
-            )*
-            _map
+					],
+				));
+				)*
+
+				// * Handlers registered with `#[event_handler]` are merged into the ones declared here, and then sorted by their order.
+				::ruva::merge_event_handlers(_declared, ::ruva::collect_event_handlers::<$E>())
 			}
 		);
 
+		pub(crate) static PATTERN_EVENT_HANDLERS: std::sync::LazyLock<Vec<::ruva::PatternEventHandler<$E>>> = std::sync::LazyLock::new(::ruva::collect_pattern_event_handlers::<$E>);
+
+		pub(crate) static COMMAND_DISPATCHERS: std::sync::LazyLock<::ruva::CommandDispatchers<$E>> = std::sync::LazyLock::new(::ruva::collect_command_dispatchers::<$E>);
+
 		impl ruva::TEventBus<$E> for ::ruva::MessageBus{
 			fn event_handler(&self) -> &'static ruva::TEventHandler<$E>{
 				&EVENT_HANDLERS
 			}
+			fn pattern_event_handler(&self) -> &'static [::ruva::PatternEventHandler<$E>] {
+				&PATTERN_EVENT_HANDLERS
+			}
+			fn command_dispatcher(&self) -> Option<&'static ::ruva::CommandDispatchers<$E>> {
+				Some(&COMMAND_DISPATCHERS)
+			}
+		}
+
+	};
+
+}
+
+/// Same as `init_event_handler!`, except that events are routed to handlers by their type rather than by topic.
+/// Registration is checked at compile time and survives renaming the event, while topic is left for outbox and message broker.
+/// Pattern handlers are still matched against topic.
+/// ## Example
+/// ```rust,no_run
+/// init_typed_event_handler!(YourServiceError);
+///
+/// init_typed_event_handler!(
+///     YourServiceError,
+///     |ctx| YourEventHandler(ApplicationRepository::new(ctx)),
+///     YourEvent:[handler1, handler2],
+/// );
+/// ```
+///
+/// ## Migration from `init_event_handler!`
+/// Arguments are the same, so replacing the macro name is all it takes. Handlers annotated with `#[event_handler]` are routed by type as well.
+/// As routing no longer depends on topic, events whose topic is overridden or differs from type name are handled by the handlers of their type.
+#[macro_export]
+#[allow(clippy::crate_in_macro_def)]
+macro_rules! init_typed_event_handler {
+	(
+		$E:ty $(,)?
+	) => {
+		$crate::init_typed_event_handler!($E, (),);
+	};
+	(
+		$E:ty,
+		$event_handler :expr,
+			$(
+				$(#[$asynchrony:ident])?
+				$event:ty:[$($(#[order($order:expr)])? $handler:ident $(=>($($injectable:ident $(( $($arg:ident),* ))? ),*))?),* $(,)? ]
+			),*
+			$(,)?
+	) => {
+		pub(crate) static TYPED_EVENT_HANDLERS: std::sync::LazyLock<::ruva::TTypedEventHandler<$E>> = std::sync::LazyLock::new(
+			|| {
+				let mut _declared: Vec<(::std::any::TypeId, bool, ::ruva::OrderedHandlers<$E>)> = vec![];
+				$(
+				_declared.push((
+					::std::any::TypeId::of::<$event>(),
+					stringify!($($asynchrony)?) == "async",
+					vec![
+					$(
+						(
+							0 $(+ ($order))?,
+							Box::new(
+							|e: ::std::sync::Arc<dyn ::ruva::TEvent>, context_manager: ::ruva::AtomicContextManager| -> ::ruva::Future<$E> {
+								#[allow(unused_imports)]
+								use ::ruva::{TResolveFallible, TResolveInfallible};
+								$($(
+									let $injectable = match (&::ruva::Injected::new(crate::dependencies::$injectable($($($arg),*)?))).resolve::<$E>() {
+										Ok(injectable) => injectable,
+										Err(err) => return Box::pin(async move { Err(err) }),
+									};
+								)*)?
+								let event = match ::ruva::TDowncastEvent::downcast_event::<$event>(&*e) {
+									Ok(event) => event,
+									Err(err) => return Box::pin(async move { Err(err.into()) }),
+								};
+								let event_handler = $event_handler(context_manager);
+								Box::pin(event_handler.$handler(event, $($($injectable),*)?))
+							}
+							) as ::ruva::Handler<$E>,
+						),
+					)*
+					],
+				));
+				)*
+
+				// * Handlers registered with `#[event_handler]` are merged into the ones declared here, and then sorted by their order.
+				::ruva::merge_event_handlers(_declared, ::ruva::collect_typed_event_handlers::<$E>())
+			}
+		);
+
+		pub(crate) static EVENT_HANDLERS: std::sync::LazyLock<::ruva::TEventHandler<$E>> = std::sync::LazyLock::new(Default::default);
+
+		pub(crate) static PATTERN_EVENT_HANDLERS: std::sync::LazyLock<Vec<::ruva::PatternEventHandler<$E>>> = std::sync::LazyLock::new(::ruva::collect_pattern_event_handlers::<$E>);
+
+		pub(crate) static COMMAND_DISPATCHERS: std::sync::LazyLock<::ruva::CommandDispatchers<$E>> = std::sync::LazyLock::new(::ruva::collect_command_dispatchers::<$E>);
+
+		impl ::ruva::TEventBus<$E> for ::ruva::MessageBus {
+			fn event_handler(&self) -> &'static ::ruva::TEventHandler<$E> {
+				&EVENT_HANDLERS
+			}
+			fn typed_event_handler(&self) -> Option<&'static ::ruva::TTypedEventHandler<$E>> {
+				Some(&TYPED_EVENT_HANDLERS)
+			}
+			fn pattern_event_handler(&self) -> &'static [::ruva::PatternEventHandler<$E>] {
+				&PATTERN_EVENT_HANDLERS
+			}
+			fn command_dispatcher(&self) -> Option<&'static ::ruva::CommandDispatchers<$E>> {
+				Some(&COMMAND_DISPATCHERS)
+			}
 		}
+	};
+}
 
+/// This macro is used to dispatch boxed commands with [TDynMessageBus]. `register_uow_services!` calls it for the commands it registers,
+/// so it is required only when [TMessageBus] is implemented manually.
+/// The commands become dispatchable from event handlers through [ContextManager::dispatch_command] as well.
+///
+/// Registering a command more than once is reported with locations of both registrations, see [MessageBusConfig::with_strict_command_registration].
+/// Prefix the command with `override` when it is meant to replace the registration made elsewhere.
+/// ## Example
+/// ```rust,no_run
+/// init_dyn_command_handler!(YourResponse, YourServiceError, YourCommand1, YourCommand2);
+/// init_dyn_command_handler!(YourResponse, YourOtherServiceError, override YourCommand1);
+/// ```
+#[macro_export]
+macro_rules! init_dyn_command_handler {
+	(@munch $response:ty, $error:ty, [$($registered:tt)*] override $command:ty $(, $($rest:tt)*)?) => {
+		$crate::init_dyn_command_handler!(@munch $response, $error, [$($registered)* ($command, true)] $($($rest)*)?);
+	};
+	(@munch $response:ty, $error:ty, [$($registered:tt)*] $command:ty $(, $($rest:tt)*)?) => {
+		$crate::init_dyn_command_handler!(@munch $response, $error, [$($registered)* ($command, false)] $($($rest)*)?);
 	};
+	(@munch $response:ty, $error:ty, [$(($command:ty, $overriding:literal))*]) => {
+		pub(crate) static DYN_COMMAND_HANDLERS: std::sync::LazyLock<::ruva::HandlerMapper<::std::any::TypeId, ::ruva::DynCommandHandler<$response, $error>>> = std::sync::LazyLock::new(
+			|| {
+				let mut _map: ::ruva::HandlerMapper<::std::any::TypeId, ::ruva::DynCommandHandler<$response, $error>> = ::ruva::HandlerMapper::new();
+				$(
+					let handler: ::ruva::DynCommandHandler<$response, $error> = |message, conn| {
+						Box::pin(async move {
+							let message = ::ruva::TDowncastCommand::downcast_command::<$command>(message)?;
+							<::ruva::MessageBus as ::ruva::TMessageBus<$response, $error, $command>>::execute_and_wait(&::ruva::MessageBus, message, conn).await
+						})
+					};
+					if _map.insert(::std::any::TypeId::of::<$command>(), handler).is_some() {
+						let location = concat!(file!(), ":", line!());
+						::ruva::report_duplicate_command(::std::any::type_name::<$command>(), location, location);
+					}
+				)*
+				_map
+			}
+		);
+
+		$(
+			::ruva::inventory::submit! {
+				::ruva::CommandDispatcherRegistration {
+					command: ::std::any::TypeId::of::<$command>,
+					name: ::std::any::type_name::<$command>,
+					location: concat!(file!(), ":", line!()),
+					overriding: $overriding,
+					dispatcher: || {
+						let dispatcher: ::ruva::CommandDispatcher<$error> = |message, conn| {
+							Box::pin(async move {
+								let message = ::ruva::TDowncastCommand::downcast_command::<$command>(message)?;
+								<::ruva::MessageBus as ::ruva::TMessageBus<$response, $error, $command>>::execute_and_wait(&::ruva::MessageBus, message, conn).await?;
+								Ok(())
+							})
+						};
+						Box::new(dispatcher)
+					},
+				}
+			}
+		)*
 
+		impl ::ruva::TDynMessageBus<$response, $error> for ::ruva::MessageBus {
+			fn dyn_command_handler(&self) -> &'static ::ruva::HandlerMapper<::std::any::TypeId, ::ruva::DynCommandHandler<$response, $error>> {
+				&DYN_COMMAND_HANDLERS
+			}
+		}
+	};
+	($response:ty, $error:ty $(, $($commands:tt)*)?) => {
+		$crate::init_dyn_command_handler!(@munch $response, $error, [] $($($commands)*)?);
+	};
 }
 
 pub struct MessageBus;
+
+static CONFIG: LazyLock<RwLock<Arc<MessageBusConfig>>> = LazyLock::new(Default::default);
+static SHUTDOWN_HANDLE: LazyLock<ShutdownHandle> = LazyLock::new(Default::default);
+static PAUSE_GATE: LazyLock<PauseGate> = LazyLock::new(Default::default);
+static RELAY_MONITOR: LazyLock<RelayMonitor> = LazyLock::new(Default::default);
+static EVENT_FEED: LazyLock<EventFeed> = LazyLock::new(|| EventFeed::new(MessageBus::config().event_feed_capacity.unwrap_or(DEFAULT_EVENT_FEED_CAPACITY)));
+
+impl MessageBus {
+	/// Set options every request handled afterwards works with.
+	/// ## Example
+	/// ```rust,no_run
+	/// MessageBus::configure(
+	///     MessageBusConfig::default()
+	///         .with_command_timeout(Duration::from_secs(10))
+	///         .with_command_timeout_for::<SlowCommand>(Duration::from_secs(60))
+	///         .with_event_handler_timeout(Duration::from_secs(5)),
+	/// );
+	/// ```
+	pub fn configure(config: MessageBusConfig) {
+		*CONFIG.write().unwrap() = Arc::new(config);
+	}
+
+	pub fn config() -> Arc<MessageBusConfig> {
+		CONFIG.read().unwrap().clone()
+	}
+
+	/// Once it is signaled, new commands and events are rejected with `BaseError::ShuttingDown` while the ones in flight are handled to completion.
+	pub fn shutdown_handle() -> ShutdownHandle {
+		SHUTDOWN_HANDLE.clone()
+	}
+
+	/// Outboxes reported as staged and relayed, which [Self::health] tells the backlog from
+	pub fn relay_monitor() -> RelayMonitor {
+		RELAY_MONITOR.clone()
+	}
+
+	/// Stream of every event handed to handlers from now on, across requests, for read-only observation such as live dashboard.
+	/// It doesn't affect handling of the events. Subscriber that falls behind skips the oldest events instead of holding the bus back.
+	/// ## Example
+	/// ```rust,no_run
+	/// let mut events = MessageBus.subscribe();
+	/// while let Some(event) = events.next().await {
+	///     dashboard.push(event.metadata().topic);
+	/// }
+	/// ```
+	pub fn subscribe(&self) -> impl futures::Stream<Item = Arc<dyn TEvent>> + Send + 'static {
+		EVENT_FEED.subscribe()
+	}
+
+	/// Snapshot of what components of the bus already track, cheap enough to be taken on every probe
+	pub fn health(&self) -> HealthSnapshot {
+		let commands = inventory::iter::<CommandDispatcherRegistration>.into_iter().map(|registration| (registration.command)()).collect::<hashbrown::HashSet<_>>();
+		HealthSnapshot {
+			command_handlers: commands.len(),
+			event_handlers: inventory::iter::<EventHandlerRegistration>.into_iter().count(),
+			last_relay_tick: RELAY_MONITOR.last_tick(),
+			outbox_backlog: RELAY_MONITOR.backlog(),
+			shutting_down: SHUTDOWN_HANDLE.is_signaled(),
+		}
+	}
+
+	/// Current time of the clock given to [MessageBusConfig::with_clock]
+	pub fn now() -> Timestamp {
+		Self::config().clock.as_ref().map_or_else(Utc::now, |clock| clock.now())
+	}
+
+	/// Hold handling of events back, as during maintenance of what their handlers call, until [Self::resume].
+	/// Commands are still executed and committed along with their outboxes, but the events they raised wait in their queue,
+	/// so [TMessageBus::execute_and_wait] doesn't return until resumed. Use [TMessageBus::execute_and_forget] to respond without waiting.
+	/// Events raised by handlers running at the moment are held back as well, while those handlers run to completion.
+	/// Queued events live in memory only; handlers that must survive restart while paused belong behind `externally_notifiable` events.
+	/// ## Example
+	/// ```rust,no_run
+	/// MessageBus.pause();
+	/// migrate_read_models().await?;
+	/// MessageBus.resume();
+	/// ```
+	pub fn pause(&self) {
+		PAUSE_GATE.pause()
+	}
+
+	/// Let events held back by [Self::pause] be handled, in the order they were queued in each request
+	pub fn resume(&self) {
+		PAUSE_GATE.resume()
+	}
+
+	pub fn is_paused(&self) -> bool {
+		PAUSE_GATE.is_paused()
+	}
+
+	/// Stop accepting commands and wait until in-flight commands and events they raised are handled
+	pub async fn shutdown(&self) {
+		SHUTDOWN_HANDLE.shutdown().await
+	}
+}
+
+pub const DEFAULT_MAX_COMMAND_DEPTH: usize = 16;
+
+/// Options for [MessageBus]. Nothing is limited by default, except depth of commands dispatched from event handlers.
+#[derive(Default, Clone)]
+pub struct MessageBusConfig {
+	pub(crate) command_timeout: Option<Duration>,
+	pub(crate) command_timeouts: hashbrown::HashMap<TypeId, Duration>,
+	pub(crate) event_handler_timeout: Option<Duration>,
+	pub(crate) request_deadline: Option<Duration>,
+	pub(crate) event_queue_capacity: Option<(usize, OverflowPolicy)>,
+	pub(crate) max_command_depth: Option<usize>,
+	pub(crate) handler_concurrency: hashbrown::HashMap<String, Arc<Semaphore>>,
+	pub(crate) event_enrichers: Vec<Arc<dyn TEventEnricher>>,
+	pub(crate) event_deduplication: bool,
+	pub(crate) clock: Option<Arc<dyn TClock>>,
+	pub(crate) strict_command_registration: bool,
+	pub(crate) aggregate_lanes: bool,
+	pub(crate) audit_sink: Option<Arc<dyn TAuditSink>>,
+	pub(crate) retry_policies: hashbrown::HashMap<TypeId, Arc<dyn std::any::Any + Send + Sync>>,
+	pub(crate) deterministic_execution: bool,
+	pub(crate) dead_letter_sink: Option<Arc<dyn TDeadLetterSink>>,
+	pub(crate) stop_sentinel_publisher: Option<Arc<dyn TOutBoxPublisher>>,
+	pub(crate) request_lifecycles: Vec<Arc<dyn TRequestLifecycle>>,
+	pub(crate) poison_message_threshold: Option<usize>,
+	pub(crate) catch_all_handlers: hashbrown::HashMap<TypeId, Arc<dyn std::any::Any + Send + Sync>>,
+	pub(crate) outbox_compression: Option<(Arc<dyn TCodec>, usize)>,
+	pub(crate) event_feed_capacity: Option<usize>,
+	pub(crate) event_shards: Option<usize>,
+	pub(crate) circuit_breakers: hashbrown::HashMap<String, CircuitBreaker>,
+	pub(crate) outbox_batching: bool,
+	pub(crate) effect_handlers: hashbrown::HashMap<TypeId, Arc<dyn TErasedEffectHandler>>,
+	pub(crate) metrics_recorder: Option<Arc<dyn TMetricsRecorder>>,
+	pub(crate) inbox: Option<Arc<dyn TInbox>>,
+}
+
+impl MessageBusConfig {
+	/// Timeout applied to every command unless it is overridden by [Self::with_command_timeout_for]
+	pub fn with_command_timeout(mut self, after: Duration) -> Self {
+		self.command_timeout = Some(after);
+		self
+	}
+
+	pub fn with_command_timeout_for<C: TCommand>(mut self, after: Duration) -> Self {
+		self.command_timeouts.insert(TypeId::of::<C>(), after);
+		self
+	}
+
+	/// Send event to `sink` for each of its handlers that failed, with index of the handler given in [DeadLetter::handler].
+	/// Handlers that succeeded are not dead-lettered along with it, nor are the ones that returned stop sentinel.
+	pub fn with_dead_letter_sink(mut self, sink: impl TDeadLetterSink + 'static) -> Self {
+		self.dead_letter_sink = Some(Arc::new(sink));
+		self
+	}
+
+	/// Publish outbox of the event an event handler stops the others with through `StopSentinelWithEvent`, if it is externally notifiable.
+	/// The event is queued to be handled either way.
+	pub fn with_stop_sentinel_publisher(mut self, publisher: impl TOutBoxPublisher + 'static) -> Self {
+		self.stop_sentinel_publisher = Some(Arc::new(publisher));
+		self
+	}
+
+	/// Call `lifecycle` once at the start and once at the finish of every request, including the events it raised. See [TRequestLifecycle].
+	pub fn with_request_lifecycle(mut self, lifecycle: impl TRequestLifecycle + 'static) -> Self {
+		self.request_lifecycles.push(Arc::new(lifecycle));
+		self
+	}
+
+	/// Retry event handlers whose error type is `E` when they fail, with policy picked by classification of the error.
+	/// Each attempt is given the timeout of [Self::with_event_handler_timeout] afresh.
+	pub fn with_retry_policies<E: 'static + Send + Sync>(mut self, policies: RetryPolicies<E>) -> Self {
+		self.retry_policies.insert(TypeId::of::<E>(), Arc::new(policies));
+		self
+	}
+
+	/// Stop running handlers registered for `topic` while `breaker` is open, failing them fast instead. Pattern and catch-all handlers are not stopped.
+	/// Give clones of the same breaker to the topics whose handlers call the same dependency. See [CircuitBreaker].
+	pub fn with_circuit_breaker_for(mut self, topic: impl Into<String>, breaker: CircuitBreaker) -> Self {
+		self.circuit_breakers.insert(topic.into(), breaker);
+		self
+	}
+
+	/// Timeout applied to each event handler
+	pub fn with_event_handler_timeout(mut self, after: Duration) -> Self {
+		self.event_handler_timeout = Some(after);
+		self
+	}
+
+	/// Deadline that every request is given `after` it is received, which events raised within it share rather than each handler having its own.
+	/// Once it passes, handling of the events left is aborted with [BaseError::DeadlineExceeded] before the next event, or handler run one after another, is dispatched.
+	/// Handler already running is not cut short, but it can bound its own operations with [ContextManager::remaining].
+	pub fn with_request_deadline(mut self, after: Duration) -> Self {
+		self.request_deadline = Some(after);
+		self
+	}
+
+	/// Limit how many handlers registered for `topic` run at once, across every request. Pattern handlers are not limited.
+	pub fn with_handler_concurrency_for(mut self, topic: impl Into<String>, permits: usize) -> Self {
+		self.handler_concurrency.insert(topic.into(), Arc::new(Semaphore::new(permits)));
+		self
+	}
+
+	/// Enrich every event raised within a request. Enrichers run in the order they are registered.
+	pub fn with_event_enricher(mut self, enricher: impl TEventEnricher + 'static) -> Self {
+		self.event_enrichers.push(Arc::new(enricher));
+		self
+	}
+
+	/// Replace [crate::prelude::SystemClock] that timestamps are taken from, to pin time in tests
+	pub fn with_clock(mut self, clock: impl TClock + 'static) -> Self {
+		self.clock = Some(Arc::new(clock));
+		self
+	}
+
+	/// Record every command accepted for execution and every event dispatched to handlers in `sink`. See [super::audit] for what is recorded.
+	pub fn with_audit_sink(mut self, sink: impl TAuditSink + 'static) -> Self {
+		self.audit_sink = Some(Arc::new(sink));
+		self
+	}
+
+	/// Panic when a command is registered more than once without `override`, instead of logging it and keeping the later registration.
+	/// Registrations are checked when handlers of `init_dyn_command_handler!` and command dispatchers are first looked up.
+	pub fn with_strict_command_registration(mut self) -> Self {
+		self.strict_command_registration = true;
+		self
+	}
+
+	/// Drop event raised within a request when the one of the same [TEvent::message_id] was already queued or handled in the request.
+	/// It doesn't look across requests.
+	pub fn with_event_deduplication(mut self) -> Self {
+		self.event_deduplication = true;
+		self
+	}
+
+	/// Handle events queued within a request concurrently, except that events of the same [crate::prelude::EventMetadata::aggregate_id] are handled one after another
+	/// in the order they were raised. Events without aggregate id share a lane of their own.
+	/// Failure of an event no longer stops handling of the events queued after it.
+	pub fn with_aggregate_lanes(mut self) -> Self {
+		self.aggregate_lanes = true;
+		self
+	}
+
+	/// For tests only. Handle events one by one on the task of the request, so that assertions on their order are stable.
+	/// - Events are handled in the order they are popped from the queue, even with [Self::with_aggregate_lanes].
+	/// - Handlers registered as async run one after another in the order of registration.
+	/// - [TMessageBus::execute_and_forget] handles events before it returns, instead of spawning a task for them.
+	///
+	/// Throughput drops accordingly, so it is not meant for production.
+	pub fn with_deterministic_execution(mut self) -> Self {
+		self.deterministic_execution = true;
+		self
+	}
+
+	/// Limit number of events queued within a request. Events raised over the limit are handled according to `policy`.
+	pub fn with_event_queue_capacity(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+		self.event_queue_capacity = Some((capacity, policy));
+		self
+	}
+
+	/// Limit how deep commands can be dispatched from handlers of events raised by dispatched commands, [DEFAULT_MAX_COMMAND_DEPTH] by default.
+	/// It guards against commands and events triggering each other endlessly. Commands over the limit are discarded with [BaseError::CommandDepthExceeded] logged.
+	pub fn with_max_command_depth(mut self, depth: usize) -> Self {
+		self.max_command_depth = Some(depth);
+		self
+	}
+
+	/// Stop re-enqueueing event that handler keeps returning through `StopSentinelWithEvent` once it was re-enqueued `threshold` times within a request.
+	/// The event is dead-lettered with [BaseError::PoisonMessage] instead. Events are told apart by [TEvent::message_id], so those without it are not limited.
+	pub fn with_poison_message_threshold(mut self, threshold: usize) -> Self {
+		self.poison_message_threshold = Some(threshold);
+		self
+	}
+
+	/// Subscribe `handler` to every event handled by buses whose error type is `E`, whatever its topic is, as for audit or logging.
+	/// It takes either [AtomicContextManager] or [HandlerContext], so it runs within the context of the request.
+	/// - Catch-all handlers run after the handlers of the topic and pattern handlers, one after another in the order they are subscribed.
+	/// - Unlike pattern handlers, they don't run when stop sentinel arrived in the handlers of the topic. Stop sentinel they return stops the rest of them.
+	/// - Event that has no other handler is handled by them instead of failing with [BaseError::NotFound].
+	pub fn subscribe_all<E, C, F, Fut>(mut self, handler: F) -> Self
+	where
+		E: 'static,
+		C: From<AtomicContextManager>,
+		F: Fn(Arc<dyn TEvent>, C) -> Fut + Send + Sync + 'static,
+		Fut: std::future::Future<Output = Result<(), E>> + MaybeSend + 'static,
+	{
+		let handler: Handler<E> = Box::new(move |event, context_manager| Box::pin(handler(event, context_manager.into())));
+		let mut handlers = self.catch_all_handlers_of::<E>().to_vec();
+		handlers.push(Arc::new(handler));
+		self.catch_all_handlers.insert(TypeId::of::<E>(), Arc::new(handlers));
+		self
+	}
+
+	/// Compress outbox whose event is serialized into `threshold` bytes or more with `codec`. See [crate::prelude::TCodec].
+	/// Consumers decompress with the codec given here, so services that consume compressed outboxes are to be given the same codec.
+	pub fn with_outbox_compression(mut self, codec: impl TCodec + 'static, threshold: usize) -> Self {
+		self.outbox_compression = Some((Arc::new(codec), threshold));
+		self
+	}
+
+	/// Coalesce outboxes of events of the same topic that a unit of work collected into one with [OutBox::batch], rather than writing one for each,
+	/// so that they are published as a single message. Consumer drivers split it back into events, each with its own metadata, and handle them one after another.
+	/// The message is committed only when every one of them is handled, so the ones handled before a failure are handled again when it is redelivered.
+	pub fn with_outbox_batching(mut self) -> Self {
+		self.outbox_batching = true;
+		self
+	}
+
+	/// Perform effects of type `F` declared through [ContextManager::declare_effect] with `handler`, replacing the one registered before for the type.
+	/// Effects of types no handler is registered for are discarded with warning.
+	pub fn with_effect_handler<F: TEffect>(mut self, handler: impl TEffectHandler<F> + 'static) -> Self {
+		self.effect_handlers.insert(TypeId::of::<F>(), Arc::new(TypedEffectHandler(handler, std::marker::PhantomData)));
+		self
+	}
+
+	/// Keep events for [Delivery::AtLeastOnce] handlers in `inbox` until they succeed on them. See [TEventBus::redeliver].
+	///
+	/// [Delivery::AtLeastOnce]: super::inbox::Delivery::AtLeastOnce
+	pub fn with_inbox(mut self, inbox: impl TInbox + 'static) -> Self {
+		self.inbox = Some(Arc::new(inbox));
+		self
+	}
+
+	/// Report how commands, events and their handlers went to `recorder`, such as `PrometheusRecorder` of `event-driven-prometheus` feature
+	pub fn with_metrics_recorder(mut self, recorder: impl TMetricsRecorder + 'static) -> Self {
+		self.metrics_recorder = Some(Arc::new(recorder));
+		self
+	}
+
+	/// Number of events each subscriber of [MessageBus::subscribe] may fall behind by before it skips the oldest, [DEFAULT_EVENT_FEED_CAPACITY] by default.
+	/// It takes effect only when it is configured before the first event is handled or subscribed to.
+	pub fn with_event_feed_capacity(mut self, capacity: usize) -> Self {
+		self.event_feed_capacity = Some(capacity);
+		self
+	}
+
+	/// Number of lanes [ShardedDispatcher] spreads events over by their aggregate id. Events are handled in a single lane unless it is given.
+	///
+	/// [ShardedDispatcher]: super::sharding::ShardedDispatcher
+	pub fn with_event_shards(mut self, lanes: usize) -> Self {
+		self.event_shards = Some(lanes.max(1));
+		self
+	}
+
+	pub(crate) fn max_command_depth(&self) -> usize {
+		self.max_command_depth.unwrap_or(DEFAULT_MAX_COMMAND_DEPTH)
+	}
+
+	pub(crate) fn timeout_of<C: TCommand>(&self) -> Option<Duration> {
+		self.command_timeouts.get(&TypeId::of::<C>()).copied().or(self.command_timeout)
+	}
+
+	pub(crate) fn retry_policies_of<E: 'static>(&self) -> Option<&RetryPolicies<E>> {
+		self.retry_policies.get(&TypeId::of::<E>()).and_then(|policies| policies.downcast_ref())
+	}
+
+	pub(crate) fn catch_all_handlers_of<E: 'static>(&self) -> &[Arc<Handler<E>>] {
+		self.catch_all_handlers.get(&TypeId::of::<E>()).and_then(|handlers| handlers.downcast_ref::<Vec<Arc<Handler<E>>>>()).map_or(&[], Vec::as_slice)
+	}
+}
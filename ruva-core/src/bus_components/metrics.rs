@@ -0,0 +1,64 @@
+//! ### Metrics
+//! [TMetricsRecorder] given to [MessageBusConfig::with_metrics_recorder] is told how each command, event and handler went,
+//! so that they are exported to whatever metrics backend it works with. `PrometheusRecorder` of `event-driven-prometheus` feature
+//! is ready to use for Prometheus.
+//!
+//! [MessageBusConfig::with_metrics_recorder]: super::messagebus::MessageBusConfig::with_metrics_recorder
+
+use super::messagebus::MessageBus;
+use crate::prelude::{BaseError, TCommand};
+use std::time::{Duration, Instant};
+
+/// Destination of measurements taken by [MessageBus]. Every method does nothing by default, so only the ones of interest are implemented.
+/// They are called on the path of the request, so they are to return quickly rather than doing I/O.
+pub trait TMetricsRecorder: Send + Sync {
+	/// Command was executed, including the time it spent waiting for its timeout, but not the events it raised
+	fn record_command(&self, _command: &str, _succeeded: bool, _elapsed: Duration) {}
+
+	/// Handlers of the event were all run. It failed when any of them failed, other than by stop sentinel.
+	fn record_event(&self, _topic: &str, _succeeded: bool) {}
+
+	/// Handler of the event was run, along with its retries. Stop sentinel is not a failure.
+	fn record_handler(&self, _topic: &str, _succeeded: bool, _elapsed: Duration) {}
+}
+
+pub(crate) async fn measure_command<C, R, E>(execution: impl std::future::Future<Output = Result<R, E>>) -> Result<R, E>
+where
+	C: TCommand,
+{
+	let Some(recorder) = MessageBus::config().metrics_recorder.clone() else {
+		return execution.await;
+	};
+	let started = Instant::now();
+	let res = execution.await;
+	recorder.record_command(std::any::type_name::<C>(), res.is_ok(), started.elapsed());
+	res
+}
+
+pub(crate) async fn measure_handler<E>(handling: impl std::future::Future<Output = Result<(), E>>, topic: &str) -> Result<(), E>
+where
+	E: std::convert::From<BaseError>,
+	BaseError: std::convert::From<E>,
+{
+	let Some(recorder) = MessageBus::config().metrics_recorder.clone() else {
+		return handling.await;
+	};
+	let started = Instant::now();
+	match handling.await {
+		Ok(()) => {
+			recorder.record_handler(topic, true, started.elapsed());
+			Ok(())
+		}
+		Err(err) => {
+			let err = BaseError::from(err);
+			recorder.record_handler(topic, matches!(err, BaseError::StopSentinel | BaseError::StopSentinelWithEvent(_)), started.elapsed());
+			Err(err.into())
+		}
+	}
+}
+
+pub(crate) fn record_event(topic: &str, succeeded: bool) {
+	if let Some(recorder) = MessageBus::config().metrics_recorder.as_ref() {
+		recorder.record_event(topic, succeeded);
+	}
+}
@@ -1,4 +1,22 @@
+pub mod audit;
+pub mod cancellation;
+pub mod circuit_breaker;
 pub mod contexts;
+pub mod dead_letter;
+pub mod dynamic;
+pub mod effect;
+pub mod enricher;
 pub mod executor;
+pub mod feed;
 pub mod handler;
+pub mod health;
+pub mod inbox;
+pub mod lifecycle;
 pub mod messagebus;
+pub mod metrics;
+pub(crate) mod pause;
+pub mod retry;
+pub mod router;
+pub mod sharding;
+pub mod shutdown;
+pub(crate) mod telemetry;
@@ -0,0 +1,39 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+/// Gate events are handled through, closed by [MessageBus::pause] and opened by [MessageBus::resume].
+///
+/// [MessageBus::pause]: super::messagebus::MessageBus::pause
+/// [MessageBus::resume]: super::messagebus::MessageBus::resume
+#[derive(Default)]
+pub(crate) struct PauseGate {
+	paused: AtomicBool,
+	notify: Notify,
+}
+
+impl PauseGate {
+	pub(crate) fn pause(&self) {
+		self.paused.store(true, Ordering::SeqCst);
+	}
+
+	pub(crate) fn resume(&self) {
+		self.paused.store(false, Ordering::SeqCst);
+		self.notify.notify_waiters();
+	}
+
+	pub(crate) fn is_paused(&self) -> bool {
+		self.paused.load(Ordering::SeqCst)
+	}
+
+	/// Resolves once the gate is open, right away if it is not paused
+	pub(crate) async fn opened(&self) {
+		loop {
+			// * Registered before checking so that `resume` in between is not missed
+			let notified = self.notify.notified();
+			if !self.is_paused() {
+				return;
+			}
+			notified.await;
+		}
+	}
+}
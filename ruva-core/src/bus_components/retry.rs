@@ -0,0 +1,111 @@
+//! Retry of failed event handlers, with policy picked by classification of the error.
+//!
+//! Errors are classified into [RetryClass]es by a classifier given to [RetryPolicies::classify], and each class is retried
+//! according to the [RetryPolicy] registered for it. Class without policy is not retried, nor is [RetryClass::Fatal] ever.
+//! ```rust,no_run
+//! let policies = RetryPolicies::classify(|err: &ServiceError| match err {
+//!     ServiceError::Deadlock => RetryClass::Transient,
+//!     ServiceError::TooManyRequests => RetryClass::RateLimited,
+//!     ServiceError::PaymentGateway(_) => RetryClass::Named("payment_gateway"),
+//!     _ => RetryClass::Fatal,
+//! })
+//! .policy(RetryClass::Transient, RetryPolicy::new(5, Duration::from_millis(10)))
+//! .policy(RetryClass::RateLimited, RetryPolicy::new(3, Duration::from_secs(1)).with_multiplier(2.0))
+//! .policy(RetryClass::Named("payment_gateway"), RetryPolicy::new(4, Duration::ZERO).with_backoff(Backoff::exponential(Duration::from_millis(500), Duration::from_secs(5), 0.5)));
+//!
+//! MessageBus::configure(MessageBusConfig::default().with_retry_policies(policies));
+//! ```
+
+use crate::prelude::Backoff;
+use std::{sync::Arc, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RetryClass {
+	/// Failure that goes away on its own shortly, such as deadlock or serialization failure
+	Transient,
+	/// Failure caused by calling the other side too often, which calls for longer backoff
+	RateLimited,
+	/// Failure that fails the same way however many times it is retried. It is never retried.
+	Fatal,
+	Named(&'static str),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+	pub(crate) retries: usize,
+	pub(crate) backoff: Backoff,
+}
+
+impl RetryPolicy {
+	/// Retry up to `retries` times after the first attempt, waiting `backoff` between attempts
+	pub fn new(retries: usize, backoff: Duration) -> Self {
+		Self { retries, backoff: Backoff::constant(backoff) }
+	}
+
+	/// Multiply backoff by `multiplier` on every retry
+	pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+		self.backoff = self.backoff.with_multiplier(multiplier);
+		self
+	}
+
+	/// Wait between attempts as `backoff` gives, to cap or jitter the delays
+	pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+		self.backoff = backoff;
+		self
+	}
+
+	fn backoff_of(&self, retried: usize) -> Duration {
+		self.backoff.delay(retried as u32)
+	}
+}
+
+type Classifier<E> = Arc<dyn Fn(&E) -> RetryClass + Send + Sync>;
+
+/// Retry policies of handlers whose error type is `E`, given to [MessageBusConfig::with_retry_policies]
+///
+/// [MessageBusConfig::with_retry_policies]: super::messagebus::MessageBusConfig::with_retry_policies
+pub struct RetryPolicies<E> {
+	classifier: Classifier<E>,
+	policies: hashbrown::HashMap<RetryClass, RetryPolicy>,
+}
+
+impl<E> RetryPolicies<E> {
+	pub fn classify(classifier: impl Fn(&E) -> RetryClass + Send + Sync + 'static) -> Self {
+		Self { classifier: Arc::new(classifier), policies: Default::default() }
+	}
+
+	/// Retry errors of `class` according to `policy`. Policy given for [RetryClass::Fatal] is ignored.
+	pub fn policy(mut self, class: RetryClass, policy: RetryPolicy) -> Self {
+		self.policies.insert(class, policy);
+		self
+	}
+
+	fn policy_of(&self, err: &E) -> Option<&RetryPolicy> {
+		match (self.classifier)(err) {
+			RetryClass::Fatal => None,
+			class => self.policies.get(&class),
+		}
+	}
+}
+
+/// Run `handling` until it succeeds or the policy picked for its error runs out of retries
+pub(crate) async fn handle_with_retry<E, Fut>(mut handling: impl FnMut() -> Fut, policies: Option<&RetryPolicies<E>>) -> Result<(), E>
+where
+	E: std::fmt::Debug,
+	Fut: std::future::Future<Output = Result<(), E>>,
+{
+	let mut retried = 0;
+	loop {
+		let result = handling().await;
+		let Err(err) = &result else {
+			return result;
+		};
+		let Some(policy) = policies.and_then(|policies| policies.policy_of(err)).filter(|policy| retried < policy.retries) else {
+			return result;
+		};
+		let backoff = policy.backoff_of(retried);
+		tracing::warn!("Retrying handler in {:?} after failure! Error:{:?}", backoff, err);
+		tokio::time::sleep(backoff).await;
+		retried += 1;
+	}
+}
@@ -0,0 +1,115 @@
+//! ### BusRouter
+//! In a monolith with several bounded contexts, each context registers its commands with `init_dyn_command_handler!` on its own response and error.
+//! [BusRouter] puts them together so that a command could be handed over without knowing which context it belongs to.
+//!
+//! As response and error differ by context, they are normalized. Response comes in [RoutedResponse], which can be downcast back to
+//! the response of the context, and error comes boxed as [ApplicationError], whose [ApplicationError::code] is kept.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! let router = BusRouter::default().with_bus::<OrderResponse, OrderError>(&MessageBus).with_bus::<BillingResponse, BillingError>(&MessageBus);
+//!
+//! let command: Box<dyn TCommand> = route(path, body)?;
+//! let res = router.execute(command, &CONNECTION).await?;
+//! let status = res.status_code();
+//! ```
+
+use super::executor::TConnection;
+use super::messagebus::{CommandFuture, DynCommandHandler, TDynMessageBus};
+use crate::prelude::TCommand;
+use crate::responses::{ApplicationError, ApplicationResponse, BaseError, ResponseMetadata};
+use std::any::{Any, TypeId};
+
+pub type RoutedResult = Result<RoutedResponse, Box<dyn ApplicationError>>;
+
+/// Response of the bus that handled command routed by [BusRouter]
+pub struct RoutedResponse {
+	status_code: u16,
+	metadata: ResponseMetadata,
+	response: Box<dyn Any + Send + Sync>,
+}
+
+impl RoutedResponse {
+	fn new<R: ApplicationResponse + 'static>(response: R) -> Self {
+		Self { status_code: response.status_code(), metadata: response.response_metadata(), response: Box::new(response) }
+	}
+
+	/// Response as the bus that handled the command returned it. It is given back as it is when `R` is not the response of the bus.
+	pub fn downcast<R: ApplicationResponse + 'static>(self) -> Result<R, Self> {
+		match self.response.downcast::<R>() {
+			Ok(response) => Ok(*response),
+			Err(response) => Err(Self { status_code: self.status_code, metadata: self.metadata, response }),
+		}
+	}
+}
+
+impl ApplicationResponse for RoutedResponse {
+	fn status_code(&self) -> u16 {
+		self.status_code
+	}
+
+	fn response_metadata(&self) -> ResponseMetadata {
+		self.metadata.clone()
+	}
+}
+
+impl std::fmt::Debug for RoutedResponse {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("RoutedResponse").field("status_code", &self.status_code).field("metadata", &self.metadata).finish_non_exhaustive()
+	}
+}
+
+/// Command handlers of a bus, with its response and error normalized
+trait TRoute: Send + Sync {
+	fn handles(&self, command: TypeId) -> bool;
+	fn execute(&self, command: Box<dyn TCommand>, conn: &'static dyn TConnection) -> CommandFuture<RoutedResult>;
+}
+
+struct Route<R: 'static, E: 'static>(&'static hashbrown::HashMap<TypeId, DynCommandHandler<R, E>>);
+
+impl<R, E> TRoute for Route<R, E>
+where
+	R: ApplicationResponse + 'static,
+	E: ApplicationError,
+{
+	fn handles(&self, command: TypeId) -> bool {
+		self.0.contains_key(&command)
+	}
+
+	fn execute(&self, command: Box<dyn TCommand>, conn: &'static dyn TConnection) -> CommandFuture<RoutedResult> {
+		let handler = self.0[&command.as_any().type_id()];
+		Box::pin(async move {
+			match handler(command, conn).await {
+				Ok(response) => Ok(RoutedResponse::new(response)),
+				Err(err) => Err(Box::new(err) as Box<dyn ApplicationError>),
+			}
+		})
+	}
+}
+
+/// Routes command to the bus whose command handlers include it. When more than one bus claims the command, the one added first handles it.
+#[derive(Default)]
+pub struct BusRouter {
+	routes: Vec<Box<dyn TRoute>>,
+}
+
+impl BusRouter {
+	/// Route commands registered on `bus` with `init_dyn_command_handler!` for response `R` and error `E`
+	pub fn with_bus<R, E>(mut self, bus: &impl TDynMessageBus<R, E>) -> Self
+	where
+		R: ApplicationResponse + 'static,
+		E: ApplicationError + std::convert::From<BaseError>,
+	{
+		self.routes.push(Box::new(Route(bus.dyn_command_handler())));
+		self
+	}
+
+	/// Returns [BaseError::CommandNotFound] when none of the buses handles the command
+	pub async fn execute(&self, command: Box<dyn TCommand>, conn: &'static dyn TConnection) -> RoutedResult {
+		let Some(route) = self.routes.iter().find(|route| route.handles(command.as_any().type_id())) else {
+			tracing::error!("No Bus Handles Command {}! {:?}", command.command_name(), command);
+			return Err(BaseError::CommandNotFound(command.command_name().to_string()).into());
+		};
+		route.execute(command, conn).await
+	}
+}
@@ -0,0 +1,90 @@
+//! ### ShardedDispatcher
+//! When events of a type dominate the load, such as those consumed from a busy topic, handling them one after another doesn't keep up,
+//! while handling them all concurrently reorders events of the same aggregate.
+//! [ShardedDispatcher] hashes [EventMetadata::aggregate_id] to one of the lanes given to [MessageBusConfig::with_event_shards].
+//! Each lane is a queue consumed by a worker of its own that hands events to [TEventBus::handle_event] one after another,
+//! so that throughput scales with the number of lanes while events of an aggregate are handled in the order they were dispatched.
+//!
+//! #### Usage Pattern
+//! ```rust,no_run
+//! MessageBus::configure(MessageBusConfig::default().with_event_shards(8));
+//! let dispatcher = ShardedDispatcher::spawn::<ServiceError>(&MessageBus, &CONNECTION);
+//!
+//! while let Some(event) = consumer.next().await {
+//!     dispatcher.dispatch(event).await?;
+//! }
+//! dispatcher.close().await;
+//! ```
+//!
+//! [EventMetadata::aggregate_id]: crate::prelude::EventMetadata
+//! [MessageBusConfig::with_event_shards]: super::messagebus::MessageBusConfig::with_event_shards
+
+use super::executor::TConnection;
+use super::messagebus::{spawn, MessageBus, TEventBus};
+use crate::prelude::{ApplicationError, BaseError, TEvent};
+use std::{
+	hash::{DefaultHasher, Hash, Hasher},
+	sync::Arc,
+};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+/// Events each lane holds before [ShardedDispatcher::dispatch] waits for the lane to catch up
+pub const SHARD_LANE_CAPACITY: usize = 1024;
+
+pub struct ShardedDispatcher {
+	lanes: Vec<mpsc::Sender<Arc<dyn TEvent>>>,
+	workers: Vec<JoinHandle<()>>,
+}
+
+impl ShardedDispatcher {
+	/// Spawn a worker for each lane, as many as [MessageBusConfig::with_event_shards] tells. It is a single lane unless it is configured.
+	///
+	/// [MessageBusConfig::with_event_shards]: super::messagebus::MessageBusConfig::with_event_shards
+	pub fn spawn<E>(bus: &'static (impl TEventBus<E> + Sync), conn: &'static dyn TConnection) -> Self
+	where
+		E: ApplicationError + std::convert::From<BaseError>,
+		BaseError: std::convert::From<E>,
+	{
+		let (lanes, workers) = (0..MessageBus::config().event_shards.unwrap_or(1))
+			.map(|lane| {
+				let (sender, mut receiver) = mpsc::channel::<Arc<dyn TEvent>>(SHARD_LANE_CAPACITY);
+				let worker = spawn(async move {
+					while let Some(event) = receiver.recv().await {
+						// * Failure of an event doesn't hold back the ones behind it in the lane
+						if let Err(err) = bus.handle_event(event, conn).await {
+							tracing::error!("Error Occurred While Handling Event In {}th Lane! Error:{:?}", lane, err);
+						}
+					}
+				});
+				(sender, worker)
+			})
+			.unzip();
+		Self { lanes, workers }
+	}
+
+	/// Lane that events of the aggregate are handled in
+	pub fn lane_of(&self, aggregate_id: &str) -> usize {
+		let mut hasher = DefaultHasher::new();
+		aggregate_id.hash(&mut hasher);
+		(hasher.finish() % self.lanes.len() as u64) as usize
+	}
+
+	/// Queue event in the lane of its aggregate, waiting while the lane is full
+	pub async fn dispatch(&self, event: Arc<dyn TEvent>) -> Result<(), BaseError> {
+		let lane = self.lane_of(&event.metadata().aggregate_id);
+		self.lanes[lane].send(event).await.map_err(|_| {
+			tracing::error!("{}th Lane Is Closed!", lane);
+			BaseError::ShuttingDown
+		})
+	}
+
+	/// Stop taking events and wait until those already queued are handled
+	pub async fn close(self) {
+		drop(self.lanes);
+		for worker in self.workers {
+			if let Err(err) = worker.await {
+				tracing::error!("Worker Of Lane Panicked! Error:{:?}", err);
+			}
+		}
+	}
+}
@@ -0,0 +1,86 @@
+use super::cancellation::CancellationToken;
+use crate::responses::BaseError;
+use std::sync::{
+	atomic::{AtomicBool, AtomicUsize, Ordering},
+	Arc,
+};
+use tokio::sync::Notify;
+
+/// Handle to stop accepting new work while letting in-flight work drain.
+/// ## Example
+/// ```rust,no_run
+/// // On SIGTERM
+/// MessageBus::shutdown_handle().shutdown().await;
+/// ```
+#[derive(Clone, Default)]
+pub struct ShutdownHandle(Arc<ShutdownState>);
+
+#[derive(Default)]
+struct ShutdownState {
+	signaled: AtomicBool,
+	in_flight: AtomicUsize,
+	notify: Notify,
+	/// Parent of tokens of requests, cancelled on signal
+	cancellation: CancellationToken,
+}
+
+impl ShutdownHandle {
+	/// Reject work coming in from now on, and cancel tokens of the requests in flight
+	pub fn signal(&self) {
+		self.0.signaled.store(true, Ordering::SeqCst);
+		self.0.notify.notify_waiters();
+		self.0.cancellation.cancel();
+	}
+
+	pub fn is_signaled(&self) -> bool {
+		self.0.signaled.load(Ordering::SeqCst)
+	}
+
+	/// Resolves once shutdown is signaled
+	pub async fn signaled(&self) {
+		loop {
+			let notified = self.0.notify.notified();
+			if self.is_signaled() {
+				return;
+			}
+			notified.await;
+		}
+	}
+
+	/// Signal shutdown and wait until every in-flight work is drained
+	pub async fn shutdown(&self) {
+		self.signal();
+		loop {
+			let notified = self.0.notify.notified();
+			if self.0.in_flight.load(Ordering::SeqCst) == 0 {
+				return;
+			}
+			notified.await;
+		}
+	}
+
+	pub(crate) fn cancellation_token(&self) -> CancellationToken {
+		self.0.cancellation.clone()
+	}
+
+	/// Register work that is about to start. It is counted as in-flight until returned guard is dropped.
+	pub(crate) fn enter(&self) -> Result<InFlight, BaseError> {
+		// * Counted before checking signal so that `shutdown` never misses work that passed the check.
+		self.0.in_flight.fetch_add(1, Ordering::SeqCst);
+		let in_flight = InFlight(self.clone());
+		if self.is_signaled() {
+			return Err(BaseError::ShuttingDown);
+		}
+		Ok(in_flight)
+	}
+}
+
+pub(crate) struct InFlight(ShutdownHandle);
+
+impl Drop for InFlight {
+	fn drop(&mut self) {
+		if self.0 .0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+			self.0 .0.notify.notify_waiters();
+		}
+	}
+}
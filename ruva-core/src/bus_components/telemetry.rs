@@ -0,0 +1,58 @@
+//! Spans for commands and events, created only with `event-driven-otel` feature.
+//!
+//! They are plain `tracing` spans carrying attributes `tracing-opentelemetry` layer understands, such as `otel.name` and `otel.status_code`,
+//! so that they are exported as OpenTelemetry spans once the layer is installed.
+//! Spans of events raised by a command are children of the command span.
+
+use crate::prelude::TCommand;
+use std::collections::HashMap;
+use tracing::Span;
+
+pub(crate) fn command_span<C: TCommand>(command: &C) -> Span {
+	#[cfg(feature = "event-driven-otel")]
+	{
+		let name = std::any::type_name::<C>();
+		tracing::info_span!("command", otel.name = name, command = name, aggregate_id = command.aggregate_id(), otel.status_code = tracing::field::Empty)
+	}
+	#[cfg(not(feature = "event-driven-otel"))]
+	{
+		let _ = command;
+		Span::none()
+	}
+}
+
+pub(crate) fn event_span(topic: &str, handler_count: usize) -> Span {
+	#[cfg(feature = "event-driven-otel")]
+	{
+		tracing::info_span!("event", otel.name = topic, event = topic, handler_count, succeeded = tracing::field::Empty, failed = tracing::field::Empty, otel.status_code = tracing::field::Empty)
+	}
+	#[cfg(not(feature = "event-driven-otel"))]
+	{
+		let _ = (topic, handler_count);
+		Span::none()
+	}
+}
+
+pub(crate) fn record_outcome(span: &Span, succeeded: bool) {
+	span.record("otel.status_code", if succeeded { "OK" } else { "ERROR" });
+}
+
+pub(crate) fn record_handler_outcome(span: &Span, succeeded: usize, failed: usize) {
+	span.record("succeeded", succeeded);
+	span.record("failed", failed);
+	record_outcome(span, failed == 0);
+}
+
+/// Put trace context of current span into headers so that downstream services continue the trace
+pub(crate) fn inject_trace_context(headers: &mut HashMap<String, String>) {
+	#[cfg(feature = "event-driven-otel")]
+	{
+		use tracing_opentelemetry::OpenTelemetrySpanExt;
+		let context = Span::current().context();
+		opentelemetry::global::get_text_map_propagator(|propagator| propagator.inject_context(&context, headers));
+	}
+	#[cfg(not(feature = "event-driven-otel"))]
+	{
+		let _ = headers;
+	}
+}
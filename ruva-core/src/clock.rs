@@ -0,0 +1,68 @@
+//! ### Clock
+//! Source of current time for timestamps put by the library, such as `create_dt` of outbox and `deleted_at` of soft-deleted aggregate.
+//! It is [SystemClock] unless replaced through [MessageBusConfig::with_clock], so that tests can pin time with [MockClock].
+//!
+//! ```rust,no_run
+//! let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+//! MessageBus::configure(MessageBusConfig::default().with_clock(clock.clone()));
+//!
+//! let first = event.outbox();
+//! clock.advance(Duration::from_secs(60));
+//! let second = event.outbox();
+//! ```
+//!
+//! With `time` feature, current time can be taken as `time::OffsetDateTime` as well.
+//!
+//! [MessageBusConfig::with_clock]: crate::prelude::MessageBusConfig::with_clock
+
+use chrono::{DateTime, Utc};
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+pub type Timestamp = DateTime<Utc>;
+
+pub trait TClock: Send + Sync {
+	fn now(&self) -> Timestamp;
+
+	#[cfg(feature = "time")]
+	fn now_offset(&self) -> time::OffsetDateTime {
+		let now = self.now();
+		time::OffsetDateTime::from_unix_timestamp(now.timestamp()).expect("Timestamp out of range!") + time::Duration::nanoseconds(now.timestamp_subsec_nanos().into())
+	}
+}
+
+/// Clock of the system
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl TClock for SystemClock {
+	fn now(&self) -> Timestamp {
+		Utc::now()
+	}
+}
+
+/// Clock that stays at the time it is set to. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct MockClock(Arc<Mutex<Timestamp>>);
+
+impl MockClock {
+	pub fn new(at: Timestamp) -> Self {
+		Self(Arc::new(Mutex::new(at)))
+	}
+
+	pub fn set(&self, at: Timestamp) {
+		*self.0.lock().unwrap() = at;
+	}
+
+	pub fn advance(&self, by: Duration) {
+		*self.0.lock().unwrap() += chrono::Duration::from_std(by).expect("Duration out of range!");
+	}
+}
+
+impl TClock for MockClock {
+	fn now(&self) -> Timestamp {
+		*self.0.lock().unwrap()
+	}
+}
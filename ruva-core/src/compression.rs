@@ -0,0 +1,74 @@
+//! ### Outbox Compression
+//! Outbox whose event is serialized into as many bytes as the threshold given to [MessageBusConfig::with_outbox_compression] or more
+//! is compressed with the codec given along with it. Smaller ones stay uncompressed, as compression doesn't pay off for them.
+//!
+//! Both `payload` of [OutBox] and `data` of its [Envelope] are compressed, the latter encoded in base64 as envelope is json.
+//! Name of the codec is recorded in [CONTENT_ENCODING_HEADER], which goes along with the other headers,
+//! so that [OutBox::envelope] and consumers of the drivers decompress with the same codec.
+//!
+//! No codec comes with the library, so as not to impose compression crate on those who don't compress.
+//! ```rust,no_run
+//! struct Gzip;
+//! impl TCodec for Gzip {
+//!     fn name(&self) -> &'static str {
+//!         "gzip"
+//!     }
+//!     fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+//!         let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+//!         encoder.write_all(bytes).unwrap();
+//!         encoder.finish().unwrap()
+//!     }
+//!     fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, BaseError> {
+//!         let mut decompressed = vec![];
+//!         flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+//!         Ok(decompressed)
+//!     }
+//! }
+//!
+//! MessageBus::configure(MessageBusConfig::default().with_outbox_compression(Gzip, 64 * 1024));
+//! ```
+//!
+//! [MessageBusConfig::with_outbox_compression]: crate::prelude::MessageBusConfig::with_outbox_compression
+//! [OutBox]: crate::prelude::OutBox
+//! [OutBox::envelope]: crate::prelude::OutBox::envelope
+//! [Envelope]: crate::prelude::Envelope
+
+use crate::{bus_components::messagebus::MessageBus, responses::BaseError};
+use std::sync::Arc;
+
+/// Header under which name of the codec that outbox is compressed with is carried. Absent when it is not compressed.
+pub const CONTENT_ENCODING_HEADER: &str = "content_encoding";
+
+/// Compression codec such as gzip or zstd
+pub trait TCodec: Send + Sync {
+	/// Recorded in [CONTENT_ENCODING_HEADER] for consumers to tell the codec by
+	fn name(&self) -> &'static str;
+	fn compress(&self, bytes: &[u8]) -> Vec<u8>;
+	fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, BaseError>;
+}
+
+/// Compress `bytes` when they reach threshold of the codec configured, returning name of the codec along with them
+pub(crate) fn compress(bytes: &[u8]) -> Option<(&'static str, Vec<u8>)> {
+	let config = MessageBus::config();
+	let (codec, _) = config.outbox_compression.as_ref().filter(|(_, threshold)| bytes.len() >= *threshold)?;
+	Some((codec.name(), codec.compress(bytes)))
+}
+
+/// Codec configured, only if it is the one named `encoding`
+pub(crate) fn codec(encoding: &str) -> Option<Arc<dyn TCodec>> {
+	MessageBus::config().outbox_compression.as_ref().filter(|(codec, _)| codec.name() == encoding).map(|(codec, _)| Arc::clone(codec))
+}
+
+pub(crate) fn decompress(encoding: &str, bytes: &[u8]) -> Result<Vec<u8>, BaseError> {
+	let codec = codec(encoding).ok_or_else(|| BaseError::DeserializationError(format!("Codec {} is not configured!", encoding)))?;
+	codec.decompress(bytes)
+}
+
+/// Decompress payload of message as [CONTENT_ENCODING_HEADER] in its `headers` tells. It is given back as it is when the header is absent.
+#[cfg(any(feature = "kafka", feature = "event-driven-redis"))]
+pub(crate) fn decompress_payload<'a>(headers: &std::collections::HashMap<String, String>, payload: &'a [u8]) -> Result<std::borrow::Cow<'a, [u8]>, BaseError> {
+	match headers.get(CONTENT_ENCODING_HEADER) {
+		Some(encoding) => decompress(encoding, payload).map(std::borrow::Cow::Owned),
+		None => Ok(std::borrow::Cow::Borrowed(payload)),
+	}
+}
@@ -0,0 +1,106 @@
+//! ### TEventSourcedRepository
+//! Aggregate whose state is derived from its events rather than stored as it is.
+//!
+//! Rebuilding the aggregate from every event it ever raised gets slow as events pile up, so its state is snapshotted every
+//! [TEventSourcedRepository::snapshot_every] events. `load` restores the latest snapshot and applies only the events after it.
+//!
+//! Version of the aggregate is the number of events in its stream. It is returned by `load` and handed back to `save`,
+//! which gives it to `_append_events` so that concurrent modification can be detected by the store.
+//!
+//! #### Usage Pattern
+//!
+//! ```rust,no_run
+//! pub async fn deposit(cmd: Deposit, mut repo: impl TEventSourcedRepository<Account, i64>) -> Result<(), CustomError> {
+//!     let (mut account, version) = repo.load(&cmd.id).await?;
+//!     account.deposit(cmd.amount);
+//!     repo.save(&cmd.id, &mut account, version).await?;
+//!     Ok(())
+//! }
+//! ```
+
+use crate::prelude::{BaseError, TAggregate, TEvent, TSetCurrentEvents};
+use std::{future::Future, sync::Arc};
+
+pub trait TEventSourced: TAggregate {
+	type Snapshot: Send + Sync;
+
+	/// Change state as the event says. It is called for events loaded from the store, while newly raised events are expected
+	/// to be applied by the aggregate itself as it raises them. See [crate::match_event] for matching the event on its concrete type.
+	fn apply(&mut self, event: &dyn TEvent);
+
+	/// Aggregate folded from its default state by applying `events` in the order they are given
+	fn rebuild(events: impl IntoIterator<Item = Arc<dyn TEvent>>) -> Self {
+		events.into_iter().fold(Self::default(), |mut aggregate, event| {
+			aggregate.apply(event.as_ref());
+			aggregate
+		})
+	}
+	fn snapshot(&self) -> Self::Snapshot;
+	fn from_snapshot(snapshot: Self::Snapshot) -> Self;
+}
+
+/// Storage of the latest snapshot of each aggregate
+pub trait TSnapshotStore<S, Id>: Send + Sync {
+	/// Latest snapshot along with the version it was taken at
+	fn load(&self, id: &Id) -> impl Future<Output = Result<Option<(S, u64)>, BaseError>> + Send;
+	fn save(&self, id: &Id, snapshot: S, version: u64) -> impl Future<Output = Result<(), BaseError>> + Send;
+}
+
+pub trait TEventSourcedRepository<A, Id>: TSetCurrentEvents
+where
+	A: TEventSourced,
+	Id: Send + Sync,
+{
+	type SnapshotStore: TSnapshotStore<A::Snapshot, Id>;
+
+	// Storage primitives which concrete implementation must implement
+	// Events of the aggregate raised after `after_version`, in the order they were appended
+	fn _load_events(&self, id: &Id, after_version: u64) -> impl Future<Output = Result<Vec<Arc<dyn TEvent>>, BaseError>> + Send;
+	// Append events to the stream of the aggregate, failing when the stream has moved past `expected_version`
+	fn _append_events(&mut self, id: &Id, expected_version: u64, events: &[Arc<dyn TEvent>]) -> impl Future<Output = Result<(), BaseError>> + Send;
+
+	fn snapshot_store(&self) -> &Self::SnapshotStore;
+
+	/// Take snapshot once every this many events. Snapshot is not taken when it is `None`.
+	fn snapshot_every(&self) -> Option<u64> {
+		None
+	}
+
+	/// Aggregate rebuilt from its latest snapshot and the events after it, along with its version.
+	/// Returns [BaseError::NotFound] when there is neither snapshot nor event of the aggregate.
+	fn load(&self, id: &Id) -> impl Future<Output = Result<(A, u64), BaseError>> + Send {
+		async move {
+			let snapshot = self.snapshot_store().load(id).await?;
+			let found = snapshot.is_some();
+			let (mut aggregate, version) = snapshot.map_or_else(|| (A::default(), 0), |(snapshot, version)| (A::from_snapshot(snapshot), version));
+
+			let events = self._load_events(id, version).await?;
+			if !found && events.is_empty() {
+				return Err(BaseError::NotFound);
+			}
+			events.iter().for_each(|event| aggregate.apply(event.as_ref()));
+			Ok((aggregate, version + events.len() as u64))
+		}
+	}
+
+	/// Append events raised in the aggregate to its stream, returning the new version.
+	/// `version` is the one the aggregate was loaded at, 0 for a new one. Snapshot is taken when the new version crosses the cadence.
+	fn save(&mut self, id: &Id, aggregate: &mut A, version: u64) -> impl Future<Output = Result<u64, BaseError>> + Send {
+		async move {
+			let mut events = aggregate.collect_events();
+			if events.is_empty() {
+				return Ok(version);
+			}
+			self._append_events(id, version, events.make_contiguous()).await?;
+			let new_version = version + events.len() as u64;
+
+			if let Some(every) = self.snapshot_every().filter(|every| *every > 0) {
+				if version / every < new_version / every {
+					self.snapshot_store().save(id, aggregate.snapshot(), new_version).await?;
+				}
+			}
+			self.set_current_events(events);
+			Ok(new_version)
+		}
+	}
+}
@@ -2,30 +2,77 @@ mod adapters;
 mod aggregate;
 mod backtrace;
 mod bus_components;
+mod clock;
+mod compression;
+mod event_sourcing;
 mod macros;
 mod message;
 mod outbox;
+mod repository;
 mod responses;
+mod serialization;
 mod snowflake;
 mod unit_of_work;
+mod upcaster;
+mod util;
 
 pub mod prelude {
+	#[cfg(feature = "event-driven-amqp")]
+	pub use crate::adapters::amqp::*;
+	#[cfg(feature = "kafka")]
+	pub use crate::adapters::kafka::*;
+	#[cfg(feature = "event-driven-prometheus")]
+	pub use crate::adapters::prometheus::*;
+	#[cfg(feature = "event-driven-redis")]
+	pub use crate::adapters::redis::*;
 	pub use crate::aggregate::*;
+	pub use crate::bus_components::audit::{AuditKind, AuditRecord, InMemoryAuditSink, JsonLinesAuditSink, TAuditSink};
+	pub use crate::bus_components::cancellation::CancellationToken;
+	pub use crate::bus_components::circuit_breaker::{CircuitBreaker, CircuitState};
 	pub use crate::bus_components::contexts::AtomicContextManager;
 	pub use crate::bus_components::contexts::Context;
 	pub use crate::bus_components::contexts::ContextManager;
+	pub use crate::bus_components::contexts::EventQueue;
+	pub use crate::bus_components::contexts::HandlerContext;
+	pub use crate::bus_components::contexts::OverflowPolicy;
 	pub use crate::bus_components::contexts::TSetCurrentEvents;
-	pub use crate::bus_components::executor::TConnection;
+	pub use crate::bus_components::dead_letter::{DeadLetter, DeadLetterReplay, DeserializationErrorStrategy, TDeadLetterSink, TDeadLetterStore};
+	pub use crate::bus_components::dynamic::{DynamicMessageBus, HandlerId};
+	pub use crate::bus_components::effect::{TEffect, TEffectHandler};
+	pub use crate::bus_components::enricher::TEventEnricher;
+	pub use crate::bus_components::executor::{Executor, ReadReplicas, Reader, TConnection};
+	pub use crate::bus_components::feed::DEFAULT_EVENT_FEED_CAPACITY;
 	pub use crate::bus_components::handler::*;
+	pub use crate::bus_components::health::{HealthSnapshot, RelayMonitor};
+	pub use crate::bus_components::inbox::{Delivery, InMemoryInbox, InboxEntry, Redelivery, TInbox};
+	pub use crate::bus_components::lifecycle::TRequestLifecycle;
 	pub use crate::bus_components::messagebus::*;
+	pub use crate::bus_components::metrics::TMetricsRecorder;
+	pub use crate::bus_components::retry::{RetryClass, RetryPolicies, RetryPolicy};
+	pub use crate::bus_components::router::{BusRouter, RoutedResponse, RoutedResult};
+	pub use crate::bus_components::sharding::{ShardedDispatcher, SHARD_LANE_CAPACITY};
+	pub use crate::bus_components::shutdown::ShutdownHandle;
+	pub use crate::clock::{MockClock, SystemClock, TClock, Timestamp};
+	pub use crate::compression::{TCodec, CONTENT_ENCODING_HEADER};
+	pub use crate::event_sourcing::{TEventSourced, TEventSourcedRepository, TSnapshotStore};
 
 	pub use crate::message::*;
-	pub use crate::outbox::OutBox;
-	pub use crate::responses::{ApplicationError, ApplicationResponse, BaseError};
+	pub use crate::outbox::{Envelope, EnvelopeMetadata, InMemoryOutBoxStore, OutBox, TOutBoxPublisher, BATCH_HEADER, PARTITION_KEY_HEADER};
+	pub use crate::repository::{Cursor, Page, TRepository};
+	pub use crate::responses::{ApplicationError, ApplicationResponse, BaseError, DatabaseFailure, FieldError, ResponseMetadata};
+	#[cfg(feature = "schemars")]
+	pub use crate::serialization::EventSchemas;
+	pub use crate::serialization::{redact, CommandDeserializer, CommandDeserializers, CommandEnvelope, EventDeserializer, EventDeserializers, SerFormat, FORMAT_HEADER, REDACTED};
 	pub use crate::snowflake::SnowFlake;
 	pub use crate::unit_of_work::*;
+	pub use crate::upcaster::{Upcast, Upcaster, VERSION_HEADER};
+	pub use crate::util::Backoff;
 	pub use async_trait::async_trait;
+	pub use chrono;
 	pub use hashbrown::HashMap as HandlerMapper;
+	pub use inventory;
+	#[cfg(feature = "schemars")]
+	pub use schemars;
 	pub use serde;
 	pub use serde::{Deserialize, Serialize};
 	pub use serde_json;
@@ -38,8 +85,13 @@ pub mod prelude {
 pub mod event_macros {
 	// pub use crate::init_command_handler;
 	// pub use crate::init_event_handler;
+	pub use crate::convert_event;
+	pub use crate::create_dependency;
 	pub use crate::error;
+	pub use crate::init_dyn_command_handler;
+	pub use crate::init_typed_event_handler;
 	pub use crate::make_conversion;
 	pub use crate::make_smart_pointer;
+	pub use crate::match_event;
 	pub use crate::prepare_bulk_operation;
 }
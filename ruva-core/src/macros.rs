@@ -103,3 +103,165 @@ macro_rules! error {
             ::ruva::tracing::error!("{} {}:{}", format!($stmt, $($arg),*),file!(),line!())
         };
 	}
+
+/// Convert type-erased event into variant of user-defined enum, looking up `metadata().topic`.
+/// Evaluates to `Result<$enum_name, BaseError>`, giving `BaseError::EventNotFound` with the topic
+/// when topic is not in the table or event is not of the type registered for the topic.
+///
+/// ```rust,no_run
+/// enum AccountEvent {
+///     Created(AccountCreated),
+///     Closed(AccountClosed),
+/// }
+///
+/// let event: std::sync::Arc<dyn TEvent> = AccountCreated { id: 1 }.to_message();
+/// let account_event = convert_event!(event, AccountEvent {
+///     "AccountCreated" => Created(AccountCreated),
+///     "AccountClosed" | "AccountDeleted" => Closed(AccountClosed),
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! convert_event {
+    (
+        $event:expr, $enum_name:ident {
+            $($($topic:literal)|+ => $variant:ident($event_type:ty)),* $(,)?
+        }
+    ) => {{
+        let event = &$event;
+        let topic = event.metadata().topic;
+        match topic.as_str() {
+            $(
+                $($topic)|+ => event
+                    .downcast_ref::<$event_type>()
+                    .map(|e| $enum_name::$variant(e.clone()))
+                    .ok_or(::ruva::BaseError::EventNotFound(topic.clone())),
+            )*
+            _ => Err(::ruva::BaseError::EventNotFound(topic)),
+        }
+    }};
+}
+
+/// Run the arm for the concrete type of type-erased event, given reference to it. Arms are tried in order.
+/// Evaluates to `bool` telling whether the event was of any of the types, so that events of other types are either ignored or logged.
+///
+/// ```rust,no_run
+/// impl TEventSourced for Account {
+///     fn apply(&mut self, event: &dyn TEvent) {
+///         match_event!(event, {
+///             Deposited => |e| self.balance += e.amount,
+///             Withdrawn => |e| self.balance -= e.amount,
+///             AccountClosed => |_| self.closed = true,
+///         });
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! match_event {
+    (
+        $event:expr, {
+            $($event_type:ty => |$binding:pat_param| $body:expr),* $(,)?
+        }
+    ) => {{
+        let event = &$event;
+        $(
+            if let Some($binding) = event.downcast_ref::<$event_type>() {
+                $body;
+                true
+            } else
+        )*
+        { false }
+    }};
+}
+
+/// Define `Dependency` container and `dependency()` accessor that initializes it once on first access.
+/// ## Example
+/// ```rust,no_run
+/// // Without fields
+/// create_dependency!();
+///
+/// // With fields and initializer
+/// create_dependency!(
+///     struct {
+///         config: Config,
+///         client: reqwest::Client,
+///     }
+///     init {
+///         Dependency { config: Config::from_env(), client: reqwest::Client::new() }
+///     }
+/// );
+///
+/// let client = &dependency().client;
+/// ```
+#[macro_export]
+macro_rules! create_dependency {
+    () => {
+        pub struct Dependency;
+
+        pub fn dependency() -> &'static Dependency {
+            static DEPENDENCY: ::std::sync::OnceLock<Dependency> = ::std::sync::OnceLock::new();
+            DEPENDENCY.get_or_init(|| Dependency)
+        }
+    };
+    (
+        struct {
+            $($field:ident : $field_type:ty),* $(,)?
+        }
+        init $init:block
+    ) => {
+        pub struct Dependency {
+            $(pub $field: $field_type),*
+        }
+
+        pub fn dependency() -> &'static Dependency {
+            static DEPENDENCY: ::std::sync::OnceLock<Dependency> = ::std::sync::OnceLock::new();
+            DEPENDENCY.get_or_init(|| $init)
+        }
+    };
+}
+
+/// Declare a bounded context as a module of the given name, holding its dependency, command handlers and event handlers together.
+/// Each section is optional and takes what `create_dependency!`, `register_uow_services!` and `init_event_handler!` take respectively.
+/// `bus()` gives [MessageBus] the commands of the context are sent through.
+///
+/// As the items generated live in the module, more than one context can be declared in a binary,
+/// provided that each has its own error type as handlers are looked up by it.
+/// ## Example
+/// ```rust,no_run
+/// bounded_context! {
+///     ordering,
+///     dependency {
+///         struct { warehouse: String }
+///         init { Dependency { warehouse: "seoul".into() } }
+///     },
+///     commands { OrderResponse, OrderError, PlaceOrder => place_order },
+///     events { OrderError, |ctx| OrderEventHandler(ctx), OrderPlaced: [notify_customer] },
+/// }
+///
+/// ordering::bus().execute_and_wait(PlaceOrder { .. }, &conn).await?;
+/// let warehouse = &ordering::dependency().warehouse;
+/// ```
+///
+/// [MessageBus]: crate::prelude::MessageBus
+#[macro_export]
+macro_rules! bounded_context {
+    (
+        $name:ident
+        $(, dependency { $($dependency:tt)* })?
+        $(, commands { $($commands:tt)* })?
+        $(, events { $($events:tt)* })?
+        $(,)?
+    ) => {
+        pub mod $name {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $crate::create_dependency!($($($dependency)*)?);
+            $($crate::register_uow_services!($($commands)*);)?
+            $($crate::init_event_handler!($($events)*);)?
+
+            pub fn bus() -> ::ruva::MessageBus {
+                ::ruva::MessageBus
+            }
+        }
+    };
+}
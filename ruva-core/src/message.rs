@@ -15,9 +15,9 @@
 //! Here, `internally_notifiable` indicates that the event will be handled internally by `MessageBus`
 //! And the `externally_notifiable` means that the event will be stored in the form of `OutBox` and
 //! will be handled in the separate process (or thread)
-use crate::prelude::OutBox;
+use crate::prelude::{BaseError, OutBox, SerFormat, PARTITION_KEY_HEADER, VERSION_HEADER};
 use downcast_rs::{impl_downcast, Downcast};
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug};
 
 pub trait TEvent: Sync + Send + Downcast {
 	fn externally_notifiable(&self) -> bool {
@@ -26,17 +26,125 @@ pub trait TEvent: Sync + Send + Downcast {
 	fn internally_notifiable(&self) -> bool {
 		false
 	}
+	/// Events of higher priority are handled first within the same request. Events of the same priority are handled in order.
+	fn priority(&self) -> i8 {
+		0
+	}
 
 	fn metadata(&self) -> EventMetadata {
 		let event_name = std::any::type_name::<Self>().split("::").last().unwrap();
-		EventMetadata { aggregate_id: Default::default(), aggregate_name: Default::default(), topic: event_name.to_string() }
+		EventMetadata { aggregate_id: Default::default(), aggregate_name: Default::default(), topic: event_name.to_string(), version: self.version(), headers: self.headers() }
+	}
+
+	/// Topic of the event type, which handlers are keyed on. `#[derive(TEvent)]` also puts it in `TOPIC` associated constant.
+	fn topic() -> &'static str
+	where
+		Self: Sized,
+	{
+		std::any::type_name::<Self>().split("::").last().unwrap()
+	}
+
+	/// JSON Schema of the event type, for schema registry and consumer contracts. It is generated from `schemars::JsonSchema` the event derives.
+	/// See [crate::prelude::EventSchemas] to collect schemas of events.
+	#[cfg(feature = "schemars")]
+	fn schema() -> schemars::schema::RootSchema
+	where
+		Self: Sized + schemars::JsonSchema,
+	{
+		schemars::schema_for!(Self)
+	}
+
+	/// Identifies the message so that the same one raised twice within a request is handled once, when deduplication is enabled by
+	/// [crate::prelude::MessageBusConfig::with_event_deduplication]. Annotate field with `#[message_id]` to set it.
+	fn message_id(&self) -> Option<String> {
+		None
+	}
+
+	/// Key that decides partition the event is published to, so that events of the same key stay in order, as Kafka does within a partition.
+	/// It is aggregate id by default. Annotate field with `#[partition_key]` to set it.
+	/// `None` when neither is given, as with `#[aggregate_id = "none"]`, in which case the broker picks partition, round-robin for Kafka.
+	fn partition_key(&self) -> Option<String> {
+		Some(self.metadata().aggregate_id).filter(|aggregate_id| !aggregate_id.is_empty())
+	}
+
+	/// Version of event schema. Bump it along with registering [crate::prelude::Upcaster] when shape of event changes.
+	fn version(&self) -> u32 {
+		1
+	}
+
+	/// Free-form headers such as content-type, schema version or tenant, carried across service boundaries.
+	/// To store them on event, annotate `HashMap<String, String>` field with `#[headers]`.
+	fn headers(&self) -> HashMap<String, String> {
+		Default::default()
+	}
+	fn headers_mut(&mut self) -> Option<&mut HashMap<String, String>> {
+		None
+	}
+	/// Set header on event that has `#[headers]` field. It is ignored otherwise.
+	fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self
+	where
+		Self: Sized,
+	{
+		match self.headers_mut() {
+			Some(headers) => {
+				headers.insert(key.into(), value.into());
+			}
+			None => tracing::warn!("Header is ignored as {} doesn't have `#[headers]` field!", self.metadata().topic),
+		}
+		self
+	}
+
+	/// Format in which event is serialized for outbox. Annotate `#[ser_format(..)]` to change it.
+	fn ser_format(&self) -> SerFormat {
+		SerFormat::Json
+	}
+
+	/// Serialize event in the given format. Other than json, it is supported only for events with `#[derive(TEvent)]`.
+	fn serialize(&self, format: SerFormat) -> Vec<u8> {
+		self.try_serialize(format).expect("Failed to serialize")
+	}
+
+	/// [Self::serialize] that returns [BaseError::SerializationError] instead of panicking
+	#[allow(unreachable_patterns)]
+	fn try_serialize(&self, format: SerFormat) -> Result<Vec<u8>, BaseError> {
+		match format {
+			SerFormat::Json => self.try_state().map(String::into_bytes),
+			format => Err(BaseError::SerializationError(format!("{:?} serialization is supported only for events with `#[derive(TEvent)]`!", format))),
+		}
 	}
+
 	fn outbox(&self) -> OutBox {
-		let metadata = self.metadata();
-		OutBox::new(metadata.aggregate_id, metadata.aggregate_name, metadata.topic, self.state())
+		self.try_outbox().expect("Failed to serialize")
 	}
 
+	/// [Self::outbox] that returns [BaseError::SerializationError] when event could not be serialized
+	fn try_outbox(&self) -> Result<OutBox, BaseError> {
+		let mut metadata = self.metadata();
+		let format = self.ser_format();
+		metadata.headers.entry(VERSION_HEADER.to_string()).or_insert(metadata.version.to_string());
+		// * Partition key is kept only when it is not aggregate id, which outbox falls back on
+		if let Some(partition_key) = self.partition_key().filter(|partition_key| *partition_key != metadata.aggregate_id) {
+			metadata.headers.insert(PARTITION_KEY_HEADER.to_string(), partition_key);
+		}
+		let outbox = OutBox::new(metadata.aggregate_id, metadata.aggregate_name, metadata.topic, self.try_state()?, metadata.headers);
+		if format == SerFormat::Json {
+			return Ok(outbox);
+		}
+		Ok(outbox.with_payload(format, self.try_serialize(format)?))
+	}
+
+	/// Json representation of event
 	fn state(&self) -> String;
+
+	/// [Self::state] that returns [BaseError::SerializationError] instead of panicking. Events with `#[derive(TEvent)]` implement it.
+	fn try_state(&self) -> Result<String, BaseError> {
+		Ok(self.state())
+	}
+
+	/// [Self::state] with values of fields annotated with `#[redact]` masked, as recorded by [crate::prelude::TAuditSink]
+	fn redacted_state(&self) -> String {
+		self.state()
+	}
 }
 
 impl_downcast!(TEvent);
@@ -51,6 +159,31 @@ pub struct EventMetadata {
 	pub aggregate_id: String,
 	pub aggregate_name: String,
 	pub topic: String,
+	pub version: u32,
+	pub headers: HashMap<String, String>,
 }
 
-pub trait TCommand: 'static + Send + Sync + Debug {}
+/// As it is [Downcast], boxed command can be dispatched without knowing its type. See [crate::prelude::TDynMessageBus]
+pub trait TCommand: 'static + Send + Sync + Debug + Downcast {
+	/// Run by message bus before the command is dispatched. Error returned is given back to the caller without the command being handled.
+	fn validate(&self) -> Result<(), BaseError> {
+		Ok(())
+	}
+
+	/// Id of the aggregate the command targets, recorded in the span of the command. Set it by annotating field with `#[aggregate_id]`.
+	fn aggregate_id(&self) -> Option<String> {
+		None
+	}
+
+	/// Representation of command recorded by [crate::prelude::TAuditSink], debug output by default.
+	/// Command declared with `#[into_command]` that has fields annotated with `#[redact]` is recorded as json with their values masked.
+	fn redacted_state(&self) -> String {
+		format!("{:?}", self)
+	}
+
+	/// Type name of the command, which is that of the concrete command even when it is boxed as `dyn TCommand`
+	fn command_name(&self) -> &'static str {
+		std::any::type_name::<Self>()
+	}
+}
+impl_downcast!(TCommand);
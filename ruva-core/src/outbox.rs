@@ -1,6 +1,23 @@
 use chrono::{DateTime, Utc};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
 
-use crate::prelude::SnowFlake;
+use crate::{
+	bus_components::{messagebus::MessageBus, telemetry},
+	compression::{self, CONTENT_ENCODING_HEADER},
+	prelude::{BaseError, SerFormat, SnowFlake, TEvent, VERSION_HEADER},
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Header under which partition key of event is carried when it is other than aggregate id. See [TEvent::partition_key].
+pub const PARTITION_KEY_HEADER: &str = "partition_key";
+/// Header of outbox made by [OutBox::batch], carrying the number of events in it
+pub const BATCH_HEADER: &str = "batch";
 
 #[derive(Debug, Clone)]
 pub struct OutBox {
@@ -8,13 +25,268 @@ pub struct OutBox {
 	pub aggregate_id: String,
 	pub aggregate_name: String,
 	pub topic: String,
+	/// [Envelope] of the event in json, carrying its metadata along with the data
 	pub state: String,
+	/// Headers of the event serialized in json
+	pub headers: String,
+	/// Format in which `payload` is serialized
+	pub format: SerFormat,
+	/// Event serialized in `format`, without envelope. Metadata goes along as headers of message when it is published.
+	/// It is compressed when [CONTENT_ENCODING_HEADER] is in headers.
+	pub payload: Vec<u8>,
 	pub processed: bool,
 	pub create_dt: DateTime<Utc>,
 }
 
 impl OutBox {
-	pub fn new(aggregate_id: String, aggregate_name: String, topic: String, state: String) -> Self {
-		Self { id: *SnowFlake::generate(), aggregate_id, aggregate_name, topic, state, processed: false, create_dt: Default::default() }
+	/// `state` is json representation of the event, which is wrapped in [Envelope].
+	/// With `event-driven-otel` feature, trace context of current span is put into headers as well.
+	/// `state` as large as threshold of [crate::prelude::MessageBusConfig::with_outbox_compression] is compressed. See [crate::prelude::TCodec].
+	pub fn new(aggregate_id: String, aggregate_name: String, topic: String, state: String, mut headers: HashMap<String, String>) -> Self {
+		telemetry::inject_trace_context(&mut headers);
+		let compressed = compression::compress(state.as_bytes());
+		if let Some((encoding, _)) = compressed.as_ref() {
+			headers.insert(CONTENT_ENCODING_HEADER.to_string(), encoding.to_string());
+		}
+		let metadata = EnvelopeMetadata {
+			id: *SnowFlake::generate(),
+			aggregate_id,
+			aggregate_name,
+			topic,
+			version: headers.get(VERSION_HEADER).and_then(|version| version.parse().ok()).unwrap_or(1),
+			headers,
+			create_dt: MessageBus::now(),
+		};
+		// * State of event implemented by hand may not be json
+		let data = match compressed.as_ref() {
+			Some((_, compressed)) => serde_json::Value::String(BASE64.encode(compressed)),
+			None => serde_json::from_str(&state).unwrap_or(serde_json::Value::String(state.clone())),
+		};
+		let envelope = serde_json::to_string(&Envelope { metadata: metadata.clone(), data }).expect("Failed to serialize");
+
+		Self {
+			id: metadata.id,
+			aggregate_id: metadata.aggregate_id,
+			aggregate_name: metadata.aggregate_name,
+			topic: metadata.topic,
+			state: envelope,
+			headers: serde_json::to_string(&metadata.headers).expect("Failed to serialize"),
+			format: SerFormat::Json,
+			payload: compressed.map_or_else(|| state.into_bytes(), |(_, compressed)| compressed),
+			processed: false,
+			create_dt: metadata.create_dt,
+		}
+	}
+
+	/// Coalesce outboxes of events of the same topic into one, whose `state` and `payload` are json array of their [Envelope]s,
+	/// so that each event keeps its metadata. It is published to the topic and partition of the first one, and [BATCH_HEADER] tells consumers to split it.
+	/// Returns `None` when `outboxes` is empty. See [crate::prelude::MessageBusConfig::with_outbox_batching].
+	pub fn batch(outboxes: Vec<OutBox>) -> Option<Self> {
+		let first = outboxes.first()?;
+		let mut headers = HashMap::from([(BATCH_HEADER.to_string(), outboxes.len().to_string())]);
+		if let Some(partition_key) = first.partition_key() {
+			headers.insert(PARTITION_KEY_HEADER.to_string(), partition_key);
+		}
+		telemetry::inject_trace_context(&mut headers);
+		// * Envelopes are kept as they are, along with data compressed in them
+		let envelopes = outboxes.iter().map(|outbox| serde_json::from_str::<Envelope>(&outbox.state)).collect::<Result<Vec<_>, _>>().expect("Failed to deserialize");
+		let state = serde_json::to_string(&envelopes).expect("Failed to serialize");
+
+		Some(Self {
+			id: *SnowFlake::generate(),
+			aggregate_id: first.aggregate_id.clone(),
+			aggregate_name: first.aggregate_name.clone(),
+			topic: first.topic.clone(),
+			headers: serde_json::to_string(&headers).expect("Failed to serialize"),
+			format: SerFormat::Json,
+			payload: state.clone().into_bytes(),
+			state,
+			processed: false,
+			create_dt: MessageBus::now(),
+		})
+	}
+
+	/// Whether the outbox is made by [Self::batch]
+	pub fn is_batch(&self) -> bool {
+		let headers: HashMap<String, String> = serde_json::from_str(&self.headers).unwrap_or_default();
+		headers.contains_key(BATCH_HEADER)
+	}
+
+	/// Envelopes of the events in the outbox with their `data` decompressed, which are more than one when it is made by [Self::batch]
+	pub fn envelopes(&self) -> Result<Vec<Envelope>, BaseError> {
+		if !self.is_batch() {
+			return Ok(vec![self.envelope()?]);
+		}
+		let envelopes = serde_json::from_str::<Vec<Envelope>>(&self.state).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		envelopes.into_iter().map(Envelope::decompress).collect()
+	}
+
+	/// [Envelope] with its `data` decompressed, if it was compressed
+	pub fn envelope(&self) -> Result<Envelope, BaseError> {
+		serde_json::from_str::<Envelope>(&self.state).map_err(|err| BaseError::DeserializationError(err.to_string()))?.decompress()
+	}
+
+	/// Just the `data` section of [Envelope], for consumers that don't care about metadata
+	pub fn data(&self) -> Result<serde_json::Value, BaseError> {
+		Ok(self.envelope()?.data)
+	}
+
+	/// Partition key of the event, which is aggregate id unless the event set another one. `None` when neither is given.
+	pub fn partition_key(&self) -> Option<String> {
+		let headers: HashMap<String, String> = serde_json::from_str(&self.headers).unwrap_or_default();
+		headers.get(PARTITION_KEY_HEADER).cloned().or_else(|| Some(self.aggregate_id.clone()).filter(|aggregate_id| !aggregate_id.is_empty()))
+	}
+
+	/// Name of the codec that the outbox is compressed with. `None` when it is not compressed.
+	pub fn content_encoding(&self) -> Option<String> {
+		let mut headers: HashMap<String, String> = serde_json::from_str(&self.headers).unwrap_or_default();
+		headers.remove(CONTENT_ENCODING_HEADER)
+	}
+
+	/// Payload is compressed along with envelope, if it is compressed, so that they are read as [CONTENT_ENCODING_HEADER] tells
+	pub fn with_payload(mut self, format: SerFormat, payload: Vec<u8>) -> Self {
+		self.format = format;
+		self.payload = match self.content_encoding().and_then(|encoding| compression::codec(&encoding)) {
+			Some(codec) => codec.compress(&payload),
+			None => payload,
+		};
+		self
+	}
+}
+
+/// What is stored as `state` of [OutBox]
+/// ```json
+/// {"metadata": {"id": 1, "aggregate_id": "1", "aggregate_name": "Account", "topic": "AccountOpened", "version": 1, "headers": {}, "create_dt": "..."}, "data": {"id": 1}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+	pub metadata: EnvelopeMetadata,
+	pub data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvelopeMetadata {
+	/// Id of the outbox, which identifies the message
+	pub id: i64,
+	pub aggregate_id: String,
+	pub aggregate_name: String,
+	pub topic: String,
+	pub version: u32,
+	pub headers: HashMap<String, String>,
+	pub create_dt: DateTime<Utc>,
+}
+
+impl Envelope {
+	pub fn data<T: DeserializeOwned>(&self) -> Result<T, BaseError> {
+		serde_json::from_value(self.clone().decompress()?.data).map_err(|err| BaseError::DeserializationError(err.to_string()))
+	}
+
+	/// Reconstruct event of type `E` along with headers it carried. Headers are restored only on event with `#[headers]` field.
+	pub fn into_event<E: TEvent + DeserializeOwned>(self) -> Result<std::sync::Arc<dyn TEvent>, BaseError> {
+		let envelope = self.decompress()?;
+		let mut event: E = envelope.data()?;
+		if let Some(headers) = event.headers_mut() {
+			headers.extend(envelope.metadata.headers);
+		}
+		Ok(std::sync::Arc::new(event))
+	}
+
+	/// Restore `data` compressed with the codec [CONTENT_ENCODING_HEADER] names, dropping the header. It is given back as it is when the header is absent.
+	pub fn decompress(mut self) -> Result<Self, BaseError> {
+		let Some(encoding) = self.metadata.headers.remove(CONTENT_ENCODING_HEADER) else {
+			return Ok(self);
+		};
+		let encoded = self.data.as_str().ok_or_else(|| BaseError::DeserializationError("Compressed data must be base64 string!".to_string()))?;
+		let compressed = BASE64.decode(encoded).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		let state = String::from_utf8(compression::decompress(&encoding, &compressed)?).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		self.data = serde_json::from_str(&state).unwrap_or(serde_json::Value::String(state));
+		Ok(self)
+	}
+}
+
+/// Hook to relay outboxes to message broker
+#[async_trait]
+pub trait TOutBoxPublisher: Send + Sync {
+	/// Resolves only after the broker acknowledged the outbox, so it is safe to mark it processed then.
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError>;
+
+	/// Delays between attempts to publish an outbox that failed, tried once more after each. It is not retried by default.
+	fn backoff(&self) -> Box<dyn Iterator<Item = Duration> + Send> {
+		Box::new(std::iter::empty())
+	}
+
+	/// Publish outboxes in order, marking each processed once it is acknowledged.
+	/// It stops at the first failure that [Self::backoff] runs out on, so that the rest are relayed later in order.
+	/// Each run is reported to [MessageBus::relay_monitor] along with the number of outboxes acknowledged.
+	async fn publish_all(&self, outboxes: &mut [OutBox]) -> Result<(), BaseError> {
+		let mut relayed = 0;
+		let mut result = Ok(());
+		'relay: for outbox in outboxes.iter_mut().filter(|outbox| !outbox.processed) {
+			let mut backoff = self.backoff();
+			while let Err(err) = self.publish(outbox).await {
+				let Some(delay) = backoff.next() else {
+					result = Err(err);
+					break 'relay;
+				};
+				tracing::warn!("Retrying outbox {} in {:?} after failure! Error:{:?}", outbox.id, delay, err);
+				tokio::time::sleep(delay).await;
+			}
+			outbox.processed = true;
+			relayed += 1;
+		}
+		MessageBus::relay_monitor().relayed(relayed, MessageBus::now());
+		result
+	}
+}
+
+/// Outboxes staged in memory, to assert on in tests without relaying them. Clones share the outboxes.
+///
+/// Unit of work under test stages outboxes of its events from `process_external_events`:
+/// ```rust,no_run
+/// async fn process_external_events(&mut self) -> Result<(), BaseError> {
+///     self.outbox.stage(self.context.outboxes());
+///     Ok(())
+/// }
+///
+/// MessageBus.execute_and_wait(OpenAccount { id: 1 }, &conn).await?;
+/// let outbox = OUTBOX.assert_outboxed::<AccountOpened>();
+/// assert_eq!(outbox.aggregate_id, "1");
+/// ```
+/// As it is [TOutBoxPublisher] as well, outboxes published to it are staged the same way.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryOutBoxStore(Arc<Mutex<Vec<OutBox>>>);
+
+impl InMemoryOutBoxStore {
+	pub fn stage(&self, outboxes: impl IntoIterator<Item = OutBox>) {
+		self.0.lock().unwrap().extend(outboxes)
+	}
+
+	pub fn outboxes(&self) -> Vec<OutBox> {
+		self.0.lock().unwrap().clone()
+	}
+
+	/// Take every outbox staged so far, leaving the store empty
+	pub fn drain_outbox(&self) -> Vec<OutBox> {
+		std::mem::take(&mut *self.0.lock().unwrap())
+	}
+
+	/// First outbox staged for event `E`, whose envelope carries the topic of `E` as well.
+	/// Panics when there is none, listing topics of the ones staged.
+	#[track_caller]
+	pub fn assert_outboxed<E: TEvent>(&self) -> OutBox {
+		let outboxes = self.0.lock().unwrap();
+		let topic = E::topic();
+		match outboxes.iter().find(|outbox| outbox.topic == topic && outbox.envelope().is_ok_and(|envelope| envelope.metadata.topic == topic)) {
+			Some(outbox) => outbox.clone(),
+			None => panic!("Outbox of {} is not staged! Staged:{:?}", topic, outboxes.iter().map(|outbox| outbox.topic.as_str()).collect::<Vec<_>>()),
+		}
+	}
+}
+
+#[async_trait]
+impl TOutBoxPublisher for InMemoryOutBoxStore {
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		self.stage([outbox.clone()]);
+		Ok(())
 	}
 }
@@ -0,0 +1,169 @@
+//! ### TRepository
+//! [TRepository] gives common CRUD operations on aggregate for free.
+//!
+//! Concrete repository only implements storage primitives, `_insert`, `_find`, `_update`, `_delete` and `_find_all`,
+//! which are run within transaction governed by [TUnitOfWork].
+//!
+//! Events raised in aggregate are collected on `add` and `update` so they are processed on commit.
+//!
+//! Aggregate with `#[deleted_at]` field is soft-deleted: `delete` sets the field instead of removing it,
+//! and `get` and `list` leave out deleted ones. `get_including_deleted` and `restore` give access to them again.
+//!
+//! `list_page` pages through aggregates by keyset, once `_find_after` and `_cursor_of` are implemented on an indexed column:
+//! ```rust,no_run
+//! let mut cursor = None;
+//! loop {
+//!     let page = repo.list_page(cursor, 100).await?;
+//!     // ...
+//!     let Some(next_cursor) = page.next_cursor else { break };
+//!     cursor = Some(next_cursor);
+//! }
+//! ```
+//!
+//! Reads can be spread over read replicas by routing storage primitives for reads, such as `_find`, through [Executor::reader].
+//! It reads from the transaction while one is active, so that writes staged in it are seen before commit:
+//! ```rust,no_run
+//! async fn _find(&self, id: &i64) -> Result<Option<Account>, BaseError> {
+//!     match self.executor.reader(&self.replicas).await {
+//!         Reader::Transaction(mut trx) => find_account(&mut **trx, id).await,
+//!         Reader::Replica(pool) => find_account(pool, id).await,
+//!     }
+//! }
+//! ```
+//!
+//! #### Usage Pattern
+//!
+//! ```rust,no_run
+//! pub async fn create_aggregate(cmd: CreateCommand, mut repo: impl TRepository<CustomAggregate, i64>) -> Result<i64, CustomError> {
+//!     repo.begin().await?;
+//!     let mut aggregate = CustomAggregate::new(cmd);
+//!     repo.add(&mut aggregate).await?;
+//!     repo.commit().await?;
+//!     Ok(aggregate.id)
+//! }
+//! ```
+//!
+//! [TUnitOfWork]: crate::unit_of_work::TUnitOfWork
+//! [Executor::reader]: crate::bus_components::executor::Executor::reader
+
+use crate::prelude::{BaseError, MessageBus, TAggregate, TSetCurrentEvents, TUnitOfWork};
+use std::{future::Future, str::FromStr};
+
+/// Key of the last aggregate of a page in the column pages are ordered by. The next page starts right after it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Cursor(String);
+
+impl Cursor {
+	pub fn new(key: impl ToString) -> Self {
+		Self(key.to_string())
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+
+	/// Parse the key back into the type of the column, such as `i64` for id
+	pub fn parse<T: FromStr>(&self) -> Result<T, BaseError> {
+		self.0.parse().map_err(|_| BaseError::DeserializationError(format!("Invalid Cursor! {}", self.0)))
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Page<A> {
+	pub items: Vec<A>,
+	/// `None` on the last page
+	pub next_cursor: Option<Cursor>,
+}
+
+pub trait TRepository<A, Id>: TUnitOfWork + TSetCurrentEvents
+where
+	A: TAggregate,
+	Id: Send + Sync,
+{
+	// Storage primitives which concrete implementation must implement
+	fn _insert(&mut self, aggregate: &A) -> impl Future<Output = Result<Id, BaseError>> + Send;
+	fn _find(&self, id: &Id) -> impl Future<Output = Result<Option<A>, BaseError>> + Send;
+	fn _update(&mut self, aggregate: &A) -> impl Future<Output = Result<(), BaseError>> + Send;
+	fn _delete(&mut self, id: &Id) -> impl Future<Output = Result<(), BaseError>> + Send;
+	fn _find_all(&self) -> impl Future<Output = Result<Vec<A>, BaseError>> + Send;
+
+	// Up to `limit` aggregates whose key comes after `cursor`, ordered by the key. Concrete implementation supporting `list_page` must implement
+	fn _find_after(&self, _cursor: Option<&Cursor>, _limit: usize) -> impl Future<Output = Result<Vec<A>, BaseError>> + Send {
+		async {
+			tracing::error!("Pagination Is Not Supported!");
+			Err(BaseError::ServiceError)
+		}
+	}
+	// Key `_find_after` orders aggregates by
+	fn _cursor_of(&self, _aggregate: &A) -> Cursor {
+		unimplemented!("_cursor_of must be implemented along with _find_after")
+	}
+
+	fn add(&mut self, aggregate: &mut A) -> impl Future<Output = Result<Id, BaseError>> + Send {
+		async move {
+			let id = self._insert(aggregate).await?;
+			self.set_current_events(aggregate.collect_events());
+			Ok(id)
+		}
+	}
+
+	/// Returns [BaseError::NotFound] when aggregate of given id is absent or soft-deleted
+	fn get(&self, id: &Id) -> impl Future<Output = Result<A, BaseError>> + Send {
+		async move { self._find(id).await?.filter(|aggregate| aggregate.deleted_at().is_none()).ok_or(BaseError::NotFound) }
+	}
+
+	fn get_including_deleted(&self, id: &Id) -> impl Future<Output = Result<A, BaseError>> + Send {
+		async move { self._find(id).await?.ok_or(BaseError::NotFound) }
+	}
+
+	fn update(&mut self, aggregate: &mut A) -> impl Future<Output = Result<(), BaseError>> + Send {
+		async move {
+			self._update(aggregate).await?;
+			self.set_current_events(aggregate.collect_events());
+			Ok(())
+		}
+	}
+
+	fn delete(&mut self, id: &Id) -> impl Future<Output = Result<(), BaseError>> + Send {
+		async move {
+			if !A::SOFT_DELETE {
+				return self._delete(id).await;
+			}
+			let mut aggregate = self.get(id).await?;
+			aggregate.set_deleted_at(Some(MessageBus::now()));
+			self._update(&aggregate).await
+		}
+	}
+
+	/// Bring back soft-deleted aggregate
+	fn restore(&mut self, id: &Id) -> impl Future<Output = Result<A, BaseError>> + Send {
+		async move {
+			let mut aggregate = self.get_including_deleted(id).await?;
+			aggregate.set_deleted_at(None);
+			self._update(&aggregate).await?;
+			Ok(aggregate)
+		}
+	}
+
+	fn list(&self) -> impl Future<Output = Result<Vec<A>, BaseError>> + Send {
+		async move { Ok(self._find_all().await?.into_iter().filter(|aggregate| aggregate.deleted_at().is_none()).collect()) }
+	}
+
+	/// Page of at most `limit` aggregates that come after `cursor`, starting from the first one when it is `None`.
+	/// Soft-deleted ones are left out, so a page may hold fewer than `limit` even when it is not the last one.
+	fn list_page(&self, cursor: Option<Cursor>, limit: usize) -> impl Future<Output = Result<Page<A>, BaseError>> + Send {
+		async move {
+			// * One more is fetched to tell whether there is the next page
+			let mut items = self._find_after(cursor.as_ref(), limit + 1).await?;
+			let next_cursor = match items.len() > limit {
+				true => {
+					items.truncate(limit);
+					items.last().map(|aggregate| self._cursor_of(aggregate))
+				}
+				false => None,
+			};
+			items.retain(|aggregate| aggregate.deleted_at().is_none());
+			Ok(Page { items, next_cursor })
+		}
+	}
+}
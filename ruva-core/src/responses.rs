@@ -1,19 +1,240 @@
-use crate::prelude::TEvent;
+use crate::prelude::{TEvent, REDACTED};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub enum BaseError {
 	NotFound,
+	EventNotFound(String),
+	/// None of the buses handles the command given, whose type name it carries
+	CommandNotFound(String),
 	StopSentinel,
 	TransactionError,
+	/// Stop the rest of the handlers and queue the event instead. Its `metadata` and `state` are logged at the stop point,
+	/// and its outbox is published as well when [crate::prelude::MessageBusConfig::with_stop_sentinel_publisher] is given.
 	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
-	DatabaseError(String),
+	DatabaseError(DatabaseFailure),
+	/// Transaction was aborted as it could not be serialized against concurrent ones. It is safe to retry.
+	SerializationFailure,
+	/// Message could not be serialized, as with non-finite float or map with non-string keys in json
+	SerializationError(String),
+	DeserializationError(String),
+	MessageBrokerError(String),
 	ServiceError,
+	/// Command given is invalid
+	ValidationError(String),
+	/// Fields of command given are invalid
+	ValidationFailed(Vec<FieldError>),
+	Timeout {
+		command: String,
+		after: std::time::Duration,
+	},
+	ShuttingDown,
+	/// Deadline of the request passed before its events were handled. See [crate::prelude::MessageBusConfig::with_request_deadline].
+	DeadlineExceeded,
+	/// Event queue of the request is full
+	QueueFull,
+	/// Chain of commands dispatched from event handlers got deeper than the limit
+	CommandDepthExceeded(usize),
+	/// Event kept being re-enqueued through stop sentinel more times than the limit within a request
+	PoisonMessage {
+		message_id: String,
+		requeued: usize,
+	},
+	/// Event handed to handler is not of the type the handler takes, which means handlers are registered under the wrong topic
+	EventDowncastFailed {
+		topic: String,
+		expected_type: &'static str,
+	},
+	/// Boxed command handed to handler is not of the type the handler takes
+	CommandDowncastFailed {
+		expected_type: &'static str,
+	},
+	/// Handlers of the topic were not run as its circuit breaker is open. See [crate::prelude::CircuitBreaker].
+	CircuitOpen {
+		topic: String,
+	},
 }
 
-pub trait ApplicationResponse: Send + Sync {}
+impl BaseError {
+	/// Stable identifier of the variant for API clients to branch on. It doesn't change when messages are reworded.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::NotFound => "not_found",
+			Self::EventNotFound(_) => "event_not_found",
+			Self::CommandNotFound(_) => "command_not_found",
+			Self::StopSentinel => "stop_sentinel",
+			Self::TransactionError => "transaction_error",
+			Self::StopSentinelWithEvent(_) => "stop_sentinel_with_event",
+			Self::DatabaseError(_) => "database_error",
+			Self::SerializationFailure => "serialization_failure",
+			Self::SerializationError(_) => "serialization_error",
+			Self::DeserializationError(_) => "deserialization_error",
+			Self::MessageBrokerError(_) => "message_broker_error",
+			Self::ServiceError => "service_error",
+			Self::ValidationError(_) => "validation_error",
+			Self::ValidationFailed(_) => "validation_failed",
+			Self::Timeout { .. } => "timeout",
+			Self::ShuttingDown => "shutting_down",
+			Self::DeadlineExceeded => "deadline_exceeded",
+			Self::QueueFull => "queue_full",
+			Self::CommandDepthExceeded(_) => "command_depth_exceeded",
+			Self::PoisonMessage { .. } => "poison_message",
+			Self::EventDowncastFailed { .. } => "event_downcast_failed",
+			Self::CommandDowncastFailed { .. } => "command_downcast_failed",
+			Self::CircuitOpen { .. } => "circuit_open",
+		}
+	}
+}
+
+impl BaseError {
+	/// Record which query failed on [BaseError::DatabaseError], labelled by `operation` such as `insert_outbox`.
+	/// String literals in `query` are masked, while values bound as parameters never appear in it. Other errors are returned as they are.
+	/// ```rust,no_run
+	/// sqlx::query(FIND_ACCOUNT).bind(id).fetch_optional(pool).await.map_err(|err| BaseError::from(err).with_query("find_account", FIND_ACCOUNT))?;
+	/// ```
+	pub fn with_query(self, operation: &str, query: &str) -> Self {
+		match self {
+			Self::DatabaseError(failure) => Self::DatabaseError(DatabaseFailure { operation: Some(operation.to_string()), query: Some(redact_query(query)), ..failure }),
+			err => err,
+		}
+	}
+}
+
+/// What [BaseError::DatabaseError] carries. It converts from and into the message, so `String` works as the field of `#[database_error]` too,
+/// though `operation` and `query` are lost then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseFailure {
+	pub message: String,
+	/// Label of the operation that failed, such as `insert_outbox`
+	pub operation: Option<String>,
+	/// Query that failed, with string literals masked
+	pub query: Option<String>,
+}
+
+impl From<String> for DatabaseFailure {
+	fn from(message: String) -> Self {
+		Self { message, operation: None, query: None }
+	}
+}
+
+impl From<&str> for DatabaseFailure {
+	fn from(message: &str) -> Self {
+		message.to_string().into()
+	}
+}
+
+impl From<DatabaseFailure> for String {
+	fn from(failure: DatabaseFailure) -> Self {
+		failure.to_string()
+	}
+}
+
+impl std::fmt::Display for DatabaseFailure {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		if let Some(operation) = &self.operation {
+			write!(f, "[{}] ", operation)?;
+		}
+		write!(f, "{}", self.message)?;
+		if let Some(query) = &self.query {
+			write!(f, " Query:{}", query)?;
+		}
+		Ok(())
+	}
+}
+
+// * Values put in query as string literals may be sensitive, so they are masked along with the quotes being kept
+fn redact_query(query: &str) -> String {
+	let mut redacted = String::with_capacity(query.len());
+	let mut chars = query.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c != '\'' {
+			redacted.push(c);
+			continue;
+		}
+		// * Quote doubled within literal is escaped quote, not the end of it
+		while let Some(c) = chars.next() {
+			if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+				break;
+			}
+		}
+		redacted.push_str(&format!("'{}'", REDACTED));
+	}
+	redacted
+}
+
+/// Why a field failed validation. `code` is a stable identifier of the rule, such as `range` or `length`, and `message` is for humans.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FieldError {
+	pub field: String,
+	pub code: String,
+	pub message: String,
+}
+
+impl FieldError {
+	pub fn new(field: impl Into<String>, code: impl Into<String>, message: impl Into<String>) -> Self {
+		Self { field: field.into(), code: code.into(), message: message.into() }
+	}
+}
+
+/// What web adapters respond with besides the payload, such as `Location` of what was created
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResponseMetadata {
+	/// Put in HTTP headers as they are
+	pub headers: HashMap<String, String>,
+	/// Left for adapters to interpret, such as generated ids or number of affected rows
+	pub extras: HashMap<String, serde_json::Value>,
+}
+
+impl ResponseMetadata {
+	pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.headers.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn with_location(self, location: impl Into<String>) -> Self {
+		self.with_header("Location", location)
+	}
+
+	pub fn with_extra(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+		self.extras.insert(key.into(), value.into());
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.headers.is_empty() && self.extras.is_empty()
+	}
+}
 
-pub trait ApplicationError: 'static + std::fmt::Debug + Send + Sync {}
-impl ApplicationError for BaseError {}
+pub trait ApplicationResponse: Send + Sync {
+	/// HTTP status of success that web adapters respond with, such as 201 for what was created
+	fn status_code(&self) -> u16 {
+		200
+	}
+
+	/// Headers and extras that web adapters translate into HTTP response along with [ApplicationResponse::status_code]. It is empty by default.
+	fn response_metadata(&self) -> ResponseMetadata {
+		ResponseMetadata::default()
+	}
+}
+
+pub trait ApplicationError: 'static + std::fmt::Debug + Send + Sync {
+	/// Stable identifier of the error for API clients, see [BaseError::code]
+	fn code(&self) -> &'static str {
+		"service_error"
+	}
+}
+impl ApplicationError for BaseError {
+	fn code(&self) -> &'static str {
+		BaseError::code(self)
+	}
+}
+
+// * Lets infallible conversion of response, such as the one from the response itself, meet `TryInto` bound of command handler
+impl From<std::convert::Infallible> for BaseError {
+	fn from(value: std::convert::Infallible) -> Self {
+		match value {}
+	}
+}
 
 impl From<BaseError> for Box<dyn ApplicationError> {
 	fn from(value: BaseError) -> Self {
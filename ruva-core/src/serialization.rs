@@ -0,0 +1,321 @@
+//! ### Serialization format
+//! Events are serialized in json by default. For high-throughput topics, compact binary encodings can be chosen
+//! with `messagepack` and `bincode` features.
+//! ```rust,no_run
+//! #[derive(Serialize, Deserialize, Clone, TEvent)]
+//! #[externally_notifiable(Order)]
+//! #[ser_format(MessagePack)]
+//! pub struct OrderPlaced {
+//!     #[identifier]
+//!     pub id: i64,
+//! }
+//! ```
+use crate::{
+	outbox::Envelope,
+	prelude::{TCommand, TEvent},
+	responses::BaseError,
+	upcaster::Upcaster,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Header under which serialization format of payload is carried across service boundaries.
+pub const FORMAT_HEADER: &str = "format";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SerFormat {
+	#[default]
+	Json,
+	#[cfg(feature = "messagepack")]
+	MessagePack,
+	#[cfg(feature = "bincode")]
+	Bincode,
+}
+
+impl SerFormat {
+	pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Vec<u8> {
+		self.try_serialize(value).expect("Failed to serialize")
+	}
+
+	pub fn try_serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, BaseError> {
+		match self {
+			Self::Json => serde_json::to_vec(value).map_err(|err| BaseError::SerializationError(err.to_string())),
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|err| BaseError::SerializationError(err.to_string())),
+			#[cfg(feature = "bincode")]
+			Self::Bincode => bincode::serialize(value).map_err(|err| BaseError::SerializationError(err.to_string())),
+		}
+	}
+
+	pub fn deserialize<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, BaseError> {
+		match self {
+			Self::Json => serde_json::from_slice(payload).map_err(|err| BaseError::DeserializationError(err.to_string())),
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => rmp_serde::from_slice(payload).map_err(|err| BaseError::DeserializationError(err.to_string())),
+			#[cfg(feature = "bincode")]
+			Self::Bincode => bincode::deserialize(payload).map_err(|err| BaseError::DeserializationError(err.to_string())),
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Json => "json",
+			#[cfg(feature = "messagepack")]
+			Self::MessagePack => "messagepack",
+			#[cfg(feature = "bincode")]
+			Self::Bincode => "bincode",
+		}
+	}
+}
+
+impl std::str::FromStr for SerFormat {
+	type Err = BaseError;
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"json" => Ok(Self::Json),
+			#[cfg(feature = "messagepack")]
+			"messagepack" => Ok(Self::MessagePack),
+			#[cfg(feature = "bincode")]
+			"bincode" => Ok(Self::Bincode),
+			format => Err(BaseError::DeserializationError(format!("Unsupported serialization format {}", format))),
+		}
+	}
+}
+
+pub type EventDeserializer = fn(&[u8], SerFormat) -> Result<Arc<dyn TEvent>, BaseError>;
+
+/// Deserializers of events keyed by topic, for payloads coming back from outside of the application
+#[derive(Default, Clone)]
+pub struct EventDeserializers(hashbrown::HashMap<String, EventDeserializer>);
+
+impl EventDeserializers {
+	/// Deserialize payload of `topic` into `T`
+	pub fn register<T>(mut self, topic: &str) -> Self
+	where
+		T: TEvent + DeserializeOwned,
+	{
+		self.0.insert(topic.to_string(), |payload, format| {
+			let event: T = format.deserialize(payload)?;
+			Ok(Arc::new(event))
+		});
+		self
+	}
+
+	/// Deserialize payload of the topic of `T`, which is its `TOPIC` when it derives [TEvent]
+	pub fn register_event<T>(self) -> Self
+	where
+		T: TEvent + DeserializeOwned,
+	{
+		self.register::<T>(T::topic())
+	}
+
+	/// Take deserializers of `other` as well. Those of the same topic are replaced by the ones of `other`.
+	pub fn merge(mut self, other: EventDeserializers) -> Self {
+		self.0.extend(other.0);
+		self
+	}
+
+	pub fn topics(&self) -> impl Iterator<Item = &str> {
+		self.0.keys().map(String::as_str)
+	}
+
+	/// Returns [BaseError::EventNotFound] when no deserializer is registered for `topic`
+	pub fn deserialize(&self, topic: &str, payload: &[u8], format: SerFormat) -> Result<Arc<dyn TEvent>, BaseError> {
+		let deserialize = self.0.get(topic).ok_or_else(|| BaseError::EventNotFound(topic.to_string()))?;
+		deserialize(payload, format)
+	}
+
+	/// Split payload of outbox made by [crate::prelude::OutBox::batch] into its events, each deserialized by the topic and upcast from the version its envelope carries.
+	/// Headers of each envelope are set on its event when it has `#[headers]` field. It fails as a whole when any of them fails.
+	pub fn deserialize_batch(&self, payload: &[u8], upcaster: &Upcaster) -> Result<Vec<Arc<dyn TEvent>>, BaseError> {
+		let envelopes: Vec<Envelope> = serde_json::from_slice(payload).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		envelopes
+			.into_iter()
+			.map(|envelope| {
+				let envelope = envelope.decompress()?;
+				let data = serde_json::to_vec(&envelope.data).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+				let data = upcaster.upcast(&envelope.metadata.topic, envelope.metadata.version, &data)?;
+				let mut event = self.deserialize(&envelope.metadata.topic, &data, SerFormat::Json)?;
+				if let Some(headers) = Arc::get_mut(&mut event).and_then(|event| event.headers_mut()) {
+					headers.extend(envelope.metadata.headers);
+				}
+				Ok(event)
+			})
+			.collect()
+	}
+}
+
+/// Untyped command as it comes in through HTTP or message entrypoints, with `type` telling which command `payload` is
+/// ```json
+/// {"type": "PlaceOrder", "payload": {"quantity": 1}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandEnvelope {
+	#[serde(rename = "type")]
+	pub kind: String,
+	pub payload: serde_json::Value,
+}
+
+pub type CommandDeserializer = fn(serde_json::Value) -> Result<Box<dyn TCommand>, BaseError>;
+
+/// Deserializers of commands keyed by type discriminator, so that [CommandEnvelope] is turned into the command to be given to `execute_dyn`.
+/// ## Example
+/// ```rust,no_run
+/// let deserializers = CommandDeserializers::default().register_command::<PlaceOrder>().register_command::<CancelOrder>();
+///
+/// let command = deserializers.deserialize(serde_json::from_slice(&body)?)?;
+/// let res = MessageBus.execute_dyn(command, &CONNECTION).await?;
+/// ```
+#[derive(Default, Clone)]
+pub struct CommandDeserializers(hashbrown::HashMap<String, CommandDeserializer>);
+
+impl CommandDeserializers {
+	/// Deserialize payload of `kind` into `C`
+	pub fn register<C>(mut self, kind: &str) -> Self
+	where
+		C: TCommand + DeserializeOwned,
+	{
+		self.0.insert(kind.to_string(), |payload| {
+			let command: C = serde_json::from_value(payload).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+			Ok(Box::new(command))
+		});
+		self
+	}
+
+	/// Deserialize payload whose type is the name of `C`
+	pub fn register_command<C>(self) -> Self
+	where
+		C: TCommand + DeserializeOwned,
+	{
+		self.register::<C>(std::any::type_name::<C>().split("::").last().unwrap())
+	}
+
+	/// Take deserializers of `other` as well. Those of the same type are replaced by the ones of `other`.
+	pub fn merge(mut self, other: CommandDeserializers) -> Self {
+		self.0.extend(other.0);
+		self
+	}
+
+	pub fn kinds(&self) -> impl Iterator<Item = &str> {
+		self.0.keys().map(String::as_str)
+	}
+
+	/// Returns [BaseError::CommandNotFound] when no deserializer is registered for type of `envelope`
+	pub fn deserialize(&self, envelope: CommandEnvelope) -> Result<Box<dyn TCommand>, BaseError> {
+		let Some(deserialize) = self.0.get(&envelope.kind) else {
+			tracing::error!("No Deserializer Is Registered For Command {}!", envelope.kind);
+			return Err(BaseError::CommandNotFound(envelope.kind));
+		};
+		deserialize(envelope.payload)
+	}
+}
+
+/// JSON Schemas of events keyed by topic. Dump them on CI and diff against the ones committed to catch breaking changes of events.
+/// ## Example
+/// ```rust,no_run
+/// #[derive(Serialize, Deserialize, Clone, JsonSchema, TEvent)]
+/// #[externally_notifiable]
+/// pub struct OrderPlaced {
+///     pub order_id: i64,
+/// }
+///
+/// EventSchemas::default().register_event::<OrderPlaced>().register_event::<OrderCancelled>().dump("schemas/events")?;
+/// ```
+#[cfg(feature = "schemars")]
+#[derive(Default, Clone)]
+pub struct EventSchemas(std::collections::BTreeMap<String, schemars::schema::RootSchema>);
+
+#[cfg(feature = "schemars")]
+impl EventSchemas {
+	pub fn register_event<T>(mut self) -> Self
+	where
+		T: TEvent + schemars::JsonSchema,
+	{
+		self.0.insert(T::topic().to_string(), T::schema());
+		self
+	}
+
+	/// Take schemas of `other` as well. Those of the same topic are replaced by the ones of `other`.
+	pub fn merge(mut self, other: EventSchemas) -> Self {
+		self.0.extend(other.0);
+		self
+	}
+
+	pub fn get(&self, topic: &str) -> Option<&schemars::schema::RootSchema> {
+		self.0.get(topic)
+	}
+
+	pub fn topics(&self) -> impl Iterator<Item = &str> {
+		self.0.keys().map(String::as_str)
+	}
+
+	/// Write schema of each topic to `{topic}.json` under `dir`, creating `dir` if it doesn't exist. Returns paths written, in the order of topic.
+	pub fn dump(&self, dir: impl AsRef<std::path::Path>) -> Result<Vec<std::path::PathBuf>, BaseError> {
+		let failed = |path: &std::path::Path, err: &dyn std::fmt::Display| {
+			tracing::error!("Failed to dump event schema to {}! Error:{}", path.display(), err);
+			BaseError::ServiceError
+		};
+		let dir = dir.as_ref();
+		std::fs::create_dir_all(dir).map_err(|err| failed(dir, &err))?;
+		self.0
+			.iter()
+			.map(|(topic, schema)| {
+				let path = dir.join(format!("{topic}.json"));
+				let json = serde_json::to_string_pretty(schema).map_err(|err| failed(&path, &err))?;
+				std::fs::write(&path, json + "\n").map_err(|err| failed(&path, &err))?;
+				Ok(path)
+			})
+			.collect()
+	}
+}
+
+/// What values of fields annotated with `#[redact]` are replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Json representation of `value` whose top-level `fields` are masked with [REDACTED]. It backs `redacted_state` of messages with `#[redact]` fields.
+pub fn redact<T: Serialize + ?Sized>(value: &T, fields: &[&str]) -> String {
+	let mut state = serde_json::to_value(value).expect("Failed to serialize");
+	if let serde_json::Value::Object(object) = &mut state {
+		for field in fields {
+			if let Some(value) = object.get_mut(*field) {
+				*value = serde_json::Value::String(REDACTED.to_string());
+			}
+		}
+	}
+	state.to_string()
+}
+
+#[test]
+fn test_round_trip_in_every_enabled_format() {
+	#[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+	struct OrderPlaced {
+		id: i64,
+		items: Vec<String>,
+	}
+
+	let formats = [
+		SerFormat::Json,
+		#[cfg(feature = "messagepack")]
+		SerFormat::MessagePack,
+		#[cfg(feature = "bincode")]
+		SerFormat::Bincode,
+	];
+	for format in formats {
+		let event = OrderPlaced { id: 1, items: vec!["book".into()] };
+		let payload = format.serialize(&event);
+		assert_eq!(format.deserialize::<OrderPlaced>(&payload).unwrap(), event);
+		assert_eq!(format.as_str().parse::<SerFormat>().unwrap(), format);
+	}
+}
+
+#[test]
+fn test_redact_masks_only_given_fields() {
+	#[derive(Serialize)]
+	struct SignedUp {
+		email: String,
+		password: String,
+	}
+
+	let state = redact(&SignedUp { email: "user@bering.com".into(), password: "secret".into() }, &["password", "absent"]);
+	assert_eq!(state, r#"{"email":"user@bering.com","password":"[REDACTED]"}"#);
+}
@@ -9,7 +9,40 @@
 //! [TCommitHook] as well.
 //!
 //!
+//! ### TSharedTransaction
+//! When a command touches multiple aggregates, repositories of them must enlist in the same transaction.
+//! Unit of work that implements [TSharedTransaction] hands out repositories bound to its [Executor],
+//! so that writes through them are committed or rolled back altogether.
+//!
+//! ```rust,no_run
+//! uow.begin().await?;
+//! let mut accounts: AccountRepository = uow.repository();
+//! let mut ledgers: LedgerRepository = uow.repository();
+//! accounts.update(&mut account).await?;
+//! ledgers.add(&mut ledger).await?;
+//! uow.commit().await?;
+//! ```
+//!
+//! ### TRequestTransaction
+//! When event handlers are to write atomically with the command, the command handler opens [TRequestTransaction] and hands it over to
+//! [ContextManager::share_transaction] instead of committing it. Event handlers of the request then write through [ContextManager::transaction],
+//! and [MessageBus] commits it once every event raised within the request is handled.
+//!
+//! ```rust,no_run
+//! let mut transaction = pool.begin().await?;
+//! insert_order(&mut transaction, &order).await?;
+//! context_manager.share_transaction(transaction);
+//! context_manager.push_event(OrderPlaced { id: order.id }.to_message()).await?;
+//!
+//! // In handler of OrderPlaced
+//! let transaction = context_manager.transaction::<sqlx::Transaction<'static, sqlx::Postgres>>().unwrap();
+//! reserve_stock(transaction, event.id).await?;
+//! ```
+//!
 //! [UOW]: crate::unit_of_work::TUnitOfWork
+//! [ContextManager::share_transaction]: crate::prelude::ContextManager::share_transaction
+//! [ContextManager::transaction]: crate::prelude::ContextManager::transaction
+//! [MessageBus]: crate::prelude::MessageBus
 //! [TCommitHook]: crate::unit_of_work::TCommitHook
 
 //! [Handler]: crate::unit_of_work::Handler
@@ -43,7 +76,9 @@
 //! ```
 //!
 
-use crate::prelude::BaseError;
+use crate::prelude::{BaseError, Executor};
+use async_trait::async_trait;
+use downcast_rs::{impl_downcast, Downcast};
 
 /// Template for Unit of Work
 /// Concrete implementation must implement `_commit` method
@@ -52,12 +87,37 @@ use crate::prelude::BaseError;
 pub trait TUnitOfWork: Send + Sync {
 	fn begin(&mut self) -> impl std::future::Future<Output = Result<(), BaseError>> + Send;
 
+	/// Begin transaction at given isolation level. [IsolationLevel::Default] behaves the same as `begin`.
+	///
+	/// Under [IsolationLevel::Serializable], operations may fail with [BaseError::SerializationFailure]
+	/// when they conflict with concurrent transactions. Callers should roll back and retry the whole transaction in that case.
+	fn begin_with(&mut self, isolation_level: IsolationLevel) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
+		async move {
+			self.begin().await?;
+			if isolation_level != IsolationLevel::Default {
+				self._set_isolation_level(isolation_level).await?;
+			}
+			Ok(())
+		}
+	}
+
+	// Issued right after transaction begins, before the first statement. Concrete implementation supporting isolation level must implement
+	fn _set_isolation_level(&mut self, _isolation_level: IsolationLevel) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
+		async {
+			tracing::error!("Isolation Level Is Not Supported!");
+			Err(BaseError::TransactionError)
+		}
+	}
+
 	// Template method
+	// * Outboxes are saved within the transaction, while internal events are queued only once it is committed so that they don't fire when it fails
+	/// Outboxes staged by `process_external_events` are committed atomically with the aggregates only when they are written through the same transaction,
+	/// as [crate::prelude::Context] does with Postgres. Rolling back then discards both.
 	fn commit(&mut self) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
 		async {
-			self.process_internal_events().await?;
 			self.process_external_events().await?;
 			self._commit().await?;
+			self.process_internal_events().await?;
 			Ok(())
 		}
 	}
@@ -68,6 +128,21 @@ pub trait TUnitOfWork: Send + Sync {
 
 	fn close(&mut self) -> impl std::future::Future<Output = ()> + Send;
 
+	/// Mark a point within active transaction so that work done after it can be undone by `rollback_to` without aborting the whole transaction
+	fn savepoint(&mut self, _name: &str) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
+		async {
+			tracing::error!("Savepoint Is Not Supported!");
+			Err(BaseError::TransactionError)
+		}
+	}
+
+	fn rollback_to(&mut self, _name: &str) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
+		async {
+			tracing::error!("Savepoint Is Not Supported!");
+			Err(BaseError::TransactionError)
+		}
+	}
+
 	// Hook
 	fn process_internal_events(&mut self) -> impl std::future::Future<Output = Result<(), BaseError>> + Send {
 		async { Ok(()) }
@@ -77,3 +152,62 @@ pub trait TUnitOfWork: Send + Sync {
 		async { Ok(()) }
 	}
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+	/// Whatever the connection is configured with
+	#[default]
+	Default,
+	ReadUncommitted,
+	ReadCommitted,
+	RepeatableRead,
+	Serializable,
+}
+
+impl IsolationLevel {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			IsolationLevel::Default => "DEFAULT",
+			IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+			IsolationLevel::ReadCommitted => "READ COMMITTED",
+			IsolationLevel::RepeatableRead => "REPEATABLE READ",
+			IsolationLevel::Serializable => "SERIALIZABLE",
+		}
+	}
+}
+
+/// Unit of work whose transaction is shared with repositories handed out from it
+pub trait TSharedTransaction: TUnitOfWork {
+	type Transaction: Send;
+
+	fn executor(&self) -> &Executor<Self::Transaction>;
+
+	/// Repository bound to transaction of this unit of work
+	fn repository<R: TBindExecutor<Self::Transaction>>(&self) -> R {
+		R::bind(self.executor().clone())
+	}
+}
+
+/// Repository that works on transaction shared through [Executor]
+pub trait TBindExecutor<T> {
+	fn bind(executor: Executor<T>) -> Self;
+}
+
+/// Transaction that command and event handlers of a request share, kept on [ContextManager] by [ContextManager::share_transaction].
+/// [MessageBus] settles it when the request is done with its events, on `execute_and_wait`, `execute_with_report`, `execute_and_forget` and `handle_event`:
+/// - It is committed when the command and every event handler succeeded. Stop sentinels are not failures.
+/// - It is rolled back otherwise. When only event handlers failed, the request fails with [BaseError::TransactionError] as the write of the command is undone.
+///
+/// Commands of `execute_batch` and `execute_stream` are committed each by their own unit of work, so transaction left on their context is just dropped,
+/// except for the batch given `BatchContext::Atomic`, whose commands write through the same transaction, settled once for the whole batch.
+/// Handlers taking it must not run concurrently, so register them without `#[async]`.
+///
+/// [ContextManager]: crate::prelude::ContextManager
+/// [ContextManager::share_transaction]: crate::prelude::ContextManager::share_transaction
+/// [MessageBus]: crate::prelude::MessageBus
+#[async_trait]
+pub trait TRequestTransaction: Send + Sync + Downcast {
+	async fn commit(self: Box<Self>) -> Result<(), BaseError>;
+	async fn rollback(self: Box<Self>) -> Result<(), BaseError>;
+}
+impl_downcast!(TRequestTransaction);
@@ -0,0 +1,77 @@
+//! ### Upcaster
+//! Events published with older schema may still be sitting in outbox or message broker.
+//! [Upcaster] transforms such payload forward to the current version of event before it is deserialized.
+//! ```rust,no_run
+//! let upcaster = Upcaster::default()
+//!     // v1 -> v2 : `name` is split into `first_name` and `last_name`
+//!     .register("UserCreated", 1, |mut payload| {
+//!         let name = payload["name"].take();
+//!         payload["first_name"] = name;
+//!         payload["last_name"] = "".into();
+//!         payload
+//!     });
+//! ```
+use crate::responses::BaseError;
+use std::borrow::Cow;
+
+/// Header under which version of event is carried across service boundaries.
+pub const VERSION_HEADER: &str = "version";
+
+pub type Upcast = fn(serde_json::Value) -> serde_json::Value;
+
+#[derive(Default, Clone)]
+pub struct Upcaster {
+	upcasts: hashbrown::HashMap<(String, u32), Upcast>,
+}
+
+impl Upcaster {
+	/// Register transformation of `topic` payload from `from_version` to `from_version + 1`
+	pub fn register(mut self, topic: &str, from_version: u32, upcast: Upcast) -> Self {
+		self.upcasts.insert((topic.to_string(), from_version), upcast);
+		self
+	}
+
+	/// Apply registered transformations one version at a time, starting from `version`.
+	/// Payload is returned as it is when there is nothing to upcast.
+	pub fn upcast<'a>(&self, topic: &str, mut version: u32, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, BaseError> {
+		if !self.upcasts.contains_key(&(topic.to_string(), version)) {
+			return Ok(Cow::Borrowed(payload));
+		}
+
+		let mut value: serde_json::Value = serde_json::from_slice(payload).map_err(|err| BaseError::DeserializationError(err.to_string()))?;
+		while let Some(upcast) = self.upcasts.get(&(topic.to_string(), version)) {
+			value = upcast(value);
+			version += 1;
+		}
+		Ok(Cow::Owned(serde_json::to_vec(&value).map_err(|err| BaseError::DeserializationError(err.to_string()))?))
+	}
+}
+
+#[test]
+fn test_upcast_v1_payload_to_v3() {
+	#[derive(serde::Deserialize, Debug, PartialEq)]
+	struct UserCreatedV3 {
+		first_name: String,
+		last_name: String,
+		active: bool,
+	}
+
+	let upcaster = Upcaster::default()
+		.register("UserCreated", 1, |mut payload| {
+			let name = payload["name"].take();
+			payload["first_name"] = name;
+			payload["last_name"] = "".into();
+			payload
+		})
+		.register("UserCreated", 2, |mut payload| {
+			payload["active"] = true.into();
+			payload
+		});
+
+	let upcasted = upcaster.upcast("UserCreated", 1, br#"{"name":"migo"}"#).unwrap();
+	let event: UserCreatedV3 = serde_json::from_slice(&upcasted).unwrap();
+	assert_eq!(event, UserCreatedV3 { first_name: "migo".into(), last_name: "".into(), active: true });
+
+	let current = br#"{"first_name":"migo","last_name":"lee","active":false}"#;
+	assert!(matches!(upcaster.upcast("UserCreated", 3, current).unwrap(), Cow::Borrowed(payload) if payload == current));
+}
@@ -0,0 +1,63 @@
+//! ### Backoff
+//! Delays between attempts of an operation that may fail for a while, such as calls to external services.
+//! It is what [RetryPolicy] and [TOutBoxPublisher::backoff] wait by, and can be used the same way elsewhere:
+//! ```rust,no_run
+//! for delay in Backoff::exponential(Duration::from_millis(100), Duration::from_secs(5), 0.2).take(3) {
+//!     match client.call().await {
+//!         Ok(response) => return Ok(response),
+//!         Err(err) => tokio::time::sleep(delay).await,
+//!     }
+//! }
+//! ```
+//!
+//! [RetryPolicy]: crate::prelude::RetryPolicy
+//! [TOutBoxPublisher::backoff]: crate::prelude::TOutBoxPublisher::backoff
+
+use std::time::Duration;
+
+/// Endless iterator of delays growing by `multiplier` from `base` up to `cap`.
+/// Each delay is shortened by a random fraction of up to `jitter` of it, so that clients failing together don't retry together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+	base: Duration,
+	cap: Duration,
+	multiplier: f64,
+	jitter: f64,
+	attempt: u32,
+}
+
+impl Backoff {
+	/// Delays doubling from `base` up to `cap`. `jitter` is clamped to between 0.0 and 1.0.
+	pub fn exponential(base: Duration, cap: Duration, jitter: f64) -> Self {
+		Self { base, cap, multiplier: 2.0, jitter: jitter.clamp(0.0, 1.0), attempt: 0 }
+	}
+
+	/// Same delay of `delay` every time
+	pub fn constant(delay: Duration) -> Self {
+		Self::exponential(delay, Duration::MAX, 0.0).with_multiplier(1.0)
+	}
+
+	pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+		self.multiplier = multiplier;
+		self
+	}
+
+	/// Delay before `attempt`th retry, counting from 0
+	pub(crate) fn delay(&self, attempt: u32) -> Duration {
+		let capped = Duration::try_from_secs_f64(self.base.as_secs_f64() * self.multiplier.powi(attempt as i32)).map_or(self.cap, |delay| delay.min(self.cap));
+		match self.jitter > 0.0 {
+			true => capped.mul_f64(1.0 - self.jitter * fastrand::f64()),
+			false => capped,
+		}
+	}
+}
+
+impl Iterator for Backoff {
+	type Item = Duration;
+
+	fn next(&mut self) -> Option<Duration> {
+		let delay = self.delay(self.attempt);
+		self.attempt = self.attempt.saturating_add(1);
+		Some(delay)
+	}
+}
@@ -4,6 +4,7 @@ use syn::{parse_macro_input, punctuated::Punctuated, Attribute, Data, DataStruct
 
 use crate::{
 	helpers::{derive_helpers::add_derive_macros, generic_helpers::add_sync_trait_bounds},
+	message::redacted_fields,
 	utils::{get_attributes, get_type_name, skip_given_attribute, skip_over_attributes, strip_generic_constraints},
 };
 
@@ -128,7 +129,7 @@ fn into_command_body(derive_input: &DeriveInput) -> (Option<DeriveInput>, proc_m
 	}
 }
 
-pub fn declare_command(ast: &mut DeriveInput) -> TokenStream {
+pub fn declare_command(ast: &mut DeriveInput, validations: Vec<TokenStream>, redacted_fields: Vec<String>, aggregate_id: Option<syn::Ident>) -> TokenStream {
 	let name = ast.ident.clone();
 
 	// add `Send`, `Sync`, `'static` and `std::fmt::Debug` to TypeGenerics if it doesn't have it
@@ -136,11 +137,96 @@ pub fn declare_command(ast: &mut DeriveInput) -> TokenStream {
 
 	let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
+	let validate = match validations.is_empty() {
+		true => quote!(),
+		false => quote!(
+			fn validate(&self) -> Result<(), ruva::BaseError> {
+				let mut errors: Vec<ruva::FieldError> = vec![];
+				#(#validations)*
+				if !errors.is_empty() {
+					return Err(ruva::BaseError::ValidationFailed(errors));
+				}
+				Ok(())
+			}
+		),
+	};
+	let redacted_state = match redacted_fields.is_empty() {
+		true => quote!(),
+		false => quote!(
+			fn redacted_state(&self) -> ::std::string::String {
+				ruva::redact(self, &[#(#redacted_fields),*])
+			}
+		),
+	};
+	let aggregate_id = match aggregate_id {
+		None => quote!(),
+		Some(field) => quote!(
+			fn aggregate_id(&self) -> Option<::std::string::String> {
+				Some(self.#field.to_string())
+			}
+		),
+	};
 	quote!(
-		impl #impl_generics ruva::TCommand for #name #ty_generics #where_clause {}
+		impl #impl_generics ruva::TCommand for #name #ty_generics #where_clause {
+			#validate
+
+			#redacted_state
+
+			#aggregate_id
+		}
 	)
 }
 
+/// Field annotated with `#[aggregate_id]`, which is what the command is routed and logged by.
+fn aggregate_id_field(ast: &DeriveInput) -> Option<syn::Ident> {
+	let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &ast.data else {
+		return None;
+	};
+	fields.named.iter().find(|f| get_attributes(f).into_iter().any(|ident| ident == *"aggregate_id")).and_then(|f| f.ident.clone())
+}
+
+/// Render checks of `#[validate(range(min = .., max = ..))]` and `#[validate(length(min = .., max = ..))]` on fields.
+/// Every violation is collected into `errors`, coded by the name of the rule.
+fn render_validations(ast: &DeriveInput) -> syn::Result<Vec<TokenStream>> {
+	let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &ast.data else {
+		return Ok(vec![]);
+	};
+	let mut validations = vec![];
+	for field in fields.named.iter() {
+		let ident = field.ident.as_ref().unwrap();
+		let field_name = ident.to_string();
+		for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+			attr.parse_nested_meta(|rule| {
+				let code = rule.path.get_ident().map(ToString::to_string).unwrap_or_default();
+				let (value, subject) = if rule.path.is_ident("range") {
+					(quote!(self.#ident), ident.to_string())
+				} else if rule.path.is_ident("length") {
+					(quote!(self.#ident.len()), format!("length of {}", ident))
+				} else {
+					return Err(rule.error("expected `range` or `length`"));
+				};
+				rule.parse_nested_meta(|bound| {
+					let limit: syn::Expr = bound.value()?.parse()?;
+					let (violated, message) = if bound.path.is_ident("min") {
+						(quote!(#value < #limit), format!("{} must be at least {{}}", subject))
+					} else if bound.path.is_ident("max") {
+						(quote!(#value > #limit), format!("{} must be at most {{}}", subject))
+					} else {
+						return Err(bound.error("expected `min` or `max`"));
+					};
+					validations.push(quote!(
+						if #violated {
+							errors.push(ruva::FieldError::new(#field_name, #code, format!(#message, #limit)));
+						}
+					));
+					Ok(())
+				})
+			})?;
+		}
+	}
+	Ok(validations)
+}
+
 fn parse_attributes(attrs: &proc_macro::TokenStream) -> (Vec<String>, Vec<String>) {
 	let mut macros_to_inject_to_body = vec!["Debug".to_string(), "ruva::Deserialize".to_string()];
 	let normalized_body_macro = macros_to_inject_to_body.iter().map(|x| x.split("::").last().unwrap().to_string()).collect::<Vec<String>>();
@@ -221,6 +307,16 @@ pub fn render_into_command(input: proc_macro::TokenStream, attrs: proc_macro::To
 
 	let mut ast = parse_macro_input!(input as DeriveInput);
 
+	let validations = match render_validations(&ast) {
+		Ok(validations) => validations,
+		Err(err) => return err.into_compile_error().into(),
+	};
+	skip_given_attribute(&mut ast, "validate");
+	let redacted_fields = redacted_fields(&ast);
+	skip_given_attribute(&mut ast, "redact");
+	let aggregate_id = aggregate_id_field(&ast);
+	skip_given_attribute(&mut ast, "aggregate_id");
+
 	let mut quotes = vec![];
 
 	let (body_ast, into_statement) = into_command_body(&ast);
@@ -241,7 +337,7 @@ pub fn render_into_command(input: proc_macro::TokenStream, attrs: proc_macro::To
 	skip_given_attribute(&mut ast, "required_input");
 	add_sync_trait_bounds(&mut ast.generics, &COMMAND_CONSTRAINT);
 
-	let t_command = declare_command(&mut ast);
+	let t_command = declare_command(&mut ast, validations, redacted_fields, aggregate_id);
 	quotes.push(quote!(#t_command));
 
 	if macros_to_inject_to_original.contains(&"ruva::TEvent".to_string()) {
@@ -23,6 +23,8 @@ pub(crate) fn render_aggregate(input: TokenStream, attrs: TokenStream) -> TokenS
 
 	let crates = locate_crate_on_derive_macro(&ast);
 
+	let soft_delete = render_soft_delete(&mut ast.data, &crates);
+
 	let adapter_quote = create_struct_adapter_quote(&ast, true);
 
 	let setters = set_entity_fields(&mut ast.data, true);
@@ -42,6 +44,7 @@ pub(crate) fn render_aggregate(input: TokenStream, attrs: TokenStream) -> TokenS
 				tracing::info!("event raised! {:?}", event.metadata());
 				self.events.push_back(event)
 			}
+			#soft_delete
 		}
 
 		impl #impl_generics #name #ty_generics #where_clause{
@@ -54,6 +57,26 @@ pub(crate) fn render_aggregate(input: TokenStream, attrs: TokenStream) -> TokenS
 	.into()
 }
 
+/// Field marked with `#[deleted_at]` makes aggregate soft-deleted by repository
+fn render_soft_delete(input_data: &mut syn::Data, crates: &Ident) -> proc_macro2::TokenStream {
+	let syn::Data::Struct(DataStruct { fields: syn::Fields::Named(ref mut fields), .. }) = input_data else {
+		return quote!();
+	};
+	let Some(field) = fields.named.iter_mut().find_map(|f| skip_over_attributes(f, "deleted_at").then(|| f.ident.clone().unwrap())) else {
+		return quote!();
+	};
+	quote!(
+		const SOFT_DELETE: bool = true;
+		fn deleted_at(&self) -> Option<#crates::chrono::DateTime<#crates::chrono::Utc>> {
+			self.#field
+		}
+		fn set_deleted_at(&mut self, deleted_at: Option<#crates::chrono::DateTime<#crates::chrono::Utc>>) {
+			self.#field = deleted_at;
+			self.is_updated = true;
+		}
+	)
+}
+
 pub(crate) fn render_entity_token(input: TokenStream, attrs: TokenStream) -> TokenStream {
 	let mut macros_to_inject = vec!["ruva::Serialize".to_string(), "Debug".to_string(), "Default".to_string()];
 	sort_macros_to_inject(&mut macros_to_inject, attrs);
@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 
-use syn::{punctuated::Punctuated, token::Comma, FnArg, Ident, ImplItemFn, ItemFn, Pat, PatIdent, PatType, ReturnType, Signature};
+use syn::{punctuated::Punctuated, token::Comma, FnArg, Ident, ImplItemFn, ItemFn, LitStr, Pat, PatIdent, PatType, ReturnType, Signature, TypePath};
 
 #[allow(unused)]
 pub fn parse_handler(ast: ItemFn) -> TokenStream {
@@ -143,3 +143,90 @@ pub fn parse_event_handler(ast: ImplItemFn) -> TokenStream {
 	)
 	.into()
 }
+
+/// `#[event_handler(SomeEvent)]` or `#[event_handler(SomeEvent, order = -1)]`
+pub struct EventHandlerArgs {
+	event: TypePath,
+	order: Option<syn::Expr>,
+	filter: Option<syn::Expr>,
+	delivery: Option<syn::Expr>,
+}
+
+impl syn::parse::Parse for EventHandlerArgs {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let event = input.parse()?;
+		let (mut order, mut filter, mut delivery) = (None, None, None);
+		while input.parse::<Option<Comma>>()?.is_some() && !input.is_empty() {
+			let key: Ident = input.parse()?;
+			let slot = match key.to_string().as_str() {
+				"order" => &mut order,
+				"filter" => &mut filter,
+				"delivery" => &mut delivery,
+				_ => return Err(syn::Error::new(key.span(), "expected `order`, `filter` or `delivery`")),
+			};
+			if slot.is_some() {
+				return Err(syn::Error::new(key.span(), format!("`{key}` is given more than once")));
+			}
+			input.parse::<syn::Token![=]>()?;
+			*slot = Some(input.parse()?);
+		}
+		Ok(Self { event, order, filter, delivery })
+	}
+}
+
+pub fn render_event_handler(EventHandlerArgs { event, order, filter, delivery }: EventHandlerArgs, ast: ItemFn) -> TokenStream {
+	if ast.sig.asyncness.is_none() {
+		panic!("#[event_handler] can be attached only to async fn!");
+	}
+	if ast.sig.inputs.len() != 2 {
+		panic!("#[event_handler] fn must take event and either ::ruva::AtomicContextManager or ::ruva::HandlerContext!");
+	}
+
+	let ident = &ast.sig.ident;
+
+	// ! topic must correspond to the one that `metadata()` returns, so only the last segment of path is taken
+	let topic = event.path.segments.last().expect("Event type must be given! Example: #[event_handler(SomeEvent)]").ident.to_string();
+	let order = order.map_or(quote!(0), |order| quote!(#order));
+	let name = quote!(::std::concat!(::std::module_path!(), "::", ::std::stringify!(#ident)));
+	let delivery = delivery.map_or(quote!(::ruva::Delivery::AtMostOnce), |delivery| quote!(#delivery));
+	let filter = filter.map_or(quote!(|_: &#event| true), |filter| quote!(#filter));
+
+	quote!(
+		#ast
+
+		::ruva::inventory::submit! {
+			::ruva::EventHandlerRegistration {
+				topic: #topic,
+				event: ::std::any::TypeId::of::<#event>,
+				order: #order,
+				name: #name,
+				delivery: #delivery,
+				handler: || ::ruva::EventHandlerRegistration::erase_with::<#event, _, _, _, _, _>(#ident, #name, #filter, #delivery),
+			}
+		}
+	)
+	.into()
+}
+
+pub fn render_pattern_event_handler(pattern: LitStr, ast: ItemFn) -> TokenStream {
+	if ast.sig.asyncness.is_none() {
+		panic!("#[event_handler] can be attached only to async fn!");
+	}
+	if ast.sig.inputs.len() != 2 {
+		panic!("#[event_handler] fn must take ::std::sync::Arc<dyn ::ruva::TEvent> and either ::ruva::AtomicContextManager or ::ruva::HandlerContext!");
+	}
+
+	let ident = &ast.sig.ident;
+
+	quote!(
+		#ast
+
+		::ruva::inventory::submit! {
+			::ruva::PatternEventHandlerRegistration {
+				pattern: #pattern,
+				handler: || ::ruva::PatternEventHandlerRegistration::erase(#ident),
+			}
+		}
+	)
+	.into()
+}
@@ -2,13 +2,8 @@ use syn::Attribute;
 
 pub fn add_derive_macros(input: &mut syn::DeriveInput, macros_to_add: &[String]) {
 	let mut derive_paths = vec![];
-	// extract meta from input
-	let meta_vec = input.attrs.iter().map(|attr| &attr.meta).collect::<Vec<_>>();
-
-	let mut meta_tokens = vec![];
-	if let Some(meta) = meta_vec.first() {
-		meta_tokens = extract_meta_tokens(meta);
-	}
+	// extract meta from every derive attribute of input
+	let meta_tokens = input.attrs.iter().filter(|attr| attr.path().is_ident("derive")).flat_map(|attr| extract_meta_tokens(&attr.meta)).collect::<Vec<_>>();
 
 	for macro_to_add in macros_to_add {
 		// if it doesn't have in existing derive paths for a given input, push it
@@ -20,7 +15,8 @@ pub fn add_derive_macros(input: &mut syn::DeriveInput, macros_to_add: &[String])
 
 	let derive_attr: Attribute = syn::parse_quote!(#[derive(#(#derive_paths),*)]);
 
-	input.attrs.push(derive_attr);
+	// * Derive goes first so that helper attributes of the derived macros, such as `#[internally_notifiable]`, come after it
+	input.attrs.insert(0, derive_attr);
 }
 
 fn extract_meta_tokens(meta: &syn::Meta) -> Vec<String> {
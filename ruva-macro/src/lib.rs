@@ -15,7 +15,7 @@ mod message_handler;
 mod result;
 mod utils;
 
-#[proc_macro_derive(TEvent, attributes(internally_notifiable, externally_notifiable, identifier))]
+#[proc_macro_derive(TEvent, attributes(internally_notifiable, externally_notifiable, identifier, priority, version, headers, ser_format, message_id, partition_key, redact, topic, aggregate_id))]
 pub fn derive_tevent(attr: TokenStream) -> TokenStream {
 	let mut ast: DeriveInput = syn::parse(attr.clone()).unwrap();
 	let externally_notifiable_event_req = extract_externally_notifiable_event_req(&mut ast);
@@ -24,6 +24,34 @@ pub fn derive_tevent(attr: TokenStream) -> TokenStream {
 	render_message_token(&ast, visibilities, externally_notifiable_event_req).into()
 }
 
+/// Define event with derives that [TEvent](derive@TEvent) takes, which are `Debug`, `Clone`, `Serialize` and `Deserialize`, on top of [TEvent](derive@TEvent) itself.
+/// Derives already given are not added again. Attributes of [TEvent](derive@TEvent) are put on the event as they are.
+/// ## Example
+/// ```rust,no_run
+/// #[event]
+/// #[internally_notifiable]
+/// pub struct OrderPlaced {
+///     pub order_id: i64,
+/// }
+/// ```
+///
+/// Additional derives can be given as arguments
+/// ```rust,no_run
+/// #[event(PartialEq)]
+/// #[externally_notifiable(Order)]
+/// pub struct OrderShipped {
+///     #[identifier]
+///     pub order_id: i64,
+/// }
+/// ```
+///
+/// Deriving [TEvent](derive@TEvent) directly is still possible for event that needs to choose its derives, such as one that can't be deserialized.
+#[proc_macro_attribute]
+pub fn event(attrs: TokenStream, input: TokenStream) -> TokenStream {
+	let ast = parse_macro_input!(input as DeriveInput);
+	message::render_event_attribute(ast, attrs).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
 /// Define Aggregate root
 /// ## Example
 /// ```rust,no_run
@@ -77,6 +105,18 @@ pub fn derive_tevent(attr: TokenStream) -> TokenStream {
 /// assert_eq!(serialized, "{\"some_other_field\":0}");
 /// ```
 ///
+/// ## Soft delete
+/// Put `#[deleted_at]` on `Option<chrono::DateTime<chrono::Utc>>` field to have `TRepository` mark the aggregate deleted instead of removing it.
+/// ```rust,no_run
+/// #[aggregate]
+/// pub struct AggregateStruct {
+///     #[adapter_ignore]
+///     id: i32,
+///     #[deleted_at]
+///     deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+/// }
+/// ```
+///
 /// ## Automatic derive macro
 /// `#[derive(Default, Debug, Serialize, Deserialize)]` will be automatically added to the struct.
 /// ```rust,no_run
@@ -165,8 +205,20 @@ pub fn aggregate(attrs: TokenStream, input: TokenStream) -> TokenStream {
 ///     Response1
 ///     Response2
 /// }
+///
+/// #[derive(Debug, Serialize, ApplicationResponse)]
+/// #[created]
+/// struct AccountCreated {
+///     id: i64,
+/// }
 /// ```
-#[proc_macro_derive(ApplicationResponse)]
+///
+/// ## Attributes
+/// - `#[ok]` - `ApplicationResponse::status_code` is 200, which is the default.
+/// - `#[created]` - `ApplicationResponse::status_code` is 201.
+///
+/// Either can be given on the type or on a variant of enum, the latter taking precedence.
+#[proc_macro_derive(ApplicationResponse, attributes(ok, created, crates))]
 pub fn response_derive(attr: TokenStream) -> TokenStream {
 	let ast: DeriveInput = syn::parse(attr.clone()).unwrap();
 	result::render_response_token(&ast)
@@ -215,7 +267,10 @@ pub fn event_hook(_: TokenStream, input: TokenStream) -> TokenStream {
 /// - `#[crates(...)]` - Specify the name of root of ruva crate. (Default is `ruva`)
 /// - `#[stop_sentinel]` - Specify the error matching for `BaseError::StopSentinel`.
 /// - `#[stop_sentinel_with_event]` - Specify the error matching for `BaseError::StopSentinelWithEvent`.
-/// - `#[database_error]` - Specify the error matching for `BaseError::DatabaseError`.
+/// - `#[database_error]` - Specify the error matching for `BaseError::DatabaseError`. Its field is either `DatabaseFailure` or `String`, the latter keeping only the message.
+/// - `#[validation_failed]` - Optionally specify the error matching for `BaseError::ValidationFailed`. Without it, the error is kept in `BaseError` variant.
+/// - `#[code("...")]` - Specify stable code `ApplicationError::code` returns for the variant. Without it, the code is the name of the variant in snake case.
+///   `BaseError` variant returns code of the error it holds.
 ///
 /// ## Example
 /// ```rust,no_run
@@ -230,13 +285,80 @@ pub fn event_hook(_: TokenStream, input: TokenStream) -> TokenStream {
 ///   DatabaseError(Box<AnyError>),
 /// }
 /// ```
-#[proc_macro_derive(ApplicationError, attributes(stop_sentinel, stop_sentinel_with_event, database_error, crates))]
+#[proc_macro_derive(ApplicationError, attributes(stop_sentinel, stop_sentinel_with_event, database_error, validation_failed, code, crates))]
 pub fn error_derive(attr: TokenStream) -> TokenStream {
 	let ast: DeriveInput = syn::parse(attr).unwrap();
 
 	result::render_error_token(&ast)
 }
 
+/// Register async fn as event handler without listing it on `init_event_handler!`.
+/// Handlers for the same event may live in different modules; they are all collected under the topic of the given event.
+/// ## Example
+/// ```rust,no_run
+/// #[event_handler(SomethingHappened)]
+/// async fn send_notification(event: SomethingHappened, context: AtomicContextManager) -> Result<(), ServiceError> {
+///     Ok(())
+/// }
+///
+/// // Collects discovered handlers into the event handler map of `MessageBus`
+/// init_event_handler!(ServiceError);
+/// ```
+///
+/// `HandlerContext` can be taken in place of `AtomicContextManager` to raise events and commands without boxing them.
+/// ```rust,no_run
+/// #[event_handler(SomethingHappened)]
+/// async fn follow_up(event: SomethingHappened, context: HandlerContext) -> Result<(), ServiceError> {
+///     context.emit(FollowedUp { id: event.id }).await?;
+///     Ok(())
+/// }
+/// ```
+///
+/// `order` decides when the handler runs among the handlers of the same event, lower first. It is 0 by default.
+/// ```rust,no_run
+/// #[event_handler(SomethingHappened, order = -1)]
+/// async fn update_read_model(event: SomethingHappened, context: AtomicContextManager) -> Result<(), ServiceError> {
+///     Ok(())
+/// }
+/// ```
+///
+/// `filter` takes `Fn(&Event) -> bool`, for which the handler runs only when it returns `true`, so that the handler doesn't start with early return.
+/// Skipped handler is logged at trace level and taken as succeeded.
+/// ```rust,no_run
+/// #[event_handler(OrderPlaced, filter = |event| event.amount >= 1_000)]
+/// async fn review_large_order(event: OrderPlaced, context: AtomicContextManager) -> Result<(), ServiceError> {
+///     Ok(())
+/// }
+/// ```
+///
+/// `delivery` decides what happens to the event when the handler fails on it. It is `Delivery::AtMostOnce` by default.
+/// With `Delivery::AtLeastOnce`, the event is kept in the inbox until the handler succeeds on it, to be redelivered after failure.
+/// ```rust,no_run
+/// #[event_handler(OrderPlaced, delivery = Delivery::AtLeastOnce)]
+/// async fn decrement_inventory(event: OrderPlaced, context: AtomicContextManager) -> Result<(), ServiceError> {
+///     Ok(())
+/// }
+/// ```
+///
+/// Given pattern in which `*` matches any sequence of characters, the handler subscribes to every matching topic.
+/// It takes the event as `Arc<dyn TEvent>` and runs after the handlers of the exact topic.
+/// ```rust,no_run
+/// #[event_handler("Order*")]
+/// async fn audit(event: Arc<dyn TEvent>, context: AtomicContextManager) -> Result<(), ServiceError> {
+///     Ok(())
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn event_handler(attrs: TokenStream, input: TokenStream) -> TokenStream {
+	if let Ok(pattern) = syn::parse::<syn::LitStr>(attrs.clone()) {
+		let ast = parse_macro_input!(input as ItemFn);
+		return handler::render_pattern_event_handler(pattern, ast);
+	}
+	let args = parse_macro_input!(attrs as handler::EventHandlerArgs);
+	let ast = parse_macro_input!(input as ItemFn);
+	handler::render_event_handler(args, ast)
+}
+
 #[proc_macro_attribute]
 pub fn entity(attrs: TokenStream, input: TokenStream) -> TokenStream {
 	domain::render_entity_token(input, attrs)
@@ -259,6 +381,37 @@ pub fn entity(attrs: TokenStream, input: TokenStream) -> TokenStream {
 /// #[into_command]
 /// pub struct W{}
 /// ```
+///
+/// Fields can be validated with `#[validate(range(min = .., max = ..))]` or `#[validate(length(min = .., max = ..))]`.
+/// Checks are run by `TCommand::validate` before the command is dispatched, failing with `BaseError::ValidationFailed` listing every invalid field.
+/// ```rust,no_run
+/// #[into_command]
+/// pub struct SignUp{
+///     #[validate(length(min = 2, max = 10))]
+///     name: String,
+///     #[validate(range(min = 14))]
+///     age: i32,
+/// }
+/// ```
+///
+/// Values of fields annotated with `#[redact]` are masked in what `TCommand::redacted_state` gives to audit sink.
+/// ```rust,no_run
+/// #[into_command]
+/// pub struct ChangePassword{
+///     user_id: i64,
+///     #[redact]
+///     password: String,
+/// }
+/// ```
+///
+/// Field annotated with `#[aggregate_id]` is what `TCommand::aggregate_id` returns, recorded in the span of the command.
+/// ```rust,no_run
+/// #[into_command]
+/// pub struct ShipOrder{
+///     #[aggregate_id]
+///     order_id: i64,
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn into_command(attrs: TokenStream, input: TokenStream) -> TokenStream {
 	command::render_into_command(input, attrs)
@@ -1,29 +1,99 @@
 use proc_macro2::TokenStream;
-use syn::{parse_quote, Data, DataStruct, DeriveInput, Fields, FieldsNamed, FnArg, ItemFn, Meta, MetaList, Pat, PatIdent, PatType, Path, Type};
+use quote::ToTokens;
+use syn::{parse_quote, Data, DataStruct, DeriveInput, Expr, ExprLit, Fields, FieldsNamed, FnArg, ItemFn, Lit, Meta, MetaList, MetaNameValue, Pat, PatIdent, PatType, Path, Type};
 
-use crate::utils::{get_attributes, get_trait_checking_stmts, locate_crate_on_derive_macro};
+use crate::{
+	helpers::derive_helpers::add_derive_macros,
+	utils::{get_attributes, get_trait_checking_stmts, locate_crate_on_derive_macro, sort_macros_to_inject},
+};
 
 pub(crate) fn render_message_token(ast: &DeriveInput, visibilities: Vec<TokenStream>, externally_notifiable_event_req: Option<(TokenStream, TokenStream)>) -> TokenStream {
 	let name = &ast.ident;
 	let crates = locate_crate_on_derive_macro(ast);
+	let topic = match render_event_topic(ast) {
+		Ok(topic) => topic,
+		Err(err) => return err.into_compile_error(),
+	};
+	let aggregate_id = match render_event_aggregate_id(ast) {
+		Ok(aggregate_id) => aggregate_id,
+		Err(err) => return err.into_compile_error(),
+	};
 
-	let (metadata_generator, impl_assertion) = externally_notifiable_event_req.unwrap_or_else(|| (TokenStream::new(), TokenStream::new()));
+	let (mut metadata_generator, impl_assertion) = externally_notifiable_event_req.unwrap_or_else(|| (TokenStream::new(), TokenStream::new()));
+	// * Default metadata takes topic from name of the type, which overridden topic must replace
+	if metadata_generator.is_empty() && (ast.attrs.iter().any(|attr| attr.path().is_ident("topic")) || aggregate_id.is_some()) {
+		let aggregate_id = aggregate_id.unwrap_or_else(|| quote!(::std::default::Default::default()));
+		metadata_generator = quote!(
+			fn metadata(&self) -> #crates::EventMetadata {
+				#crates::EventMetadata {
+					aggregate_id: #aggregate_id,
+					aggregate_name: ::std::default::Default::default(),
+					topic: Self::TOPIC.into(),
+					version: self.version(),
+					headers: self.headers(),
+				}
+			}
+		);
+	}
+	let priority = render_event_priority(ast);
+	let version = render_event_version(ast);
+	let ser_format = render_event_ser_format(ast);
+	let headers = render_event_headers(ast);
+	let message_id = render_event_message_id(ast);
+	let partition_key = render_event_partition_key(ast);
+	let redacted_state = render_event_redacted_state(ast);
+	let prerequisites = render_event_prerequisites(ast);
 
 	quote! {
+		#prerequisites
+
 		impl #crates::TEvent for #name {
 
 			#metadata_generator
 
 			fn state(&self) -> ::std::string::String {
-				serde_json::to_string(&self).expect("Failed to serialize")
+				#crates::TEvent::try_state(self).expect("Failed to serialize")
 			}
 
+			fn try_state(&self) -> ::std::result::Result<::std::string::String, #crates::BaseError> {
+				serde_json::to_string(&self).map_err(|err| #crates::BaseError::SerializationError(err.to_string()))
+			}
+
+			fn try_serialize(&self, format: #crates::SerFormat) -> ::std::result::Result<::std::vec::Vec<u8>, #crates::BaseError> {
+				format.try_serialize(self)
+			}
+
+			#ser_format
+
 			#(#visibilities)*
+
+			#priority
+
+			#version
+
+			#headers
+
+			#message_id
+
+			#partition_key
+
+			#redacted_state
+
+			fn topic() -> &'static str {
+				Self::TOPIC
+			}
 		}
 		impl #name{
+			pub const TOPIC: &'static str = #topic;
+
 			pub(crate) fn to_message(self)->  ::std::sync::Arc<dyn #crates::TEvent> {
 				::std::sync::Arc::new(self)
 			}
+
+			/// Stop the rest of the handlers and queue the event instead, as in `return Err(event.stop())`
+			pub(crate) fn stop<E: ::std::convert::From<#crates::BaseError>>(self) -> E {
+				#crates::BaseError::StopSentinelWithEvent(self.to_message()).into()
+			}
 		}
 		#impl_assertion
 	}
@@ -62,7 +132,8 @@ pub(crate) fn render_event_visibility(ast: &DeriveInput) -> Vec<TokenStream> {
 					vec![quote!()]
 				}
 			}
-			_ => panic!("Notifiability was not specified!"),
+			// * Name-value attributes such as `#[aggregate_id = "none"]` say nothing of notifiability
+			Meta::NameValue(_) => vec![],
 		})
 		.collect::<Vec<_>>();
 	if propagatability.is_empty() {
@@ -71,6 +142,187 @@ pub(crate) fn render_event_visibility(ast: &DeriveInput) -> Vec<TokenStream> {
 	propagatability
 }
 
+/// Assert that the event implements traits its [TEvent] impl and handlers rely on, naming the missing one on the type
+/// rather than leaving it to errors deep in generated code.
+///
+/// [TEvent]: https://docs.rs/ruva-core/latest/ruva_core/message/trait.TEvent.html
+fn render_event_prerequisites(ast: &DeriveInput) -> TokenStream {
+	let name = &ast.ident;
+	let crates = locate_crate_on_derive_macro(ast);
+	quote_spanned! {name.span()=>
+		const _: () = {
+			#[diagnostic::on_unimplemented(message = "event `{Self}` must implement `Clone`", label = "derive `Clone` on the event, or declare it with `#[event]`")]
+			trait EventIsClone {}
+			impl<T: ::std::clone::Clone> EventIsClone for T {}
+
+			#[diagnostic::on_unimplemented(message = "event `{Self}` must implement `Serialize`", label = "derive `Serialize` on the event, or declare it with `#[event]`")]
+			trait EventIsSerialize {}
+			impl<T: #crates::Serialize> EventIsSerialize for T {}
+
+			fn assert_prerequisites<T: EventIsClone + EventIsSerialize>() {}
+			let _ = assert_prerequisites::<#name>;
+		};
+	}
+}
+
+/// Expand `#[event]` into derives that [TEvent] takes, and the [TEvent] derive itself.
+/// Derives given as arguments, as in `#[event(PartialEq)]`, are added on top of them.
+///
+/// [TEvent]: https://docs.rs/ruva-core/latest/ruva_core/message/trait.TEvent.html
+pub(crate) fn render_event_attribute(mut ast: DeriveInput, attrs: proc_macro::TokenStream) -> Result<TokenStream, syn::Error> {
+	if !ast.attrs.iter().any(|attr| attr.path().is_ident("internally_notifiable") || attr.path().is_ident("externally_notifiable")) {
+		return Err(syn::Error::new_spanned(&ast.ident, "Event is missing `#[internally_notifiable]` or `#[externally_notifiable(SomeAggregate)]`!"));
+	}
+	let mut macros_to_inject = vec!["Debug".to_string(), "Clone".to_string(), "ruva::Serialize".to_string(), "ruva::Deserialize".to_string(), "ruva::TEvent".to_string()];
+	sort_macros_to_inject(&mut macros_to_inject, attrs);
+	add_derive_macros(&mut ast, &macros_to_inject);
+	Ok(ast.into_token_stream())
+}
+
+/// Topic given with `#[topic("billing.invoice.created")]`, or name of the type when it is not given
+pub(crate) fn render_event_topic(ast: &DeriveInput) -> Result<TokenStream, syn::Error> {
+	let name = &ast.ident;
+	let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("topic")) else {
+		return Ok(quote!(stringify!(#name)));
+	};
+	let topic: syn::LitStr = attr.parse_args().map_err(|err| syn::Error::new(err.span(), "Topic must be given as string literal!\rExample: #[topic(\"billing.invoice.created\")]"))?;
+	if topic.value().trim().is_empty() {
+		return Err(syn::Error::new_spanned(topic, "Topic must not be empty!"));
+	}
+	Ok(quote!(#topic))
+}
+
+/// Aggregate id given on the type rather than by `#[identifier]` field, either opted out with `#[aggregate_id = "none"]`
+/// or computed with `#[aggregate_id(expr = "...")]` from anything that implements `Display`
+pub(crate) fn render_event_aggregate_id(ast: &DeriveInput) -> Result<Option<TokenStream>, syn::Error> {
+	let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("aggregate_id")) else {
+		return Ok(None);
+	};
+	const USAGE: &str = "Aggregate id must be either opted out or computed!\rExample: #[aggregate_id = \"none\"] or #[aggregate_id(expr = \"self.order_id\")]";
+	match &attr.meta {
+		Meta::NameValue(MetaNameValue { value: Expr::Lit(ExprLit { lit: Lit::Str(value), .. }), .. }) if value.value() == "none" => Ok(Some(quote!(::std::string::String::new()))),
+		Meta::List(_) => {
+			let mut expr: Option<Expr> = None;
+			attr.parse_nested_meta(|meta| {
+				if !meta.path.is_ident("expr") {
+					return Err(meta.error(USAGE));
+				}
+				expr = Some(meta.value()?.parse::<syn::LitStr>()?.parse()?);
+				Ok(())
+			})?;
+			let expr = expr.ok_or_else(|| syn::Error::new_spanned(attr, USAGE))?;
+			Ok(Some(quote!(::std::string::ToString::to_string(&(#expr)))))
+		}
+		_ => Err(syn::Error::new_spanned(attr, USAGE)),
+	}
+}
+
+pub(crate) fn render_event_priority(ast: &DeriveInput) -> TokenStream {
+	let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("priority")) else {
+		return TokenStream::new();
+	};
+	let priority: syn::Expr = attr.parse_args().expect("Priority must be given as i8 value!\rExample: #[priority(10)]");
+	quote!(
+		fn priority(&self) -> i8 {
+			#priority
+		}
+	)
+}
+
+pub(crate) fn render_event_version(ast: &DeriveInput) -> TokenStream {
+	let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("version")) else {
+		return TokenStream::new();
+	};
+	let version: syn::LitInt = attr.parse_args().expect("Version must be given as u32 value!\rExample: #[version(2)]");
+	quote!(
+		fn version(&self) -> u32 {
+			#version
+		}
+	)
+}
+
+pub(crate) fn render_event_ser_format(ast: &DeriveInput) -> TokenStream {
+	let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("ser_format")) else {
+		return TokenStream::new();
+	};
+	let crates = locate_crate_on_derive_macro(ast);
+	let format: syn::Ident = attr.parse_args().expect("Serialization format must be given as variant of SerFormat!\rExample: #[ser_format(MessagePack)]");
+	quote!(
+		fn ser_format(&self) -> #crates::SerFormat {
+			#crates::SerFormat::#format
+		}
+	)
+}
+
+pub(crate) fn render_event_headers(ast: &DeriveInput) -> TokenStream {
+	let Data::Struct(DataStruct { fields: Fields::Named(FieldsNamed { named, .. }), .. }) = &ast.data else {
+		return TokenStream::new();
+	};
+	let Some(field) = named.iter().find(|f| get_attributes(f).into_iter().any(|ident| ident == *"headers")) else {
+		return TokenStream::new();
+	};
+	let ident = field.ident.as_ref().unwrap();
+	quote!(
+		fn headers(&self) -> ::std::collections::HashMap<::std::string::String, ::std::string::String> {
+			self.#ident.clone()
+		}
+		fn headers_mut(&mut self) -> ::std::option::Option<&mut ::std::collections::HashMap<::std::string::String, ::std::string::String>> {
+			::std::option::Option::Some(&mut self.#ident)
+		}
+	)
+}
+
+pub(crate) fn render_event_message_id(ast: &DeriveInput) -> TokenStream {
+	let Data::Struct(DataStruct { fields: Fields::Named(FieldsNamed { named, .. }), .. }) = &ast.data else {
+		return TokenStream::new();
+	};
+	let Some(field) = named.iter().find(|f| get_attributes(f).into_iter().any(|ident| ident == *"message_id")) else {
+		return TokenStream::new();
+	};
+	let ident = field.ident.as_ref().unwrap();
+	quote!(
+		fn message_id(&self) -> ::std::option::Option<::std::string::String> {
+			::std::option::Option::Some(self.#ident.to_string())
+		}
+	)
+}
+
+pub(crate) fn render_event_partition_key(ast: &DeriveInput) -> TokenStream {
+	let Data::Struct(DataStruct { fields: Fields::Named(FieldsNamed { named, .. }), .. }) = &ast.data else {
+		return TokenStream::new();
+	};
+	let Some(field) = named.iter().find(|f| get_attributes(f).into_iter().any(|ident| ident == *"partition_key")) else {
+		return TokenStream::new();
+	};
+	let ident = field.ident.as_ref().unwrap();
+	quote!(
+		fn partition_key(&self) -> ::std::option::Option<::std::string::String> {
+			::std::option::Option::Some(self.#ident.to_string())
+		}
+	)
+}
+
+/// Names of fields annotated with `#[redact]`
+pub(crate) fn redacted_fields(ast: &DeriveInput) -> Vec<String> {
+	let Data::Struct(DataStruct { fields: Fields::Named(FieldsNamed { named, .. }), .. }) = &ast.data else {
+		return vec![];
+	};
+	named.iter().filter(|f| get_attributes(f).into_iter().any(|ident| ident == *"redact")).map(|f| f.ident.as_ref().unwrap().to_string()).collect()
+}
+
+pub(crate) fn render_event_redacted_state(ast: &DeriveInput) -> TokenStream {
+	let fields = redacted_fields(ast);
+	if fields.is_empty() {
+		return TokenStream::new();
+	}
+	let crates = locate_crate_on_derive_macro(ast);
+	quote!(
+		fn redacted_state(&self) -> ::std::string::String {
+			#crates::redact(self, &[#(#fields),*])
+		}
+	)
+}
+
 // first return token is for identifier, second return token is for aggregate assertion
 pub(crate) fn extract_externally_notifiable_event_req(ast: &mut DeriveInput) -> Option<(TokenStream, TokenStream)> {
 	let mut token: Option<(TokenStream, TokenStream)> = None;
@@ -78,7 +330,7 @@ pub(crate) fn extract_externally_notifiable_event_req(ast: &mut DeriveInput) ->
 		if let Meta::List(MetaList { path, tokens, .. }) = &mut attr.meta {
 			let ident = path.get_ident();
 			if ident.unwrap() != "externally_notifiable" {
-				return None;
+				continue;
 			}
 
 			// * Asserting that the given type is TAggregate
@@ -110,24 +362,33 @@ pub(crate) fn extract_externally_notifiable_event_req(ast: &mut DeriveInput) ->
 }
 
 pub(crate) fn generate_event_metadata(ast: &DeriveInput, aggregate_metadata: String) -> TokenStream {
-	let name = &ast.ident;
 	let crates = locate_crate_on_derive_macro(ast);
 
 	match &ast.data {
 		Data::Struct(DataStruct { fields: Fields::Named(FieldsNamed { named, .. }), .. }) => {
 			let identifier = named.iter().filter(|f| get_attributes(f).into_iter().any(|ident| ident == *"identifier")).collect::<Vec<_>>();
-			if identifier.len() != 1 {
-				panic!("One identifier Must Be Given To TEvent!")
-			}
-
-			let ident = identifier.first().unwrap().ident.clone().unwrap().clone();
+			let aggregate_id = match render_event_aggregate_id(ast) {
+				Ok(Some(_)) if !identifier.is_empty() => return syn::Error::new_spanned(&identifier[0].ident, "Identifier must not be given along with #[aggregate_id]!").into_compile_error(),
+				Ok(Some(aggregate_id)) => aggregate_id,
+				Ok(None) => {
+					if identifier.len() != 1 {
+						panic!("One identifier Must Be Given To TEvent!\rExample: #[identifier] field, or #[aggregate_id = \"none\"] on event that belongs to no aggregate")
+					}
+					let ident = identifier.first().unwrap().ident.clone().unwrap().clone();
+					quote!(self.#ident.to_string())
+				}
+				// * Reported by render_message_token
+				Err(_) => return TokenStream::new(),
+			};
 
 			quote!(
 				fn metadata(&self) -> #crates::EventMetadata {
 					#crates::EventMetadata{
-					aggregate_id: self.#ident.to_string(),
+					aggregate_id: #aggregate_id,
 					aggregate_name: #aggregate_metadata.into(),
-					topic: stringify!(#name).into()
+					topic: Self::TOPIC.into(),
+					version: self.version(),
+					headers: self.headers(),
 				}
 			}
 			)
@@ -4,13 +4,53 @@ use syn::DeriveInput;
 use crate::utils::{find_enum_variant, locate_crate_on_derive_macro};
 
 pub(crate) fn render_response_token(ast: &DeriveInput) -> TokenStream {
-	let syn::Data::Enum(_data) = &ast.data else { panic!("Only Enum type is supported by #[derive(ApplicationError)].") };
 	let name = &ast.ident;
 	let crates = locate_crate_on_derive_macro(ast);
+	let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-	quote! {
-		impl #crates::ApplicationResponse for #name{}
+	/* \#\[ok\], \#\[created\] */
+	let status_of = |attrs: &[syn::Attribute]| match (attrs.iter().any(|attr| attr.path().is_ident("ok")), attrs.iter().any(|attr| attr.path().is_ident("created"))) {
+		(true, true) => panic!("Only one of #[ok] and #[created] can be given."),
+		(true, false) => Some(200u16),
+		(false, true) => Some(201u16),
+		(false, false) => None,
+	};
+	let default_status = status_of(&ast.attrs);
+
+	let status = match &ast.data {
+		syn::Data::Struct(_) => default_status.map(|status| quote!(#status)),
+		syn::Data::Enum(data_enum) => {
+			let statuses = data_enum.variants.iter().filter_map(|variant| status_of(&variant.attrs).map(|status| (&variant.ident, status))).collect::<Vec<_>>();
+			match (statuses.is_empty(), default_status) {
+				(true, None) => None,
+				(true, Some(status)) => Some(quote!(#status)),
+				(false, default_status) => {
+					let default_status = default_status.unwrap_or(200);
+					let arms = statuses.iter().map(|(ident, status)| quote!(Self::#ident { .. } => #status,));
+					Some(quote!(
+						match self {
+							#(#arms)*
+							#[allow(unreachable_patterns)]
+							_ => #default_status,
+						}
+					))
+				}
+			}
+		}
+		syn::Data::Union(_) => panic!("Union type is not supported by #[derive(ApplicationResponse)]."),
+	};
+	let status_code = status.map(|status| {
+		quote!(
+			fn status_code(&self) -> u16 {
+				#status
+			}
+		)
+	});
 
+	quote! {
+		impl #impl_generics #crates::ApplicationResponse for #name #ty_generics #where_clause {
+			#status_code
+		}
 	}
 	.into()
 }
@@ -68,20 +108,53 @@ pub(crate) fn render_error_token(ast: &DeriveInput) -> TokenStream {
 	if let Some(database_error) = database_error {
 		if let syn::Fields::Unnamed(_) = database_error.fields {
 		} else {
-			panic!("#[database_error] expects Field(Box<AnyError>).")
+			panic!("#[database_error] expects Field(DatabaseFailure) or Field(String).")
 		}
 	}
 	let database_error = if let Some(database_error) = database_error { database_error.ident.clone() } else { syn::Ident::new("DatabaseError", proc_macro2::Span::call_site()) };
 
+	/* \#\[validation_failed\] */
+	let validation_failed = find_variant("validation_failed");
+	if let Some(validation_failed) = validation_failed {
+		if let syn::Fields::Unnamed(_) = validation_failed.fields {
+		} else {
+			panic!("#[validation_failed] expects Field(Vec<FieldError>).")
+		}
+	}
+	let (validation_failed_from, validation_failed_into) = match validation_failed.map(|variant| &variant.ident) {
+		Some(validation_failed) => {
+			(quote!(#crates::BaseError::ValidationFailed(errors) => Self::#validation_failed(errors),), quote!(#name::#validation_failed(errors) => #crates::BaseError::ValidationFailed(errors),))
+		}
+		None => (quote!(), quote!()),
+	};
+
+	/* \#\[code("...")\] */
+	let codes = data_enum.variants.iter().filter(|variant| variant.ident != "BaseError").map(|variant| {
+		let ident = &variant.ident;
+		let code = match variant.attrs.iter().find(|attr| attr.path().is_ident("code")) {
+			Some(attr) => attr.parse_args::<syn::LitStr>().expect("#[code] expects string literal. Example: #[code(\"insufficient_balance\")]").value(),
+			None => to_snake_case(&ident.to_string()),
+		};
+		quote!(Self::#ident { .. } => #code,)
+	});
+
 	quote!(
-		impl #crates::ApplicationError for #name {}
+		impl #crates::ApplicationError for #name {
+			fn code(&self) -> &'static str {
+				match self {
+					#(#codes)*
+					Self::BaseError(error) => error.code(),
+				}
+			}
+		}
 
 		impl ::std::convert::From<#crates::BaseError> for #name {
 			fn from(value: #crates::BaseError) -> Self {
 				match value {
 					#crates::BaseError::StopSentinel => Self::#stop_sentinel,
 					#crates::BaseError::StopSentinelWithEvent(event) => Self::#stop_sentinel_with_event(event),
-					#crates::BaseError::DatabaseError(error) => Self::#database_error(error),
+					#crates::BaseError::DatabaseError(error) => Self::#database_error(error.into()),
+					#validation_failed_from
 					err => Self::BaseError(err),
 				}
 			}
@@ -92,7 +165,9 @@ pub(crate) fn render_error_token(ast: &DeriveInput) -> TokenStream {
 				let data = match value {
 					#name::#stop_sentinel => #crates::BaseError::StopSentinel,
 					#name::#stop_sentinel_with_event(event) => #crates::BaseError::StopSentinelWithEvent(event),
-					#name::#database_error(error) => #crates::BaseError::DatabaseError(error),
+					#name::#database_error(error) => #crates::BaseError::DatabaseError(error.into()),
+					#validation_failed_into
+					#name::BaseError(error) => error,
 					// _ => #crates::BaseError::ServiceError(::std::boxed::Box::new(value)),
 					_=> #crates::BaseError::ServiceError,
 				};
@@ -104,3 +179,15 @@ pub(crate) fn render_error_token(ast: &DeriveInput) -> TokenStream {
 	)
 	.into()
 }
+
+// * Code of variant without `#[code]` follows its name, such as `insufficient_balance` for `InsufficientBalance`
+fn to_snake_case(ident: &str) -> String {
+	let mut snake_case = String::new();
+	for (i, c) in ident.chars().enumerate() {
+		if c.is_uppercase() && i != 0 {
+			snake_case.push('_');
+		}
+		snake_case.extend(c.to_lowercase());
+	}
+	snake_case
+}
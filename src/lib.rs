@@ -57,15 +57,36 @@
 //! }
 //! ```
 //! Note that use of `internally_notifiable`(or `externally_notifiable`) and `identifier` are MUST.
+//! `#[event]` puts the derives above, along with `Debug`, on the struct in one go, leaving out those already given.
+//!
+//! ```rust,ignore
+//! #[ruva::event]
+//! #[internally_notifiable]
+//! pub struct OrderFailed {
+//!     pub user_id: i64,
+//! }
+//! ```
 //!
 //! * `internally_notifiable` is marker to let the system know that the event should be handled
-//!    within the application
+//!   within the application
 //! * `externally_notifiable` is to leave `OutBox`.
 //! * `identifier` is to record aggregate id.
+//! * `aggregate_id` replaces `identifier` on event that has no field for it, such as one across aggregates.
+//!   `#[aggregate_id = "none"]` leaves aggregate id empty, while `#[aggregate_id(expr = "self.order_id")]` computes it from expression that implements `Display`.
+//!   Event without aggregate id has no partition key unless `partition_key` is given, so publishers leave partitioning to the broker, which spreads such events round-robin.
+//! * `priority` is optional, as in `#[priority(10)]`. Events of higher priority are handled first within a request.
+//! * `version` is optional, as in `#[version(2)]`. It is 1 by default and used to upcast payload of older version.
+//! * `topic` is optional, as in `#[topic("billing.invoice.created")]`, to publish the event under a topic other than name of the type.
+//!   Handlers are still looked up by the type, so renaming the type doesn't break the contract on the wire.
+//! * `ser_format` is optional, as in `#[ser_format(MessagePack)]`, to choose format of outbox payload. It requires corresponding feature.
+//! * `headers` is optional, to be put on `HashMap<String, String>` field that keeps headers set by `with_header()`.
+//! * `partition_key` is optional, to be put on field whose value decides partition the event is published to. It is aggregate id by default.
 //!
 //! This results in the following method attach to the struct for example,
 //! * `to_message()` : to convert the struct to heap allocated data structure so messagebus can handle them.
 //! * `state()` : to record event's state for outboxing
+//! * `stop()` : to stop the rest of the handlers with the event queued instead, as in `return Err(SomeEvent { .. }.stop())`.
+//!   It turns into the `#[stop_sentinel_with_event]` variant of any error with `#[derive(ApplicationError)]`.
 //!
 //!
 //! ## Initializing TCommandService
@@ -108,6 +129,9 @@
 //!
 //! ```
 //!
+//! Handler doesn't have to return `ServiceResponse` itself. It may return its own DTO as long as the DTO converts into `ServiceResponse`
+//! with `From` or `TryFrom`, in which case failed conversion is returned as error of the command.
+//!
 //! ## Registering Event
 //!
 //! [TEvent] is a side effect of [TCommand] or yet another [TEvent] processing.
@@ -133,7 +157,7 @@
 //! ```
 //! In the `MakeOrder` TCommand Handling, we have either `OrderFailed` or `OrderSucceeded` event with their own processing handlers.
 //! Events are raised in the handlers that are thrown to [TMessageBus] by [ContextManager].
-//! [TMessageBus] then loops through the handlers UNLESS `StopSentinel` is received.
+//! [TMessageBus] then loops through the handlers UNLESS `StopSentinel` is received, or `StopSentinelWithEvent` which `stop()` on event returns.
 //!
 //! ## Handler API Example
 //!
@@ -213,18 +237,24 @@
 //!
 //!
 //! #### Error from MessageBus
-//! When command has not yet been regitered, it returns an error - `BaseError::NotFound`
+//! When command has not yet been regitered, it returns an error - `BaseError::CommandNotFound` carrying the type name of the command
 //! Be mindful that bus does NOT return the result of event processing as in distributed event processing.
 
 pub extern crate static_assertions;
 
 pub use ruva_core::__register_uow_services_internal;
+pub use ruva_core::bounded_context;
+pub use ruva_core::convert_event;
+pub use ruva_core::create_dependency;
 pub use ruva_core::error;
+pub use ruva_core::init_dyn_command_handler;
 pub use ruva_core::init_event_handler;
+pub use ruva_core::init_typed_event_handler;
 pub use ruva_core::make_conversion;
 pub use ruva_core::make_smart_pointer;
+pub use ruva_core::match_event;
 pub use ruva_core::prelude::*;
 pub use ruva_core::prepare_bulk_operation;
 pub use ruva_core::register_uow_services;
 
-pub use ruva_macro::{aggregate, entity, event_hook, into_command, ApplicationError, ApplicationResponse, TConstruct, TEvent};
+pub use ruva_macro::{aggregate, entity, event, event_handler, event_hook, into_command, ApplicationError, ApplicationResponse, TConstruct, TEvent};
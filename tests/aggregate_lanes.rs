@@ -0,0 +1,83 @@
+use ruva::*;
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct CheckoutStarted;
+
+#[derive(Debug, Clone)]
+struct ItemAdded {
+	cart: &'static str,
+	seq: usize,
+}
+impl TEvent for ItemAdded {
+	fn metadata(&self) -> EventMetadata {
+		EventMetadata { aggregate_id: self.cart.into(), aggregate_name: "Cart".into(), topic: "ItemAdded".into(), version: 1, headers: Default::default() }
+	}
+	fn state(&self) -> String {
+		"{}".into()
+	}
+}
+
+static PROJECTION: Mutex<Vec<(&str, usize)>> = Mutex::new(Vec::new());
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(CheckoutStarted)]
+async fn add_items(_event: CheckoutStarted, context: HandlerContext) -> Result<(), TestError> {
+	for (cart, seq) in [("a", 1), ("b", 1), ("a", 2), ("b", 2), ("a", 3)] {
+		context.emit(ItemAdded { cart, seq }).await?;
+	}
+	Ok(())
+}
+
+#[event_handler(ItemAdded)]
+async fn project_item(event: ItemAdded, _context: AtomicContextManager) -> Result<(), TestError> {
+	let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+	MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+	// * Earlier events of cart `a` take longer, so they would be overtaken if the cart were not handled in a lane
+	tokio::time::sleep(Duration::from_millis(if event.cart == "a" { 40 / event.seq as u64 } else { 5 })).await;
+	PROJECTION.lock().unwrap().push((event.cart, event.seq));
+	IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_events_of_the_same_aggregate_keep_their_order() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_aggregate_lanes());
+
+	//WHEN
+	let report = MessageBus.handle_event_with_report(CheckoutStarted.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(report.processed(), 6);
+	let projection = PROJECTION.lock().unwrap();
+	let of = |cart| projection.iter().filter(|(c, _)| *c == cart).map(|(_, seq)| *seq).collect::<Vec<_>>();
+	assert_eq!(of("a"), vec![1, 2, 3]);
+	assert_eq!(of("b"), vec![1, 2]);
+	// * Cart `b` is done while cart `a` is still being handled
+	assert_eq!(projection.last(), Some(&("a", 3)));
+	assert_eq!(MAX_IN_FLIGHT.load(Ordering::SeqCst), 2);
+}
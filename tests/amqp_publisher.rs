@@ -0,0 +1,59 @@
+#![cfg(feature = "event-driven-amqp")]
+
+use ruva::*;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	id: i64,
+	#[headers]
+	#[serde(skip)]
+	headers: std::collections::HashMap<String, String>,
+}
+
+#[derive(Default)]
+struct MockChannel {
+	exchange: Mutex<Option<String>>,
+	published: Mutex<Vec<AmqpMessage>>,
+}
+
+#[async_trait]
+impl TAmqpChannel for &'static MockChannel {
+	async fn setup(&self, exchange: &str) -> Result<(), BaseError> {
+		*self.exchange.lock().unwrap() = Some(exchange.to_string());
+		Ok(())
+	}
+	async fn publish(&self, message: AmqpMessage) -> Result<(), BaseError> {
+		// * Broker refuses negative id
+		if message.payload.starts_with(br#"{"id":-"#) {
+			return Err(BaseError::MessageBrokerError("Nacked".into()));
+		}
+		self.published.lock().unwrap().push(message);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_rabbit_publisher_marks_processed_only_when_acked() {
+	//GIVEN
+	let channel: &'static MockChannel = Box::leak(Box::default());
+	let publisher = RabbitPublisher::new(channel, "order-service").await.unwrap();
+	let mut outboxes = [1, -2, 3].map(|id| OrderPlaced { id, headers: Default::default() }.with_header("tenant", "bering").outbox());
+
+	//WHEN
+	let result = publisher.publish_all(&mut outboxes).await;
+
+	//THEN
+	assert!(matches!(result, Err(BaseError::MessageBrokerError(_))));
+	assert_eq!(outboxes.iter().map(|outbox| outbox.processed).collect::<Vec<_>>(), vec![true, false, false]);
+	assert_eq!(channel.exchange.lock().unwrap().as_deref(), Some("order-service"));
+
+	let published = channel.published.lock().unwrap();
+	assert_eq!(published.len(), 1);
+	assert_eq!(published[0].exchange, "order-service");
+	assert_eq!(published[0].routing_key, "OrderPlaced");
+	assert_eq!(published[0].payload, br#"{"id":1}"#);
+	assert_eq!(published[0].headers["tenant"], "bering");
+	assert_eq!(published[0].headers[FORMAT_HEADER], "json");
+}
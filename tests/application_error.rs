@@ -25,3 +25,108 @@ fn application_error_derive_test() {
 		}
 	}
 }
+
+#[test]
+fn validation_failed_survives_round_trip() {
+	#[allow(dead_code)]
+	#[derive(Debug, ApplicationError)]
+	#[crates(ruva)]
+	enum Err {
+		StopSentinel,
+		StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+		DatabaseError(String),
+		#[validation_failed]
+		InvalidInput(Vec<FieldError>),
+		BaseError(BaseError),
+	}
+
+	let errors = vec![FieldError::new("age", "range", "age must be at least 14")];
+
+	let Err::InvalidInput(converted) = Err::from(BaseError::ValidationFailed(errors.clone())) else { panic!("Must be converted into #[validation_failed] variant!") };
+	assert_eq!(converted, errors);
+	let BaseError::ValidationFailed(converted) = BaseError::from(Err::InvalidInput(errors.clone())) else { panic!("Must not be flattened into ServiceError!") };
+	assert_eq!(converted, errors);
+}
+
+#[test]
+fn base_error_codes_are_stable() {
+	let codes = [
+		(BaseError::NotFound, "not_found"),
+		(BaseError::EventNotFound("OrderPlaced".into()), "event_not_found"),
+		(BaseError::CommandNotFound("PlaceOrder".into()), "command_not_found"),
+		(BaseError::StopSentinel, "stop_sentinel"),
+		(BaseError::TransactionError, "transaction_error"),
+		(BaseError::DatabaseError("Connection refused".into()), "database_error"),
+		(BaseError::SerializationFailure, "serialization_failure"),
+		(BaseError::SerializationError("Key must be a string".into()), "serialization_error"),
+		(BaseError::DeserializationError("Unexpected EOF".into()), "deserialization_error"),
+		(BaseError::MessageBrokerError("Nacked".into()), "message_broker_error"),
+		(BaseError::ServiceError, "service_error"),
+		(BaseError::ValidationError("Name is empty".into()), "validation_error"),
+		(BaseError::ValidationFailed(vec![]), "validation_failed"),
+		(BaseError::Timeout { command: "PlaceOrder".into(), after: std::time::Duration::from_secs(1) }, "timeout"),
+		(BaseError::ShuttingDown, "shutting_down"),
+		(BaseError::DeadlineExceeded, "deadline_exceeded"),
+		(BaseError::QueueFull, "queue_full"),
+		(BaseError::CommandDepthExceeded(16), "command_depth_exceeded"),
+		(BaseError::PoisonMessage { message_id: "1".into(), requeued: 3 }, "poison_message"),
+		(BaseError::EventDowncastFailed { topic: "OrderPlaced".into(), expected_type: "OrderPlaced" }, "event_downcast_failed"),
+		(BaseError::CommandDowncastFailed { expected_type: "PlaceOrder" }, "command_downcast_failed"),
+		(BaseError::CircuitOpen { topic: "OrderPlaced".into() }, "circuit_open"),
+	];
+	for (error, code) in codes {
+		assert_eq!(error.code(), code);
+	}
+}
+
+#[test]
+fn application_error_codes_follow_attribute_or_variant_name() {
+	#[allow(dead_code)]
+	#[derive(Debug, ApplicationError)]
+	#[crates(ruva)]
+	enum Err {
+		StopSentinel,
+		StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+		DatabaseError(String),
+		#[code("balance_too_low")]
+		InsufficientBalance {
+			balance: i64,
+		},
+		AccountFrozen,
+		BaseError(BaseError),
+	}
+
+	assert_eq!(Err::InsufficientBalance { balance: 3 }.code(), "balance_too_low");
+	assert_eq!(Err::AccountFrozen.code(), "account_frozen");
+	assert_eq!(Err::DatabaseError("Connection refused".into()).code(), "database_error");
+	assert_eq!(Err::BaseError(BaseError::QueueFull).code(), "queue_full");
+}
+
+#[test]
+fn database_failure_keeps_operation_and_redacted_query() {
+	#[allow(dead_code)]
+	#[derive(Debug, ApplicationError)]
+	#[crates(ruva)]
+	enum Err {
+		StopSentinel,
+		StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+		#[database_error]
+		Database(DatabaseFailure),
+		BaseError(BaseError),
+	}
+
+	//GIVEN
+	let error = BaseError::DatabaseError("unique violation".into()).with_query("find_account", "SELECT * FROM account WHERE email = 'bering''s@lab.com' AND id = $1");
+
+	//WHEN
+	let Err::Database(failure) = Err::from(error) else { panic!("Must be converted into #[database_error] variant!") };
+
+	//THEN
+	assert_eq!(failure.operation.as_deref(), Some("find_account"));
+	assert_eq!(failure.query.as_deref(), Some("SELECT * FROM account WHERE email = '[REDACTED]' AND id = $1"));
+	let BaseError::DatabaseError(round_tripped) = BaseError::from(Err::Database(failure.clone())) else { panic!("Must be converted back into DatabaseError!") };
+	assert_eq!(round_tripped, failure);
+	assert!(!failure.to_string().contains("bering"));
+	// * Errors other than DatabaseError are left as they are
+	assert!(matches!(BaseError::NotFound.with_query("find_account", "SELECT 1"), BaseError::NotFound));
+}
@@ -0,0 +1,69 @@
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, Serialize, ApplicationResponse)]
+struct AccountDto {
+	id: i64,
+	name: String,
+}
+
+#[derive(Debug, Serialize, ApplicationResponse)]
+#[created]
+struct AccountCreated {
+	id: i64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationResponse)]
+struct Paged<T: Send + Sync> {
+	items: Vec<T>,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationResponse)]
+enum OrderResponse {
+	#[created]
+	Placed(i64),
+	Cancelled,
+}
+
+fn status_of(response: &impl ApplicationResponse) -> u16 {
+	response.status_code()
+}
+
+#[test]
+fn test_application_response_derived_on_struct_carries_status() {
+	assert_eq!(status_of(&AccountDto { id: 1, name: "bering".into() }), 200);
+	assert_eq!(status_of(&AccountCreated { id: 1 }), 201);
+	assert_eq!(status_of(&Paged { items: vec![AccountCreated { id: 1 }] }), 200);
+	assert_eq!(status_of(&OrderResponse::Placed(1)), 201);
+	assert_eq!(status_of(&OrderResponse::Cancelled), 200);
+}
+
+#[derive(Debug)]
+struct OrderPlaced {
+	id: i64,
+}
+impl ApplicationResponse for OrderPlaced {
+	fn status_code(&self) -> u16 {
+		201
+	}
+	fn response_metadata(&self) -> ResponseMetadata {
+		ResponseMetadata::default().with_location(format!("/orders/{}", self.id)).with_extra("id", self.id)
+	}
+}
+
+/// Headers as web adapter would set them on HTTP response
+fn headers_of(response: &impl ApplicationResponse) -> Vec<(String, String)> {
+	response.response_metadata().headers.into_iter().collect()
+}
+
+#[test]
+fn test_response_metadata_carries_location() {
+	let placed = OrderPlaced { id: 7 };
+
+	assert_eq!(headers_of(&placed), vec![("Location".to_string(), "/orders/7".to_string())]);
+	assert_eq!(placed.response_metadata().extras["id"], 7);
+	// * Derived response leaves it empty
+	assert!(AccountCreated { id: 1 }.response_metadata().is_empty());
+}
@@ -0,0 +1,122 @@
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[allow(dead_code)]
+#[into_command]
+struct ChangePassword {
+	user_id: i64,
+	#[redact]
+	password: String,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PasswordChanged {
+	user_id: i64,
+	#[redact]
+	email: String,
+}
+
+#[derive(Debug)]
+struct NotifyUser;
+impl TCommand for NotifyUser {}
+
+struct ChangePasswordService(AtomicContextManager, ChangePassword);
+impl TCommandService<TestResponse, TestError> for ChangePasswordService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![PasswordChanged { user_id: self.1.user_id, email: "user@bering.com".into() }.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, ChangePassword> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: ChangePassword) -> impl TCommandService<TestResponse, TestError> {
+		ChangePasswordService(context_manager, cmd)
+	}
+}
+
+struct NotifyUserService;
+impl TCommandService<TestResponse, TestError> for NotifyUserService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, NotifyUser> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: NotifyUser) -> impl TCommandService<TestResponse, TestError> {
+		NotifyUserService
+	}
+}
+
+#[event_handler(PasswordChanged)]
+async fn notify_user(_event: PasswordChanged, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	context_manager.dispatch_command(Box::new(NotifyUser));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+init_dyn_command_handler!(TestResponse, TestError, NotifyUser);
+
+#[tokio::test]
+async fn test_command_and_its_events_are_audited_with_redacted_payload() {
+	//GIVEN
+	let sink = InMemoryAuditSink::default();
+	MessageBus::configure(MessageBusConfig::default().with_audit_sink(sink.clone()));
+
+	//WHEN
+	MessageBus.execute_and_wait(ChangePassword { user_id: 1, password: "secret".into() }, &Connection).await.unwrap();
+
+	//THEN
+	let records = sink.records();
+	assert_eq!(records.iter().map(|record| record.kind).collect::<Vec<_>>(), vec![AuditKind::Command, AuditKind::Event, AuditKind::Command]);
+	assert!(records[0].name.ends_with("ChangePassword"));
+	assert_eq!(records[0].payload, r#"{"password":"[REDACTED]","user_id":1}"#);
+	assert_eq!(records[1].name, "PasswordChanged");
+	assert_eq!(records[1].payload, r#"{"email":"[REDACTED]","user_id":1}"#);
+	assert!(records[2].name.ends_with("NotifyUser"));
+	assert_eq!(records[2].payload, "NotifyUser");
+
+	// * Command dispatched from the event handler carries on correlation id of the request
+	assert!(records.iter().all(|record| record.correlation_id == records[0].correlation_id));
+
+	// * Other than the audit record, serialization is left as it is
+	assert!(PasswordChanged { user_id: 1, email: "user@bering.com".into() }.state().contains("user@bering.com"));
+}
+
+#[tokio::test]
+async fn test_json_lines_sink_appends_record_per_line() {
+	//GIVEN
+	let path = std::env::temp_dir().join(format!("ruva-audit-{}.jsonl", std::process::id()));
+	let sink = JsonLinesAuditSink::open(&path).await.unwrap();
+	let record = |name: &str| AuditRecord { kind: AuditKind::Event, name: name.into(), correlation_id: "request-1".into(), payload: "{}".into(), timestamp: MessageBus::now() };
+
+	//WHEN
+	sink.record_event(record("PasswordChanged")).await.unwrap();
+	sink.record_event(record("UserNotified")).await.unwrap();
+
+	//THEN
+	let lines = std::fs::read_to_string(&path).unwrap();
+	std::fs::remove_file(&path).unwrap();
+	let records = lines.lines().map(|line| serde_json::from_str::<AuditRecord>(line).unwrap()).collect::<Vec<_>>();
+	assert_eq!(records.iter().map(|record| record.name.as_str()).collect::<Vec<_>>(), vec!["PasswordChanged", "UserNotified"]);
+	assert!(records.iter().all(|record| record.kind == AuditKind::Event && record.correlation_id == "request-1"));
+	assert!(lines.lines().all(|line| line.contains(r#""kind":"event""#)));
+}
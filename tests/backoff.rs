@@ -0,0 +1,71 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
+
+#[test]
+fn test_backoff_grows_until_cap() {
+	//WHEN
+	let delays = Backoff::exponential(Duration::from_millis(100), Duration::from_secs(1), 0.0).take(6).collect::<Vec<_>>();
+
+	//THEN
+	assert_eq!(delays, [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis));
+	assert_eq!(Backoff::exponential(Duration::from_secs(1), Duration::from_secs(30), 0.0).nth(200), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn test_backoff_jitter_stays_within_bounds() {
+	//WHEN
+	let delays = Backoff::exponential(Duration::from_millis(100), Duration::from_millis(400), 0.25).take(1000).collect::<Vec<_>>();
+
+	//THEN
+	let unjittered = [100, 200].into_iter().chain(std::iter::repeat(400)).map(Duration::from_millis);
+	for (delay, unjittered) in delays.iter().zip(unjittered) {
+		assert!(*delay <= unjittered);
+		assert!(*delay >= unjittered.mul_f64(0.75));
+	}
+	assert!(delays[2..].iter().any(|delay| *delay != delays[2]));
+}
+
+/// Broker that refuses the first two attempts
+#[derive(Default)]
+struct RecoveringPublisher(AtomicUsize);
+#[async_trait]
+impl TOutBoxPublisher for RecoveringPublisher {
+	async fn publish(&self, _outbox: &OutBox) -> Result<(), BaseError> {
+		match self.0.fetch_add(1, Ordering::SeqCst) {
+			0 | 1 => Err(BaseError::MessageBrokerError("Connection refused".into())),
+			_ => Ok(()),
+		}
+	}
+	fn backoff(&self) -> Box<dyn Iterator<Item = Duration> + Send> {
+		Box::new(Backoff::exponential(Duration::from_millis(1), Duration::from_millis(5), 0.5).take(2))
+	}
+}
+
+#[aggregate(Serialize, Debug)]
+struct Order {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[externally_notifiable(Order)]
+struct OrderShipped {
+	#[identifier]
+	id: i64,
+}
+
+#[tokio::test]
+async fn test_outbox_relay_retries_with_backoff() {
+	//GIVEN
+	let mut outboxes = [OrderShipped { id: 1 }.outbox()];
+
+	//WHEN
+	let result = RecoveringPublisher::default().publish_all(&mut outboxes).await;
+
+	//THEN
+	assert!(result.is_ok());
+	assert!(outboxes[0].processed);
+}
@@ -0,0 +1,128 @@
+use ruva::*;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct Done;
+impl ApplicationResponse for Done {}
+
+/// Runs handler of the command without transaction, as there is no database to begin one on
+struct WithoutTransaction<C>(CommandHandler<(C, Context)>);
+impl<C, R, E> TCommandService<R, E> for WithoutTransaction<C>
+where
+	C: TCommand + for<'a> TGetHandler<&'a mut Context, Result<R, E>>,
+	R: Send,
+	E: Send + From<BaseError>,
+{
+	async fn execute(self) -> Result<R, E> {
+		let (cmd, mut context) = self.0.destruct();
+		let result = (C::get_handler())(cmd, &mut context).await;
+		context.send_internally_notifiable_messages().await?;
+		result
+	}
+}
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum OrderError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum BillingError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct PlaceOrder {
+	order_id: i64,
+}
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug)]
+struct IssueInvoice {
+	amount: i64,
+}
+impl TCommand for IssueInvoice {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	order_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct InvoiceIssued {
+	amount: i64,
+}
+
+static RESERVED: AtomicI64 = AtomicI64::new(0);
+static CHARGED: AtomicI64 = AtomicI64::new(0);
+
+async fn place_order(cmd: PlaceOrder, context: &mut Context) -> Result<Done, OrderError> {
+	context.set_current_events(vec![OrderPlaced { order_id: cmd.order_id }.to_message()].into());
+	Ok(Done)
+}
+
+async fn issue_invoice(cmd: IssueInvoice, context: &mut Context) -> Result<Done, BillingError> {
+	context.set_current_events(vec![InvoiceIssued { amount: cmd.amount }.to_message()].into());
+	Ok(Done)
+}
+
+struct OrderEventHandler;
+impl OrderEventHandler {
+	async fn reserve_stock(self, event: OrderPlaced) -> Result<(), OrderError> {
+		RESERVED.store(event.order_id, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+struct BillingEventHandler;
+impl BillingEventHandler {
+	async fn charge(self, event: InvoiceIssued) -> Result<(), BillingError> {
+		CHARGED.store(event.amount * billing::dependency().exchange_rate, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+bounded_context! {
+	ordering,
+	commands { Done, OrderError, WithoutTransaction, PlaceOrder => place_order },
+	events { OrderError, |_ctx| OrderEventHandler, OrderPlaced: [reserve_stock] },
+}
+
+bounded_context! {
+	billing,
+	dependency {
+		struct { exchange_rate: i64 }
+		init { Dependency { exchange_rate: 1300 } }
+	},
+	commands { Done, BillingError, WithoutTransaction, IssueInvoice => issue_invoice },
+	events { BillingError, |_ctx| BillingEventHandler, InvoiceIssued: [charge] },
+}
+
+#[tokio::test]
+async fn test_contexts_declared_side_by_side_handle_their_own_messages() {
+	//WHEN
+	ordering::bus().execute_and_wait(PlaceOrder { order_id: 7 }, &Connection).await.unwrap();
+	billing::bus().execute_and_wait(IssueInvoice { amount: 2 }, &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(RESERVED.load(Ordering::SeqCst), 7);
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 2600);
+	assert_eq!(ordering::EVENT_HANDLERS.len(), 1);
+	assert_eq!(billing::EVENT_HANDLERS.len(), 1);
+	assert!(std::ptr::eq(ordering::dependency(), ordering::dependency()));
+}
@@ -0,0 +1,127 @@
+use ruva::*;
+
+struct Connection;
+impl TConnection for Connection {}
+
+mod ordering {
+	use super::*;
+
+	#[allow(dead_code)]
+	#[derive(Debug, ApplicationError)]
+	#[crates(ruva)]
+	pub enum OrderError {
+		StopSentinel,
+		StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+		DatabaseError(String),
+		OutOfStock,
+		BaseError(BaseError),
+	}
+
+	#[derive(Debug, PartialEq, ApplicationResponse)]
+	#[crates(ruva)]
+	pub enum OrderResponse {
+		#[created]
+		Placed(i64),
+	}
+
+	#[derive(Debug)]
+	pub struct PlaceOrder {
+		pub quantity: u32,
+	}
+	impl TCommand for PlaceOrder {}
+
+	struct PlaceOrderService(PlaceOrder);
+	impl TCommandService<OrderResponse, OrderError> for PlaceOrderService {
+		async fn execute(self) -> Result<OrderResponse, OrderError> {
+			match self.0.quantity {
+				0 => Err(OrderError::OutOfStock),
+				_ => Ok(OrderResponse::Placed(1)),
+			}
+		}
+	}
+
+	impl TMessageBus<OrderResponse, OrderError, PlaceOrder> for MessageBus {
+		fn command_handler(&self, _context_manager: AtomicContextManager, cmd: PlaceOrder) -> impl TCommandService<OrderResponse, OrderError> {
+			PlaceOrderService(cmd)
+		}
+	}
+
+	init_event_handler!(OrderError);
+	init_dyn_command_handler!(OrderResponse, OrderError, PlaceOrder);
+}
+
+mod billing {
+	use super::*;
+
+	#[allow(dead_code)]
+	#[derive(Debug, ApplicationError)]
+	#[crates(ruva)]
+	pub enum BillingError {
+		StopSentinel,
+		StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+		DatabaseError(String),
+		BaseError(BaseError),
+	}
+
+	#[derive(Debug, PartialEq)]
+	pub struct Invoiced {
+		pub amount: u64,
+	}
+	impl ApplicationResponse for Invoiced {
+		fn response_metadata(&self) -> ResponseMetadata {
+			ResponseMetadata::default().with_location("/invoices/1")
+		}
+	}
+
+	#[derive(Debug)]
+	pub struct IssueInvoice;
+	impl TCommand for IssueInvoice {}
+
+	struct IssueInvoiceService;
+	impl TCommandService<Invoiced, BillingError> for IssueInvoiceService {
+		async fn execute(self) -> Result<Invoiced, BillingError> {
+			Ok(Invoiced { amount: 100 })
+		}
+	}
+
+	impl TMessageBus<Invoiced, BillingError, IssueInvoice> for MessageBus {
+		fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: IssueInvoice) -> impl TCommandService<Invoiced, BillingError> {
+			IssueInvoiceService
+		}
+	}
+
+	init_event_handler!(BillingError);
+	init_dyn_command_handler!(Invoiced, BillingError, IssueInvoice);
+}
+
+use billing::*;
+use ordering::*;
+
+#[derive(Debug)]
+struct ShipOrder;
+impl TCommand for ShipOrder {}
+
+#[tokio::test]
+async fn test_commands_are_routed_to_bus_that_handles_them() {
+	//GIVEN
+	let router = BusRouter::default().with_bus::<OrderResponse, OrderError>(&MessageBus).with_bus::<Invoiced, BillingError>(&MessageBus);
+
+	//WHEN
+	let placed = router.execute(Box::new(PlaceOrder { quantity: 1 }), &Connection).await.unwrap();
+	let invoiced = router.execute(Box::new(IssueInvoice), &Connection).await.unwrap();
+	let out_of_stock = router.execute(Box::new(PlaceOrder { quantity: 0 }), &Connection).await;
+	let unclaimed = router.execute(Box::new(ShipOrder), &Connection).await;
+	let placed_metadata = placed.response_metadata();
+
+	//THEN
+	assert_eq!(placed.status_code(), 201);
+	assert_eq!(placed.downcast::<OrderResponse>().unwrap(), OrderResponse::Placed(1));
+	assert_eq!(invoiced.status_code(), 200);
+	assert!(placed_metadata.is_empty());
+	assert_eq!(invoiced.response_metadata().headers["Location"], "/invoices/1");
+	// * Response of the other bus is given back as it is
+	let invoiced = invoiced.downcast::<OrderResponse>().unwrap_err();
+	assert_eq!(invoiced.downcast::<Invoiced>().unwrap(), Invoiced { amount: 100 });
+	assert_eq!(out_of_stock.unwrap_err().code(), "out_of_stock");
+	assert_eq!(unclaimed.unwrap_err().code(), "command_not_found");
+}
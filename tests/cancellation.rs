@@ -0,0 +1,105 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct RequestReport;
+impl TCommand for RequestReport {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct ReportRequested;
+
+struct RequestReportService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for RequestReportService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![ReportRequested.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, RequestReport> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: RequestReport) -> impl TCommandService<TestResponse, TestError> {
+		RequestReportService(context_manager)
+	}
+}
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static COMPLETED: AtomicBool = AtomicBool::new(false);
+
+#[event_handler(ReportRequested)]
+async fn build_report(_event: ReportRequested, context: HandlerContext) -> Result<(), TestError> {
+	STARTED.store(true, Ordering::SeqCst);
+	let token = context.cancellation_token();
+	tokio::select! {
+		_ = token.cancelled() => {
+			CANCELLED.store(true, Ordering::SeqCst);
+			Err(BaseError::ShuttingDown.into())
+		}
+		_ = tokio::time::sleep(Duration::from_secs(60)) => {
+			COMPLETED.store(true, Ordering::SeqCst);
+			Ok(())
+		}
+	}
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_slow_handler_returns_early_once_shutdown_cancels_its_request() {
+	// * Tasks are spawned locally with `local` feature
+	tokio::task::LocalSet::new()
+		.run_until(async {
+			//GIVEN
+			let res = MessageBus.execute_and_forget(RequestReport, &Connection).await.unwrap();
+			while !STARTED.load(Ordering::SeqCst) {
+				tokio::task::yield_now().await;
+			}
+
+			//WHEN
+			tokio::time::timeout(Duration::from_secs(5), MessageBus.shutdown()).await.expect("Shutdown must not wait for the handler to complete!");
+
+			//THEN
+			assert!(res.wait_until_event_processing_done().await.is_ok());
+			assert!(CANCELLED.load(Ordering::SeqCst));
+			assert!(!COMPLETED.load(Ordering::SeqCst));
+		})
+		.await;
+}
+
+#[test]
+fn test_cancellation_flows_from_parent_to_child_only() {
+	//GIVEN
+	let token = CancellationToken::new();
+	let child = token.child_token();
+
+	//WHEN
+	child.cancel();
+
+	//THEN
+	assert!(!token.is_cancelled());
+	token.cancel();
+	assert!(token.child_token().is_cancelled());
+}
@@ -0,0 +1,79 @@
+use ruva::*;
+use std::sync::{Arc, Mutex};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountOpened {
+	account_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountClosed {
+	account_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountFrozen {
+	account_id: i64,
+}
+
+static OBSERVED: Mutex<Vec<String>> = Mutex::new(vec![]);
+
+struct AccountEventHandler;
+impl AccountEventHandler {
+	async fn welcome(self, _event: AccountOpened) -> Result<(), TestError> {
+		OBSERVED.lock().unwrap().push("welcome".into());
+		Ok(())
+	}
+	async fn settle(self, _event: AccountClosed) -> Result<(), TestError> {
+		OBSERVED.lock().unwrap().push("settle".into());
+		Ok(())
+	}
+	async fn hold(self, _event: AccountFrozen) -> Result<(), TestError> {
+		Err(TestError::StopSentinel)
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| AccountEventHandler,
+	AccountOpened: [welcome],
+	AccountClosed: [settle],
+	AccountFrozen: [hold],
+);
+
+async fn audit(event: Arc<dyn TEvent>, _context: AtomicContextManager) -> Result<(), TestError> {
+	OBSERVED.lock().unwrap().push(format!("audit {}", event.metadata().topic));
+	Ok(())
+}
+
+#[tokio::test]
+async fn test_catch_all_handler_observes_events_of_every_topic() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().subscribe_all(audit));
+
+	//WHEN
+	let opened = MessageBus.handle_event_with_report(AccountOpened { account_id: 1 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(AccountClosed { account_id: 1 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(AccountFrozen { account_id: 1 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!((opened.succeeded, opened.failed), (2, 0));
+	// * Catch-all handler runs after the handlers of the topic, and not at all once stop sentinel arrived
+	assert_eq!(*OBSERVED.lock().unwrap(), vec!["welcome", "audit AccountOpened", "settle", "audit AccountClosed"]);
+}
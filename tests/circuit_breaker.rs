@@ -0,0 +1,90 @@
+use ruva::*;
+use std::{
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc, Mutex,
+	},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentRequested {
+	order_id: i64,
+}
+
+static CHARGED: AtomicUsize = AtomicUsize::new(0);
+static GATEWAY_DOWN: AtomicBool = AtomicBool::new(true);
+
+#[event_handler(PaymentRequested)]
+async fn charge(_event: PaymentRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	CHARGED.fetch_add(1, Ordering::SeqCst);
+	if GATEWAY_DOWN.load(Ordering::SeqCst) {
+		return Err(TestError::DatabaseError("Payment gateway is unreachable!".into()));
+	}
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[derive(Default, Clone)]
+struct InMemoryDeadLetterSink(Arc<Mutex<Vec<DeadLetter>>>);
+
+#[async_trait]
+impl TDeadLetterSink for InMemoryDeadLetterSink {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(dead_letter);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_breaker_short_circuits_handler_until_cooldown_elapses() {
+	//GIVEN
+	let (sink, breaker) = (InMemoryDeadLetterSink::default(), CircuitBreaker::new(3, Duration::from_millis(200)));
+	MessageBus::configure(MessageBusConfig::default().with_dead_letter_sink(sink.clone()).with_circuit_breaker_for("PaymentRequested", breaker.clone()));
+	for order_id in 0..3 {
+		MessageBus.handle_event(PaymentRequested { order_id }.to_message(), &Connection).await.unwrap();
+	}
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 3);
+	assert_eq!(breaker.state(), CircuitState::Open);
+
+	//WHEN
+	for order_id in 3..5 {
+		MessageBus.handle_event(PaymentRequested { order_id }.to_message(), &Connection).await.unwrap();
+	}
+
+	//THEN
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 3);
+	let dead_letters = sink.0.lock().unwrap().clone();
+	assert_eq!(dead_letters.len(), 5);
+	assert!(dead_letters[3..].iter().all(|dead_letter| dead_letter.reason.contains("CircuitOpen")));
+
+	// * Probe after the cooldown fails, which opens the breaker again
+	tokio::time::sleep(Duration::from_millis(250)).await;
+	MessageBus.handle_event(PaymentRequested { order_id: 5 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(PaymentRequested { order_id: 6 }.to_message(), &Connection).await.unwrap();
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 4);
+	assert_eq!(breaker.state(), CircuitState::Open);
+
+	// * Probe succeeds once the gateway is back, which closes the breaker
+	GATEWAY_DOWN.store(false, Ordering::SeqCst);
+	tokio::time::sleep(Duration::from_millis(250)).await;
+	MessageBus.handle_event(PaymentRequested { order_id: 7 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(PaymentRequested { order_id: 8 }.to_message(), &Connection).await.unwrap();
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 6);
+	assert_eq!(breaker.state(), CircuitState::Closed);
+}
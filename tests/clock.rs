@@ -0,0 +1,28 @@
+use ruva::*;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+#[test]
+fn test_outbox_timestamped_by_configured_clock() {
+	//GIVEN
+	let at = chrono::DateTime::from_naive_utc_and_offset(chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap(), chrono::Utc);
+	let clock = MockClock::new(at);
+	MessageBus::configure(MessageBusConfig::default().with_clock(clock.clone()));
+
+	//WHEN
+	let first = OrderPlaced.outbox();
+	clock.advance(Duration::from_secs(60));
+	let second = OrderPlaced.outbox();
+
+	//THEN
+	assert_eq!(first.create_dt, at);
+	assert_eq!(first.envelope().unwrap().metadata.create_dt, at);
+	assert_eq!(second.create_dt - first.create_dt, chrono::Duration::seconds(60));
+	assert_eq!(MessageBus::now(), at + chrono::Duration::seconds(60));
+
+	#[cfg(feature = "time")]
+	assert_eq!(clock.now_offset().unix_timestamp(), clock.now().timestamp());
+}
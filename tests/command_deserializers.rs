@@ -0,0 +1,81 @@
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, PartialEq, ApplicationResponse)]
+enum TestResponse {
+	Placed(u32),
+	Cancelled(i64),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Deserialize)]
+struct PlaceOrder {
+	quantity: u32,
+}
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Deserialize)]
+struct CancelOrder {
+	order_id: i64,
+}
+impl TCommand for CancelOrder {}
+
+struct PlaceOrderService(PlaceOrder);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		Ok(TestResponse::Placed(self.0.quantity))
+	}
+}
+
+struct CancelOrderService(CancelOrder);
+impl TCommandService<TestResponse, TestError> for CancelOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		Ok(TestResponse::Cancelled(self.0.order_id))
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(cmd)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, CancelOrder> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, cmd: CancelOrder) -> impl TCommandService<TestResponse, TestError> {
+		CancelOrderService(cmd)
+	}
+}
+
+init_event_handler!(TestError);
+init_dyn_command_handler!(TestResponse, TestError, PlaceOrder, CancelOrder);
+
+#[tokio::test]
+async fn test_commands_are_dispatched_from_their_json_envelopes() {
+	//GIVEN
+	let deserializers = CommandDeserializers::default().register_command::<PlaceOrder>().register::<CancelOrder>("order.cancel");
+	let place = serde_json::from_str(r#"{"type": "PlaceOrder", "payload": {"quantity": 3}}"#).unwrap();
+	let cancel = serde_json::from_str(r#"{"type": "order.cancel", "payload": {"order_id": 7}}"#).unwrap();
+
+	//WHEN
+	let placed = MessageBus.execute_dyn(deserializers.deserialize(place).unwrap(), &Connection).await.unwrap();
+	let cancelled = MessageBus.execute_dyn(deserializers.deserialize(cancel).unwrap(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(placed, TestResponse::Placed(3));
+	assert_eq!(cancelled, TestResponse::Cancelled(7));
+	let unknown = CommandEnvelope { kind: "RefundOrder".into(), payload: serde_json::json!({}) };
+	assert!(matches!(deserializers.deserialize(unknown), Err(BaseError::CommandNotFound(kind)) if kind == "RefundOrder"));
+	let malformed = CommandEnvelope { kind: "PlaceOrder".into(), payload: serde_json::json!({"quantity": "three"}) };
+	assert!(matches!(deserializers.deserialize(malformed), Err(BaseError::DeserializationError(_))));
+}
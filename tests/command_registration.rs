@@ -0,0 +1,147 @@
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum OtherError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct Accepted;
+impl ApplicationResponse for Accepted {}
+
+#[derive(Debug)]
+struct Done;
+impl ApplicationResponse for Done {}
+
+#[derive(Debug)]
+struct Refund;
+impl TCommand for Refund {}
+
+struct RefundService;
+impl<R: ApplicationResponse + Default, E: ApplicationError> TCommandService<R, E> for RefundService {
+	async fn execute(self) -> Result<R, E> {
+		Ok(R::default())
+	}
+}
+
+impl Default for Accepted {
+	fn default() -> Self {
+		Accepted
+	}
+}
+impl Default for Done {
+	fn default() -> Self {
+		Done
+	}
+}
+
+impl TMessageBus<Accepted, TestError, Refund> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: Refund) -> impl TCommandService<Accepted, TestError> {
+		RefundService
+	}
+}
+impl TMessageBus<Done, TestError, Refund> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: Refund) -> impl TCommandService<Done, TestError> {
+		RefundService
+	}
+}
+impl TMessageBus<Accepted, OtherError, Refund> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: Refund) -> impl TCommandService<Accepted, OtherError> {
+		RefundService
+	}
+}
+impl TMessageBus<Done, OtherError, Refund> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: Refund) -> impl TCommandService<Done, OtherError> {
+		RefundService
+	}
+}
+
+mod billing {
+	use super::*;
+	init_dyn_command_handler!(Accepted, TestError, Refund);
+}
+mod support {
+	use super::*;
+	// * Registered again by accident
+	init_dyn_command_handler!(Done, TestError, Refund);
+}
+
+mod legacy {
+	use super::*;
+	init_dyn_command_handler!(Done, OtherError, Refund);
+}
+mod migrated {
+	use super::*;
+	init_dyn_command_handler!(Accepted, OtherError, override Refund);
+}
+
+mod handlers {
+	use super::*;
+	init_event_handler!(TestError);
+}
+mod other_handlers {
+	use super::*;
+	init_event_handler!(OtherError);
+}
+
+#[test]
+fn test_duplicate_command_registration_panics_in_strict_mode() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_strict_command_registration());
+
+	//WHEN
+	let panic = std::panic::catch_unwind(collect_command_dispatchers::<TestError>).unwrap_err();
+
+	//THEN
+	let message = panic.downcast_ref::<String>().unwrap();
+	assert!(message.contains("Refund"));
+	assert!(message.contains("command_registration.rs:76"));
+	assert!(message.contains("command_registration.rs:81"));
+}
+
+#[test]
+fn test_command_marked_override_replaces_registration() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_strict_command_registration());
+
+	//WHEN
+	let dispatchers = collect_command_dispatchers::<OtherError>();
+
+	//THEN
+	assert_eq!(dispatchers.len(), 1);
+}
+
+#[derive(Debug)]
+struct Chargeback;
+impl TCommand for Chargeback {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[tokio::test]
+async fn test_command_of_other_type_fails_handler_instead_of_panicking() {
+	//GIVEN
+	let handler = <MessageBus as TDynMessageBus<Accepted, OtherError>>::dyn_command_handler(&MessageBus).get(&std::any::TypeId::of::<Refund>()).unwrap();
+
+	//WHEN
+	let result = handler(Box::new(Chargeback), &Connection).await;
+
+	//THEN
+	let Err(OtherError::BaseError(BaseError::CommandDowncastFailed { expected_type })) = result else { panic!("Handler must fail with downcast error!") };
+	assert!(expected_type.ends_with("Refund"));
+}
@@ -120,3 +120,45 @@ fn test_into_command_with_tevent() {
 	let serilaized = serde_json::to_string(&command).unwrap();
 	assert_eq!(serilaized, "{\"id\":1,\"Name\":\"migo\",\"foo\":2}".to_string());
 }
+
+#[test]
+fn test_into_command_with_validation() {
+	#[into_command]
+	struct SignUp {
+		#[validate(length(min = 2, max = 10))]
+		name: String,
+		#[validate(range(min = 14))]
+		age: i32,
+		#[required_input]
+		#[validate(range(max = 3))]
+		plan: i32,
+	}
+
+	let command = SignUpBody { name: "migo".into(), age: 20 }.into_command(1);
+	assert!(command.validate().is_ok());
+
+	let Err(BaseError::ValidationFailed(errors)) = SignUp { name: "m".into(), age: 20, plan: 1 }.validate() else { panic!("Name must be too short!") };
+	assert_eq!(errors, vec![FieldError::new("name", "length", "length of name must be at least 2")]);
+	let Err(BaseError::ValidationFailed(errors)) = SignUp { name: "migo".into(), age: 13, plan: 4 }.validate() else { panic!("Age and plan must be invalid!") };
+	assert_eq!(errors, vec![FieldError::new("age", "range", "age must be at least 14"), FieldError::new("plan", "range", "plan must be at most 3")]);
+}
+
+#[test]
+fn test_into_command_with_aggregate_id() {
+	#[into_command]
+	struct ShipOrder {
+		#[required_input]
+		#[aggregate_id]
+		order_id: i64,
+		carrier: String,
+	}
+	#[into_command]
+	struct Unrouted {
+		carrier: String,
+	}
+
+	let command = ShipOrderBody { carrier: "dhl".into() }.into_command(7);
+	assert_eq!(command.aggregate_id(), Some("7".to_string()));
+	assert_eq!(format!("{:?}", ShipOrderBody { carrier: "dhl".into() }), "ShipOrderBody { carrier: \"dhl\" }");
+	assert_eq!(UnroutedBody { carrier: "dhl".into() }.into_command().aggregate_id(), None);
+}
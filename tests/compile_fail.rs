@@ -0,0 +1,35 @@
+#[test]
+fn test_command_handler_signature_mismatch() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/handler_arguments_in_wrong_order.rs");
+}
+
+#[test]
+fn test_command_handler_returning_its_own_response() {
+	let t = trybuild::TestCases::new();
+	t.pass("tests/ui/handler_returns_own_response.rs");
+}
+
+#[test]
+fn test_event_with_empty_topic() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/empty_topic.rs");
+}
+
+#[test]
+fn test_event_with_aggregate_id_misused() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/aggregate_id_misuse.rs");
+}
+
+#[test]
+fn test_event_attribute_without_notifiability() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/event_without_notifiability.rs");
+}
+
+#[test]
+fn test_event_derived_without_clone() {
+	let t = trybuild::TestCases::new();
+	t.compile_fail("tests/ui/event_without_clone.rs");
+}
@@ -0,0 +1,70 @@
+use ruva::*;
+use std::sync::Mutex;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct ReportRequested;
+
+#[derive(Debug, PartialEq)]
+struct AuthenticatedUser(&'static str);
+
+/// Stands in for middleware that authenticates the request before it reaches handlers
+struct Authentication;
+impl TRequestLifecycle for Authentication {
+	fn on_request_start(&self, context: &ContextManager) {
+		context.insert(AuthenticatedUser("migo"));
+	}
+}
+
+static REQUESTED_BY: Mutex<Vec<&str>> = Mutex::new(vec![]);
+
+#[event_handler(ReportRequested)]
+async fn build_report(_event: ReportRequested, context: HandlerContext) -> Result<(), TestError> {
+	let user = context.get::<AuthenticatedUser>().ok_or(BaseError::NotFound)?;
+	REQUESTED_BY.lock().unwrap().push(user.0);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_value_inserted_in_middleware_is_read_in_handler() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_request_lifecycle(Authentication));
+
+	//WHEN
+	MessageBus.handle_event(ReportRequested.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(*REQUESTED_BY.lock().unwrap(), vec!["migo"]);
+}
+
+#[test]
+fn test_extension_is_replaced_by_value_of_the_same_type() {
+	//GIVEN
+	let context = ContextManager::new(&Connection);
+	assert!(context.get::<AuthenticatedUser>().is_none());
+	context.insert(AuthenticatedUser("migo"));
+
+	//WHEN
+	let previous = context.insert(AuthenticatedUser("bering"));
+
+	//THEN
+	assert_eq!(previous.as_deref(), Some(&AuthenticatedUser("migo")));
+	assert_eq!(context.get::<AuthenticatedUser>().as_deref(), Some(&AuthenticatedUser("bering")));
+	context.insert(42_u32);
+	assert_eq!(context.get::<u32>().as_deref(), Some(&42));
+}
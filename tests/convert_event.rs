@@ -0,0 +1,69 @@
+use ruva::*;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountCreated {
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountClosed {
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountDeleted {
+	id: i64,
+}
+
+#[derive(Debug)]
+enum AccountEvent {
+	Created(AccountCreated),
+	Closed(AccountClosed),
+}
+
+fn convert(event: std::sync::Arc<dyn TEvent>) -> Result<AccountEvent, BaseError> {
+	convert_event!(event, AccountEvent {
+		"AccountCreated" => Created(AccountCreated),
+		"AccountClosed" => Closed(AccountClosed),
+	})
+}
+
+#[test]
+fn test_convert_event_into_variant_by_topic() {
+	//GIVEN
+	let events = vec![AccountCreated { id: 1 }.to_message(), AccountClosed { id: 2 }.to_message()];
+
+	//WHEN
+	let converted = events.into_iter().map(convert).collect::<Result<Vec<_>, _>>().unwrap();
+
+	//THEN
+	assert!(matches!(converted[0], AccountEvent::Created(AccountCreated { id: 1 })));
+	assert!(matches!(converted[1], AccountEvent::Closed(AccountClosed { id: 2 })));
+}
+
+#[test]
+fn test_convert_event_with_unknown_topic() {
+	//WHEN
+	let result = convert(AccountDeleted { id: 3 }.to_message());
+
+	//THEN
+	assert!(matches!(result, Err(BaseError::EventNotFound(topic)) if topic == "AccountDeleted"));
+}
+
+#[test]
+fn test_convert_event_with_many_topics_to_one_variant() {
+	//GIVEN
+	let event: Box<dyn TEvent> = Box::new(AccountClosed { id: 4 });
+
+	//WHEN
+	let result = convert_event!(event, AccountEvent {
+		"AccountCreated" => Created(AccountCreated),
+		"AccountClosed" | "AccountTerminated" => Closed(AccountClosed),
+	});
+
+	//THEN
+	assert!(matches!(result, Ok(AccountEvent::Closed(AccountClosed { id: 4 }))));
+}
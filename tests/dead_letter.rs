@@ -0,0 +1,87 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicBool, AtomicI64, Ordering},
+	Mutex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct InvoiceIssued {
+	amount: i64,
+}
+
+static LEDGER_BROKEN: AtomicBool = AtomicBool::new(true);
+static BOOKED: AtomicI64 = AtomicI64::new(0);
+
+#[event_handler(InvoiceIssued)]
+async fn book_invoice(event: InvoiceIssued, _context: AtomicContextManager) -> Result<(), TestError> {
+	if LEDGER_BROKEN.load(Ordering::SeqCst) {
+		return Err(TestError::DatabaseError("Ledger is down".into()));
+	}
+	BOOKED.fetch_add(event.amount, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[derive(Default)]
+struct InMemoryDeadLetterStore(Mutex<Vec<(i64, DeadLetter)>>);
+
+#[async_trait]
+impl TDeadLetterSink for InMemoryDeadLetterStore {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		let mut stored = self.0.lock().unwrap();
+		let id = stored.len() as i64;
+		stored.push((id, dead_letter));
+		Ok(())
+	}
+}
+
+#[cfg_attr(not(feature = "local"), async_trait)]
+#[cfg_attr(feature = "local", async_trait(?Send))]
+impl TDeadLetterStore for InMemoryDeadLetterStore {
+	async fn dead_letters(&self) -> Result<Vec<(i64, DeadLetter)>, BaseError> {
+		Ok(self.0.lock().unwrap().clone())
+	}
+	async fn remove(&self, id: i64) -> Result<(), BaseError> {
+		self.0.lock().unwrap().retain(|(stored, _)| *stored != id);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_replay_drains_dead_letters_once_handler_is_fixed() {
+	//GIVEN
+	let store = InMemoryDeadLetterStore::default();
+	let event = InvoiceIssued { amount: 30 };
+	let report = MessageBus.handle_event_with_report(event.clone().to_message(), &Connection).await.unwrap();
+	assert_eq!(report.failed, 1);
+	store.send(DeadLetter { topic: "InvoiceIssued".into(), payload: serde_json::to_vec(&event).unwrap(), reason: "Ledger is down".into(), handler: None, metadata: Default::default() }).await.unwrap();
+	store.send(DeadLetter { topic: "InvoiceVoided".into(), payload: b"{}".to_vec(), reason: "Unknown topic".into(), handler: None, metadata: Default::default() }).await.unwrap();
+	let deserializers = EventDeserializers::default().register::<InvoiceIssued>("InvoiceIssued");
+
+	//WHEN
+	let failed_again = store.replay_dead_letters::<TestError, _>(|_| true, &deserializers, &MessageBus, &Connection).await.unwrap();
+	LEDGER_BROKEN.store(false, Ordering::SeqCst);
+	let fixed = store.replay_dead_letters::<TestError, _>(|dead_letter| dead_letter.topic == "InvoiceIssued", &deserializers, &MessageBus, &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(failed_again, DeadLetterReplay { replayed: 0, failed: 2 });
+	assert_eq!(fixed, DeadLetterReplay { replayed: 1, failed: 0 });
+	assert_eq!(BOOKED.load(Ordering::SeqCst), 30);
+	let remaining = store.dead_letters().await.unwrap();
+	assert_eq!(remaining.iter().map(|(_, dead_letter)| dead_letter.topic.as_str()).collect::<Vec<_>>(), vec!["InvoiceVoided"]);
+}
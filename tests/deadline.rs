@@ -0,0 +1,85 @@
+use ruva::*;
+use std::{
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct ImportOrders;
+impl TCommand for ImportOrders {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderImported {
+	id: usize,
+}
+
+static INDEXED: AtomicUsize = AtomicUsize::new(0);
+static BUDGETS: Mutex<Vec<Duration>> = Mutex::new(vec![]);
+
+struct ImportOrdersService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for ImportOrdersService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events((0..5).map(|id| OrderImported { id }.to_message()).collect());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, ImportOrders> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: ImportOrders) -> impl TCommandService<TestResponse, TestError> {
+		ImportOrdersService(context_manager)
+	}
+}
+
+#[event_handler(OrderImported)]
+async fn index_order(_event: OrderImported, context: AtomicContextManager) -> Result<(), TestError> {
+	BUDGETS.lock().unwrap().push(context.remaining().expect("Deadline must be given!"));
+	tokio::time::sleep(Duration::from_millis(60)).await;
+	INDEXED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_handling_of_queued_events_stops_once_deadline_of_request_passes() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_request_deadline(Duration::from_millis(150)));
+
+	//WHEN
+	let res = MessageBus.execute_and_wait(ImportOrders, &Connection).await;
+
+	//THEN
+	assert!(matches!(res, Err(TestError::BaseError(BaseError::DeadlineExceeded))));
+	// * Handler already running is not cut short, but no more events are dispatched after the deadline
+	let indexed = INDEXED.load(Ordering::SeqCst);
+	assert!((1..5).contains(&indexed), "{indexed} events were indexed");
+
+	// * Handlers see the budget shrinking as the request goes on
+	let budgets = BUDGETS.lock().unwrap();
+	assert_eq!(budgets.len(), indexed);
+	assert!(budgets.windows(2).all(|pair| pair[0] > pair[1]));
+	assert!(budgets[0] <= Duration::from_millis(150));
+}
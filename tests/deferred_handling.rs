@@ -0,0 +1,105 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicBool, Ordering},
+	time::Duration,
+};
+use tokio::sync::Notify;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder {
+	panics: bool,
+}
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	panics: bool,
+}
+
+struct PlaceOrderService(AtomicContextManager, PlaceOrder);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced { panics: self.1.panics }.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager, cmd)
+	}
+}
+
+static RELEASE: Notify = Notify::const_new();
+static NOTIFIED: AtomicBool = AtomicBool::new(false);
+
+#[event_handler(OrderPlaced)]
+async fn notify_customer(event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	if event.panics {
+		panic!("Mail server is unreachable!");
+	}
+	RELEASE.notified().await;
+	NOTIFIED.store(true, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_response_returns_before_event_handlers_finish() {
+	// * Tasks are spawned locally with `local` feature
+	tokio::task::LocalSet::new()
+		.run_until(async {
+			//WHEN
+			let res = tokio::time::timeout(Duration::from_secs(5), MessageBus.handle_deferred(PlaceOrder { panics: false }, &Connection)).await;
+
+			//THEN
+			assert!(res.expect("Response must not wait for the handlers!").is_ok());
+			assert!(!NOTIFIED.load(Ordering::SeqCst));
+
+			// * Handler runs to completion in the background once it is let go
+			RELEASE.notify_one();
+			tokio::time::timeout(Duration::from_secs(5), async {
+				while !NOTIFIED.load(Ordering::SeqCst) {
+					tokio::task::yield_now().await;
+				}
+			})
+			.await
+			.expect("Deferred handler must finish!");
+		})
+		.await;
+}
+
+#[tokio::test]
+async fn test_panic_in_deferred_handler_does_not_reach_caller() {
+	// * Tasks are spawned locally with `local` feature
+	tokio::task::LocalSet::new()
+		.run_until(async {
+			//WHEN
+			let res = MessageBus.handle_deferred(PlaceOrder { panics: true }, &Connection).await;
+
+			//THEN
+			assert!(res.is_ok());
+		})
+		.await;
+}
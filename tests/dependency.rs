@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static INITIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+mod empty {
+	ruva::create_dependency!();
+}
+
+mod with_fields {
+	use super::*;
+
+	pub struct Config {
+		pub service_name: String,
+	}
+
+	ruva::create_dependency!(
+		struct {
+			config: Config,
+			retries: usize,
+		}
+		init {
+			INITIALIZED.fetch_add(1, Ordering::SeqCst);
+			Dependency { config: Config { service_name: "account".into() }, retries: 3 }
+		}
+	);
+}
+
+#[test]
+fn test_create_dependency_without_fields() {
+	assert!(std::ptr::eq(empty::dependency(), empty::dependency()));
+}
+
+#[test]
+fn test_create_dependency_with_fields_initialized_once() {
+	//WHEN
+	let dependency = with_fields::dependency();
+	let again = with_fields::dependency();
+
+	//THEN
+	assert_eq!(dependency.config.service_name, "account");
+	assert_eq!(dependency.retries, 3);
+	assert!(std::ptr::eq(dependency, again));
+	assert_eq!(INITIALIZED.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,103 @@
+use ruva::*;
+use std::{sync::Mutex, time::Duration};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+#[derive(Debug, Clone)]
+struct ItemReserved {
+	warehouse: &'static str,
+}
+impl TEvent for ItemReserved {
+	fn metadata(&self) -> EventMetadata {
+		EventMetadata { aggregate_id: self.warehouse.into(), aggregate_name: "Warehouse".into(), topic: "ItemReserved".into(), version: 1, headers: Default::default() }
+	}
+	fn state(&self) -> String {
+		"{}".into()
+	}
+}
+
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+struct OrderEventHandler(AtomicContextManager);
+impl OrderEventHandler {
+	async fn notify_slowly(self, _event: OrderPlaced) -> Result<(), TestError> {
+		// * Would finish last if handlers ran concurrently
+		tokio::time::sleep(Duration::from_millis(20)).await;
+		LOG.lock().unwrap().push("notify".into());
+		Ok(())
+	}
+	async fn reserve(self, _event: OrderPlaced) -> Result<(), TestError> {
+		LOG.lock().unwrap().push("reserve".into());
+		let context = HandlerContext::from(self.0);
+		context.emit(ItemReserved { warehouse: "north" }).await?;
+		context.emit(ItemReserved { warehouse: "south" }).await?;
+		Ok(())
+	}
+	async fn project(self, event: ItemReserved) -> Result<(), TestError> {
+		// * Would be overtaken by the other warehouse if lanes ran concurrently
+		tokio::time::sleep(Duration::from_millis(if event.warehouse == "north" { 20 } else { 0 })).await;
+		LOG.lock().unwrap().push(format!("project {}", event.warehouse));
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	OrderEventHandler,
+	#[async]
+	OrderPlaced: [notify_slowly, reserve],
+	ItemReserved: [project],
+);
+
+#[tokio::test]
+async fn test_events_are_handled_in_order_on_the_task_of_the_request() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_aggregate_lanes().with_deterministic_execution());
+
+	//WHEN
+	let res = MessageBus.execute_and_forget(PlaceOrder, &Connection).await.unwrap();
+
+	//THEN
+	// * Events are already handled by the time the command returns
+	assert_eq!(*LOG.lock().unwrap(), vec!["notify", "reserve", "project north", "project south"]);
+	res.wait_until_event_processing_done().await.unwrap();
+}
@@ -0,0 +1,84 @@
+use ruva::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, PartialEq)]
+enum TestResponse {
+	Placed(u32),
+}
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+#[tokio::test]
+async fn test_unsubscribed_handler_no_longer_runs() {
+	//GIVEN
+	static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+	static SHIPPED: AtomicUsize = AtomicUsize::new(0);
+	let bus = DynamicMessageBus::<TestResponse, TestError>::default();
+	let notify = bus.subscribe_event(|_event: OrderPlaced, _context: AtomicContextManager| async {
+		NOTIFIED.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	});
+	bus.subscribe_event(|_event: OrderPlaced, _context: AtomicContextManager| async {
+		SHIPPED.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	});
+	bus.handle_event(OrderPlaced.to_message(), &Connection).await.unwrap();
+
+	//WHEN
+	assert!(bus.unsubscribe_event::<OrderPlaced>(notify));
+	bus.handle_event(OrderPlaced.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(NOTIFIED.load(Ordering::SeqCst), 1);
+	assert_eq!(SHIPPED.load(Ordering::SeqCst), 2);
+	assert!(!bus.unsubscribe_event::<OrderPlaced>(notify));
+}
+
+#[tokio::test]
+async fn test_replaced_command_handler_handles_the_command() {
+	//GIVEN
+	static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+	let bus = DynamicMessageBus::<TestResponse, TestError>::default();
+	bus.subscribe_event(|_event: OrderPlaced, _context: AtomicContextManager| async {
+		NOTIFIED.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	});
+	assert!(!bus.replace_command(|_command: PlaceOrder, _context: AtomicContextManager| async { Ok(TestResponse::Placed(1)) }));
+	assert_eq!(bus.execute(PlaceOrder, &Connection).await.unwrap(), TestResponse::Placed(1));
+
+	//WHEN
+	let replaced = bus.replace_command(|_command: PlaceOrder, context: AtomicContextManager| async move {
+		let mut context = Context::new(context);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse::Placed(2))
+	});
+
+	//THEN
+	assert!(replaced);
+	assert_eq!(bus.execute(PlaceOrder, &Connection).await.unwrap(), TestResponse::Placed(2));
+	// * Events raised by the command are handled with the handlers subscribed to the bus
+	assert_eq!(NOTIFIED.load(Ordering::SeqCst), 1);
+	assert!(bus.remove_command::<PlaceOrder>());
+	assert!(matches!(bus.execute(PlaceOrder, &Connection).await, Err(TestError::BaseError(BaseError::CommandNotFound(_)))));
+}
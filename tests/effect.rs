@@ -0,0 +1,99 @@
+use ruva::*;
+use std::sync::{Arc, Mutex};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder {
+	email: &'static str,
+}
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	email: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SendEmail {
+	to: &'static str,
+}
+impl TEffect for SendEmail {}
+
+struct PlaceOrderService(AtomicContextManager, PlaceOrder);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced { email: self.1.email }.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager, cmd)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn notify_customer(event: OrderPlaced, context: AtomicContextManager) -> Result<(), TestError> {
+	context.declare_effect(SendEmail { to: event.email });
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[derive(Default, Clone)]
+struct InMemoryMailer(Arc<Mutex<Vec<SendEmail>>>);
+
+#[async_trait]
+impl TEffectHandler<SendEmail> for InMemoryMailer {
+	async fn perform(&self, effect: SendEmail) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(effect);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_effect_declared_by_handler_is_asserted_without_performing_it() {
+	//GIVEN
+	let context_manager = Arc::new(ContextManager::new(&Connection));
+
+	//WHEN
+	notify_customer(OrderPlaced { email: "buyer@example.com" }, Arc::clone(&context_manager)).await.unwrap();
+
+	//THEN
+	let effects = context_manager.declared_effects();
+	assert_eq!(effects.len(), 1);
+	assert_eq!(effects[0].downcast_ref::<SendEmail>(), Some(&SendEmail { to: "buyer@example.com" }));
+}
+
+#[tokio::test]
+async fn test_declared_effect_reaches_its_handler_once_request_succeeds() {
+	//GIVEN
+	let mailer = InMemoryMailer::default();
+	MessageBus::configure(MessageBusConfig::default().with_effect_handler(mailer.clone()));
+
+	//WHEN
+	MessageBus.execute_and_wait(PlaceOrder { email: "buyer@example.com" }, &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(*mailer.0.lock().unwrap(), vec![SendEmail { to: "buyer@example.com" }]);
+}
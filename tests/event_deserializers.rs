@@ -0,0 +1,34 @@
+use ruva::*;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct InvoiceIssued {
+	invoice_id: i64,
+	amount: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct InvoicePaid {
+	invoice_id: i64,
+}
+
+#[test]
+fn test_payloads_round_trip_through_deserializers_registered_by_topic_const() {
+	//GIVEN
+	let deserializers = EventDeserializers::default().register_event::<InvoiceIssued>().merge(EventDeserializers::default().register_event::<InvoicePaid>());
+	let issued = InvoiceIssued { invoice_id: 1, amount: 100 };
+	let paid = InvoicePaid { invoice_id: 1 };
+
+	//WHEN
+	let deserialized_issued = deserializers.deserialize(InvoiceIssued::TOPIC, &TEvent::serialize(&issued, SerFormat::Json), SerFormat::Json).unwrap();
+	let deserialized_paid = deserializers.deserialize(&paid.outbox().topic, &paid.outbox().payload, SerFormat::Json).unwrap();
+
+	//THEN
+	let mut topics = deserializers.topics().collect::<Vec<_>>();
+	topics.sort();
+	assert_eq!(topics, vec!["InvoiceIssued", "InvoicePaid"]);
+	assert_eq!(deserialized_issued.downcast_ref::<InvoiceIssued>(), Some(&issued));
+	assert_eq!(deserialized_paid.downcast_ref::<InvoicePaid>(), Some(&paid));
+	assert!(matches!(deserializers.deserialize("InvoiceVoided", b"{}", SerFormat::Json), Err(BaseError::EventNotFound(_))));
+}
@@ -0,0 +1,106 @@
+use ruva::*;
+use std::{collections::HashMap, sync::Mutex};
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	#[headers]
+	#[serde(skip)]
+	headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct StockReserved {
+	#[headers]
+	#[serde(skip)]
+	headers: HashMap<String, String>,
+}
+
+struct TenantEnricher;
+impl TEventEnricher for TenantEnricher {
+	fn enrich(&self, event: &mut dyn TEvent, _context: &ContextManager) {
+		if let Some(headers) = event.headers_mut() {
+			headers.insert("tenant".into(), "bering".into());
+		}
+	}
+}
+
+/// Relies on tenant being stamped by the enricher registered before
+struct CorrelationEnricher;
+impl TEventEnricher for CorrelationEnricher {
+	fn enrich(&self, event: &mut dyn TEvent, _context: &ContextManager) {
+		if let Some(headers) = event.headers_mut() {
+			let correlation_id = format!("{}-1", headers.get("tenant").map(String::as_str).unwrap_or_default());
+			headers.insert("correlation_id".into(), correlation_id);
+		}
+	}
+}
+
+static HEADERS_HANDLED: Mutex<Vec<(String, HashMap<String, String>)>> = Mutex::new(Vec::new());
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced { headers: Default::default() }.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn reserve_stock(event: OrderPlaced, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	HEADERS_HANDLED.lock().unwrap().push((event.metadata().topic, event.headers));
+	context_manager.push_event(StockReserved { headers: Default::default() }.to_message()).await?;
+	Ok(())
+}
+
+#[event_handler(StockReserved)]
+async fn notify_customer(event: StockReserved, _context: AtomicContextManager) -> Result<(), TestError> {
+	HEADERS_HANDLED.lock().unwrap().push((event.metadata().topic, event.headers));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_enrichers_run_in_order_on_every_event() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_event_enricher(TenantEnricher).with_event_enricher(CorrelationEnricher));
+
+	//WHEN
+	MessageBus.execute_and_wait(PlaceOrder, &Connection).await.unwrap();
+
+	//THEN
+	let expected = HashMap::from([("tenant".to_string(), "bering".to_string()), ("correlation_id".to_string(), "bering-1".to_string())]);
+	let handled = HEADERS_HANDLED.lock().unwrap();
+	assert_eq!(*handled, vec![("OrderPlaced".to_string(), expected.clone()), ("StockReserved".to_string(), expected)]);
+}
@@ -0,0 +1,78 @@
+use futures::StreamExt;
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct StockReserved;
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+struct OrderEventHandler(AtomicContextManager);
+impl OrderEventHandler {
+	async fn reserve_stock(self, _event: OrderPlaced) -> Result<(), TestError> {
+		self.0.push_event(StockReserved.to_message()).await?;
+		Ok(())
+	}
+	async fn notify_warehouse(self, _event: StockReserved) -> Result<(), TestError> {
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	OrderEventHandler,
+	OrderPlaced: [reserve_stock],
+	StockReserved: [notify_warehouse],
+);
+
+#[tokio::test]
+async fn test_subscriber_observes_events_raised_by_command() {
+	//GIVEN
+	let events = MessageBus.subscribe();
+
+	//WHEN
+	MessageBus.execute_and_wait(PlaceOrder, &Connection).await.unwrap();
+
+	//THEN
+	let topics = events.take(2).map(|event| event.metadata().topic).collect::<Vec<_>>().await;
+	assert_eq!(topics, vec!["OrderPlaced", "StockReserved"]);
+}
@@ -0,0 +1,195 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Mutex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct SomethingHappened {
+	id: i64,
+}
+
+static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+static AUDITED: AtomicUsize = AtomicUsize::new(0);
+
+mod notification {
+	use super::*;
+
+	#[event_handler(SomethingHappened)]
+	async fn notify(event: SomethingHappened, _context: AtomicContextManager) -> Result<(), TestError> {
+		NOTIFIED.fetch_add(event.id as usize, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+mod audit {
+	use super::*;
+
+	#[event_handler(super::SomethingHappened)]
+	async fn audit(event: SomethingHappened, _context: AtomicContextManager) -> Result<(), TestError> {
+		AUDITED.fetch_add(event.id as usize, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderCreated;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderCancelled;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountCreated;
+
+static ORDER_LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[event_handler(OrderCreated)]
+async fn ship_order(_event: OrderCreated, _context: AtomicContextManager) -> Result<(), TestError> {
+	ORDER_LOG.lock().unwrap().push("ship OrderCreated".into());
+	Ok(())
+}
+
+#[event_handler("Order*")]
+async fn audit_order(event: std::sync::Arc<dyn TEvent>, _context: AtomicContextManager) -> Result<(), TestError> {
+	ORDER_LOG.lock().unwrap().push(format!("audit {}", event.metadata().topic));
+	Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct StockRequested {
+	quantity: usize,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct StockReserved {
+	quantity: usize,
+}
+
+static RESERVED: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(StockRequested)]
+async fn reserve_stock(event: StockRequested, context: HandlerContext) -> Result<(), TestError> {
+	assert!(!context.is_replaying());
+	context.emit(StockReserved { quantity: event.quantity }).await?;
+	Ok(())
+}
+
+#[event_handler(StockReserved)]
+async fn count_reserved(event: StockReserved, _context: AtomicContextManager) -> Result<(), TestError> {
+	RESERVED.fetch_add(event.quantity, Ordering::SeqCst);
+	Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentReceived {
+	amount: usize,
+}
+
+static REVIEWED: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+#[event_handler(PaymentReceived, filter = |event| event.amount >= 1_000)]
+async fn review_large_payment(event: PaymentReceived, _context: AtomicContextManager) -> Result<(), TestError> {
+	REVIEWED.lock().unwrap().push(event.amount);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_event_handlers_in_different_modules_subscribe_to_same_event() {
+	struct Connection;
+	impl TConnection for Connection {}
+
+	//GIVEN
+	let handlers = MessageBus.event_handler().get("SomethingHappened").expect("Handlers must be discovered!");
+	let EventHandlers::Sync(handlers) = handlers else { panic!("Discovered handlers must be registered as sync handlers!") };
+	assert_eq!(handlers.len(), 2);
+
+	//WHEN
+	let context_manager = std::sync::Arc::new(ContextManager::new(&Connection));
+	for handler in handlers {
+		handler(SomethingHappened { id: 3 }.to_message(), context_manager.clone()).await.unwrap();
+	}
+
+	//THEN
+	assert_eq!(NOTIFIED.load(Ordering::SeqCst), 3);
+	assert_eq!(AUDITED.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_pattern_event_handler_runs_after_exact_handlers() {
+	struct Connection;
+	impl TConnection for Connection {}
+
+	//WHEN
+	MessageBus.handle_event(OrderCreated.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(OrderCancelled.to_message(), &Connection).await.unwrap();
+	let unmatched = MessageBus.handle_event(AccountCreated.to_message(), &Connection).await;
+
+	//THEN
+	assert_eq!(*ORDER_LOG.lock().unwrap(), vec!["ship OrderCreated", "audit OrderCreated", "audit OrderCancelled"]);
+	assert!(matches!(unmatched, Err(TestError::BaseError(BaseError::NotFound))));
+}
+
+#[tokio::test]
+async fn test_event_handler_taking_handler_context_emits_event() {
+	struct Connection;
+	impl TConnection for Connection {}
+
+	//WHEN
+	let report = MessageBus.handle_event_with_report(StockRequested { quantity: 5 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(report.topics, vec!["StockRequested", "StockReserved"]);
+	assert_eq!(RESERVED.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn test_event_of_other_type_fails_handler_instead_of_panicking() {
+	struct Connection;
+	impl TConnection for Connection {}
+
+	//GIVEN
+	let handlers = MessageBus.event_handler().get("OrderCreated").unwrap();
+	let EventHandlers::Sync(handlers) = handlers else { panic!("Discovered handlers must be registered as sync handlers!") };
+
+	//WHEN
+	let result = handlers[0](AccountCreated.to_message(), std::sync::Arc::new(ContextManager::new(&Connection))).await;
+
+	//THEN
+	let Err(TestError::BaseError(BaseError::EventDowncastFailed { topic, expected_type })) = result else { panic!("Handler must fail with downcast error!") };
+	assert_eq!(topic, "AccountCreated");
+	assert!(expected_type.ends_with("OrderCreated"));
+}
+
+#[tokio::test]
+async fn test_filtered_event_handler_is_skipped_for_events_filter_rejects() {
+	struct Connection;
+	impl TConnection for Connection {}
+
+	//WHEN
+	let skipped = MessageBus.handle_event_with_report(PaymentReceived { amount: 10 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(PaymentReceived { amount: 5_000 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(*REVIEWED.lock().unwrap(), vec![5_000]);
+	// * Skipped handler is not a failure
+	assert_eq!((skipped.succeeded, skipped.failed), (1, 0));
+}
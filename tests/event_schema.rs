@@ -0,0 +1,48 @@
+#![cfg(feature = "schemars")]
+use ruva::schemars::JsonSchema;
+use ruva::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TEvent)]
+#[schemars(crate = "ruva::schemars")]
+#[internally_notifiable]
+struct ParcelDispatched {
+	parcel_id: i64,
+	carrier: String,
+	weight_grams: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, TEvent)]
+#[schemars(crate = "ruva::schemars")]
+#[internally_notifiable]
+struct ParcelDelivered {
+	parcel_id: i64,
+}
+
+#[test]
+fn test_schema_of_event_contains_its_fields() {
+	//WHEN
+	let schema = serde_json::to_value(ParcelDispatched::schema()).unwrap();
+
+	//THEN
+	assert_eq!(schema["title"], "ParcelDispatched");
+	let properties = schema["properties"].as_object().unwrap();
+	assert_eq!(properties.keys().map(String::as_str).collect::<Vec<_>>(), vec!["carrier", "parcel_id", "weight_grams"]);
+	assert_eq!(properties["parcel_id"]["type"], "integer");
+	assert_eq!(schema["required"], serde_json::json!(["carrier", "parcel_id"]));
+}
+
+#[test]
+fn test_registered_schemas_are_dumped_per_topic() {
+	//GIVEN
+	let dir = std::env::temp_dir().join(format!("ruva-event-schemas-{}", std::process::id()));
+	let schemas = EventSchemas::default().register_event::<ParcelDispatched>().merge(EventSchemas::default().register_event::<ParcelDelivered>());
+
+	//WHEN
+	let paths = schemas.dump(&dir).unwrap();
+
+	//THEN
+	assert_eq!(paths, vec![dir.join("ParcelDelivered.json"), dir.join("ParcelDispatched.json")]);
+	let dumped: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&paths[1]).unwrap()).unwrap();
+	assert_eq!(dumped, serde_json::to_value(schemas.get("ParcelDispatched").unwrap()).unwrap());
+	std::fs::remove_dir_all(dir).unwrap();
+}
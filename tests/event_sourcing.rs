@@ -0,0 +1,147 @@
+use ruva::*;
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex},
+};
+
+#[aggregate(Clone)]
+struct Counter {
+	#[adapter_ignore]
+	count: u64,
+	/// Sequence of the events applied while rebuilding
+	#[adapter_ignore]
+	replayed: Vec<u64>,
+}
+
+impl Counter {
+	fn increment(&mut self) {
+		self.count += 1;
+		self.raise_event(Arc::new(Incremented { seq: self.count }));
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct Incremented {
+	seq: u64,
+}
+
+impl TEventSourced for Counter {
+	type Snapshot = u64;
+
+	fn apply(&mut self, event: &dyn TEvent) {
+		match_event!(event, {
+			Incremented => |Incremented { seq }| {
+				self.count = *seq;
+				self.replayed.push(*seq);
+			},
+		});
+	}
+	fn snapshot(&self) -> u64 {
+		self.count
+	}
+	fn from_snapshot(count: u64) -> Self {
+		Self { count, ..Default::default() }
+	}
+}
+
+#[derive(Default)]
+struct InMemorySnapshotStore(Mutex<HashMap<i64, (u64, u64)>>);
+
+impl TSnapshotStore<u64, i64> for InMemorySnapshotStore {
+	async fn load(&self, id: &i64) -> Result<Option<(u64, u64)>, BaseError> {
+		Ok(self.0.lock().unwrap().get(id).copied())
+	}
+	async fn save(&self, id: &i64, snapshot: u64, version: u64) -> Result<(), BaseError> {
+		self.0.lock().unwrap().insert(*id, (snapshot, version));
+		Ok(())
+	}
+}
+
+#[derive(Default)]
+struct CounterRepository {
+	streams: HashMap<i64, Vec<Arc<dyn TEvent>>>,
+	snapshots: InMemorySnapshotStore,
+	events: VecDeque<Arc<dyn TEvent>>,
+}
+
+impl TSetCurrentEvents for CounterRepository {
+	fn set_current_events(&mut self, events: VecDeque<Arc<dyn TEvent>>) {
+		self.events.extend(events)
+	}
+}
+
+impl TEventSourcedRepository<Counter, i64> for CounterRepository {
+	type SnapshotStore = InMemorySnapshotStore;
+
+	async fn _load_events(&self, id: &i64, after_version: u64) -> Result<Vec<Arc<dyn TEvent>>, BaseError> {
+		Ok(self.streams.get(id).map(|stream| stream[after_version as usize..].to_vec()).unwrap_or_default())
+	}
+	async fn _append_events(&mut self, id: &i64, expected_version: u64, events: &[Arc<dyn TEvent>]) -> Result<(), BaseError> {
+		let stream = self.streams.entry(*id).or_default();
+		if stream.len() as u64 != expected_version {
+			return Err(BaseError::TransactionError);
+		}
+		stream.extend_from_slice(events);
+		Ok(())
+	}
+	fn snapshot_store(&self) -> &InMemorySnapshotStore {
+		&self.snapshots
+	}
+	fn snapshot_every(&self) -> Option<u64> {
+		Some(100)
+	}
+}
+
+#[tokio::test]
+async fn test_rebuild_applies_only_events_after_snapshot() {
+	//GIVEN
+	let mut repo = CounterRepository::default();
+	let mut counter = Counter::default();
+	(0..100).for_each(|_| counter.increment());
+	let version = repo.save(&1, &mut counter, 0).await.unwrap();
+	(0..5).for_each(|_| counter.increment());
+	let version = repo.save(&1, &mut counter, version).await.unwrap();
+
+	//WHEN
+	let (rebuilt, rebuilt_version) = repo.load(&1).await.unwrap();
+
+	//THEN
+	assert_eq!(version, 105);
+	assert_eq!(repo.snapshots.0.lock().unwrap()[&1], (100, 100));
+	assert_eq!(rebuilt.replayed, vec![101, 102, 103, 104, 105]);
+	assert_eq!((rebuilt.count, rebuilt_version), (105, 105));
+	assert_eq!(repo.events.len(), 105);
+}
+
+#[tokio::test]
+async fn test_save_on_stale_version_is_rejected() {
+	//GIVEN
+	let mut repo = CounterRepository::default();
+	let mut counter = Counter::default();
+	counter.increment();
+	repo.save(&1, &mut counter, 0).await.unwrap();
+
+	//WHEN
+	let (mut stale, _) = repo.load(&1).await.unwrap();
+	stale.increment();
+	let result = repo.save(&1, &mut stale, 0).await;
+
+	//THEN
+	assert!(matches!(result, Err(BaseError::TransactionError)));
+	assert!(matches!(repo.load(&2).await, Err(BaseError::NotFound)));
+}
+
+#[test]
+fn test_rebuild_folds_events_into_default_state() {
+	//GIVEN
+	let events: Vec<Arc<dyn TEvent>> = (1..=3).map(|seq| Incremented { seq }.to_message()).collect();
+
+	//WHEN
+	let counter = Counter::rebuild(events);
+
+	//THEN
+	assert_eq!(counter.count, 3);
+	assert_eq!(counter.replayed, vec![1, 2, 3]);
+	assert!(counter.events().is_empty());
+}
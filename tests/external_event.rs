@@ -30,3 +30,237 @@ fn test_declare_external_event() {
 	assert_eq!(metadata.topic, "SomeExternalEvent");
 	assert_eq!(event.state(), "{\"id\":1,\"name\":\"migo\",\"foo\":2}");
 }
+
+#[test]
+fn test_external_event_published_under_topic_overridden() {
+	#[aggregate(Serialize, Debug)]
+	pub struct Invoice {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[externally_notifiable(Invoice)]
+	#[topic("billing.invoice.created")]
+	pub struct InvoiceCreated {
+		#[identifier]
+		id: i32,
+	}
+
+	let event = InvoiceCreated { id: 1 }.to_message();
+
+	assert_eq!(event.metadata().topic, "billing.invoice.created");
+	assert_eq!(event.outbox().topic, "billing.invoice.created");
+}
+
+#[test]
+fn test_external_event_with_aggregate_id_opted_out_or_computed() {
+	#[aggregate(Serialize, Debug)]
+	pub struct Order {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[externally_notifiable(Order)]
+	#[aggregate_id = "none"]
+	pub struct DailyReportGenerated {
+		day: u32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[externally_notifiable(Order)]
+	#[aggregate_id(expr = "format!(\"{}-{}\", self.region, self.order_no)")]
+	pub struct OrderShipped {
+		region: String,
+		order_no: i32,
+	}
+
+	let report = DailyReportGenerated { day: 15 }.to_message();
+	let shipped = OrderShipped { region: "kr".into(), order_no: 7 }.to_message();
+
+	assert_eq!((report.metadata().aggregate_id, report.metadata().aggregate_name), ("".to_string(), "Order".to_string()));
+	// * Without aggregate id, partition is left to the broker
+	assert_eq!(report.outbox().partition_key(), None);
+	assert_eq!(shipped.metadata().aggregate_id, "kr-7");
+	assert_eq!(shipped.outbox().partition_key().as_deref(), Some("kr-7"));
+}
+
+#[test]
+fn test_external_event_with_headers() {
+	#[aggregate(Serialize, Debug)]
+	pub struct SomeAggregate {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[externally_notifiable(SomeAggregate)]
+	pub struct SomeExternalEvent {
+		#[identifier]
+		id: i32,
+		#[headers]
+		#[serde(skip)]
+		headers: std::collections::HashMap<String, String>,
+	}
+
+	let event = SomeExternalEvent { id: 1, ..Default::default() }.with_header("tenant", "bering").with_header("schema-version", "2").to_message();
+
+	let metadata = event.metadata();
+	assert_eq!(metadata.headers.len(), 2);
+	assert_eq!(metadata.headers["tenant"], "bering");
+
+	let outbox = event.outbox();
+	assert_eq!(outbox.data().unwrap(), serde_json::json!({"id": 1}));
+	let mut headers: std::collections::HashMap<String, String> = serde_json::from_str(&outbox.headers).unwrap();
+	assert_eq!(headers.remove(VERSION_HEADER).as_deref(), Some("1"));
+	assert_eq!(headers, metadata.headers);
+}
+
+#[test]
+fn test_outbox_payload_in_json_by_default() {
+	#[aggregate(Serialize, Debug)]
+	pub struct SomeAggregate {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[externally_notifiable(SomeAggregate)]
+	pub struct SomeExternalEvent {
+		#[identifier]
+		id: i32,
+	}
+
+	let outbox = SomeExternalEvent { id: 1 }.to_message().outbox();
+	assert_eq!(outbox.format, SerFormat::Json);
+	assert_eq!(outbox.payload, b"{\"id\":1}");
+}
+
+#[cfg(feature = "messagepack")]
+#[test]
+fn test_outbox_payload_in_messagepack() {
+	#[aggregate(Serialize, Debug)]
+	pub struct SomeAggregate {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, TEvent)]
+	#[externally_notifiable(SomeAggregate)]
+	#[ser_format(MessagePack)]
+	pub struct SomeExternalEvent {
+		#[identifier]
+		id: i32,
+		name: String,
+	}
+
+	let event = SomeExternalEvent { id: 1, name: "migo".into() };
+	let outbox = event.clone().to_message().outbox();
+	assert_eq!(outbox.format, SerFormat::MessagePack);
+	assert_eq!(outbox.data().unwrap(), serde_json::json!({"id": 1, "name": "migo"}));
+	assert_eq!(SerFormat::MessagePack.deserialize::<SomeExternalEvent>(&outbox.payload).unwrap(), event);
+}
+
+#[test]
+fn test_outbox_envelope_round_trip() {
+	#[aggregate(Serialize, Debug)]
+	pub struct SomeAggregate {
+		#[adapter_ignore]
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, TEvent)]
+	#[externally_notifiable(SomeAggregate)]
+	pub struct SomeExternalEvent {
+		#[identifier]
+		id: i32,
+		name: String,
+		#[headers]
+		#[serde(skip)]
+		headers: std::collections::HashMap<String, String>,
+	}
+
+	let event = SomeExternalEvent { id: 1, name: "migo".into(), ..Default::default() }.with_header("tenant", "bering");
+	let outbox = event.clone().to_message().outbox();
+
+	let envelope = outbox.envelope().unwrap();
+	assert_eq!(envelope.metadata.id, outbox.id);
+	assert_eq!(envelope.metadata.aggregate_id, "1");
+	assert_eq!(envelope.metadata.aggregate_name, "SomeAggregate");
+	assert_eq!(envelope.metadata.topic, "SomeExternalEvent");
+	assert_eq!(envelope.metadata.version, 1);
+	assert_eq!(envelope.metadata.create_dt, outbox.create_dt);
+	assert_eq!(envelope.data, serde_json::json!({"id": 1, "name": "migo"}));
+
+	let reconstructed = envelope.into_event::<SomeExternalEvent>().unwrap();
+	let reconstructed = reconstructed.downcast_ref::<SomeExternalEvent>().unwrap();
+	assert_eq!((reconstructed.id, &reconstructed.name), (event.id, &event.name));
+	assert_eq!(reconstructed.headers["tenant"], "bering");
+	assert_eq!(reconstructed.headers[VERSION_HEADER], "1");
+}
+
+#[test]
+fn test_events_of_same_aggregate_share_partition_key() {
+	#[aggregate(Serialize, Debug)]
+	pub struct Shipment {
+		#[adapter_ignore]
+		id: i64,
+	}
+
+	#[derive(Debug, Clone, Serialize, TEvent)]
+	#[externally_notifiable(Shipment)]
+	pub struct ShipmentPacked {
+		#[identifier]
+		id: i64,
+	}
+
+	#[derive(Debug, Clone, Serialize, TEvent)]
+	#[externally_notifiable(Shipment)]
+	pub struct ShipmentDispatched {
+		#[identifier]
+		id: i64,
+		#[partition_key]
+		warehouse: String,
+	}
+
+	//GIVEN
+	let packed = ShipmentPacked { id: 1 };
+	let packed_again = ShipmentPacked { id: 1 };
+	let dispatched = ShipmentDispatched { id: 1, warehouse: "north".into() };
+
+	//THEN
+	assert_eq!(packed.partition_key(), Some("1".to_string()));
+	assert_eq!(packed.partition_key(), packed_again.partition_key());
+	assert_eq!(packed.outbox().partition_key(), packed_again.outbox().partition_key());
+	// * Partition key other than aggregate id is carried by outbox as header
+	assert!(!packed.outbox().headers.contains(PARTITION_KEY_HEADER));
+	assert_eq!(dispatched.outbox().partition_key().as_deref(), Some("north"));
+	assert_eq!(dispatched.outbox().aggregate_id, "1");
+}
+
+#[test]
+fn test_unserializable_event_fails_with_serialization_error() {
+	#[aggregate(Serialize, Debug)]
+	pub struct Warehouse {
+		#[adapter_ignore]
+		id: i64,
+	}
+
+	#[derive(Debug, Clone, Serialize, TEvent)]
+	#[externally_notifiable(Warehouse)]
+	pub struct StockCounted {
+		#[identifier]
+		id: i64,
+		// * Json allows only string keys for map
+		stocks: std::collections::HashMap<(i64, i64), i64>,
+	}
+
+	//GIVEN
+	let event = StockCounted { id: 1, stocks: [((1, 2), 10)].into_iter().collect() };
+
+	//THEN
+	assert!(matches!(event.try_state(), Err(BaseError::SerializationError(_))));
+	assert!(matches!(event.try_serialize(SerFormat::Json), Err(BaseError::SerializationError(_))));
+	assert!(matches!(event.try_outbox(), Err(BaseError::SerializationError(_))));
+}
@@ -0,0 +1,65 @@
+use ruva::*;
+use std::sync::Mutex;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+static EXECUTED: Mutex<Vec<&'static str>> = Mutex::new(vec![]);
+
+struct OrderEventHandler;
+impl OrderEventHandler {
+	async fn notify_customer(self, _event: OrderPlaced) -> Result<(), TestError> {
+		EXECUTED.lock().unwrap().push("notify_customer");
+		Ok(())
+	}
+	async fn send_invoice(self, _event: OrderPlaced) -> Result<(), TestError> {
+		EXECUTED.lock().unwrap().push("send_invoice");
+		Ok(())
+	}
+	async fn update_read_model(self, _event: OrderPlaced) -> Result<(), TestError> {
+		EXECUTED.lock().unwrap().push("update_read_model");
+		Ok(())
+	}
+}
+
+#[event_handler(OrderPlaced, order = -10)]
+async fn reserve_stock(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	EXECUTED.lock().unwrap().push("reserve_stock");
+	Ok(())
+}
+
+#[event_handler(OrderPlaced)]
+async fn archive(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	EXECUTED.lock().unwrap().push("archive");
+	Ok(())
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| OrderEventHandler,
+	OrderPlaced: [notify_customer, #[order(5)] send_invoice, #[order(-1)] update_read_model],
+);
+
+#[tokio::test]
+async fn test_handlers_run_in_order_regardless_of_declaration() {
+	//WHEN
+	MessageBus.handle_event(OrderPlaced.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	// * Declared ones come first among the handlers of the same order
+	assert_eq!(*EXECUTED.lock().unwrap(), vec!["reserve_stock", "update_read_model", "notify_customer", "archive", "send_invoice"]);
+}
@@ -0,0 +1,86 @@
+use ruva::*;
+use std::sync::{Arc, Mutex};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderShipped {
+	order_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderReturned {
+	order_id: i64,
+}
+
+struct OrderEventHandler;
+impl OrderEventHandler {
+	async fn notify_customer(self, _event: OrderShipped) -> Result<(), TestError> {
+		Ok(())
+	}
+	async fn update_ledger(self, _event: OrderShipped) -> Result<(), TestError> {
+		Err(TestError::DatabaseError("Ledger is down".into()))
+	}
+	async fn restock(self, _event: OrderReturned) -> Result<(), TestError> {
+		Err(TestError::DatabaseError("Warehouse is down".into()))
+	}
+	async fn refund(self, _event: OrderReturned) -> Result<(), TestError> {
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| OrderEventHandler,
+	#[async]
+	OrderShipped: [notify_customer, update_ledger],
+	OrderReturned: [restock, refund],
+);
+
+#[derive(Default, Clone)]
+struct InMemoryDeadLetterSink(Arc<Mutex<Vec<DeadLetter>>>);
+
+#[async_trait]
+impl TDeadLetterSink for InMemoryDeadLetterSink {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(dead_letter);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_only_failed_handler_is_dead_lettered() {
+	//GIVEN
+	let sink = InMemoryDeadLetterSink::default();
+	MessageBus::configure(MessageBusConfig::default().with_dead_letter_sink(sink.clone()));
+
+	//WHEN
+	let shipped = MessageBus.handle_event_with_report(OrderShipped { order_id: 1 }.to_message(), &Connection).await.unwrap();
+	let returned = MessageBus.handle_event_with_report(OrderReturned { order_id: 2 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	// * Handlers run concurrently
+	assert_eq!((shipped.succeeded, shipped.failed), (1, 1));
+	assert_eq!(shipped.failed_handlers, vec![("OrderShipped".to_string(), 1)]);
+	// * Handlers run one after another
+	assert_eq!((returned.succeeded, returned.failed), (1, 1));
+	assert_eq!(returned.failed_handlers, vec![("OrderReturned".to_string(), 0)]);
+
+	let dead_letters = sink.0.lock().unwrap().clone();
+	assert_eq!(dead_letters.iter().map(|dead_letter| (dead_letter.topic.as_str(), dead_letter.handler)).collect::<Vec<_>>(), vec![("OrderShipped", Some(1)), ("OrderReturned", Some(0))]);
+	assert_eq!(dead_letters[0].payload, br#"{"order_id":1}"#);
+	assert!(dead_letters[0].reason.contains("Ledger is down"));
+}
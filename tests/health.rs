@@ -0,0 +1,88 @@
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+#[derive(Debug)]
+struct ShipOrder;
+impl TCommand for ShipOrder {}
+
+struct ShipOrderService;
+impl TCommandService<TestResponse, TestError> for ShipOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, ShipOrder> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: ShipOrder) -> impl TCommandService<TestResponse, TestError> {
+		ShipOrderService
+	}
+}
+
+#[aggregate(Serialize, Debug)]
+struct Order {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[externally_notifiable(Order)]
+struct OrderShipped {
+	#[identifier]
+	id: i64,
+}
+
+#[event_handler(OrderShipped)]
+async fn notify_customer(_event: OrderShipped, _context: AtomicContextManager) -> Result<(), TestError> {
+	Ok(())
+}
+
+init_event_handler!(TestError);
+init_dyn_command_handler!(TestResponse, TestError, ShipOrder);
+
+/// Broker that goes down after acknowledging the first two outboxes
+struct FlakyPublisher;
+#[async_trait]
+impl TOutBoxPublisher for FlakyPublisher {
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		match outbox.aggregate_id.as_str() {
+			"3" => Err(BaseError::MessageBrokerError("Connection refused".into())),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[tokio::test]
+async fn test_health_reports_outboxes_staged_but_not_relayed() {
+	//GIVEN
+	let mut outboxes = [1, 2, 3].map(|id| OrderShipped { id }.outbox());
+	MessageBus::relay_monitor().staged(outboxes.len());
+	let before_relay = MessageBus.health();
+
+	//WHEN
+	let result = FlakyPublisher.publish_all(&mut outboxes).await;
+
+	//THEN
+	assert!(result.is_err());
+	assert_eq!(before_relay.outbox_backlog, 3);
+	assert_eq!(before_relay.last_relay_tick, None);
+
+	let health = MessageBus.health();
+	assert_eq!(health.outbox_backlog, 1);
+	assert!(health.last_relay_tick.is_some());
+	assert_eq!(health.command_handlers, 1);
+	assert_eq!(health.event_handlers, 1);
+	assert!(!health.shutting_down);
+}
@@ -0,0 +1,74 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicBool, AtomicUsize, Ordering},
+	Arc,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	quantity: usize,
+}
+
+static INVENTORY_DOWN: AtomicBool = AtomicBool::new(true);
+static DECREMENTED: AtomicUsize = AtomicUsize::new(0);
+static METRICS_ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(OrderPlaced, delivery = Delivery::AtLeastOnce)]
+async fn decrement_inventory(event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	if INVENTORY_DOWN.load(Ordering::SeqCst) {
+		return Err(TestError::DatabaseError("Inventory is unreachable!".into()));
+	}
+	DECREMENTED.fetch_add(event.quantity, Ordering::SeqCst);
+	Ok(())
+}
+
+#[event_handler(OrderPlaced)]
+async fn record_metrics(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	METRICS_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+	Err(TestError::DatabaseError("Metrics server is unreachable!".into()))
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_at_least_once_failure_is_kept_for_redelivery_while_at_most_once_failure_is_dropped() {
+	//GIVEN
+	let inbox = InMemoryInbox::default();
+	MessageBus::configure(MessageBusConfig::default().with_inbox(inbox.clone()));
+
+	//WHEN
+	MessageBus.handle_event(OrderPlaced { quantity: 3 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	// * Only the event of the at-least-once handler is kept
+	let pending = inbox.pending().await.unwrap();
+	assert_eq!(pending.len(), 1);
+	assert!(pending[0].handler.ends_with("decrement_inventory"));
+	assert_eq!(pending[0].attempts, 1);
+
+	// * Redelivery runs the at-least-once handler alone until it succeeds
+	let deserializers = EventDeserializers::default().register_event::<OrderPlaced>();
+	let redelivery = MessageBus.redeliver(&deserializers, 5, &Connection).await.unwrap();
+	assert_eq!(redelivery, Redelivery { failed: 1, ..Default::default() });
+
+	INVENTORY_DOWN.store(false, Ordering::SeqCst);
+	let redelivery = MessageBus.redeliver(&deserializers, 5, &Connection).await.unwrap();
+	assert_eq!(redelivery, Redelivery { redelivered: 1, ..Default::default() });
+	assert_eq!(DECREMENTED.load(Ordering::SeqCst), 3);
+	assert!(inbox.pending().await.unwrap().is_empty());
+	assert_eq!(METRICS_ATTEMPTS.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,78 @@
+use ruva::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+mod dependencies {
+	use super::*;
+
+	pub struct Mailer;
+	pub struct Pool;
+
+	pub fn mailer() -> Mailer {
+		Mailer
+	}
+
+	/// Database can't be reached
+	pub fn pool() -> Result<Pool, BaseError> {
+		Err(BaseError::DatabaseError("Connection refused".into()))
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderRefunded;
+
+static MAILED: AtomicUsize = AtomicUsize::new(0);
+static REFUNDED: AtomicUsize = AtomicUsize::new(0);
+
+struct OrderEventHandler;
+impl OrderEventHandler {
+	async fn send_receipt(self, _event: OrderPlaced, _mailer: dependencies::Mailer) -> Result<(), TestError> {
+		MAILED.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	}
+	async fn record_refund(self, _event: OrderRefunded, _mailer: dependencies::Mailer, _pool: dependencies::Pool) -> Result<(), TestError> {
+		REFUNDED.fetch_add(1, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| OrderEventHandler,
+	OrderPlaced: [send_receipt => (mailer)],
+	OrderRefunded: [record_refund => (mailer, pool)],
+);
+
+#[tokio::test]
+async fn test_failing_injectable_surfaces_as_handler_error() {
+	//GIVEN
+	let handlers = MessageBus.event_handler().get("OrderRefunded").unwrap();
+	let EventHandlers::Sync(handlers) = handlers else { panic!("Handlers must be registered as sync handlers!") };
+
+	//WHEN
+	let result = handlers[0](OrderRefunded.to_message(), std::sync::Arc::new(ContextManager::new(&Connection))).await;
+	let placed = MessageBus.handle_event_with_report(OrderPlaced.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert!(matches!(result, Err(TestError::DatabaseError(_))));
+	assert_eq!(REFUNDED.load(Ordering::SeqCst), 0);
+	assert_eq!((placed.succeeded, placed.failed), (1, 0));
+	assert_eq!(MAILED.load(Ordering::SeqCst), 1);
+}
@@ -6,6 +6,7 @@ use ruva::*;
 fn test_declare_internal_event() {
 	#[aggregate]
 	#[derive(Debug, Clone, Serialize, Default)]
+	#[allow(dead_code)]
 	pub struct SomeAggregate {
 		#[adapter_ignore]
 		id: i32,
@@ -28,3 +29,90 @@ fn test_declare_internal_event() {
 	assert_eq!(metadata.aggregate_name, "");
 	assert_eq!(metadata.topic, "SomeInternalEvent");
 }
+
+#[test]
+fn test_topic_is_referenceable_from_event_type() {
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[internally_notifiable]
+	pub struct SomeEvent {
+		id: i32,
+	}
+
+	assert_eq!(SomeEvent::TOPIC, "SomeEvent");
+	assert_eq!(<SomeEvent as TEvent>::topic(), SomeEvent::TOPIC);
+	assert_eq!(SomeEvent { id: 1 }.to_message().metadata().topic, SomeEvent::TOPIC);
+}
+
+#[test]
+fn test_declare_prioritized_event() {
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[internally_notifiable]
+	#[priority(10)]
+	pub struct UrgentEvent {
+		id: i32,
+	}
+
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[internally_notifiable]
+	pub struct OrdinaryEvent {
+		id: i32,
+	}
+
+	assert_eq!(UrgentEvent { id: 1 }.to_message().priority(), 10);
+	assert_eq!(OrdinaryEvent { id: 1 }.to_message().priority(), 0);
+}
+
+#[test]
+fn test_declare_versioned_event() {
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[internally_notifiable]
+	#[version(3)]
+	pub struct EvolvedEvent {
+		id: i32,
+	}
+
+	let event = EvolvedEvent { id: 1 }.to_message();
+	assert_eq!(event.version(), 3);
+	assert_eq!(event.metadata().version, 3);
+}
+
+#[test]
+fn test_declare_event_with_topic_overridden() {
+	#[derive(Debug, Clone, Serialize, Default, TEvent)]
+	#[internally_notifiable]
+	#[topic("billing.invoice.created")]
+	pub struct InvoiceCreated {
+		id: i32,
+	}
+
+	assert_eq!(InvoiceCreated::TOPIC, "billing.invoice.created");
+	assert_eq!(<InvoiceCreated as TEvent>::topic(), "billing.invoice.created");
+	assert_eq!(InvoiceCreated { id: 1 }.to_message().metadata().topic, "billing.invoice.created");
+}
+
+#[test]
+fn test_declare_event_with_event_attribute() {
+	#[event]
+	#[internally_notifiable]
+	#[topic("sales.order.placed")]
+	pub struct OrderPlaced {
+		id: i32,
+	}
+
+	// * Derive already given is not added again, while derives given as arguments are added
+	#[event(PartialEq)]
+	#[derive(Debug)]
+	#[internally_notifiable]
+	pub struct OrderCancelled {
+		id: i32,
+	}
+
+	let event = OrderPlaced { id: 1 };
+	let restored: OrderPlaced = serde_json::from_str(&event.clone().to_message().state()).unwrap();
+	assert_eq!(restored.id, 1);
+	assert_eq!(format!("{:?}", restored), "OrderPlaced { id: 1 }");
+	assert!(restored.to_message().internally_notifiable());
+	assert_eq!(OrderPlaced::TOPIC, "sales.order.placed");
+
+	assert_eq!(OrderCancelled { id: 2 }, OrderCancelled { id: 2 }.clone());
+}
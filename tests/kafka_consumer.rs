@@ -0,0 +1,199 @@
+#![cfg(feature = "kafka")]
+
+use ruva::*;
+use std::{
+	collections::VecDeque,
+	sync::{
+		atomic::{AtomicI64, Ordering},
+		Mutex,
+	},
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+#[version(2)]
+struct AccountCreated {
+	id: i64,
+	#[headers]
+	#[serde(skip)]
+	headers: std::collections::HashMap<String, String>,
+}
+
+static CREATED: AtomicI64 = AtomicI64::new(0);
+
+#[event_handler(AccountCreated)]
+async fn on_account_created(event: AccountCreated, _context: AtomicContextManager) -> Result<(), TestError> {
+	assert_eq!(event.headers["tenant"], "bering");
+	CREATED.fetch_add(event.id, Ordering::SeqCst);
+	Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct AccountArchived {
+	id: i64,
+	#[headers]
+	#[serde(skip)]
+	headers: std::collections::HashMap<String, String>,
+}
+
+static ARCHIVED: Mutex<Vec<(i64, String, String)>> = Mutex::new(Vec::new());
+
+#[event_handler(AccountArchived)]
+async fn on_account_archived(event: AccountArchived, _context: AtomicContextManager) -> Result<(), TestError> {
+	ARCHIVED.lock().unwrap().push((event.id, event.headers["reason"].clone(), event.headers["tenant"].clone()));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Default)]
+struct MockConsumer {
+	records: Mutex<VecDeque<ConsumedRecord>>,
+	committed: Mutex<Vec<i64>>,
+}
+
+#[async_trait]
+impl TKafkaConsumer for &'static MockConsumer {
+	fn subscribe(&self, _topics: &[&str]) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn recv(&self) -> Result<ConsumedRecord, BaseError> {
+		self.records.lock().unwrap().pop_front().ok_or(BaseError::MessageBrokerError("No more record".into()))
+	}
+	async fn commit(&self, record: &ConsumedRecord) -> Result<(), BaseError> {
+		self.committed.lock().unwrap().push(record.offset);
+		Ok(())
+	}
+}
+
+#[derive(Default)]
+struct MockDeadLetterSink(std::sync::Arc<Mutex<Vec<DeadLetter>>>);
+
+#[async_trait]
+impl TDeadLetterSink for MockDeadLetterSink {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(dead_letter);
+		Ok(())
+	}
+}
+
+fn record(topic: &str, offset: i64, payload: &str) -> ConsumedRecord {
+	let headers = [("tenant".to_string(), "bering".to_string()), (VERSION_HEADER.to_string(), "2".to_string())].into();
+	ConsumedRecord { topic: topic.into(), partition: 0, offset, payload: payload.as_bytes().to_vec(), headers }
+}
+
+#[tokio::test]
+async fn test_kafka_consumer_driver_feeds_events_into_bus() {
+	//GIVEN
+	let consumer: &'static MockConsumer = Box::leak(Box::default());
+	let mut v1_record = record("AccountCreated", 2, r#"{"account_id":4}"#);
+	v1_record.headers.insert(VERSION_HEADER.into(), "1".into());
+	consumer.records.lock().unwrap().extend([record("AccountCreated", 0, r#"{"id":3}"#), record("AccountCreated", 1, "malformed"), v1_record, record("AccountDeleted", 3, r#"{"id":4}"#)]);
+	let dead_letters = MockDeadLetterSink::default();
+	let stored = dead_letters.0.clone();
+	let upcaster = Upcaster::default().register("AccountCreated", 1, |mut payload| {
+		payload["id"] = payload["account_id"].take();
+		payload
+	});
+	let driver = KafkaConsumerDriver::new(consumer, &Connection, dead_letters).register::<AccountCreated>("AccountCreated").upcaster(upcaster);
+
+	//WHEN
+	let result = driver.run::<TestError>(&MessageBus).await;
+
+	//THEN
+	assert!(matches!(result, Err(TestError::BaseError(BaseError::MessageBrokerError(_)))));
+	assert_eq!(CREATED.load(Ordering::SeqCst), 7);
+	assert_eq!(*consumer.committed.lock().unwrap(), vec![0, 1, 2, 3]);
+
+	let stored = stored.lock().unwrap();
+	assert_eq!(stored.iter().map(|d| d.topic.as_str()).collect::<Vec<_>>(), vec!["AccountCreated", "AccountDeleted"]);
+	assert_eq!(stored[0].payload, b"malformed");
+}
+
+/// Driver with `strategy` over a malformed record followed by a record of unregistered topic, returning what it ended with
+async fn consume_malformed(strategy: DeserializationErrorStrategy) -> (Result<(), TestError>, &'static MockConsumer, Vec<DeadLetter>) {
+	let consumer: &'static MockConsumer = Box::leak(Box::default());
+	consumer.records.lock().unwrap().extend([record("AccountCreated", 0, "malformed"), record("AccountDeleted", 1, r#"{"id":4}"#)]);
+	let dead_letters = MockDeadLetterSink::default();
+	let stored = dead_letters.0.clone();
+	let driver = KafkaConsumerDriver::new(consumer, &Connection, dead_letters).register::<AccountCreated>("AccountCreated").on_deserialization_error(strategy);
+
+	let result = driver.run::<TestError>(&MessageBus).await;
+	let stored = stored.lock().unwrap().clone();
+	(result, consumer, stored)
+}
+
+#[tokio::test]
+async fn test_malformed_record_is_skipped() {
+	//WHEN
+	let (result, consumer, stored) = consume_malformed(DeserializationErrorStrategy::SkipAndLog).await;
+
+	//THEN
+	assert!(matches!(result, Err(TestError::BaseError(BaseError::MessageBrokerError(_)))));
+	assert_eq!(*consumer.committed.lock().unwrap(), vec![0, 1]);
+	assert!(stored.is_empty());
+}
+
+#[tokio::test]
+async fn test_malformed_record_is_dead_lettered_with_its_offset() {
+	//WHEN
+	let (result, consumer, stored) = consume_malformed(DeserializationErrorStrategy::DeadLetter).await;
+
+	//THEN
+	assert!(matches!(result, Err(TestError::BaseError(BaseError::MessageBrokerError(_)))));
+	assert_eq!(*consumer.committed.lock().unwrap(), vec![0, 1]);
+	assert_eq!(stored[0].payload, b"malformed");
+	assert!(stored[0].reason.contains("DeserializationError"));
+	assert_eq!(stored[0].metadata["partition"], "0");
+	assert_eq!(stored[0].metadata["offset"], "0");
+	assert_eq!(stored[0].metadata["tenant"], "bering");
+}
+
+#[tokio::test]
+async fn test_malformed_record_halts_consumer() {
+	//WHEN
+	let (result, consumer, stored) = consume_malformed(DeserializationErrorStrategy::Halt).await;
+
+	//THEN
+	assert!(matches!(result, Err(TestError::BaseError(BaseError::DeserializationError(_)))));
+	assert!(consumer.committed.lock().unwrap().is_empty());
+	// * Records behind it are left unconsumed
+	assert_eq!(consumer.records.lock().unwrap().len(), 1);
+	assert!(stored.is_empty());
+}
+
+#[tokio::test]
+async fn test_batched_record_is_split_into_its_events() {
+	//GIVEN
+	let outboxes = [(1, "dormant"), (2, "closed")]
+		.map(|(id, reason)| OutBox::new(id.to_string(), "Account".into(), "AccountArchived".into(), format!(r#"{{"id":{id}}}"#), [("reason".to_string(), reason.to_string())].into()));
+	let batch = OutBox::batch(outboxes.into()).unwrap();
+	let mut headers: std::collections::HashMap<String, String> = serde_json::from_str(&batch.headers).unwrap();
+	headers.insert("tenant".into(), "bering".into());
+	let consumer: &'static MockConsumer = Box::leak(Box::default());
+	consumer.records.lock().unwrap().push_back(ConsumedRecord { topic: "AccountArchived".into(), partition: 0, offset: 0, payload: batch.payload, headers });
+	let driver = KafkaConsumerDriver::new(consumer, &Connection, MockDeadLetterSink::default()).register::<AccountArchived>("AccountArchived");
+
+	//WHEN
+	driver.consume_one::<TestError>(&MessageBus).await.unwrap();
+
+	//THEN
+	// * Each event keeps headers of its own envelope, along with native ones of the record
+	let archived = ARCHIVED.lock().unwrap().clone();
+	assert_eq!(archived, vec![(1, "dormant".into(), "bering".into()), (2, "closed".into(), "bering".into())]);
+	assert_eq!(*consumer.committed.lock().unwrap(), vec![0]);
+}
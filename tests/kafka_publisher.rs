@@ -0,0 +1,44 @@
+#![cfg(feature = "kafka")]
+
+use ruva::*;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct SeatReserved {
+	seat: String,
+	#[partition_key]
+	flight: String,
+}
+
+#[derive(Default)]
+struct MockProducer {
+	sent: Mutex<Vec<ProducedRecord>>,
+}
+
+#[async_trait]
+impl TKafkaProducer for &'static MockProducer {
+	async fn send(&self, record: ProducedRecord) -> Result<(), BaseError> {
+		self.sent.lock().unwrap().push(record);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_kafka_publisher_keys_records_by_partition_key() {
+	//GIVEN
+	let producer: &'static MockProducer = Box::leak(Box::default());
+	let publisher = KafkaPublisher::new(producer);
+	let mut outboxes = [("1A", "KE001"), ("1B", "KE001"), ("7C", "KE002")].map(|(seat, flight)| SeatReserved { seat: seat.into(), flight: flight.into() }.outbox());
+
+	//WHEN
+	publisher.publish_all(&mut outboxes).await.unwrap();
+
+	//THEN
+	assert!(outboxes.iter().all(|outbox| outbox.processed));
+	let sent = producer.sent.lock().unwrap();
+	assert_eq!(sent.iter().map(|record| record.key.as_deref()).collect::<Vec<_>>(), vec![Some("KE001"), Some("KE001"), Some("KE002")]);
+	assert_eq!(sent[0].topic, "SeatReserved");
+	assert_eq!(sent[0].payload, br#"{"seat":"1A","flight":"KE001"}"#);
+	assert_eq!(sent[0].headers[FORMAT_HEADER], "json");
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "local")]
+
+use ruva::*;
+use std::{
+	cell::RefCell,
+	rc::Rc,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PriceRequested {
+	sku: &'static str,
+}
+
+static QUOTED: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	static PRICES: Rc<RefCell<Vec<&'static str>>> = Rc::default();
+}
+
+/// Holds `Rc` across `.await`, which makes its future `!Send`
+#[event_handler(PriceRequested)]
+async fn quote_price(event: PriceRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	let cache = PRICES.with(Rc::clone);
+	tokio::task::yield_now().await;
+	cache.borrow_mut().push(event.sku);
+	QUOTED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_handler_that_is_not_send_runs_on_local_set() {
+	//GIVEN
+	let local = tokio::task::LocalSet::new();
+
+	//WHEN
+	local.run_until(MessageBus.handle_event(PriceRequested { sku: "A-1" }.to_message(), &Connection)).await.unwrap();
+
+	//THEN
+	assert_eq!(QUOTED.load(Ordering::SeqCst), 1);
+	assert_eq!(*PRICES.with(Rc::clone).borrow(), vec!["A-1"]);
+}
@@ -0,0 +1,586 @@
+use ruva::*;
+use std::{
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering},
+		Arc,
+	},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+/// Sets flag when dropped, to tell if future holding it was cancelled
+struct DropGuard(&'static AtomicBool);
+impl Drop for DropGuard {
+	fn drop(&mut self) {
+		self.0.store(true, Ordering::SeqCst);
+	}
+}
+
+#[derive(Debug)]
+struct SlowCommand;
+impl TCommand for SlowCommand {}
+
+#[derive(Debug)]
+struct QuickCommand;
+impl TCommand for QuickCommand {}
+
+static SLOW_COMMAND_CANCELLED: AtomicBool = AtomicBool::new(false);
+
+struct SleepingService(Duration, Option<&'static AtomicBool>);
+impl TCommandService<TestResponse, TestError> for SleepingService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let _guard = self.1.map(DropGuard);
+		tokio::time::sleep(self.0).await;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, SlowCommand> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: SlowCommand) -> impl TCommandService<TestResponse, TestError> {
+		SleepingService(Duration::from_secs(10), Some(&SLOW_COMMAND_CANCELLED))
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, QuickCommand> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: QuickCommand) -> impl TCommandService<TestResponse, TestError> {
+		SleepingService(Duration::from_millis(10), None)
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct SomethingHappened;
+
+static STUCK_HANDLER_CANCELLED: AtomicBool = AtomicBool::new(false);
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(SomethingHappened)]
+async fn stuck_handler(_event: SomethingHappened, _context: AtomicContextManager) -> Result<(), TestError> {
+	let _guard = DropGuard(&STUCK_HANDLER_CANCELLED);
+	std::future::pending::<()>().await;
+	Ok(())
+}
+
+#[event_handler(SomethingHappened)]
+async fn quick_handler(_event: SomethingHappened, _context: AtomicContextManager) -> Result<(), TestError> {
+	HANDLED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+#[derive(Debug)]
+struct ImportRecord(i64);
+impl TCommand for ImportRecord {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct RecordImported;
+
+static IMPORTED: AtomicUsize = AtomicUsize::new(0);
+static IMPORTED_WHEN_HANDLED: std::sync::Mutex<Vec<usize>> = std::sync::Mutex::new(Vec::new());
+
+struct ImportService(AtomicContextManager, i64);
+impl TCommandService<TestResponse, TestError> for ImportService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		if self.1 < 0 {
+			return Err(BaseError::NotFound.into());
+		}
+		IMPORTED.fetch_add(1, Ordering::SeqCst);
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![RecordImported.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, ImportRecord> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: ImportRecord) -> impl TCommandService<TestResponse, TestError> {
+		ImportService(context_manager, cmd.0)
+	}
+}
+
+#[event_handler(RecordImported)]
+async fn on_record_imported(_event: RecordImported, _context: AtomicContextManager) -> Result<(), TestError> {
+	IMPORTED_WHEN_HANDLED.lock().unwrap().push(IMPORTED.load(Ordering::SeqCst));
+	Ok(())
+}
+
+#[derive(Debug)]
+struct OpenAccount;
+impl TCommand for OpenAccount {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountOpened;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct WelcomeMailRequested;
+
+struct OpenAccountService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for OpenAccountService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![AccountOpened.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, OpenAccount> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: OpenAccount) -> impl TCommandService<TestResponse, TestError> {
+		OpenAccountService(context_manager)
+	}
+}
+
+#[event_handler(AccountOpened)]
+async fn request_welcome_mail(_event: AccountOpened, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	let mut context = Context::new(context_manager);
+	context.set_current_events(vec![WelcomeMailRequested.to_message()].into());
+	context.send_internally_notifiable_messages().await?;
+	Ok(())
+}
+
+#[event_handler(WelcomeMailRequested)]
+async fn send_welcome_mail(_event: WelcomeMailRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	Err(BaseError::ServiceError.into())
+}
+
+#[derive(Debug)]
+struct Withdraw(i64);
+impl TCommand for Withdraw {
+	fn validate(&self) -> Result<(), BaseError> {
+		if self.0 <= 0 {
+			return Err(BaseError::ValidationError("amount must be positive".into()));
+		}
+		Ok(())
+	}
+}
+
+static WITHDRAWN: AtomicUsize = AtomicUsize::new(0);
+
+struct WithdrawService;
+impl TCommandService<TestResponse, TestError> for WithdrawService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		WITHDRAWN.fetch_add(1, Ordering::SeqCst);
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, Withdraw> for MessageBus {
+	fn command_handler(&self, _context_manager: AtomicContextManager, _cmd: Withdraw) -> impl TCommandService<TestResponse, TestError> {
+		WithdrawService
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentReceived(i64);
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct ReceiptRequested;
+
+static BALANCE_PROJECTION: AtomicUsize = AtomicUsize::new(0);
+static RECEIPTS_SENT: AtomicUsize = AtomicUsize::new(0);
+static RECEIPTS_REPLAYED: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(PaymentReceived)]
+async fn project_balance(event: PaymentReceived, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	BALANCE_PROJECTION.fetch_add(event.0 as usize, Ordering::SeqCst);
+	let mut context = Context::new(context_manager);
+	context.set_current_events(vec![ReceiptRequested.to_message()].into());
+	context.send_internally_notifiable_messages().await?;
+	Ok(())
+}
+
+#[event_handler(ReceiptRequested)]
+async fn send_receipt(_event: ReceiptRequested, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	match context_manager.is_replaying() {
+		true => RECEIPTS_REPLAYED.fetch_add(1, Ordering::SeqCst),
+		false => RECEIPTS_SENT.fetch_add(1, Ordering::SeqCst),
+	};
+	Ok(())
+}
+
+#[derive(Debug)]
+struct Ping;
+impl TCommand for Ping {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct Ponged;
+
+static PINGED: AtomicUsize = AtomicUsize::new(0);
+
+struct PingService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PingService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		PINGED.fetch_add(1, Ordering::SeqCst);
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![Ponged.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, Ping> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: Ping) -> impl TCommandService<TestResponse, TestError> {
+		PingService(context_manager)
+	}
+}
+
+// * Ping and Ponged trigger each other endlessly
+#[event_handler(Ponged)]
+async fn ping_again(_event: Ponged, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	context_manager.dispatch_command(Box::new(Ping));
+	Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct MailRequested;
+
+static MAILS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static MAX_MAILS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(MailRequested)]
+async fn send_mail(_event: MailRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	let in_flight = MAILS_IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+	MAX_MAILS_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+	tokio::time::sleep(Duration::from_millis(10)).await;
+	MAILS_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+	Ok(())
+}
+
+#[derive(Debug)]
+struct Charge;
+impl TCommand for Charge {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentCharged {
+	#[message_id]
+	id: i64,
+}
+
+static CHARGED: AtomicUsize = AtomicUsize::new(0);
+
+struct ChargeService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for ChargeService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		// * Raised twice by mistake
+		context.set_current_events(vec![PaymentCharged { id: 1 }.to_message(), PaymentCharged { id: 1 }.to_message(), PaymentCharged { id: 2 }.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, Charge> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: Charge) -> impl TCommandService<TestResponse, TestError> {
+		ChargeService(context_manager)
+	}
+}
+
+#[event_handler(PaymentCharged)]
+async fn send_invoice(_event: PaymentCharged, _context: AtomicContextManager) -> Result<(), TestError> {
+	CHARGED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+#[derive(Debug)]
+struct ExportRows(&'static str);
+impl TCommand for ExportRows {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct RowExported {
+	export: &'static str,
+}
+
+static ROWS_RECEIVED: std::sync::Mutex<Vec<(&str, usize)>> = std::sync::Mutex::new(Vec::new());
+static ROWS_RECEIVED_WHEN_HANDLED: std::sync::Mutex<Vec<(&str, usize)>> = std::sync::Mutex::new(Vec::new());
+
+struct ExportService(AtomicContextManager, &'static str);
+impl TStreamingCommandService<usize, TestError> for ExportService {
+	async fn execute(self) -> Result<impl futures::Stream<Item = usize> + Send + 'static, TestError> {
+		let ExportService(context_manager, export) = self;
+		Ok(futures::StreamExt::then(futures::stream::iter(1..=3), move |row| {
+			let context_manager = Arc::clone(&context_manager);
+			async move {
+				context_manager.push_event(RowExported { export }.to_message()).await.unwrap();
+				row
+			}
+		}))
+	}
+}
+
+impl TStreamingMessageBus<usize, TestError, ExportRows> for MessageBus {
+	fn stream_handler(&self, context_manager: AtomicContextManager, cmd: ExportRows) -> impl TStreamingCommandService<usize, TestError> {
+		ExportService(context_manager, cmd.0)
+	}
+}
+
+fn rows_received(export: &str) -> usize {
+	ROWS_RECEIVED.lock().unwrap().iter().filter(|(name, _)| *name == export).count()
+}
+
+#[event_handler(RowExported)]
+async fn count_exported_row(event: RowExported, _context: AtomicContextManager) -> Result<(), TestError> {
+	ROWS_RECEIVED_WHEN_HANDLED.lock().unwrap().push((event.export, rows_received(event.export)));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+init_dyn_command_handler!(TestResponse, TestError, QuickCommand, OpenAccount, Ping);
+
+// * Every test in this file sets the same options as they are shared across the process.
+fn configure() {
+	MessageBus::configure(
+		MessageBusConfig::default()
+			.with_command_timeout(Duration::from_secs(1))
+			.with_command_timeout_for::<SlowCommand>(Duration::from_millis(50))
+			.with_event_handler_timeout(Duration::from_millis(50))
+			.with_max_command_depth(3)
+			.with_handler_concurrency_for("MailRequested", 2)
+			.with_event_deduplication(),
+	);
+}
+
+#[tokio::test]
+async fn test_command_times_out() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let result = MessageBus.execute_and_wait(SlowCommand, &Connection).await;
+
+	//THEN
+	let Err(TestError::BaseError(BaseError::Timeout { command, after })) = result else { panic!("Command must time out!") };
+	assert!(command.ends_with("SlowCommand"));
+	assert_eq!(after, Duration::from_millis(50));
+	assert!(SLOW_COMMAND_CANCELLED.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn test_command_within_default_timeout() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let result = MessageBus.execute_and_wait(QuickCommand, &Connection).await;
+
+	//THEN
+	assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn test_event_handler_times_out_and_next_handler_runs() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let result = tokio::time::timeout(Duration::from_secs(1), MessageBus.handle_event(SomethingHappened.to_message(), &Connection)).await;
+
+	//THEN
+	assert!(matches!(result, Ok(Ok(()))));
+	assert!(STUCK_HANDLER_CANCELLED.load(Ordering::SeqCst));
+	assert_eq!(HANDLED.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_execute_batch_in_isolated_and_shared_context() {
+	//GIVEN
+	configure();
+	let commands = || vec![ImportRecord(1), ImportRecord(-1), ImportRecord(2)];
+
+	//WHEN isolated, events are handled right after the command that raised them
+	let results = MessageBus.execute_batch(commands(), &Connection, BatchContext::Isolated).await;
+
+	//THEN
+	assert_eq!(results.iter().map(Result::is_ok).collect::<Vec<_>>(), vec![true, false, true]);
+	assert_eq!(std::mem::take(&mut *IMPORTED_WHEN_HANDLED.lock().unwrap()), vec![1, 2]);
+
+	//WHEN shared, events are handled together after the whole batch
+	let results = MessageBus.execute_batch(commands(), &Connection, BatchContext::Shared).await;
+
+	//THEN
+	assert_eq!(results.iter().map(Result::is_ok).collect::<Vec<_>>(), vec![true, false, true]);
+	assert_eq!(std::mem::take(&mut *IMPORTED_WHEN_HANDLED.lock().unwrap()), vec![4, 4]);
+}
+
+#[tokio::test]
+async fn test_execute_with_report() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let (_, report) = MessageBus.execute_with_report(OpenAccount, &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(report.processed(), 2);
+	assert_eq!(report.topics, vec!["AccountOpened", "WelcomeMailRequested"]);
+	assert_eq!(report.succeeded, 1);
+	assert_eq!(report.failed, 1);
+}
+
+#[tokio::test]
+async fn test_execute_dyn() {
+	//GIVEN
+	configure();
+	let route = |path: &str| -> Box<dyn TCommand> {
+		match path {
+			"/quick" => Box::new(QuickCommand),
+			_ => Box::new(ImportRecord(1)),
+		}
+	};
+
+	//WHEN
+	let dispatched = MessageBus.execute_dyn(route("/quick"), &Connection).await;
+	let unregistered = MessageBus.execute_dyn(route("/import"), &Connection).await;
+
+	//THEN
+	assert!(dispatched.is_ok());
+	let Err(TestError::BaseError(BaseError::CommandNotFound(name))) = unregistered else { panic!("Unregistered command must not be found!") };
+	assert!(name.ends_with("ImportRecord"));
+}
+
+#[test]
+fn test_verify_commands_lists_unregistered_ones() {
+	//WHEN
+	let registered = MessageBus.verify_commands(&[CommandType::of::<QuickCommand>()]);
+	let unregistered = MessageBus.verify_commands(&[CommandType::of::<ImportRecord>(), CommandType::of::<QuickCommand>(), CommandType::of::<Withdraw>()]);
+
+	//THEN
+	assert!(registered.is_ok());
+	let Err(BaseError::CommandNotFound(names)) = unregistered else { panic!("Unregistered commands must be listed!") };
+	assert_eq!(names, format!("{}, {}", std::any::type_name::<ImportRecord>(), std::any::type_name::<Withdraw>()));
+}
+
+#[tokio::test]
+async fn test_invalid_command_is_not_dispatched() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let invalid = MessageBus.execute_and_wait(Withdraw(0), &Connection).await;
+	let valid = MessageBus.execute_and_wait(Withdraw(10), &Connection).await;
+
+	//THEN
+	assert!(matches!(invalid, Err(TestError::BaseError(BaseError::ValidationError(_)))));
+	assert!(valid.is_ok());
+	assert_eq!(WITHDRAWN.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_replay_suppresses_side_effects() {
+	//GIVEN
+	configure();
+	MessageBus.handle_event(PaymentReceived(10).to_message(), &Connection).await.unwrap();
+	let history = vec![PaymentReceived(10).to_message(), PaymentReceived(5).to_message()];
+
+	//WHEN
+	let report = MessageBus.replay(futures::stream::iter(history), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(BALANCE_PROJECTION.load(Ordering::SeqCst), 25);
+	assert_eq!(RECEIPTS_SENT.load(Ordering::SeqCst), 1);
+	assert_eq!(RECEIPTS_REPLAYED.load(Ordering::SeqCst), 2);
+	assert_eq!(report.topics, vec!["PaymentReceived", "ReceiptRequested", "PaymentReceived", "ReceiptRequested"]);
+}
+
+#[tokio::test]
+async fn test_command_dispatched_from_event_handler() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let (_, report) = MessageBus.execute_with_report(Ping, &Connection).await.unwrap();
+
+	//THEN
+	// * Initial command followed by commands dispatched up to the depth limit
+	assert_eq!(PINGED.load(Ordering::SeqCst), 4);
+	// * Events raised by dispatched commands are handled in their own context
+	assert_eq!(report.topics, vec!["Ponged"]);
+}
+
+#[tokio::test]
+async fn test_handler_concurrency_is_limited_per_topic() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let requests = (0..5).map(|_| MessageBus.handle_event(MailRequested.to_message(), &Connection));
+	let results = futures::future::join_all(requests).await;
+
+	//THEN
+	assert!(results.iter().all(Result::is_ok));
+	assert_eq!(MAX_MAILS_IN_FLIGHT.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_duplicate_event_is_dropped_within_request() {
+	//GIVEN
+	configure();
+
+	//WHEN
+	let (_, report) = MessageBus.execute_with_report(Charge, &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(CHARGED.load(Ordering::SeqCst), 2);
+	assert_eq!(report.processed(), 2);
+}
+
+#[tokio::test]
+async fn test_events_of_stream_are_handled_after_or_while_streaming() {
+	//GIVEN
+	configure();
+	let consume = |export: &'static str, events: StreamedEvents| async move {
+		let rows = MessageBus.execute_stream(ExportRows(export), &Connection, events).await.unwrap();
+		futures::StreamExt::for_each(rows, |row| async move { ROWS_RECEIVED.lock().unwrap().push((export, row)) }).await;
+	};
+
+	//WHEN
+	consume("after", StreamedEvents::AfterStream).await;
+	consume("while", StreamedEvents::WhileStreaming).await;
+
+	//THEN
+	let handled = ROWS_RECEIVED_WHEN_HANDLED.lock().unwrap();
+	let rows_received_when_handled = |export: &str| handled.iter().filter(|(name, _)| *name == export).map(|(_, count)| *count).collect::<Vec<_>>();
+	assert_eq!(rows_received_when_handled("after"), vec![3, 3, 3]);
+	assert_eq!(rows_received_when_handled("while"), vec![0, 1, 2]);
+}
+
+#[test]
+fn test_replay_filter() {
+	//GIVEN
+	let at = |hour: u32| chrono::DateTime::from_naive_utc_and_offset(chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(hour, 0, 0).unwrap(), chrono::Utc);
+	let outbox = |topic: &str, hour: u32| {
+		let mut outbox = OutBox::new("1".into(), "Account".into(), topic.into(), "{}".into(), Default::default());
+		outbox.create_dt = at(hour);
+		outbox
+	};
+	let filter = ReplayFilter::default().topics(["PaymentReceived"]).from(at(1)).until(at(3));
+
+	//THEN
+	assert!(!filter.includes(&outbox("PaymentReceived", 0)));
+	assert!(filter.includes(&outbox("PaymentReceived", 1)));
+	assert!(!filter.includes(&outbox("ReceiptRequested", 2)));
+	assert!(!filter.includes(&outbox("PaymentReceived", 3)));
+}
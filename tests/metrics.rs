@@ -0,0 +1,84 @@
+#![cfg(feature = "event-driven-prometheus")]
+
+use prometheus::Registry;
+use ruva::*;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn notify_customer(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	Err(TestError::DatabaseError("Mail server is unreachable!".into()))
+}
+
+init_event_handler!(TestError);
+
+/// Value of the counter whose labels include every one of `labels`
+fn counter(registry: &Registry, name: &str, labels: &[(&str, &str)]) -> f64 {
+	let families = registry.gather();
+	let Some(family) = families.iter().find(|family| family.get_name() == name) else {
+		return 0.0;
+	};
+	family
+		.get_metric()
+		.iter()
+		.filter(|metric| labels.iter().all(|(name, value)| metric.get_label().iter().any(|label| label.get_name() == *name && label.get_value().ends_with(value))))
+		.map(|metric| metric.get_counter().get_value())
+		.sum()
+}
+
+#[tokio::test]
+async fn test_dispatched_command_is_counted_with_its_events_and_handlers() {
+	//GIVEN
+	let recorder = PrometheusRecorder::new(Registry::new()).unwrap();
+	MessageBus::configure(MessageBusConfig::default().with_metrics_recorder(recorder.clone()));
+
+	//WHEN
+	MessageBus.execute_and_wait(PlaceOrder, &Connection).await.unwrap();
+
+	//THEN
+	let registry = recorder.registry();
+	assert_eq!(counter(registry, "commands_total", &[("command", "PlaceOrder"), ("outcome", "success")]), 1.0);
+	assert_eq!(counter(registry, "events_total", &[("event", "OrderPlaced"), ("outcome", "failure")]), 1.0);
+	assert_eq!(counter(registry, "handler_failures_total", &[("event", "OrderPlaced")]), 1.0);
+	let durations = registry.gather().into_iter().find(|family| family.get_name() == "handler_duration_seconds").unwrap();
+	assert_eq!(durations.get_metric()[0].get_histogram().get_sample_count(), 1);
+}
@@ -0,0 +1,50 @@
+use ruva::*;
+use std::{collections::VecDeque, sync::Arc};
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[aggregate(Serialize, Debug)]
+struct Order {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[externally_notifiable(Order)]
+struct OrderPlaced {
+	#[identifier]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[externally_notifiable(Order)]
+struct OrderCancelled {
+	#[identifier]
+	id: i64,
+}
+
+#[test]
+fn test_events_of_a_request_are_batched_into_one_outbox_and_split_back() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_outbox_batching());
+	let mut context = Context::new(Arc::new(ContextManager::new(&Connection)));
+	let events: VecDeque<Arc<dyn TEvent>> =
+		VecDeque::from([OrderPlaced { id: 1 }.to_message(), OrderCancelled { id: 9 }.to_message(), OrderPlaced { id: 2 }.to_message(), OrderPlaced { id: 3 }.to_message()]);
+	context.set_current_events(events);
+
+	//WHEN
+	let outboxes = context.outboxes().unwrap();
+
+	//THEN
+	// * Outbox of the single event of another topic is left as it is
+	assert_eq!(outboxes.iter().map(|outbox| (outbox.topic.as_str(), outbox.is_batch())).collect::<Vec<_>>(), vec![("OrderPlaced", true), ("OrderCancelled", false)]);
+	let batch = &outboxes[0];
+	let envelopes = batch.envelopes().unwrap();
+	assert_eq!(envelopes.iter().map(|envelope| envelope.metadata.aggregate_id.as_str()).collect::<Vec<_>>(), vec!["1", "2", "3"]);
+	assert!(envelopes.iter().all(|envelope| envelope.metadata.topic == "OrderPlaced" && envelope.metadata.id != batch.id));
+	assert_eq!(batch.partition_key().as_deref(), Some("1"));
+
+	let events = EventDeserializers::default().register_event::<OrderPlaced>().deserialize_batch(&batch.payload, &Upcaster::default()).unwrap();
+	assert_eq!(events.iter().map(|event| event.downcast_ref::<OrderPlaced>().unwrap().id).collect::<Vec<_>>(), vec![1, 2, 3]);
+}
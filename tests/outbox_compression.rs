@@ -0,0 +1,63 @@
+use ruva::*;
+
+/// Run-length encoding, standing in for gzip or zstd
+struct RunLength;
+impl TCodec for RunLength {
+	fn name(&self) -> &'static str {
+		"rle"
+	}
+	fn compress(&self, bytes: &[u8]) -> Vec<u8> {
+		let mut compressed = vec![];
+		for run in bytes.chunk_by(|a, b| a == b) {
+			for chunk in run.chunks(u8::MAX as usize) {
+				compressed.extend([chunk.len() as u8, chunk[0]]);
+			}
+		}
+		compressed
+	}
+	fn decompress(&self, bytes: &[u8]) -> Result<Vec<u8>, BaseError> {
+		if !bytes.len().is_multiple_of(2) {
+			return Err(BaseError::DeserializationError("Odd number of bytes".into()));
+		}
+		Ok(bytes.chunks(2).flat_map(|pair| std::iter::repeat_n(pair[1], pair[0] as usize)).collect())
+	}
+}
+
+#[aggregate(Serialize, Debug)]
+struct Document {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TEvent)]
+#[externally_notifiable(Document)]
+struct DocumentUploaded {
+	#[identifier]
+	id: i64,
+	body: String,
+}
+
+#[test]
+fn test_large_outbox_is_compressed_and_small_one_is_not() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_outbox_compression(RunLength, 64 * 1024));
+	let large = DocumentUploaded { id: 1, body: "a".repeat(1024 * 1024) };
+	let small = DocumentUploaded { id: 2, body: "a".repeat(16) };
+
+	//WHEN
+	let large_outbox = large.outbox();
+	let small_outbox = small.outbox();
+
+	//THEN
+	assert_eq!(large_outbox.content_encoding().as_deref(), Some("rle"));
+	assert!(large_outbox.state.len() < 64 * 1024);
+	assert!(large_outbox.payload.len() < 64 * 1024);
+	assert_eq!(RunLength.decompress(&large_outbox.payload).unwrap(), large.state().into_bytes());
+	assert_eq!(large_outbox.envelope().unwrap().data::<DocumentUploaded>().unwrap(), large);
+	// * Codec header is dropped along with compression
+	assert!(!large_outbox.envelope().unwrap().metadata.headers.contains_key(CONTENT_ENCODING_HEADER));
+
+	assert_eq!(small_outbox.content_encoding(), None);
+	assert_eq!(small_outbox.payload, small.state().into_bytes());
+	assert_eq!(small_outbox.data().unwrap(), serde_json::json!({"id": 2, "body": "a".repeat(16)}));
+}
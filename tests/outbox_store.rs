@@ -0,0 +1,99 @@
+use ruva::*;
+use std::sync::LazyLock;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[aggregate(Clone)]
+struct Account {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[externally_notifiable(Account)]
+struct AccountOpened {
+	#[identifier]
+	id: i64,
+}
+
+#[derive(Debug)]
+struct OpenAccount {
+	id: i64,
+}
+impl TCommand for OpenAccount {}
+
+static OUTBOX: LazyLock<InMemoryOutBoxStore> = LazyLock::new(Default::default);
+
+/// Unit of work that stages outboxes of its events in memory
+struct AccountUnitOfWork {
+	context: Context,
+	cmd: OpenAccount,
+}
+
+impl TUnitOfWork for AccountUnitOfWork {
+	async fn begin(&mut self) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn _commit(&mut self) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn rollback(&mut self) -> Result<(), BaseError> {
+		self.context.discard_events();
+		Ok(())
+	}
+	async fn close(&mut self) {}
+	async fn process_external_events(&mut self) -> Result<(), BaseError> {
+		OUTBOX.stage(self.context.outboxes()?);
+		Ok(())
+	}
+}
+
+impl TCommandService<TestResponse, TestError> for AccountUnitOfWork {
+	async fn execute(mut self) -> Result<TestResponse, TestError> {
+		self.begin().await?;
+		let mut account = Account { id: self.cmd.id, ..Default::default() };
+		account.raise_event(AccountOpened { id: account.id }.to_message());
+		self.context.event_hook(&mut account);
+		self.commit().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, OpenAccount> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: OpenAccount) -> impl TCommandService<TestResponse, TestError> {
+		AccountUnitOfWork { context: Context::new(context_manager), cmd }
+	}
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_command_stages_outbox_of_its_event() {
+	//WHEN
+	MessageBus.execute_and_wait(OpenAccount { id: 1 }, &Connection).await.unwrap();
+
+	//THEN
+	let outbox = OUTBOX.assert_outboxed::<AccountOpened>();
+	assert_eq!((outbox.aggregate_name.as_str(), outbox.aggregate_id.as_str()), ("Account", "1"));
+	assert_eq!(outbox.envelope().unwrap().data::<AccountOpened>().unwrap().id, 1);
+
+	let drained = OUTBOX.drain_outbox();
+	assert_eq!(drained.len(), 1);
+	assert!(OUTBOX.outboxes().is_empty());
+	assert!(std::panic::catch_unwind(|| OUTBOX.assert_outboxed::<AccountOpened>()).is_err());
+}
@@ -0,0 +1,82 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+static EXECUTED: AtomicBool = AtomicBool::new(false);
+static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		EXECUTED.store(true, Ordering::SeqCst);
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn notify_customer(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	NOTIFIED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_events_wait_while_paused_and_are_handled_once_resumed() {
+	// * Tasks are spawned locally with `local` feature
+	tokio::task::LocalSet::new()
+		.run_until(async {
+			//GIVEN
+			MessageBus.pause();
+
+			//WHEN
+			let res = MessageBus.execute_and_forget(PlaceOrder, &Connection).await.unwrap();
+			tokio::time::sleep(Duration::from_millis(100)).await;
+
+			//THEN
+			assert!(MessageBus.is_paused());
+			assert!(EXECUTED.load(Ordering::SeqCst));
+			assert_eq!(NOTIFIED.load(Ordering::SeqCst), 0);
+
+			MessageBus.resume();
+			tokio::time::timeout(Duration::from_secs(5), res.wait_until_event_processing_done()).await.expect("Events must be handled once resumed!").unwrap();
+			assert_eq!(NOTIFIED.load(Ordering::SeqCst), 1);
+		})
+		.await;
+}
@@ -0,0 +1,73 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Arc, Mutex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct InvoiceSyncRequested {
+	#[message_id]
+	invoice_id: i64,
+}
+
+static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
+
+struct InvoiceEventHandler;
+impl InvoiceEventHandler {
+	async fn sync_invoice(self, event: InvoiceSyncRequested) -> Result<(), TestError> {
+		ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+		// * Keeps asking for the same event to be handled again
+		Err(TestError::StopSentinelWithEvent(event.to_message()))
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| InvoiceEventHandler,
+	InvoiceSyncRequested: [sync_invoice],
+);
+
+#[derive(Default, Clone)]
+struct InMemoryDeadLetterSink(Arc<Mutex<Vec<DeadLetter>>>);
+
+#[async_trait]
+impl TDeadLetterSink for InMemoryDeadLetterSink {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(dead_letter);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_event_re_enqueued_over_threshold_is_dead_lettered() {
+	//GIVEN
+	let sink = InMemoryDeadLetterSink::default();
+	MessageBus::configure(MessageBusConfig::default().with_dead_letter_sink(sink.clone()).with_poison_message_threshold(3));
+
+	//WHEN
+	let report = MessageBus.handle_event_with_report(InvoiceSyncRequested { invoice_id: 7 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	// * Handled once as given and three more times as re-enqueued
+	assert_eq!(ATTEMPTS.load(Ordering::SeqCst), 4);
+	assert_eq!(report.processed(), 4);
+	let dead_letters = sink.0.lock().unwrap().clone();
+	assert_eq!(dead_letters.len(), 1);
+	assert_eq!((dead_letters[0].topic.as_str(), dead_letters[0].handler), ("InvoiceSyncRequested", Some(0)));
+	assert_eq!(dead_letters[0].payload, br#"{"invoice_id":7}"#);
+	assert!(dead_letters[0].reason.contains("PoisonMessage"));
+}
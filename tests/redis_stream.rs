@@ -0,0 +1,159 @@
+#![cfg(feature = "event-driven-redis")]
+
+use ruva::*;
+use std::{sync::Mutex, time::Duration};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TEvent)]
+#[internally_notifiable]
+struct AccountCreated {
+	id: i64,
+	#[headers]
+	#[serde(skip)]
+	headers: std::collections::HashMap<String, String>,
+}
+
+static HANDLED: Mutex<Vec<(i64, Option<String>)>> = Mutex::new(vec![]);
+
+#[event_handler(AccountCreated)]
+async fn on_account_created(event: AccountCreated, _context: AtomicContextManager) -> Result<(), TestError> {
+	HANDLED.lock().unwrap().push((event.id, event.headers.get("tenant").cloned()));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+struct Connection;
+impl TConnection for Connection {}
+
+/// Streams of a single consumer group kept in memory. Every pending entry counts as idle long enough.
+#[derive(Default)]
+struct MockRedis {
+	entries: Mutex<Vec<StreamEntry>>,
+	delivered: Mutex<usize>,
+	pending: Mutex<Vec<(StreamEntry, String)>>,
+	acked: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl TRedisStreams for &'static MockRedis {
+	async fn xadd(&self, stream: &str, fields: Vec<(String, Vec<u8>)>) -> Result<String, BaseError> {
+		let mut entries = self.entries.lock().unwrap();
+		let id = format!("{}-0", entries.len() + 1);
+		entries.push(StreamEntry { stream: stream.into(), id: id.clone(), fields: fields.into_iter().collect() });
+		Ok(id)
+	}
+	async fn create_group(&self, _stream: &str, _group: &str) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn read_group(&self, streams: &[&str], _group: &str, consumer: &str) -> Result<Vec<StreamEntry>, BaseError> {
+		let mut delivered = self.delivered.lock().unwrap();
+		let entries = self.entries.lock().unwrap()[*delivered..].iter().filter(|entry| streams.contains(&entry.stream.as_str())).cloned().collect::<Vec<_>>();
+		*delivered = self.entries.lock().unwrap().len();
+		self.pending.lock().unwrap().extend(entries.iter().map(|entry| (entry.clone(), consumer.to_string())));
+		Ok(entries)
+	}
+	async fn ack(&self, _stream: &str, _group: &str, id: &str) -> Result<(), BaseError> {
+		self.pending.lock().unwrap().retain(|(entry, _)| entry.id != id);
+		self.acked.lock().unwrap().push(id.to_string());
+		Ok(())
+	}
+	async fn claim_pending(&self, stream: &str, _group: &str, consumer: &str, _min_idle: Duration) -> Result<Vec<StreamEntry>, BaseError> {
+		let mut pending = self.pending.lock().unwrap();
+		let claimed = pending.iter_mut().filter(|(entry, owner)| entry.stream == stream && owner != consumer).map(|(entry, owner)| {
+			*owner = consumer.to_string();
+			entry.clone()
+		});
+		Ok(claimed.collect())
+	}
+}
+
+#[derive(Default)]
+struct MockDeadLetterSink(std::sync::Arc<Mutex<Vec<DeadLetter>>>);
+
+#[async_trait]
+impl TDeadLetterSink for MockDeadLetterSink {
+	async fn send(&self, dead_letter: DeadLetter) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(dead_letter);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_published_outboxes_are_handled_and_acked() {
+	//GIVEN
+	let redis: &'static MockRedis = Box::leak(Box::default());
+	let mut outboxes = [1, 2].map(|id| AccountCreated { id, headers: Default::default() }.with_header("tenant", "bering").outbox());
+	RedisStreamPublisher::new(redis).publish_all(&mut outboxes).await.unwrap();
+	redis.xadd("AccountCreated", vec![(stream_fields::PAYLOAD.into(), b"malformed".to_vec())]).await.unwrap();
+
+	let dead_letters = MockDeadLetterSink::default();
+	let stored = dead_letters.0.clone();
+	let driver = RedisStreamConsumerDriver::new(redis, "account-service", "consumer-1", &Connection, dead_letters).register::<AccountCreated>("AccountCreated");
+
+	//WHEN
+	driver.create_groups().await.unwrap();
+	driver.poll::<TestError>(&MessageBus).await.unwrap();
+
+	//THEN
+	assert!(outboxes.iter().all(|outbox| outbox.processed));
+	let entry = &redis.entries.lock().unwrap()[0];
+	assert_eq!(entry.stream, "AccountCreated");
+	assert_eq!(entry.fields[stream_fields::PAYLOAD], br#"{"id":1}"#);
+	assert_eq!(entry.fields[stream_fields::FORMAT], b"json");
+
+	let handled = HANDLED.lock().unwrap().clone();
+	assert!(handled.contains(&(1, Some("bering".into()))));
+	assert!(handled.contains(&(2, Some("bering".into()))));
+	assert_eq!(*redis.acked.lock().unwrap(), vec!["1-0", "2-0", "3-0"]);
+	assert_eq!(stored.lock().unwrap()[0].payload, b"malformed");
+}
+
+#[tokio::test]
+async fn test_entry_left_pending_by_crashed_consumer_is_reclaimed() {
+	//GIVEN
+	let redis: &'static MockRedis = Box::leak(Box::default());
+	let mut outboxes = [AccountCreated { id: 10, headers: Default::default() }.outbox()];
+	RedisStreamPublisher::new(redis).publish_all(&mut outboxes).await.unwrap();
+	// * Delivered to consumer that crashed before acking it
+	redis.read_group(&["AccountCreated"], "account-service", "crashed").await.unwrap();
+
+	let driver = RedisStreamConsumerDriver::new(redis, "account-service", "consumer-2", &Connection, MockDeadLetterSink::default())
+		.register::<AccountCreated>("AccountCreated")
+		.reclaim_after(Duration::from_secs(60));
+
+	//WHEN
+	driver.poll::<TestError>(&MessageBus).await.unwrap();
+
+	//THEN
+	assert!(HANDLED.lock().unwrap().contains(&(10, None)));
+	assert_eq!(*redis.acked.lock().unwrap(), vec!["1-0"]);
+	assert!(redis.pending.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_malformed_entry_halts_consumer_and_stays_pending() {
+	//GIVEN
+	let redis: &'static MockRedis = Box::leak(Box::default());
+	redis.xadd("AccountCreated", vec![(stream_fields::PAYLOAD.into(), b"malformed".to_vec())]).await.unwrap();
+	let driver = RedisStreamConsumerDriver::new(redis, "account-service", "consumer-1", &Connection, MockDeadLetterSink::default())
+		.register::<AccountCreated>("AccountCreated")
+		.on_deserialization_error(DeserializationErrorStrategy::Halt);
+
+	//WHEN
+	let result = driver.poll::<TestError>(&MessageBus).await;
+
+	//THEN
+	assert!(matches!(result, Err(TestError::BaseError(BaseError::DeserializationError(_)))));
+	assert!(redis.acked.lock().unwrap().is_empty());
+	assert_eq!(redis.pending.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,441 @@
+use ruva::*;
+use std::{
+	collections::{BTreeMap, VecDeque},
+	sync::{Arc, Mutex},
+};
+
+#[aggregate(Clone)]
+struct Account {
+	#[adapter_ignore]
+	id: i64,
+	balance: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct AccountOpened;
+
+#[aggregate(Clone)]
+struct Document {
+	#[adapter_ignore]
+	id: i64,
+	#[deleted_at]
+	deleted_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+trait TIdentified {
+	fn id(&self) -> i64;
+}
+impl TIdentified for Account {
+	fn id(&self) -> i64 {
+		self.id
+	}
+}
+impl TIdentified for Document {
+	fn id(&self) -> i64 {
+		self.id
+	}
+}
+
+#[derive(Default)]
+struct InMemoryRepository<A> {
+	committed: BTreeMap<i64, A>,
+	staged: Option<BTreeMap<i64, A>>,
+	savepoints: Vec<(String, BTreeMap<i64, A>)>,
+	isolation_level: IsolationLevel,
+	events: VecDeque<std::sync::Arc<dyn TEvent>>,
+}
+
+impl<A> InMemoryRepository<A> {
+	fn store(&self) -> &BTreeMap<i64, A> {
+		self.staged.as_ref().unwrap_or(&self.committed)
+	}
+	fn store_mut(&mut self) -> Result<&mut BTreeMap<i64, A>, BaseError> {
+		self.staged.as_mut().ok_or(BaseError::TransactionError)
+	}
+}
+
+impl<A: TAggregate> TSetCurrentEvents for InMemoryRepository<A> {
+	fn set_current_events(&mut self, events: VecDeque<std::sync::Arc<dyn TEvent>>) {
+		self.events.extend(events)
+	}
+}
+
+impl<A: TAggregate + Clone> TUnitOfWork for InMemoryRepository<A> {
+	async fn begin(&mut self) -> Result<(), BaseError> {
+		self.staged = Some(self.committed.clone());
+		Ok(())
+	}
+	async fn _commit(&mut self) -> Result<(), BaseError> {
+		self.committed = self.staged.take().ok_or(BaseError::TransactionError)?;
+		Ok(())
+	}
+	async fn rollback(&mut self) -> Result<(), BaseError> {
+		self.staged = None;
+		self.savepoints.clear();
+		self.events.clear();
+		Ok(())
+	}
+	async fn close(&mut self) {}
+	async fn _set_isolation_level(&mut self, isolation_level: IsolationLevel) -> Result<(), BaseError> {
+		self.store_mut()?;
+		self.isolation_level = isolation_level;
+		Ok(())
+	}
+	async fn savepoint(&mut self, name: &str) -> Result<(), BaseError> {
+		let snapshot = self.store_mut()?.clone();
+		self.savepoints.push((name.to_string(), snapshot));
+		Ok(())
+	}
+	async fn rollback_to(&mut self, name: &str) -> Result<(), BaseError> {
+		let position = self.savepoints.iter().rposition(|(savepoint, _)| savepoint == name).ok_or(BaseError::TransactionError)?;
+		// * Like SQL, savepoint itself is kept while the ones set after it are released
+		self.savepoints.truncate(position + 1);
+		let snapshot = self.savepoints[position].1.clone();
+		*self.store_mut()? = snapshot;
+		Ok(())
+	}
+}
+
+impl<A: TAggregate + TIdentified + Clone> TRepository<A, i64> for InMemoryRepository<A> {
+	async fn _insert(&mut self, aggregate: &A) -> Result<i64, BaseError> {
+		self.store_mut()?.insert(aggregate.id(), aggregate.clone());
+		Ok(aggregate.id())
+	}
+	async fn _find(&self, id: &i64) -> Result<Option<A>, BaseError> {
+		Ok(self.store().get(id).cloned())
+	}
+	async fn _update(&mut self, aggregate: &A) -> Result<(), BaseError> {
+		self.store_mut()?.insert(aggregate.id(), aggregate.clone());
+		Ok(())
+	}
+	async fn _delete(&mut self, id: &i64) -> Result<(), BaseError> {
+		self.store_mut()?.remove(id);
+		Ok(())
+	}
+	async fn _find_all(&self) -> Result<Vec<A>, BaseError> {
+		Ok(self.store().values().cloned().collect())
+	}
+	async fn _find_after(&self, cursor: Option<&Cursor>, limit: usize) -> Result<Vec<A>, BaseError> {
+		let after = cursor.map(|cursor| cursor.parse::<i64>()).transpose()?;
+		Ok(self.store().range(after.map_or(i64::MIN, |after| after + 1)..).take(limit).map(|(_, aggregate)| aggregate.clone()).collect())
+	}
+	fn _cursor_of(&self, aggregate: &A) -> Cursor {
+		Cursor::new(aggregate.id())
+	}
+}
+
+#[tokio::test]
+async fn test_repository_crud_within_transaction() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+	let mut account = Account { id: 1, balance: 100, ..Default::default() };
+	account.raise_event(AccountOpened.to_message());
+
+	//WHEN
+	repo.begin().await.unwrap();
+	let id = repo.add(&mut account).await.unwrap();
+	account.balance = 50;
+	repo.update(&mut account).await.unwrap();
+	repo.add(&mut Account { id: 2, ..Default::default() }).await.unwrap();
+	repo.delete(&2).await.unwrap();
+	repo.commit().await.unwrap();
+
+	//THEN
+	assert_eq!(id, 1);
+	assert_eq!(repo.get(&1).await.unwrap().balance, 50);
+	assert!(matches!(repo.get(&2).await, Err(BaseError::NotFound)));
+	assert_eq!(repo.list().await.unwrap().len(), 1);
+	assert_eq!(repo.events.len(), 1);
+	assert!(account.events().is_empty());
+}
+
+#[tokio::test]
+async fn test_repository_changes_discarded_on_rollback() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+
+	//WHEN
+	repo.begin().await.unwrap();
+	repo.add(&mut Account { id: 1, ..Default::default() }).await.unwrap();
+	repo.rollback().await.unwrap();
+
+	//THEN
+	assert!(matches!(repo.get(&1).await, Err(BaseError::NotFound)));
+	assert!(matches!(repo.add(&mut Account { id: 1, ..Default::default() }).await, Err(BaseError::TransactionError)));
+}
+
+#[tokio::test]
+async fn test_rollback_to_savepoint_keeps_outer_transaction() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+	repo.begin().await.unwrap();
+	repo.add(&mut Account { id: 1, ..Default::default() }).await.unwrap();
+
+	//WHEN
+	repo.savepoint("risky").await.unwrap();
+	repo.add(&mut Account { id: 2, ..Default::default() }).await.unwrap();
+	repo.rollback_to("risky").await.unwrap();
+	repo.commit().await.unwrap();
+
+	//THEN
+	assert!(repo.get(&1).await.is_ok());
+	assert!(matches!(repo.get(&2).await, Err(BaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn test_savepoint_requires_active_transaction() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+
+	//WHEN
+	let result = repo.savepoint("risky").await;
+
+	//THEN
+	assert!(matches!(result, Err(BaseError::TransactionError)));
+	assert!(matches!(repo.rollback_to("risky").await, Err(BaseError::TransactionError)));
+}
+
+#[tokio::test]
+async fn test_begin_with_isolation_level() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+	let mut uow = SharedUnitOfWork::default();
+
+	//WHEN
+	repo.begin_with(IsolationLevel::Serializable).await.unwrap();
+	let unsupported = uow.begin_with(IsolationLevel::Serializable).await;
+
+	//THEN
+	assert_eq!(repo.isolation_level, IsolationLevel::Serializable);
+	assert!(matches!(unsupported, Err(BaseError::TransactionError)));
+	uow.rollback().await.unwrap();
+	assert!(uow.begin_with(IsolationLevel::Default).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_soft_deleted_aggregate_is_hidden_but_recoverable() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Document>::default();
+	repo.begin().await.unwrap();
+	repo.add(&mut Document { id: 1, ..Default::default() }).await.unwrap();
+	repo.add(&mut Document { id: 2, ..Default::default() }).await.unwrap();
+
+	//WHEN
+	repo.delete(&1).await.unwrap();
+
+	//THEN
+	assert!(matches!(repo.get(&1).await, Err(BaseError::NotFound)));
+	assert_eq!(repo.list().await.unwrap().iter().map(|document| document.id).collect::<Vec<_>>(), vec![2]);
+	assert!(repo.get_including_deleted(&1).await.unwrap().deleted_at.is_some());
+
+	//WHEN
+	let restored = repo.restore(&1).await.unwrap();
+
+	//THEN
+	assert!(restored.deleted_at.is_none());
+	assert!(repo.get(&1).await.is_ok());
+	assert_eq!(repo.list().await.unwrap().len(), 2);
+}
+
+type Table = BTreeMap<i64, i64>;
+
+#[derive(Default, Clone)]
+struct Tables {
+	accounts: Table,
+	ledgers: Table,
+}
+
+#[derive(Default)]
+struct SharedUnitOfWork {
+	database: Arc<Mutex<Tables>>,
+	executor: Executor<Tables>,
+}
+
+impl TUnitOfWork for SharedUnitOfWork {
+	async fn begin(&mut self) -> Result<(), BaseError> {
+		let snapshot = self.database.lock().unwrap().clone();
+		self.executor.begin(snapshot).await
+	}
+	async fn _commit(&mut self) -> Result<(), BaseError> {
+		*self.database.lock().unwrap() = self.executor.take().await.ok_or(BaseError::TransactionError)?;
+		Ok(())
+	}
+	async fn rollback(&mut self) -> Result<(), BaseError> {
+		self.executor.take().await;
+		Ok(())
+	}
+	async fn close(&mut self) {}
+}
+
+impl TSharedTransaction for SharedUnitOfWork {
+	type Transaction = Tables;
+	fn executor(&self) -> &Executor<Tables> {
+		&self.executor
+	}
+}
+
+struct AccountRepository(Executor<Tables>);
+impl TBindExecutor<Tables> for AccountRepository {
+	fn bind(executor: Executor<Tables>) -> Self {
+		Self(executor)
+	}
+}
+impl AccountRepository {
+	async fn save(&mut self, id: i64, balance: i64) -> Result<(), BaseError> {
+		self.0.transaction().await?.accounts.insert(id, balance);
+		Ok(())
+	}
+}
+
+struct LedgerRepository(Executor<Tables>);
+impl TBindExecutor<Tables> for LedgerRepository {
+	fn bind(executor: Executor<Tables>) -> Self {
+		Self(executor)
+	}
+}
+impl LedgerRepository {
+	async fn save(&mut self, id: i64, amount: i64) -> Result<(), BaseError> {
+		if amount < 0 {
+			return Err(BaseError::DatabaseError("check constraint violated".into()));
+		}
+		self.0.transaction().await?.ledgers.insert(id, amount);
+		Ok(())
+	}
+}
+
+async fn transfer(uow: &mut SharedUnitOfWork, amount: i64) -> Result<(), BaseError> {
+	uow.begin().await?;
+	let mut accounts: AccountRepository = uow.repository();
+	let mut ledgers: LedgerRepository = uow.repository();
+	let result = async {
+		accounts.save(1, amount).await?;
+		ledgers.save(1, amount).await
+	}
+	.await;
+	match result {
+		Ok(()) => uow.commit().await,
+		Err(err) => {
+			uow.rollback().await?;
+			Err(err)
+		}
+	}
+}
+
+#[tokio::test]
+async fn test_repositories_share_transaction_of_unit_of_work() {
+	//GIVEN
+	let mut uow = SharedUnitOfWork::default();
+
+	//WHEN
+	let committed = transfer(&mut uow, 100).await;
+	let rolled_back = transfer(&mut uow, -100).await;
+
+	//THEN
+	assert!(committed.is_ok());
+	assert!(matches!(rolled_back, Err(BaseError::DatabaseError(_))));
+	let database = uow.database.lock().unwrap();
+	assert_eq!(database.accounts[&1], 100);
+	assert_eq!(database.ledgers[&1], 100);
+}
+
+#[tokio::test]
+async fn test_repository_requires_active_transaction() {
+	//GIVEN
+	let uow = SharedUnitOfWork::default();
+	let mut accounts: AccountRepository = uow.repository();
+
+	//WHEN
+	let result = accounts.save(1, 100).await;
+
+	//THEN
+	assert!(matches!(result, Err(BaseError::TransactionError)));
+}
+
+#[tokio::test]
+async fn test_list_page_walks_through_every_row_once() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Account>::default();
+	repo.begin().await.unwrap();
+	for id in 1..=1000 {
+		repo.add(&mut Account { id, ..Default::default() }).await.unwrap();
+	}
+	repo.commit().await.unwrap();
+
+	//WHEN
+	let (mut cursor, mut ids, mut pages) = (None, vec![], 0);
+	loop {
+		let page = repo.list_page(cursor, 64).await.unwrap();
+		ids.extend(page.items.iter().map(|account| account.id));
+		pages += 1;
+		let Some(next_cursor) = page.next_cursor else { break };
+		cursor = Some(next_cursor);
+	}
+
+	//THEN
+	assert_eq!(ids, (1..=1000).collect::<Vec<_>>());
+	assert_eq!(pages, 16);
+}
+
+#[tokio::test]
+async fn test_list_page_leaves_out_soft_deleted() {
+	//GIVEN
+	let mut repo = InMemoryRepository::<Document>::default();
+	repo.begin().await.unwrap();
+	for id in 1..=5 {
+		repo.add(&mut Document { id, ..Default::default() }).await.unwrap();
+	}
+	repo.delete(&2).await.unwrap();
+	repo.commit().await.unwrap();
+
+	//WHEN
+	let first = repo.list_page(None, 3).await.unwrap();
+	let second = repo.list_page(first.next_cursor.clone(), 3).await.unwrap();
+
+	//THEN
+	assert_eq!(first.items.iter().map(|document| document.id).collect::<Vec<_>>(), vec![1, 3]);
+	assert_eq!(first.next_cursor, Some(Cursor::new(3)));
+	assert_eq!(second.items.iter().map(|document| document.id).collect::<Vec<_>>(), vec![4, 5]);
+	assert_eq!(second.next_cursor, None);
+}
+
+struct Node {
+	name: &'static str,
+	database: Arc<Mutex<Tables>>,
+}
+
+struct AccountReader {
+	executor: Executor<Tables>,
+	replicas: ReadReplicas<Node>,
+}
+impl AccountReader {
+	async fn balance(&self, id: i64) -> (&'static str, Option<i64>) {
+		match self.executor.reader(&self.replicas).await {
+			Reader::Transaction(trx) => ("transaction", trx.accounts.get(&id).copied()),
+			Reader::Replica(node) => (node.name, node.database.lock().unwrap().accounts.get(&id).copied()),
+		}
+	}
+}
+
+#[tokio::test]
+async fn test_read_after_staged_write_goes_to_primary() {
+	//GIVEN
+	let mut uow = SharedUnitOfWork::default();
+	let primary = Node { name: "primary", database: uow.database.clone() };
+	let replicas = ["replica-1", "replica-2"].map(|name| Node { name, database: Default::default() });
+	let reader = AccountReader { executor: uow.executor().clone(), replicas: ReadReplicas::new(primary).with_replicas(replicas) };
+	let before = [reader.balance(1).await, reader.balance(1).await, reader.balance(1).await];
+
+	//WHEN
+	uow.begin().await.unwrap();
+	let mut accounts: AccountRepository = uow.repository();
+	accounts.save(1, 100).await.unwrap();
+	let staged = reader.balance(1).await;
+	uow.commit().await.unwrap();
+
+	//THEN
+	assert_eq!(before.map(|(node, _)| node), ["replica-1", "replica-2", "replica-1"]);
+	assert_eq!(staged, ("transaction", Some(100)));
+	assert_eq!(reader.balance(1).await.0, "replica-2");
+	assert_eq!(ReadReplicas::new(Node { name: "primary", database: uow.database.clone() }).next().name, "primary");
+}
@@ -0,0 +1,95 @@
+use ruva::*;
+use std::sync::Mutex;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct ChargeCard {
+	declined: bool,
+}
+impl TCommand for ChargeCard {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct CardCharged;
+
+struct ChargeCardService(AtomicContextManager, ChargeCard);
+impl TCommandService<TestResponse, TestError> for ChargeCardService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		LOG.lock().unwrap().push("handler".into());
+		if self.1.declined {
+			return Err(TestError::DatabaseError("Card declined".into()));
+		}
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![CardCharged.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, ChargeCard> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: ChargeCard) -> impl TCommandService<TestResponse, TestError> {
+		ChargeCardService(context_manager, cmd)
+	}
+}
+
+struct ReceiptHandler;
+impl ReceiptHandler {
+	async fn send_receipt(self, _event: CardCharged) -> Result<(), TestError> {
+		LOG.lock().unwrap().push("event".into());
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| ReceiptHandler,
+	CardCharged: [send_receipt],
+);
+
+static LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct RecordingLifecycle(&'static str);
+impl TRequestLifecycle for RecordingLifecycle {
+	fn on_request_start(&self, context: &ContextManager) {
+		assert!(!context.correlation_id().is_empty());
+		LOG.lock().unwrap().push(format!("start {}", self.0));
+	}
+	fn on_request_finish(&self, _context: &ContextManager, succeeded: bool) {
+		LOG.lock().unwrap().push(format!("finish {} succeeded={succeeded}", self.0));
+	}
+}
+
+#[tokio::test]
+async fn test_finish_hooks_run_after_handler_error_and_after_events() {
+	//GIVEN
+	MessageBus::configure(MessageBusConfig::default().with_request_lifecycle(RecordingLifecycle("outer")).with_request_lifecycle(RecordingLifecycle("inner")));
+
+	//WHEN
+	let declined = MessageBus.execute_and_wait(ChargeCard { declined: true }, &Connection).await;
+	let declined_log = std::mem::take(&mut *LOG.lock().unwrap());
+	let charged = MessageBus.execute_and_wait(ChargeCard { declined: false }, &Connection).await;
+	let charged_log = std::mem::take(&mut *LOG.lock().unwrap());
+
+	//THEN
+	assert!(matches!(declined, Err(TestError::DatabaseError(_))));
+	assert_eq!(declined_log, vec!["start outer", "start inner", "handler", "finish inner succeeded=false", "finish outer succeeded=false"]);
+	assert!(charged.is_ok());
+	// * Finish comes once, after the events raised by the command are handled
+	assert_eq!(charged_log, vec!["start outer", "start inner", "handler", "event", "finish inner succeeded=true", "finish outer succeeded=true"]);
+}
@@ -0,0 +1,73 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+	Deadlock,
+	TooManyRequests,
+	InvalidCard,
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentRequested;
+
+static DEADLOCKED: AtomicUsize = AtomicUsize::new(0);
+static RATE_LIMITED: AtomicUsize = AtomicUsize::new(0);
+static DECLINED: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(PaymentRequested)]
+async fn reserve_balance(_event: PaymentRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	DEADLOCKED.fetch_add(1, Ordering::SeqCst);
+	Err(TestError::Deadlock)
+}
+
+#[event_handler(PaymentRequested)]
+async fn charge_card(_event: PaymentRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	RATE_LIMITED.fetch_add(1, Ordering::SeqCst);
+	Err(TestError::TooManyRequests)
+}
+
+#[event_handler(PaymentRequested)]
+async fn validate_card(_event: PaymentRequested, _context: AtomicContextManager) -> Result<(), TestError> {
+	DECLINED.fetch_add(1, Ordering::SeqCst);
+	Err(TestError::InvalidCard)
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_errors_are_retried_by_policy_of_their_class() {
+	//GIVEN
+	let policies = RetryPolicies::classify(|err: &TestError| match err {
+		TestError::Deadlock => RetryClass::Transient,
+		TestError::TooManyRequests => RetryClass::RateLimited,
+		_ => RetryClass::Fatal,
+	})
+	.policy(RetryClass::Transient, RetryPolicy::new(5, Duration::ZERO))
+	.policy(RetryClass::RateLimited, RetryPolicy::new(3, Duration::from_millis(1)).with_multiplier(2.0))
+	.policy(RetryClass::Fatal, RetryPolicy::new(10, Duration::ZERO));
+	MessageBus::configure(MessageBusConfig::default().with_retry_policies(policies));
+
+	//WHEN
+	let report = MessageBus.handle_event_with_report(PaymentRequested.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(DEADLOCKED.load(Ordering::SeqCst), 6);
+	assert_eq!(RATE_LIMITED.load(Ordering::SeqCst), 4);
+	// * Fatal error is never retried, even with policy given for it
+	assert_eq!(DECLINED.load(Ordering::SeqCst), 1);
+	assert_eq!((report.succeeded, report.failed), (0, 3));
+}
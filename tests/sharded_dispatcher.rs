@@ -0,0 +1,98 @@
+use ruva::*;
+use std::{
+	sync::{Arc, Mutex},
+	time::{Duration, Instant},
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone)]
+struct ItemAdded {
+	cart: String,
+	seq: usize,
+}
+impl TEvent for ItemAdded {
+	fn metadata(&self) -> EventMetadata {
+		EventMetadata { aggregate_id: self.cart.clone(), aggregate_name: "Cart".into(), topic: "ItemAdded".into(), version: 1, headers: Default::default() }
+	}
+	fn state(&self) -> String {
+		"{}".into()
+	}
+}
+
+static PROJECTION: Mutex<Vec<(String, usize)>> = Mutex::new(Vec::new());
+
+#[event_handler(ItemAdded)]
+async fn project_item(event: ItemAdded, _context: AtomicContextManager) -> Result<(), TestError> {
+	tokio::time::sleep(Duration::from_millis(20)).await;
+	PROJECTION.lock().unwrap().push((event.cart, event.seq));
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+const EVENTS_PER_CART: usize = 5;
+
+/// Dispatch events of the carts in turn, returning how long it took until all of them are handled
+async fn run(carts: &[String]) -> Duration {
+	PROJECTION.lock().unwrap().clear();
+	let dispatcher = ShardedDispatcher::spawn::<TestError>(&MessageBus, &Connection);
+	let started = Instant::now();
+	for seq in 1..=EVENTS_PER_CART {
+		for cart in carts {
+			dispatcher.dispatch(Arc::new(ItemAdded { cart: cart.clone(), seq })).await.unwrap();
+		}
+	}
+	dispatcher.close().await;
+	started.elapsed()
+}
+
+#[tokio::test]
+async fn test_throughput_scales_with_lanes_while_aggregates_keep_their_order() {
+	// * Tasks are spawned locally with `local` feature
+	tokio::task::LocalSet::new()
+		.run_until(async {
+			//GIVEN
+			const LANES: usize = 4;
+			MessageBus::configure(MessageBusConfig::default().with_event_shards(LANES));
+			// * A cart for each lane, so that the lanes are evenly loaded
+			let dispatcher = ShardedDispatcher::spawn::<TestError>(&MessageBus, &Connection);
+			let mut carts: Vec<Option<String>> = vec![None; LANES];
+			for cart in (0..).map(|n| format!("cart-{}", n)) {
+				let lane = dispatcher.lane_of(&cart);
+				carts[lane].get_or_insert(cart);
+				if carts.iter().all(Option::is_some) {
+					break;
+				}
+			}
+			dispatcher.close().await;
+			let carts: Vec<String> = carts.into_iter().flatten().collect();
+
+			//WHEN
+			MessageBus::configure(MessageBusConfig::default());
+			let single_lane = run(&carts).await;
+			MessageBus::configure(MessageBusConfig::default().with_event_shards(LANES));
+			let sharded = run(&carts).await;
+
+			//THEN
+			assert!(sharded * 2 < single_lane, "sharded: {:?}, single lane: {:?}", sharded, single_lane);
+			let projection = PROJECTION.lock().unwrap();
+			assert_eq!(projection.len(), LANES * EVENTS_PER_CART);
+			for cart in &carts {
+				let seqs = projection.iter().filter(|(c, _)| c == cart).map(|(_, seq)| *seq).collect::<Vec<_>>();
+				assert_eq!(seqs, (1..=EVENTS_PER_CART).collect::<Vec<_>>());
+			}
+		})
+		.await;
+}
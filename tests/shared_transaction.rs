@@ -0,0 +1,147 @@
+use ruva::*;
+use std::sync::Mutex;
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+/// Rows committed so far
+static TABLE: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Writes are applied to [TABLE] only when it is committed
+#[derive(Default)]
+struct InMemoryTransaction {
+	writes: Vec<String>,
+}
+
+#[async_trait]
+impl TRequestTransaction for InMemoryTransaction {
+	async fn commit(self: Box<Self>) -> Result<(), BaseError> {
+		TABLE.lock().unwrap().extend(self.writes);
+		Ok(())
+	}
+	async fn rollback(self: Box<Self>) -> Result<(), BaseError> {
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+struct PlaceOrder {
+	id: i64,
+	out_of_stock: bool,
+}
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	id: i64,
+	out_of_stock: bool,
+}
+
+struct PlaceOrderService(AtomicContextManager, PlaceOrder);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		let PlaceOrderService(context_manager, cmd) = self;
+		if cmd.id < 0 {
+			return Err(BaseError::ValidationError("Order id must be positive".into()).into());
+		}
+		// * Commands of atomic batch write through the transaction shared by the first one
+		match context_manager.transaction::<InMemoryTransaction>() {
+			Some(transaction) => transaction.writes.push(format!("order {}", cmd.id)),
+			None => context_manager.share_transaction(InMemoryTransaction { writes: vec![format!("order {}", cmd.id)] }),
+		}
+		context_manager.push_event(OrderPlaced { id: cmd.id, out_of_stock: cmd.out_of_stock }.to_message()).await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager, cmd)
+	}
+}
+
+struct StockHandler(AtomicContextManager);
+impl StockHandler {
+	async fn reserve_stock(self, event: OrderPlaced) -> Result<(), TestError> {
+		if event.out_of_stock {
+			return Err(TestError::DatabaseError("Out of stock".into()));
+		}
+		self.0.transaction::<InMemoryTransaction>().unwrap().writes.push(format!("stock {}", event.id));
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	StockHandler,
+	OrderPlaced: [reserve_stock],
+);
+
+fn rows_of(id: i64) -> Vec<String> {
+	TABLE.lock().unwrap().iter().filter(|row| row.ends_with(&format!(" {id}"))).cloned().collect()
+}
+
+#[tokio::test]
+async fn test_writes_of_command_and_event_handlers_are_committed_together() {
+	//WHEN
+	let res = MessageBus.execute_and_wait(PlaceOrder { id: 1, out_of_stock: false }, &Connection).await;
+
+	//THEN
+	assert!(res.is_ok());
+	assert_eq!(rows_of(1), vec!["order 1", "stock 1"]);
+}
+
+#[tokio::test]
+async fn test_failing_event_handler_rolls_back_write_of_command() {
+	//WHEN
+	let res = MessageBus.execute_and_wait(PlaceOrder { id: 2, out_of_stock: true }, &Connection).await;
+
+	//THEN
+	assert!(matches!(res, Err(TestError::BaseError(BaseError::TransactionError))));
+	assert!(rows_of(2).is_empty());
+}
+
+#[tokio::test]
+async fn test_atomic_batch_is_rolled_back_as_a_whole_when_a_command_fails() {
+	//GIVEN
+	let commands = vec![PlaceOrder { id: 3, out_of_stock: false }, PlaceOrder { id: -1, out_of_stock: false }, PlaceOrder { id: 4, out_of_stock: false }];
+
+	//WHEN
+	let results = MessageBus.execute_batch(commands, &Connection, BatchContext::Atomic).await;
+
+	//THEN
+	assert!(matches!(results[0], Err(TestError::BaseError(BaseError::TransactionError))));
+	assert!(matches!(results[1], Err(TestError::BaseError(BaseError::ValidationError(_)))));
+	assert!(matches!(results[2], Err(TestError::BaseError(BaseError::TransactionError))));
+	assert!(rows_of(3).is_empty());
+	assert!(rows_of(4).is_empty());
+}
+
+#[tokio::test]
+async fn test_atomic_batch_commits_writes_of_every_command_and_event_handler_together() {
+	//GIVEN
+	let commands = vec![PlaceOrder { id: 5, out_of_stock: false }, PlaceOrder { id: 6, out_of_stock: false }];
+
+	//WHEN
+	let results = MessageBus.execute_batch(commands, &Connection, BatchContext::Atomic).await;
+
+	//THEN
+	assert!(results.iter().all(Result::is_ok));
+	assert_eq!(rows_of(5), vec!["order 5", "stock 5"]);
+	assert_eq!(rows_of(6), vec!["order 6", "stock 6"]);
+}
@@ -0,0 +1,84 @@
+use ruva::*;
+use std::{
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
+use tokio::sync::Notify;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+static COMMAND_STARTED: Notify = Notify::const_new();
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		COMMAND_STARTED.notify_one();
+		tokio::time::sleep(Duration::from_millis(50)).await;
+
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message(), OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn send_receipt(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	tokio::time::sleep(Duration::from_millis(20)).await;
+	HANDLED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_shutdown_drains_in_flight_command_and_its_events() {
+	//GIVEN
+	// * Joined rather than spawned, so that it runs without `Send` under `local` feature as well
+	let in_flight = MessageBus.execute_and_wait(PlaceOrder, &Connection);
+	let shutdown = async {
+		COMMAND_STARTED.notified().await;
+
+		//WHEN
+		MessageBus::shutdown_handle().signal();
+		let rejected = MessageBus.execute_and_wait(PlaceOrder, &Connection).await;
+		MessageBus.shutdown().await;
+		rejected
+	};
+	let (in_flight, rejected) = tokio::join!(in_flight, shutdown);
+
+	//THEN
+	assert!(matches!(rejected, Err(TestError::BaseError(BaseError::ShuttingDown))));
+	assert_eq!(HANDLED.load(Ordering::SeqCst), 2);
+	assert!(in_flight.is_ok());
+}
@@ -0,0 +1,116 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicBool, Ordering},
+	Arc, Mutex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[aggregate(Serialize, Debug)]
+struct Order {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentFailed {
+	order_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+#[externally_notifiable(Order)]
+struct OrderCancelled {
+	#[identifier]
+	id: i64,
+}
+
+static CHARGED: AtomicBool = AtomicBool::new(false);
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+struct OrderEventHandler;
+impl OrderEventHandler {
+	async fn cancel_order(self, event: PaymentFailed) -> Result<(), TestError> {
+		Err(OrderCancelled { id: event.order_id }.stop())
+	}
+	async fn charge_again(self, _event: PaymentFailed) -> Result<(), TestError> {
+		CHARGED.store(true, Ordering::SeqCst);
+		Ok(())
+	}
+	async fn notify_customer(self, _event: OrderCancelled) -> Result<(), TestError> {
+		CANCELLED.store(true, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+init_event_handler!(
+	TestError,
+	|_ctx| OrderEventHandler,
+	PaymentFailed: [cancel_order, charge_again],
+	OrderCancelled: [notify_customer],
+);
+
+#[derive(Default, Clone)]
+struct InMemoryPublisher(Arc<Mutex<Vec<OutBox>>>);
+#[async_trait]
+impl TOutBoxPublisher for InMemoryPublisher {
+	async fn publish(&self, outbox: &OutBox) -> Result<(), BaseError> {
+		self.0.lock().unwrap().push(outbox.clone());
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_event_of_stop_sentinel_is_published_and_queued() {
+	//GIVEN
+	let publisher = InMemoryPublisher::default();
+	MessageBus::configure(MessageBusConfig::default().with_stop_sentinel_publisher(publisher.clone()));
+
+	//WHEN
+	MessageBus.handle_event(PaymentFailed { order_id: 1 }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert!(!CHARGED.load(Ordering::SeqCst));
+	assert!(CANCELLED.load(Ordering::SeqCst));
+	let outboxes = publisher.0.lock().unwrap().clone();
+	assert_eq!(outboxes.len(), 1);
+	assert_eq!((outboxes[0].topic.as_str(), outboxes[0].aggregate_id.as_str(), outboxes[0].aggregate_name.as_str()), ("OrderCancelled", "1", "Order"));
+	assert!(outboxes[0].state.contains(r#""data":{"id":1}"#));
+}
+
+#[test]
+fn test_stop_helper_wraps_event_in_stop_sentinel_variant() {
+	//WHEN
+	let err: TestError = OrderCancelled { id: 3 }.stop();
+
+	//THEN
+	let TestError::StopSentinelWithEvent(event) = err else {
+		panic!("Event must be wrapped in stop sentinel variant!");
+	};
+	assert_eq!(event.metadata().aggregate_id, "3");
+}
+
+#[test]
+fn test_event_of_stop_sentinel_carries_metadata_and_state() {
+	//GIVEN
+	let BaseError::StopSentinelWithEvent(event) = BaseError::from(TestError::StopSentinelWithEvent(OrderCancelled { id: 2 }.to_message())) else {
+		panic!("Stop sentinel must carry the event!");
+	};
+
+	//THEN
+	assert_eq!(event.metadata().topic, "OrderCancelled");
+	assert_eq!(event.metadata().aggregate_id, "2");
+	assert_eq!(event.state(), r#"{"id":2}"#);
+}
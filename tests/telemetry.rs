@@ -0,0 +1,101 @@
+#![cfg(feature = "event-driven-otel")]
+
+use opentelemetry::trace::{Status, TracerProvider};
+use opentelemetry_sdk::{
+	propagation::TraceContextPropagator,
+	trace::{InMemorySpanExporter, SdkTracerProvider},
+};
+use ruva::*;
+use std::sync::Mutex;
+use tracing_subscriber::layer::SubscriberExt;
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug)]
+struct TestResponse;
+impl ApplicationResponse for TestResponse {}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug)]
+struct PlaceOrder;
+impl TCommand for PlaceOrder {
+	fn aggregate_id(&self) -> Option<String> {
+		Some("order-1".into())
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced;
+
+static OUTBOX_HEADERS: Mutex<String> = Mutex::new(String::new());
+
+struct PlaceOrderService(AtomicContextManager);
+impl TCommandService<TestResponse, TestError> for PlaceOrderService {
+	async fn execute(self) -> Result<TestResponse, TestError> {
+		*OUTBOX_HEADERS.lock().unwrap() = OrderPlaced.outbox().headers;
+		let mut context = Context::new(self.0);
+		context.set_current_events(vec![OrderPlaced.to_message()].into());
+		context.send_internally_notifiable_messages().await?;
+		Ok(TestResponse)
+	}
+}
+
+impl TMessageBus<TestResponse, TestError, PlaceOrder> for MessageBus {
+	fn command_handler(&self, context_manager: AtomicContextManager, _cmd: PlaceOrder) -> impl TCommandService<TestResponse, TestError> {
+		PlaceOrderService(context_manager)
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn send_receipt(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	Ok(())
+}
+
+#[event_handler(OrderPlaced)]
+async fn notify_warehouse(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	Err(BaseError::ServiceError.into())
+}
+
+init_event_handler!(TestError);
+
+#[tokio::test]
+async fn test_spans_of_command_and_its_events() {
+	//GIVEN
+	let exporter = InMemorySpanExporter::default();
+	let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+	let subscriber = tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+	let _guard = tracing::subscriber::set_default(subscriber);
+	opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+	//WHEN
+	MessageBus.execute_and_wait(PlaceOrder, &Connection).await.unwrap();
+	provider.force_flush().unwrap();
+
+	//THEN
+	let spans = exporter.get_finished_spans().unwrap();
+	let command = spans.iter().find(|span| span.name.ends_with("PlaceOrder")).expect("Command span must be exported!");
+	let event = spans.iter().find(|span| span.name == "OrderPlaced").expect("Event span must be exported!");
+
+	assert_eq!(command.status, Status::Ok);
+	assert!(command.attributes.iter().any(|attribute| attribute.key.as_str() == "aggregate_id" && attribute.value.to_string() == "order-1"));
+	assert_eq!(event.parent_span_id, command.span_context.span_id());
+	assert_eq!(event.status, Status::error(""));
+	let attribute = |key: &str| event.attributes.iter().find(|attribute| attribute.key.as_str() == key).map(|attribute| attribute.value.to_string());
+	assert_eq!(attribute("handler_count").as_deref(), Some("2"));
+	assert_eq!(attribute("succeeded").as_deref(), Some("1"));
+	assert_eq!(attribute("failed").as_deref(), Some("1"));
+
+	let headers: std::collections::HashMap<String, String> = serde_json::from_str(&OUTBOX_HEADERS.lock().unwrap()).unwrap();
+	assert!(headers["traceparent"].contains(&command.span_context.trace_id().to_string()));
+}
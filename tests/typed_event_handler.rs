@@ -0,0 +1,76 @@
+use ruva::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	id: i64,
+}
+
+/// Event whose topic is different from its type name, as it is kept for consumers of message broker
+#[derive(Debug, Clone)]
+struct OrderCancelled;
+impl TEvent for OrderCancelled {
+	fn metadata(&self) -> EventMetadata {
+		EventMetadata { aggregate_id: Default::default(), aggregate_name: Default::default(), topic: "order.cancelled.v1".into(), version: 1, headers: Default::default() }
+	}
+	fn state(&self) -> String {
+		"{}".into()
+	}
+}
+
+static SHIPPED: AtomicUsize = AtomicUsize::new(0);
+static NOTIFIED: AtomicUsize = AtomicUsize::new(0);
+static REFUNDED: AtomicUsize = AtomicUsize::new(0);
+
+struct Shipping;
+impl Shipping {
+	async fn ship(self, event: OrderPlaced) -> Result<(), TestError> {
+		SHIPPED.fetch_add(event.id as usize, Ordering::SeqCst);
+		Ok(())
+	}
+}
+
+#[event_handler(OrderPlaced)]
+async fn notify(_event: OrderPlaced, _context: AtomicContextManager) -> Result<(), TestError> {
+	NOTIFIED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+#[event_handler(OrderCancelled)]
+async fn refund(_event: OrderCancelled, _context: AtomicContextManager) -> Result<(), TestError> {
+	REFUNDED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_typed_event_handler!(
+	TestError,
+	|_ctx| Shipping,
+	OrderPlaced: [ship],
+);
+
+#[tokio::test]
+async fn test_events_are_routed_by_type() {
+	//WHEN
+	MessageBus.handle_event(OrderPlaced { id: 3 }.to_message(), &Connection).await.unwrap();
+	MessageBus.handle_event(std::sync::Arc::new(OrderCancelled), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(SHIPPED.load(Ordering::SeqCst), 3);
+	assert_eq!(NOTIFIED.load(Ordering::SeqCst), 1);
+	// * Topic doesn't match name of the type handler is registered for
+	assert_eq!(REFUNDED.load(Ordering::SeqCst), 1);
+}
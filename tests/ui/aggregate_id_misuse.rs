@@ -0,0 +1,24 @@
+use ruva::*;
+
+#[aggregate(Serialize, Debug)]
+pub struct Order {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[externally_notifiable(Order)]
+#[aggregate_id = "some"]
+struct ReportGenerated {
+	day: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[externally_notifiable(Order)]
+#[aggregate_id(expr = "self.order_id")]
+struct OrderShipped {
+	#[identifier]
+	order_id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use ruva::*;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+#[topic("")]
+struct InvoiceCreated {
+	id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use ruva::*;
+
+#[derive(Debug, Serialize, TEvent)]
+#[internally_notifiable]
+struct OrderPlaced {
+	id: i64,
+}
+
+fn main() {}
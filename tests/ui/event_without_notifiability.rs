@@ -0,0 +1,8 @@
+use ruva::*;
+
+#[event]
+struct OrderPlaced {
+	id: i64,
+}
+
+fn main() {}
@@ -0,0 +1,44 @@
+use ruva::*;
+
+#[derive(Debug, ApplicationError)]
+enum ServiceError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct ServiceResponse;
+impl ApplicationResponse for ServiceResponse {}
+
+#[into_command]
+struct CreateAccount {
+	name: String,
+}
+
+async fn create_account(_context: &mut Context, _cmd: CreateAccount) -> Result<ServiceResponse, ServiceError> {
+	Ok(ServiceResponse)
+}
+
+struct NoopService;
+impl TCommandService<ServiceResponse, ServiceError> for NoopService {
+	async fn execute(self) -> Result<ServiceResponse, ServiceError> {
+		Ok(ServiceResponse)
+	}
+}
+
+fn into_service(_: CommandHandler<(CreateAccount, Context)>) -> NoopService {
+	NoopService
+}
+
+init_event_handler!(ServiceError);
+
+register_uow_services!(
+	ServiceResponse,
+	ServiceError,
+	into_service,
+
+	CreateAccount => create_account
+);
+
+fn main() {}
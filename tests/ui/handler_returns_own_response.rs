@@ -0,0 +1,99 @@
+use ruva::*;
+
+#[derive(Debug, ApplicationError)]
+enum ServiceError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+#[derive(Debug, ApplicationResponse)]
+enum ServiceResponse {
+	#[created]
+	AccountCreated(i64),
+	Empty,
+}
+
+#[derive(Debug, ApplicationResponse)]
+#[created]
+struct AccountCreated {
+	id: i64,
+}
+
+impl From<AccountCreated> for ServiceResponse {
+	fn from(value: AccountCreated) -> Self {
+		Self::AccountCreated(value.id)
+	}
+}
+
+#[derive(Debug, ApplicationResponse)]
+struct Paged {
+	items: Vec<i64>,
+}
+
+// * Fallible wrap, as the bus-wide response has no room for pages
+impl TryFrom<Paged> for ServiceResponse {
+	type Error = BaseError;
+	fn try_from(value: Paged) -> Result<Self, Self::Error> {
+		match value.items.is_empty() {
+			true => Ok(Self::Empty),
+			false => Err(BaseError::ServiceError),
+		}
+	}
+}
+
+#[into_command]
+struct CreateAccount {
+	name: String,
+}
+
+#[into_command]
+struct ListAccounts;
+
+#[into_command]
+struct DeleteAccount;
+
+async fn create_account(_cmd: CreateAccount, _context: &mut Context) -> Result<AccountCreated, ServiceError> {
+	Ok(AccountCreated { id: 1 })
+}
+
+async fn list_accounts(_cmd: ListAccounts, _context: &mut Context) -> Result<Paged, ServiceError> {
+	Ok(Paged { items: vec![1] })
+}
+
+// * Handler may still return the bus-wide response as it is
+async fn delete_account(_cmd: DeleteAccount, _context: &mut Context) -> Result<ServiceResponse, ServiceError> {
+	Ok(ServiceResponse::Empty)
+}
+
+struct NoopService;
+impl TCommandService<ServiceResponse, ServiceError> for NoopService {
+	async fn execute(self) -> Result<ServiceResponse, ServiceError> {
+		Ok(ServiceResponse::Empty)
+	}
+}
+
+fn into_service<C: TCommand>(_: CommandHandler<(C, Context)>) -> NoopService {
+	NoopService
+}
+
+init_event_handler!(ServiceError);
+
+register_uow_services!(
+	ServiceResponse,
+	ServiceError,
+	into_service,
+
+	CreateAccount => create_account,
+	ListAccounts => list_accounts,
+	DeleteAccount => delete_account
+);
+
+fn main() {
+	let created: Result<ServiceResponse, ServiceError> = Ok::<_, ServiceError>(AccountCreated { id: 1 }).into_application_result();
+	assert!(matches!(created, Ok(ServiceResponse::AccountCreated(1))));
+
+	let listed: Result<ServiceResponse, ServiceError> = Ok::<_, ServiceError>(Paged { items: vec![1] }).into_application_result();
+	assert!(matches!(listed, Err(ServiceError::BaseError(BaseError::ServiceError))));
+}
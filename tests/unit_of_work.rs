@@ -0,0 +1,190 @@
+use ruva::*;
+use std::sync::{
+	atomic::{AtomicUsize, Ordering},
+	Mutex,
+};
+
+#[allow(dead_code)]
+#[derive(Debug, ApplicationError)]
+#[crates(ruva)]
+enum TestError {
+	StopSentinel,
+	StopSentinelWithEvent(std::sync::Arc<dyn TEvent>),
+	DatabaseError(String),
+	BaseError(BaseError),
+}
+
+struct Connection;
+impl TConnection for Connection {}
+
+/// Unit of work whose database refuses to commit when told so
+struct UnitOfWork {
+	context: Context,
+	refuse_commit: bool,
+}
+
+impl TUnitOfWork for UnitOfWork {
+	async fn begin(&mut self) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn _commit(&mut self) -> Result<(), BaseError> {
+		match self.refuse_commit {
+			true => Err(BaseError::DatabaseError("Deadlock detected".into())),
+			false => Ok(()),
+		}
+	}
+	async fn rollback(&mut self) -> Result<(), BaseError> {
+		Ok(())
+	}
+	async fn close(&mut self) {}
+	async fn process_internal_events(&mut self) -> Result<(), BaseError> {
+		self.context.send_internally_notifiable_messages().await
+	}
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentRequested {
+	refuse_commit: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentAttempted;
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[internally_notifiable]
+struct PaymentCaptured;
+
+static ATTEMPTED: AtomicUsize = AtomicUsize::new(0);
+static CAPTURED: AtomicUsize = AtomicUsize::new(0);
+
+#[event_handler(PaymentRequested)]
+async fn capture_payment(event: PaymentRequested, context_manager: AtomicContextManager) -> Result<(), TestError> {
+	let mut uow = UnitOfWork { context: Context::new(context_manager.clone()), refuse_commit: event.refuse_commit };
+	uow.begin().await?;
+	context_manager.push_event(PaymentAttempted.to_message()).await?;
+	uow.context.emit_on_commit(PaymentCaptured);
+	if let Err(err) = uow.commit().await {
+		uow.rollback().await?;
+		Err(err)?
+	}
+	Ok(())
+}
+
+#[event_handler(PaymentAttempted)]
+async fn count_attempt(_event: PaymentAttempted, _context: AtomicContextManager) -> Result<(), TestError> {
+	ATTEMPTED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+#[event_handler(PaymentCaptured)]
+async fn count_capture(_event: PaymentCaptured, _context: AtomicContextManager) -> Result<(), TestError> {
+	CAPTURED.fetch_add(1, Ordering::SeqCst);
+	Ok(())
+}
+
+init_event_handler!(TestError);
+
+#[aggregate(Clone)]
+struct Account {
+	#[adapter_ignore]
+	id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, TEvent)]
+#[externally_notifiable(Account)]
+struct AccountOpened {
+	#[identifier]
+	id: i64,
+}
+
+#[derive(Default, Clone)]
+struct Tables {
+	accounts: Vec<i64>,
+	outboxes: Vec<OutBox>,
+}
+
+static DATABASE: Mutex<Tables> = Mutex::new(Tables { accounts: vec![], outboxes: vec![] });
+
+/// Unit of work that writes accounts and their outboxes through the same transaction
+struct AccountUnitOfWork {
+	context: Context,
+	transaction: Option<Tables>,
+}
+
+impl AccountUnitOfWork {
+	fn save(&mut self, account: &mut Account) -> Result<(), BaseError> {
+		self.transaction.as_mut().ok_or(BaseError::TransactionError)?.accounts.push(account.id);
+		self.context.event_hook(account);
+		Ok(())
+	}
+}
+
+impl TUnitOfWork for AccountUnitOfWork {
+	async fn begin(&mut self) -> Result<(), BaseError> {
+		self.transaction = Some(DATABASE.lock().unwrap().clone());
+		Ok(())
+	}
+	async fn _commit(&mut self) -> Result<(), BaseError> {
+		*DATABASE.lock().unwrap() = self.transaction.take().ok_or(BaseError::TransactionError)?;
+		Ok(())
+	}
+	async fn rollback(&mut self) -> Result<(), BaseError> {
+		self.transaction = None;
+		self.context.discard_events();
+		Ok(())
+	}
+	async fn close(&mut self) {}
+	async fn process_external_events(&mut self) -> Result<(), BaseError> {
+		let outboxes = self.context.outboxes()?;
+		self.transaction.as_mut().ok_or(BaseError::TransactionError)?.outboxes.extend(outboxes);
+		Ok(())
+	}
+}
+
+#[tokio::test]
+async fn test_event_emitted_on_commit_fires_only_when_transaction_commits() {
+	//WHEN
+	let rolled_back = MessageBus.handle_event_with_report(PaymentRequested { refuse_commit: true }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(rolled_back.topics, vec!["PaymentRequested", "PaymentAttempted"]);
+	assert_eq!((ATTEMPTED.load(Ordering::SeqCst), CAPTURED.load(Ordering::SeqCst)), (1, 0));
+
+	//WHEN
+	let committed = MessageBus.handle_event_with_report(PaymentRequested { refuse_commit: false }.to_message(), &Connection).await.unwrap();
+
+	//THEN
+	assert_eq!(committed.topics, vec!["PaymentRequested", "PaymentAttempted", "PaymentCaptured"]);
+	assert_eq!((ATTEMPTED.load(Ordering::SeqCst), CAPTURED.load(Ordering::SeqCst)), (2, 1));
+}
+
+async fn open_account(uow: &mut AccountUnitOfWork, id: i64, fail_before_commit: bool) -> Result<(), BaseError> {
+	let mut account = Account { id, ..Default::default() };
+	account.raise_event(AccountOpened { id }.to_message());
+	uow.begin().await?;
+	uow.save(&mut account)?;
+	// * Failure after the aggregate is written but before the transaction commits
+	if fail_before_commit {
+		uow.rollback().await?;
+		return Err(BaseError::DatabaseError("Connection reset".into()));
+	}
+	uow.commit().await
+}
+
+#[tokio::test]
+async fn test_aggregate_and_its_outbox_are_rolled_back_together() {
+	//GIVEN
+	let mut uow = AccountUnitOfWork { context: Context::new(std::sync::Arc::new(ContextManager::new(&Connection))), transaction: None };
+
+	//WHEN
+	let failed = open_account(&mut uow, 1, true).await;
+	let committed = open_account(&mut uow, 2, false).await;
+
+	//THEN
+	assert!(failed.is_err() && committed.is_ok());
+	let database = DATABASE.lock().unwrap();
+	assert_eq!(database.accounts, vec![2]);
+	assert_eq!(database.outboxes.iter().map(|outbox| outbox.aggregate_id.as_str()).collect::<Vec<_>>(), vec!["2"]);
+}